@@ -389,6 +389,10 @@ fn add_activate_script_options(
     if context.attach_ctx.flox_env_cuda_detection == "1" {
         command.arg("--cuda-detection");
     }
+
+    if context.skip_hook_on_activate {
+        command.arg("--skip-hook-on-activate");
+    }
 }
 
 /// _flox_activate_tracelevel, _flox_activate_tracer, and _activate_d still need some cleanup
@@ -5,6 +5,7 @@ use std::time::{Duration, Instant};
 use anyhow::Result;
 use clap::Args;
 use flox_core::activate::context::{ActivateCtx, InvocationType};
+use flox_core::activate::events::ActivationEventKind;
 use flox_core::activations::{
     ActivationState,
     ModeMismatch,
@@ -110,8 +111,10 @@ impl ActivateArgs {
                 &project.flox_services_socket,
                 &project.services_to_start,
             )?;
+            context.emit_event(ActivationEventKind::ServicesStarted);
         }
 
+        context.emit_event(ActivationEventKind::Ready);
         attach(
             context,
             invocation_type,
@@ -0,0 +1,200 @@
+//! Running `hook.on-deactivate`
+//!
+//! The executive runs the hook once the last process attached to an
+//! activation has exited, i.e. after the activation shell exits, whether it
+//! exited normally or because it received SIGINT or SIGTERM.
+//! It runs before services are shut down, so that the hook can still
+//! interact with them.
+//!
+//! The hook is read from the environment of the most recent activation,
+//! which may have been rebuilt since the executive was started,
+//! and runs with the `[vars]` of that environment set.
+//!
+//! The executive is detached from the terminal, so the output of the hook is
+//! written to the executive log.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use flox_core::activate::context::{AttachCtx, AttachProjectCtx};
+use flox_core::traceable_path;
+use tracing::{debug, info, warn};
+
+/// Name of the script in `<FLOX_ENV>/activate.d`, rendered from `hook.on-deactivate`
+const HOOK_ON_DEACTIVATE: &str = "hook-on-deactivate";
+
+/// Name of the script in `<FLOX_ENV>/activate.d` that exports the manifest `[vars]`
+const ENVRC: &str = "envrc";
+
+/// Source the envrc passed as `$1`, if it exists, then run the hook passed as `$0`
+const SOURCE_ENVRC_AND_RUN_HOOK: &str = r#"if [ -f "$1" ]; then source "$1"; fi; exec bash "$0""#;
+
+/// The `hook.on-deactivate` script of an environment and the variables it runs with
+#[derive(Debug, Clone)]
+pub struct HookOnDeactivate {
+    /// The environment the executive was started for,
+    /// used if there's no ready activation when the hook runs.
+    initial_env: PathBuf,
+    env_project: PathBuf,
+    envs: Vec<(&'static str, String)>,
+}
+
+impl HookOnDeactivate {
+    pub fn new(attach_ctx: &AttachCtx, project_ctx: &AttachProjectCtx) -> Self {
+        let envs = vec![
+            ("FLOX_ENV", attach_ctx.env.clone()),
+            (
+                "FLOX_ENV_CACHE",
+                attach_ctx.env_cache.to_string_lossy().to_string(),
+            ),
+            (
+                "FLOX_ENV_PROJECT",
+                project_ctx.env_project.to_string_lossy().to_string(),
+            ),
+            ("FLOX_ENV_DESCRIPTION", attach_ctx.env_description.clone()),
+        ];
+
+        Self {
+            initial_env: PathBuf::from(&attach_ctx.env),
+            env_project: project_ctx.env_project.clone(),
+            envs,
+        }
+    }
+
+    /// Run the hook of `current_env` if the environment defines one.
+    ///
+    /// `current_env` is the store path of the most recent activation,
+    /// if there is one.
+    ///
+    /// Failures are logged rather than returned,
+    /// so that a failing hook doesn't prevent the remaining cleanup.
+    pub fn run(&self, current_env: Option<&Path>) {
+        let env = current_env.unwrap_or(&self.initial_env);
+        let script = env.join("activate.d").join(HOOK_ON_DEACTIVATE);
+        if !script.exists() {
+            debug!(
+                script = traceable_path(&script),
+                "no hook.on-deactivate to run"
+            );
+            return;
+        }
+
+        info!(
+            script = traceable_path(&script),
+            "running hook.on-deactivate"
+        );
+        let output = Command::new("bash")
+            .arg("-c")
+            .arg(SOURCE_ENVRC_AND_RUN_HOOK)
+            .arg(&script)
+            .arg(env.join("activate.d").join(ENVRC))
+            .envs(self.envs.iter().cloned())
+            .current_dir(&self.env_project)
+            .stdin(Stdio::null())
+            .output();
+
+        match output {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                info!(
+                    stdout = stdout.trim(),
+                    stderr = stderr.trim(),
+                    "hook.on-deactivate output"
+                );
+                if !output.status.success() {
+                    warn!(status = %output.status, "hook.on-deactivate failed");
+                }
+            },
+            Err(err) => warn!(%err, "failed to run hook.on-deactivate"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn runs_hook_with_environment_variables() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let flox_env = temp_dir.path().join("env");
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(flox_env.join("activate.d")).unwrap();
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            flox_env.join("activate.d").join(HOOK_ON_DEACTIVATE),
+            r#"echo "$FLOX_ENV_PROJECT" > "$PWD/deactivated""#,
+        )
+        .unwrap();
+
+        let hook = HookOnDeactivate {
+            initial_env: flox_env.clone(),
+            env_project: project.clone(),
+            envs: vec![("FLOX_ENV_PROJECT", project.to_string_lossy().to_string())],
+        };
+        hook.run(None);
+
+        assert_eq!(
+            fs::read_to_string(project.join("deactivated"))
+                .unwrap()
+                .trim(),
+            project.to_string_lossy()
+        );
+    }
+
+    #[test]
+    fn runs_hook_of_current_environment_with_vars() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let initial_env = temp_dir.path().join("initial");
+        let current_env = temp_dir.path().join("current");
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(initial_env.join("activate.d")).unwrap();
+        fs::create_dir_all(current_env.join("activate.d")).unwrap();
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            initial_env.join("activate.d").join(HOOK_ON_DEACTIVATE),
+            r#"echo "initial" > "$PWD/deactivated""#,
+        )
+        .unwrap();
+        fs::write(
+            current_env.join("activate.d").join(HOOK_ON_DEACTIVATE),
+            r#"echo "current $GREETING" > "$PWD/deactivated""#,
+        )
+        .unwrap();
+        fs::write(
+            current_env.join("activate.d").join(ENVRC),
+            "export GREETING=\"hello world\"\n",
+        )
+        .unwrap();
+
+        let hook = HookOnDeactivate {
+            initial_env,
+            env_project: project.clone(),
+            envs: vec![],
+        };
+        hook.run(Some(&current_env));
+
+        assert_eq!(
+            fs::read_to_string(project.join("deactivated"))
+                .unwrap()
+                .trim(),
+            "current hello world"
+        );
+    }
+
+    #[test]
+    fn missing_hook_is_skipped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let hook = HookOnDeactivate {
+            initial_env: temp_dir.path().to_path_buf(),
+            env_project: temp_dir.path().to_path_buf(),
+            envs: vec![],
+        };
+        hook.run(None);
+
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+}
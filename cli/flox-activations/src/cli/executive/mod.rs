@@ -9,6 +9,7 @@ use flox_core::activate::vars::FLOX_EXECUTIVE_VERBOSITY_VAR;
 use flox_core::activations::{read_activations_json, state_json_path, write_activations_json};
 use flox_core::sentry::init_sentry;
 use flox_core::traceable_path;
+use hook_on_deactivate::HookOnDeactivate;
 use log_gc::{spawn_heartbeat_log, spawn_logs_gc_threads};
 use nix::sys::signal::Signal::SIGUSR1;
 use nix::sys::signal::kill;
@@ -24,6 +25,7 @@ use crate::logger::init_executive_logger;
 use crate::process_compose::{process_compose_down, start_process_compose_no_services};
 
 mod event_coordinator;
+mod hook_on_deactivate;
 mod log_gc;
 mod reaper;
 mod watcher;
@@ -178,6 +180,7 @@ fn run_event_loop(
 
     let process_compose_bin = project_ctx.process_compose_bin.to_path_buf();
     let socket_path = project_ctx.flox_services_socket.to_path_buf();
+    let hook_on_deactivate = HookOnDeactivate::new(&initial_attach_ctx, &project_ctx);
     debug!(
         socket = traceable_path(&socket_path),
         exists = &socket_path.exists(),
@@ -205,6 +208,7 @@ fn run_event_loop(
                     &process_compose_bin,
                     &socket_path,
                     &activation_state_dir,
+                    &hook_on_deactivate,
                 )?;
                 if should_exit {
                     return Ok(());
@@ -255,6 +259,7 @@ fn run_event_loop(
                         &process_compose_bin,
                         &socket_path,
                         &activation_state_dir,
+                        &hook_on_deactivate,
                     )
                     .context("cleanup failed after StateFileChanged with empty PIDs")?
                     {
@@ -324,6 +329,7 @@ fn handle_process_exited(
     process_compose_bin: &Path,
     socket_path: &Path,
     activation_state_dir: &Path,
+    hook_on_deactivate: &HookOnDeactivate,
 ) -> Result<bool> {
     // Remove from known_pids first so it can be re-monitored if it re-attached
     coordinator.stop_monitoring(pid);
@@ -378,6 +384,7 @@ fn handle_process_exited(
                 process_compose_bin,
                 socket_path,
                 activation_state_dir,
+                hook_on_deactivate,
             )
             .context("cleanup failed")?;
             Ok(cleaned_up)
@@ -393,6 +400,7 @@ fn handle_process_exited(
                 process_compose_bin,
                 socket_path,
                 activation_state_dir,
+                hook_on_deactivate,
             );
             bail!(err.context("failed while waiting for termination"))
         },
@@ -444,7 +452,8 @@ fn handle_start_services_signal(
     Ok(Some((activations, lock)))
 }
 
-/// Shutdown `process-compose` if running and remove all activation state.
+/// Run `hook.on-deactivate`, shutdown `process-compose` if running and
+/// remove all activation state.
 /// To be called when there are no longer any PIDs attached.
 /// Returns `true` if cleanup ran, `false` if PIDs were found and cleanup was skipped.
 fn cleanup_all(
@@ -452,6 +461,7 @@ fn cleanup_all(
     process_compose_bin: &Path,
     socket_path: impl AsRef<Path>,
     activation_state_dir_path: impl AsRef<Path>,
+    hook_on_deactivate: &HookOnDeactivate,
 ) -> Result<bool> {
     info!("running cleanup");
 
@@ -461,6 +471,15 @@ fn cleanup_all(
         warn!("cleanup called with PIDs still attached, skipping");
         return Ok(false);
     }
+
+    // Run the hook before stopping services, so it can still use them.
+    // The ready activation is still recorded once the last PID has detached.
+    hook_on_deactivate.run(
+        activations_json
+            .ready_start_id()
+            .map(|start_id| start_id.store_path.as_path()),
+    );
+
     let socket_path = socket_path.as_ref();
     if socket_path.exists() {
         if let Err(err) = process_compose_down(process_compose_bin, socket_path) {
@@ -699,7 +718,7 @@ mod test {
         // These are all dummy values
         let coordinator = EventCoordinator::new().unwrap();
         let mut loop_guard = LoopGuard::new(5);
-        let (_attach, project) = test_context(&dot_flox_path, &flox_env.to_string_lossy());
+        let (attach, project) = test_context(&dot_flox_path, &flox_env.to_string_lossy());
 
         stop_process(proc1);
 
@@ -712,6 +731,7 @@ mod test {
             &project.process_compose_bin,
             &project.flox_services_socket,
             &activation_state_directory,
+            &HookOnDeactivate::new(&attach, &project),
         );
 
         // Should return Ok(false) - no cleanup needed, still have active PIDs
@@ -782,7 +802,7 @@ mod test {
         let coordinator = EventCoordinator::new().unwrap();
         // Use a low limit so we can test hitting it
         let mut loop_guard = LoopGuard::new(2);
-        let (_attach, project) = test_context(&dot_flox_path, &flox_env.to_string_lossy());
+        let (attach, project) = test_context(&dot_flox_path, &flox_env.to_string_lossy());
 
         // First call: PID is in state and running, so it will be re-monitored.
         // loop_guard.allow_remonitor(pid) returns true (count=1 < limit=2)
@@ -794,6 +814,7 @@ mod test {
             &project.process_compose_bin,
             &project.flox_services_socket,
             &activation_state_directory,
+            &HookOnDeactivate::new(&attach, &project),
         );
         assert!(matches!(result, Ok(false)), "first call should succeed");
 
@@ -807,6 +828,7 @@ mod test {
             &project.process_compose_bin,
             &project.flox_services_socket,
             &activation_state_directory,
+            &HookOnDeactivate::new(&attach, &project),
         );
         assert!(matches!(result, Ok(false)), "second call should succeed");
 
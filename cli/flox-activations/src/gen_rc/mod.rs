@@ -136,6 +136,9 @@ pub(crate) mod test_helpers {
             disable_hook,
             flox_bin: "/flox".to_string(),
             auto_activate_fish_mode: None,
+            hook_timeout_secs: None,
+            skip_hook_on_activate: false,
+            events_path: None,
         };
         let deleted_var = "DELETED_VAR".to_string();
         let modified_var = "MODIFIED_VAR".to_string();
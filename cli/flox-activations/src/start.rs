@@ -6,12 +6,14 @@
 
 use std::fs::DirBuilder;
 use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::process::CommandExt;
 use std::path::Path;
-use std::process::{Command, Stdio};
-use std::time::Duration;
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
 
 use anyhow::{Result, anyhow, bail};
 use flox_core::activate::context::{ActivateCtx, AttachCtx, AttachProjectCtx};
+use flox_core::activate::events::ActivationEventKind;
 use flox_core::activate::vars::FLOX_ACTIVATIONS_BIN;
 use flox_core::activations::{
     ActivationState,
@@ -22,8 +24,8 @@ use flox_core::activations::{
     write_activations_json,
 };
 use fslock::LockFile;
-use indoc::indoc;
-use nix::sys::signal::{Signal, kill};
+use indoc::{formatdoc, indoc};
+use nix::sys::signal::{Signal, kill, killpg};
 use nix::sys::wait::{WaitPidFlag, WaitStatus, waitpid};
 use nix::unistd::{Pid, getpid};
 use signal_hook::consts::{SIGCHLD, SIGUSR1};
@@ -98,7 +100,11 @@ pub fn start(
         &start_state_dir,
     );
     debug!("spawning activate script: {:?}", start_command);
-    let status = start_command.spawn()?.wait()?;
+    context.emit_event(ActivationEventKind::HookStarted);
+    let status = match context.hook_timeout_secs {
+        Some(secs) => wait_with_timeout(&mut start_command, Duration::from_secs(secs))?,
+        None => start_command.spawn()?.wait()?,
+    };
     if !status.success() {
         // hook.on-activate may have already printed to stderr
         bail!("Running hook.on-activate failed");
@@ -112,6 +118,7 @@ pub fn start(
             - 'exit' commands, which should be replaced with 'return'
             - 'exec' commands, which should be run in a subshell: '(exec command)'"});
     }
+    context.emit_event(ActivationEventKind::HookFinished);
 
     // Re-acquire lock to mark ready
     let (activations_opt, lock) = read_activations_json(activations_json_path)?;
@@ -122,6 +129,43 @@ pub fn start(
     Ok(StartOrAttachResult::Start { start_id })
 }
 
+/// Interval at which to check whether the activate script has exited when
+/// running with a timeout.
+const HOOK_TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Run the activate script, and with it hook.on-activate,
+/// killing it if it runs longer than `timeout`.
+///
+/// The script runs in its own process group so that processes spawned by the
+/// hook are killed along with it.
+fn wait_with_timeout(command: &mut Command, timeout: Duration) -> Result<ExitStatus> {
+    command.process_group(0);
+    let mut child = command.spawn()?;
+    let process_group = Pid::from_raw(child.id() as i32);
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            debug!(pgid = %process_group, "killing activate script after timeout");
+            if let Err(err) = killpg(process_group, Signal::SIGKILL) {
+                error!(%err, "failed to kill hook.on-activate");
+            }
+            let _ = child.wait();
+            bail!(formatdoc! {"
+                Running hook.on-activate timed out after {secs} seconds.
+
+                Review your hook for commands that don't terminate,
+                or increase the limit with '--timeout'.",
+                secs = timeout.as_secs()
+            });
+        }
+        std::thread::sleep(HOOK_TIMEOUT_POLL_INTERVAL);
+    }
+}
+
 /// Start services with a new process-compose instance.
 ///
 /// The CLI has already decided that a new process-compose is needed.
@@ -301,3 +345,25 @@ fn wait_for_executive(child_pid: Pid, mut signals: Signals) -> Result<(), anyhow
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wait_with_timeout_returns_status_of_fast_command() {
+        let mut command = Command::new("true");
+        let status = wait_with_timeout(&mut command, Duration::from_secs(10)).unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn wait_with_timeout_kills_slow_command() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "sleep 60"]);
+        let started = Instant::now();
+        let err = wait_with_timeout(&mut command, Duration::from_millis(200)).unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
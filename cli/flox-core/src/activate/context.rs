@@ -2,8 +2,10 @@ use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
 use shell_gen::ShellWithPath;
+use tracing::warn;
 use uuid::Uuid;
 
+use super::events::{ActivationEventKind, write_activation_event};
 pub use super::mode::ActivateMode;
 
 /// Context needed to attach to a start of an environment
@@ -111,6 +113,35 @@ pub struct ActivateCtx {
     /// Controls how the fish shell hook responds to directory changes.
     #[serde(default)]
     pub auto_activate_fish_mode: Option<AutoActivateFishMode>,
+
+    /// Maximum number of seconds to spend running hook.on-activate.
+    /// None to run it without a time limit.
+    #[serde(default)]
+    pub hook_timeout_secs: Option<u64>,
+
+    /// Whether to skip running hook.on-activate when starting the activation.
+    #[serde(default)]
+    pub skip_hook_on_activate: bool,
+
+    /// File to append lifecycle events of the activation to,
+    /// set by `flox activate --json-events`.
+    #[serde(default)]
+    pub events_path: Option<PathBuf>,
+}
+
+impl ActivateCtx {
+    /// Append an event of `kind` to the events file, if one was requested.
+    ///
+    /// Events are only a side channel for tools wrapping the activation,
+    /// so failing to write one doesn't fail the activation.
+    pub fn emit_event(&self, kind: ActivationEventKind) {
+        let Some(path) = &self.events_path else {
+            return;
+        };
+        if let Err(err) = write_activation_event(path, kind) {
+            warn!(%err, path = %path.display(), "failed to write activation event");
+        }
+    }
 }
 
 /// Fish shell hook mode, matching direnv's `direnv_fish_mode` values.
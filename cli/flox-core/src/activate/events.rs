@@ -0,0 +1,88 @@
+//! Lifecycle events of an activation, written with `flox activate --json-events`.
+//!
+//! Events are appended to a file as newline-delimited JSON,
+//! so that tools wrapping an activation can follow its progress
+//! without parsing the output of the activated shell.
+//! `flox` writes the events up to locking the environment,
+//! `flox-activations` the ones of starting the activation.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// The stage of an activation an [ActivationEvent] marks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivationEventKind {
+    LockStarted,
+    LockFinished,
+    /// The activate script, and with it `hook.on-activate`, started running.
+    /// Only emitted when the activation is started,
+    /// not when attaching to an activation that is already running.
+    HookStarted,
+    HookFinished,
+    ServicesStarted,
+    /// The environment is activated and the shell or command is about to run
+    Ready,
+}
+
+/// A line of the events file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActivationEvent {
+    pub event: ActivationEventKind,
+    /// RFC 3339 time at which the event was emitted
+    pub timestamp: String,
+}
+
+/// Append an event of `kind`, stamped with the current time,
+/// to the events file at `path`.
+///
+/// The file is created if it doesn't exist.
+/// `path` may also be e.g. `/dev/fd/3` to write to an inherited file descriptor.
+pub fn write_activation_event(path: &Path, kind: ActivationEventKind) -> io::Result<()> {
+    let event = ActivationEvent {
+        event: kind,
+        timestamp: OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(io::Error::other)?,
+    };
+    let mut line = serde_json::to_string(&event)?;
+    line.push('\n');
+
+    // Write the line at once so that events of concurrent writers don't interleave
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_appended_as_json_lines() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("events.ndjson");
+
+        write_activation_event(&path, ActivationEventKind::LockStarted).unwrap();
+        write_activation_event(&path, ActivationEventKind::Ready).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let events = contents
+            .lines()
+            .map(|line| serde_json::from_str::<ActivationEvent>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event, ActivationEventKind::LockStarted);
+        assert_eq!(events[1].event, ActivationEventKind::Ready);
+        assert!(
+            events[0].timestamp.ends_with('Z'),
+            "{}",
+            events[0].timestamp
+        );
+        assert!(contents.starts_with(r#"{"event":"lock_started","timestamp":""#));
+    }
+}
@@ -119,7 +119,8 @@ impl FromStr for RemoteEnvironmentRef {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let (owner, name) = s
             .split_once('/')
-            .ok_or(RemoteEnvironmentRefError::InvalidOwner(s.to_string()))?;
+            .filter(|(owner, name)| !owner.is_empty() && !name.is_empty())
+            .ok_or(RemoteEnvironmentRefError::InvalidFormat(s.to_string()))?;
         Ok(Self {
             owner: EnvironmentOwner::from_str(owner)?,
             name: EnvironmentName::from_str(name)?,
@@ -151,6 +152,11 @@ pub enum RemoteEnvironmentRefError {
         "Owner '{0}' is invalid.\nEnvironment owners may only contain alphanumeric characters, '.', '_', and '-'."
     )]
     InvalidOwner(String),
+
+    #[error(
+        "'{0}' is not a valid environment reference.\nEnvironment references have the form '<owner>/<name>', e.g. 'myuser/myenv'."
+    )]
+    InvalidFormat(String),
 }
 
 impl RemoteEnvironmentRef {
@@ -201,3 +207,31 @@ impl ActivateEnvironmentRef {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_owner_and_name() {
+        let env_ref = RemoteEnvironmentRef::from_str("owner/name").unwrap();
+        assert_eq!(env_ref.owner().to_string(), "owner");
+        assert_eq!(env_ref.name().to_string(), "name");
+    }
+
+    #[test]
+    fn rejects_malformed_refs() {
+        for input in ["name", "/name", "owner/", "/"] {
+            let err = RemoteEnvironmentRef::from_str(input).unwrap_err();
+            assert!(
+                matches!(err, RemoteEnvironmentRefError::InvalidFormat(ref s) if s == input),
+                "unexpected error for '{input}': {err:?}"
+            );
+        }
+
+        assert!(matches!(
+            RemoteEnvironmentRef::from_str("owner/name/extra"),
+            Err(RemoteEnvironmentRefError::InvalidName(_))
+        ));
+    }
+}
@@ -11,20 +11,12 @@ use super::{
     shallow_merge_options,
 };
 use crate::parsed::Inner;
-use crate::parsed::common::{
-    ActivateOptions,
-    Allows,
-    Containerize,
-    Hook,
-    Include,
-    Options,
-    SemverOptions,
-    Vars,
-};
+use crate::parsed::common::{ActivateOptions, Containerize, Include, SemverOptions, Vars};
 use crate::parsed::latest::{Install, ManifestLatest, MinimumCliVersion};
 // merge_build operates on the latest schema's Build (which carries
 // `sandbox-allow`), so composing environments preserves the field.
 use crate::parsed::v1_13_0::{Build, Profile, ProfileDeactivate, Services};
+use crate::parsed::v1_14_0::{ActivationProfiles, Allows, Hook, Options};
 
 /// Merges two manifests by applying `manifest2` on top of `manifest1` and
 /// overwriting any conflicts for keys within the top-level of each `ManifestV1`
@@ -100,6 +92,10 @@ impl ShallowMerger {
                     low_priority.on_activate.as_ref(),
                     high_priority.on_activate.as_ref(),
                 ),
+                on_deactivate: append_optional_strings(
+                    low_priority.on_deactivate.as_ref(),
+                    high_priority.on_deactivate.as_ref(),
+                ),
             })),
         }
     }
@@ -198,6 +194,12 @@ impl ShallowMerger {
             high_priority.allow.licenses.as_deref(),
         );
 
+        let (merged_allow_insecure, allow_insecure_warning) = shallow_merge_options(
+            allow_key.push("insecure"),
+            low_priority.allow.insecure.as_deref(),
+            high_priority.allow.insecure.as_deref(),
+        );
+
         let (merged_semver_allow_pre_releases, allow_pre_releases_warning) = shallow_merge_options(
             root_key.extend(["semver", "allow-pre-releases"]),
             low_priority.semver.allow_pre_releases,
@@ -228,6 +230,7 @@ impl ShallowMerger {
                 unfree: merged_allow_unfree,
                 broken: merged_allow_broken,
                 licenses: merged_allow_licenses,
+                insecure: merged_allow_insecure,
             },
             semver: SemverOptions {
                 allow_pre_releases: merged_semver_allow_pre_releases,
@@ -244,6 +247,7 @@ impl ShallowMerger {
                 allow_unfree_warning,
                 allow_broken_warning,
                 allow_licenses_warning,
+                allow_insecure_warning,
                 allow_pre_releases_warning,
                 cuda_detection_warning,
                 systems_warning,
@@ -277,6 +281,21 @@ impl ShallowMerger {
         ))
     }
 
+    /// Activation profiles in `manifest2` replace profiles of the same name
+    /// in `manifest1`.
+    #[instrument(skip_all)]
+    fn merge_activation_profiles(
+        low_priority: &ActivationProfiles,
+        high_priority: &ActivationProfiles,
+    ) -> Result<(ActivationProfiles, Vec<Warning>), MergeError> {
+        let (merged, warnings) = map_union(
+            KeyPath::from_iter(["activation-profiles"]),
+            low_priority.inner(),
+            high_priority.inner(),
+        );
+        Ok((ActivationProfiles(merged), warnings))
+    }
+
     #[instrument(skip_all)]
     fn merge_build(
         low_priority: &Build,
@@ -344,6 +363,12 @@ impl ManifestMergeTrait for ShallowMerger {
             high_priority.profile.as_ref(),
         )?;
 
+        trace!(section = "activation-profiles", "merging manifest section");
+        let (activation_profiles, activation_profiles_warnings) = Self::merge_activation_profiles(
+            &low_priority.activation_profiles,
+            &high_priority.activation_profiles,
+        )?;
+
         trace!(section = "options", "merging manifest section");
         let (options, options_warnings) =
             Self::merge_options(&low_priority.options, &high_priority.options)?;
@@ -367,6 +392,7 @@ impl ManifestMergeTrait for ShallowMerger {
             minimum_cli_version_warnings,
             install_warnings,
             vars_warnings,
+            activation_profiles_warnings,
             options_warnings,
             services_warnings,
             build_warnings,
@@ -383,6 +409,7 @@ impl ManifestMergeTrait for ShallowMerger {
             vars,
             hook,
             profile,
+            activation_profiles,
             options,
             services,
             build,
@@ -402,7 +429,7 @@ mod tests {
     use proptest::prelude::*;
 
     use super::*;
-    use crate::parsed::common::{Allows, ContainerizeConfig, SemverOptions, ServiceDescriptor};
+    use crate::parsed::common::{ContainerizeConfig, SemverOptions, ServiceDescriptor};
     use crate::parsed::latest::ManifestPackageDescriptor;
     // Build merging operates on the latest schema's BuildDescriptor.
     use crate::parsed::v1_13_0::BuildDescriptor;
@@ -517,17 +544,27 @@ mod tests {
         #[test]
         fn merges_hook_section(hook1 in any::<Option<Hook>>(), hook2 in any::<Option<Hook>>()) {
             let merged = ShallowMerger::merge_hook(hook1.as_ref(), hook2.as_ref()).unwrap();
-            let expected = match (hook1.unwrap_or_default().on_activate, hook2.unwrap_or_default().on_activate) {
+            let (hook1, hook2) = (hook1.unwrap_or_default(), hook2.unwrap_or_default());
+            let expected = match (hook1.on_activate, hook2.on_activate) {
+                (Some(h1), Some(h2)) => Some(format!("{h1}\n{h2}")),
+                (Some(h1), None) => Some(h1.clone()),
+                (None, Some(h2)) => Some(h2.clone()),
+                (None, None) => None,
+            };
+            let expected_on_deactivate = match (hook1.on_deactivate, hook2.on_deactivate) {
                 (Some(h1), Some(h2)) => Some(format!("{h1}\n{h2}")),
                 (Some(h1), None) => Some(h1.clone()),
                 (None, Some(h2)) => Some(h2.clone()),
                 (None, None) => None,
             };
-            prop_assert_eq!(merged.unwrap_or_default().on_activate, expected);
+            let merged = merged.unwrap_or_default();
+            prop_assert_eq!(merged.on_activate, expected);
+            prop_assert_eq!(merged.on_deactivate, expected_on_deactivate);
         }
 
         // Ensures that two arbitrary options sections are deep merged with the exception of
-        // `options.systems` and `options.allow.licenses` which should be shallow merged.
+        // `options.systems`, `options.allow.licenses`, and `options.allow.insecure`
+        // which should be shallow merged.
         #[test]
         fn merges_options_section(options1 in any::<Options>(), options2 in any::<Options>()) {
             let (merged, _warnings) = ShallowMerger::merge_options(&options1, &options2).unwrap();
@@ -537,10 +574,16 @@ mod tests {
             } else {
                 options1.allow.licenses.clone()
             };
+            let insecure = if options2.allow.insecure.is_some() {
+                options2.allow.insecure.clone()
+            } else {
+                options1.allow.insecure.clone()
+            };
             let allow = Allows {
                 unfree: options2.allow.unfree.or(options1.allow.unfree),
                 broken: options2.allow.broken.or(options1.allow.broken),
-                licenses
+                licenses,
+                insecure,
             };
             let semver = SemverOptions { allow_pre_releases: options2.semver.allow_pre_releases.or(options1.semver.allow_pre_releases) };
             let cuda_detection = options2.cuda_detection.or(options1.cuda_detection);
@@ -1,3 +1,5 @@
+use flox_core::data::System;
+
 use crate::Parsed;
 use crate::parsed::common;
 
@@ -8,9 +10,9 @@ use crate::parsed::common;
 /// ManifestLatest outside the crate.
 pub(crate) trait CommonFields {
     fn services(&self) -> &common::Services;
-    fn options(&self) -> &common::Options;
+    fn systems(&self) -> Option<&Vec<System>>;
     #[cfg(test)]
-    fn options_mut(&mut self) -> &mut common::Options;
+    fn systems_mut(&mut self) -> &mut Option<Vec<System>>;
 }
 
 impl CommonFields for Parsed {
@@ -21,27 +23,30 @@ impl CommonFields for Parsed {
             Parsed::V1_11_0(m) => &m.services,
             Parsed::V1_12_0(m) => &m.services.service_map,
             Parsed::V1_13_0(m) => &m.services.service_map,
+            Parsed::V1_14_0(m) => &m.services.service_map,
         }
     }
 
-    fn options(&self) -> &common::Options {
+    fn systems(&self) -> Option<&Vec<System>> {
         match self {
-            Parsed::V1(m) => &m.options,
-            Parsed::V1_10_0(m) => &m.options,
-            Parsed::V1_11_0(m) => &m.options,
-            Parsed::V1_12_0(m) => &m.options,
-            Parsed::V1_13_0(m) => &m.options,
+            Parsed::V1(m) => m.options.systems.as_ref(),
+            Parsed::V1_10_0(m) => m.options.systems.as_ref(),
+            Parsed::V1_11_0(m) => m.options.systems.as_ref(),
+            Parsed::V1_12_0(m) => m.options.systems.as_ref(),
+            Parsed::V1_13_0(m) => m.options.systems.as_ref(),
+            Parsed::V1_14_0(m) => m.options.systems.as_ref(),
         }
     }
 
     #[cfg(test)]
-    fn options_mut(&mut self) -> &mut common::Options {
+    fn systems_mut(&mut self) -> &mut Option<Vec<System>> {
         match self {
-            Parsed::V1(m) => &mut m.options,
-            Parsed::V1_10_0(m) => &mut m.options,
-            Parsed::V1_11_0(m) => &mut m.options,
-            Parsed::V1_12_0(m) => &mut m.options,
-            Parsed::V1_13_0(m) => &mut m.options,
+            Parsed::V1(m) => &mut m.options.systems,
+            Parsed::V1_10_0(m) => &mut m.options.systems,
+            Parsed::V1_11_0(m) => &mut m.options.systems,
+            Parsed::V1_12_0(m) => &mut m.options.systems,
+            Parsed::V1_13_0(m) => &mut m.options.systems,
+            Parsed::V1_14_0(m) => &mut m.options.systems,
         }
     }
 }
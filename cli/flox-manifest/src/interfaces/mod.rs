@@ -12,8 +12,8 @@ pub use as_typed_only::AsTypedOnlyManifest;
 pub(crate) use common_fields::CommonFields;
 pub use contents_match::ContentsMatch;
 pub use inner_manifest::{GetInnerManifest, InnerManifest, InnerManifestMarker};
-pub use pkg_lookup::PackageLookup;
 #[allow(unused_imports)]
 pub(crate) use pkg_lookup::impl_pkg_lookup;
+pub use pkg_lookup::{InstallableKind, PackageLookup};
 pub use schema_version::{OriginalSchemaVersion, SchemaVersion};
 pub use write_manifest::{AsWritableManifest, WriteManifest};
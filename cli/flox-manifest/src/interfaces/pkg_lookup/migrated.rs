@@ -1,4 +1,4 @@
-use crate::interfaces::pkg_lookup::PackageLookup;
+use crate::interfaces::pkg_lookup::{InstallableKind, PackageLookup};
 use crate::parsed::latest;
 use crate::{Manifest, Migrated};
 
@@ -20,6 +20,10 @@ impl PackageLookup for Manifest<Migrated> {
         self.inner.migrated_parsed.pkg_descriptor_with_id(id)
     }
 
+    fn installable_kind(&self, id: impl AsRef<str>) -> Option<InstallableKind> {
+        self.inner.migrated_parsed.installable_kind(id)
+    }
+
     fn catalog_descriptor_with_id(&self, id: impl AsRef<str>) -> Option<Self::CatalogDescriptor> {
         self.inner.migrated_parsed.catalog_descriptor_with_id(id)
     }
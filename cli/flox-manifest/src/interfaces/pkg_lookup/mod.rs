@@ -36,6 +36,27 @@ pub use migrated::*;
 
 use crate::ManifestError;
 
+/// The kind of installable an install ID refers to in a manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallableKind {
+    /// A package from a catalog, installed with `pkg-path`
+    Catalog,
+    /// A flake installable, installed with `flake`
+    Flake,
+    /// A store path, installed with `store-path`
+    StorePath,
+}
+
+impl std::fmt::Display for InstallableKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallableKind::Catalog => write!(f, "catalog package"),
+            InstallableKind::Flake => write!(f, "flake"),
+            InstallableKind::StorePath => write!(f, "store path"),
+        }
+    }
+}
+
 /// An interface for looking up packages in a manifest.
 pub trait PackageLookup {
     type PkgDescriptor;
@@ -50,6 +71,10 @@ pub trait PackageLookup {
     /// Locates the package descriptor with the provided install ID.
     fn pkg_descriptor_with_id(&self, id: impl AsRef<str>) -> Option<Self::PkgDescriptor>;
 
+    /// Classifies the package descriptor with the provided install ID,
+    /// returning `None` if there is no package with the install ID.
+    fn installable_kind(&self, id: impl AsRef<str>) -> Option<InstallableKind>;
+
     /// Locates the catalog package descriptor with the provided install ID,
     /// returning `None` if there was a package descriptor of another kind
     /// with the desired install ID.
@@ -140,6 +165,25 @@ macro_rules! impl_pkg_lookup {
                 self.install.inner().get(id.as_ref()).cloned()
             }
 
+            /// Get the kind of the package with the specified install_id.
+            fn installable_kind(
+                &self,
+                id: impl AsRef<str>,
+            ) -> Option<$crate::interfaces::InstallableKind> {
+                let kind = match self.install.inner().get(id.as_ref())? {
+                    concrete::ManifestPackageDescriptor::Catalog(_) => {
+                        $crate::interfaces::InstallableKind::Catalog
+                    },
+                    concrete::ManifestPackageDescriptor::FlakeRef(_) => {
+                        $crate::interfaces::InstallableKind::Flake
+                    },
+                    concrete::ManifestPackageDescriptor::StorePath(_) => {
+                        $crate::interfaces::InstallableKind::StorePath
+                    },
+                };
+                Some(kind)
+            }
+
             /// Get the package descriptor with the specified install_id.
             fn catalog_descriptor_with_id(
                 &self,
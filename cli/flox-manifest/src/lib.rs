@@ -28,7 +28,8 @@ use proptest::prelude::*;
 use schemars::{JsonSchema, schema_for};
 use serde::de::IntoDeserializer;
 use serde::{Deserialize, Serialize};
-use toml_edit::DocumentMut;
+use toml_edit::visit_mut::VisitMut;
+use toml_edit::{DocumentMut, Item, KeyMut, Value};
 
 use crate::interfaces::{
     AsTypedOnlyManifest,
@@ -46,6 +47,7 @@ use crate::parsed::v1_10_0::ManifestV1_10_0;
 use crate::parsed::v1_11_0::ManifestV1_11_0;
 use crate::parsed::v1_12_0::ManifestV1_12_0;
 use crate::parsed::v1_13_0::ManifestV1_13_0;
+use crate::parsed::v1_14_0::ManifestV1_14_0;
 use crate::raw::{
     SyncTypedToRaw,
     TomlEditError,
@@ -122,6 +124,12 @@ pub enum ManifestError {
     #[error("no package named '{0}' in the manifest")]
     PackageNotFound(String),
 
+    #[error("no activation profile named '{name}' in the manifest")]
+    ActivationProfileNotFound {
+        name: String,
+        available: Vec<String>,
+    },
+
     #[error(
         "multiple packages match '{0}', please specify an install id from possible matches: {1:?}"
     )]
@@ -236,13 +244,14 @@ enum Parsed {
     V1_11_0(ManifestV1_11_0),
     V1_12_0(ManifestV1_12_0),
     V1_13_0(ManifestV1_13_0),
+    V1_14_0(ManifestV1_14_0),
 }
 
 impl Parsed {
     /// A helper function for creating a [`Parsed`] from whatever the latest
     /// manifest schema version happens to be.
     pub(crate) fn from_latest(manifest: ManifestLatest) -> Self {
-        Self::V1_13_0(manifest)
+        Self::V1_14_0(manifest)
     }
 
     /// Returns the known schema version of the contained manifest.
@@ -256,6 +265,7 @@ impl Parsed {
             Parsed::V1_11_0(_) => KnownSchemaVersion::V1_11_0,
             Parsed::V1_12_0(_) => KnownSchemaVersion::V1_12_0,
             Parsed::V1_13_0(_) => KnownSchemaVersion::V1_13_0,
+            Parsed::V1_14_0(_) => KnownSchemaVersion::V1_14_0,
         }
     }
 }
@@ -329,6 +339,18 @@ impl Manifest<Init> {
             .migrate(maybe_lockfile)
     }
 
+    /// Render the changes between two versions of a manifest as a unified diff.
+    ///
+    /// Returns an empty string if the contents are identical.
+    pub fn diff_text(old: impl AsRef<str>, new: impl AsRef<str>) -> String {
+        util::unified_diff(
+            old.as_ref(),
+            new.as_ref(),
+            "manifest.toml (current)",
+            "manifest.toml (edited)",
+        )
+    }
+
     /// Read the TOML manifest and JSON lockfile at the provided paths, then
     /// parse a manifest and migrate it to the latest schema version.
     pub fn read_and_migrate(
@@ -480,6 +502,45 @@ impl Manifest<TypedOnly> {
     ) -> Result<Manifest<MigratedTypedOnly>, ManifestError> {
         migrate_typed_only(self, lockfile)
     }
+
+    /// Render the manifest as TOML.
+    ///
+    /// Since a typed manifest doesn't retain the user's formatting,
+    /// inline tables are converted to proper tables so that the output
+    /// resembles a manifest a user would write.
+    pub fn to_toml_string(&self) -> Result<String, ManifestError> {
+        // A visitor that converts inline tables to proper tables
+        // Nested tables are rendered as `dotted` tables.
+        // The default behavior when instantiating with `Visitor::new_for_document`,
+        // is to render toplevel tables as non-dotted, sections.
+        struct Visitor {
+            dotted: bool,
+        }
+        impl Visitor {
+            fn new_for_document() -> Self {
+                Visitor { dotted: false }
+            }
+        }
+        impl VisitMut for Visitor {
+            fn visit_table_like_kv_mut(&mut self, _key: KeyMut<'_>, node: &mut Item) {
+                if let Item::Value(Value::InlineTable(inline_table)) = node {
+                    let mut table = std::mem::take(inline_table).into_table();
+                    table.set_implicit(true);
+                    table.set_dotted(self.dotted);
+                    toml_edit::visit_mut::visit_table_mut(
+                        &mut Visitor { dotted: true },
+                        &mut table,
+                    );
+                    *node = Item::Table(table);
+                }
+            }
+        }
+
+        let mut document = toml_edit::ser::to_document(self).map_err(ManifestError::Serialize)?;
+        toml_edit::visit_mut::visit_document_mut(&mut Visitor::new_for_document(), &mut document);
+
+        Ok(document.to_string())
+    }
 }
 
 impl<S: ManifestState> Manifest<S> {
@@ -513,6 +574,11 @@ impl<S: ManifestState> Manifest<S> {
                     .map_err(ManifestError::Invalid)?;
                 Ok(Parsed::V1_13_0(manifest))
             },
+            KnownSchemaVersion::V1_14_0 => {
+                let manifest = toml_edit::de::from_document::<ManifestV1_14_0>(toml.clone())
+                    .map_err(ManifestError::Invalid)?;
+                Ok(Parsed::V1_14_0(manifest))
+            },
         }
     }
 }
@@ -601,6 +667,16 @@ impl<'de> Deserialize<'de> for Manifest<TypedOnly> {
                     },
                 })
             },
+            KnownSchemaVersion::V1_14_0 => {
+                let d = untyped.into_deserializer();
+                let manifest = ManifestV1_14_0::deserialize(d)
+                    .map_err(|err| serde::de::Error::custom(err.to_string()))?;
+                Ok(Manifest {
+                    inner: TypedOnly {
+                        parsed: Parsed::V1_14_0(manifest),
+                    },
+                })
+            },
         }
     }
 }
@@ -37,6 +37,7 @@ impl Compose {
                 crate::Parsed::V1_11_0(manifest) => manifest.resolve_install_id(package, version),
                 crate::Parsed::V1_12_0(manifest) => manifest.resolve_install_id(package, version),
                 crate::Parsed::V1_13_0(manifest) => manifest.resolve_install_id(package, version),
+                crate::Parsed::V1_14_0(manifest) => manifest.resolve_install_id(package, version),
             };
             match res {
                 Ok(_) => return Ok(Some(include.clone())),
@@ -48,6 +49,42 @@ impl Compose {
 
         Ok(None)
     }
+
+    /// Detect which included environment provides the package with
+    /// `install_id` in the merged manifest.
+    ///
+    /// Returns `None` if the composing manifest installs the package itself,
+    /// since it takes precedence over all includes,
+    /// or if no included environment installs it.
+    pub fn include_providing_install_id(&self, install_id: &str) -> Option<&LockedInclude> {
+        let installs = |manifest: &Manifest<TypedOnly>| match &manifest.inner.parsed {
+            crate::Parsed::V1(manifest) => manifest.pkg_descriptor_with_id(install_id).is_some(),
+            crate::Parsed::V1_10_0(manifest) => {
+                manifest.pkg_descriptor_with_id(install_id).is_some()
+            },
+            crate::Parsed::V1_11_0(manifest) => {
+                manifest.pkg_descriptor_with_id(install_id).is_some()
+            },
+            crate::Parsed::V1_12_0(manifest) => {
+                manifest.pkg_descriptor_with_id(install_id).is_some()
+            },
+            crate::Parsed::V1_13_0(manifest) => {
+                manifest.pkg_descriptor_with_id(install_id).is_some()
+            },
+            crate::Parsed::V1_14_0(manifest) => {
+                manifest.pkg_descriptor_with_id(install_id).is_some()
+            },
+        };
+
+        if installs(&self.composer) {
+            return None;
+        }
+        // Reverse of merge order so that we return the highest priority match.
+        self.include
+            .iter()
+            .rev()
+            .find(|include| installs(&include.manifest))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
@@ -60,12 +97,77 @@ pub struct LockedInclude {
     )]
     pub name: String,
     pub descriptor: IncludeDescriptor,
-    // TODO: Record generation if/when:
-    // 1. We have a need for it in presentation, e.g.
-    //   - https://github.com/flox/flox/issues/2948
-    // 2. Generations work has settled:
-    //   - https://github.com/flox/product/pull/881
-    //   - https://github.com/flox/product/pull/891
-    // 3. We've exposed it from `RemoteEnvironment`/`ManagedEnvironment`
-    // pub remote: Option<Generation>,
+    /// The generation of the included environment at the time it was fetched.
+    /// Only recorded for managed and remote environments.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(
+        any(test, feature = "tests"),
+        proptest(strategy = "proptest::option::of(0..10u64)")
+    )]
+    pub generation: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::AsTypedOnlyManifest;
+    use crate::lockfile::test_helpers::fake_catalog_package_lock;
+    use crate::parsed::Inner;
+    use crate::parsed::latest::ManifestLatest;
+
+    fn locked_include(name: &str, manifest: &ManifestLatest) -> LockedInclude {
+        LockedInclude {
+            manifest: manifest.as_typed_only(),
+            name: name.to_string(),
+            descriptor: IncludeDescriptor::Local {
+                dir: name.into(),
+                name: None,
+            },
+            generation: None,
+        }
+    }
+
+    #[test]
+    fn include_providing_install_id_prefers_composer_then_last_include() {
+        let (foo_iid, foo_descriptor, _) = fake_catalog_package_lock("foo", None);
+        let (bar_iid, bar_descriptor, _) = fake_catalog_package_lock("bar", None);
+
+        let mut composer = ManifestLatest::default();
+        composer
+            .install
+            .inner_mut()
+            .insert(foo_iid.clone(), foo_descriptor.clone());
+        let mut first = ManifestLatest::default();
+        first
+            .install
+            .inner_mut()
+            .insert(foo_iid.clone(), foo_descriptor);
+        first
+            .install
+            .inner_mut()
+            .insert(bar_iid.clone(), bar_descriptor.clone());
+        let mut second = ManifestLatest::default();
+        second
+            .install
+            .inner_mut()
+            .insert(bar_iid.clone(), bar_descriptor);
+
+        let compose = Compose {
+            composer: composer.as_typed_only(),
+            include: vec![
+                locked_include("first", &first),
+                locked_include("second", &second),
+            ],
+            warnings: vec![],
+        };
+
+        assert_eq!(compose.include_providing_install_id(&foo_iid), None);
+        assert_eq!(
+            compose
+                .include_providing_install_id(&bar_iid)
+                .map(|include| include.name.as_str()),
+            Some("second")
+        );
+        assert_eq!(compose.include_providing_install_id("baz"), None);
+    }
 }
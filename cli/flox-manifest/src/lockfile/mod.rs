@@ -16,16 +16,21 @@ pub use store_path::LockedPackageStorePath;
 
 pub type FlakeRef = Value;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 use std::fs;
 use std::str::FromStr;
 
 use flox_core::Version;
 
-use crate::interfaces::{AsLatestSchema, PackageLookup, SchemaVersion};
+use crate::interfaces::{AsLatestSchema, AsTypedOnlyManifest, PackageLookup, SchemaVersion};
 use crate::parsed::common::KnownSchemaVersion;
-use crate::parsed::latest::{PackageDescriptorCatalog, PackageDescriptorFlake};
+use crate::parsed::latest::{
+    ManifestPackageDescriptor,
+    PackageDescriptorCatalog,
+    PackageDescriptorFlake,
+};
+use crate::raw::DEFAULT_SYSTEMS_STR;
 use crate::{Manifest, ManifestError, MigratedTypedOnly, TypedOnly};
 
 pub static LOCKFILE_FILENAME: &str = "manifest.lock";
@@ -48,6 +53,20 @@ pub enum LockfileError {
     Manifest(#[from] ManifestError),
 }
 
+/// Inconsistencies between the locked manifest and the locked packages of a
+/// lockfile, see [Lockfile::validate_consistency].
+#[derive(Debug, thiserror::Error)]
+pub enum LockfileConsistencyError {
+    #[error("corrupt lockfile; couldn't find package descriptor for locked install_id '{0}'")]
+    MissingPackageDescriptor(String),
+
+    #[error("corrupt lockfile; package '{install_id}' is not locked for '{system}'")]
+    MissingLockedPackage { install_id: String, system: System },
+
+    #[error(transparent)]
+    Manifest(#[from] ManifestError),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Input {
     pub from: FlakeRef,
@@ -153,6 +172,63 @@ impl Lockfile {
             .collect::<Result<Vec<_>, LockfileError>>()
     }
 
+    /// Check that the locked packages match the package descriptors of the
+    /// locked manifest.
+    ///
+    /// Every locked package needs a descriptor of the same kind with its
+    /// install_id, and every descriptor needs a locked package for each of its
+    /// systems, which default to `options.systems` of the manifest.
+    /// Lockfiles written by flox are always consistent,
+    /// so this catches lockfiles that were corrupted or edited by hand
+    /// before they cause less obvious errors, e.g. when listing packages.
+    pub fn validate_consistency(&self) -> Result<(), LockfileConsistencyError> {
+        let manifest = self.migrated_manifest()?;
+        let manifest = manifest.as_latest_schema();
+
+        let mut locked = BTreeSet::new();
+        for package in &self.packages {
+            let install_id = package.install_id();
+            let descriptor = manifest.pkg_descriptor_with_id(install_id);
+            let matches_kind = match (package, &descriptor) {
+                (LockedPackage::Catalog(_), Some(ManifestPackageDescriptor::Catalog(_)))
+                | (LockedPackage::Flake(_), Some(ManifestPackageDescriptor::FlakeRef(_)))
+                | (LockedPackage::StorePath(_), Some(ManifestPackageDescriptor::StorePath(_))) => {
+                    true
+                },
+                _ => false,
+            };
+            if !matches_kind {
+                return Err(LockfileConsistencyError::MissingPackageDescriptor(
+                    install_id.to_string(),
+                ));
+            }
+            locked.insert((install_id, package.system().as_str()));
+        }
+
+        let manifest_systems = manifest
+            .options
+            .systems
+            .as_deref()
+            .unwrap_or(&*DEFAULT_SYSTEMS_STR);
+        for (install_id, descriptor) in manifest.install.inner() {
+            let systems = match descriptor {
+                ManifestPackageDescriptor::Catalog(descriptor) => descriptor.systems.as_deref(),
+                ManifestPackageDescriptor::FlakeRef(descriptor) => descriptor.systems.as_deref(),
+                ManifestPackageDescriptor::StorePath(descriptor) => descriptor.systems.as_deref(),
+            };
+            for system in systems.unwrap_or(manifest_systems) {
+                if !locked.contains(&(install_id.as_str(), system.as_str())) {
+                    return Err(LockfileConsistencyError::MissingLockedPackage {
+                        install_id: install_id.clone(),
+                        system: system.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// The manifest the user edits (i.e. not merged)
     pub fn user_manifest(&self) -> &Manifest<TypedOnly> {
         match &self.compose {
@@ -161,6 +237,15 @@ impl Lockfile {
         }
     }
 
+    /// Render the user's manifest as TOML.
+    ///
+    /// For composed environments this is the composing environment's
+    /// manifest rather than the merged manifest, i.e. the manifest a user
+    /// would edit.
+    pub fn user_manifest_string(&self) -> Result<String, LockfileError> {
+        Ok(self.user_manifest().to_toml_string()?)
+    }
+
     /// Returns true if the provided manifest matches the serialized form of the
     /// user's manifest (e.g. it doesn't check whether there are new comments
     /// or other formatting changes in the provided manifest).
@@ -168,11 +253,43 @@ impl Lockfile {
         manifest == self.user_manifest()
     }
 
+    /// Returns a copy of this lockfile with the activation profile `name`
+    /// layered onto the locked (merged) manifest.
+    ///
+    /// Building the returned lockfile produces an environment with the
+    /// profile's `vars` and `hook` applied.
+    pub fn with_activation_profile(&self, name: &str) -> Result<Lockfile, LockfileError> {
+        let manifest = self.migrated_manifest()?;
+        let layered = manifest.as_latest_schema().with_activation_profile(name)?;
+        Ok(Lockfile {
+            manifest: layered.as_typed_only(),
+            ..self.clone()
+        })
+    }
+
     pub fn locked_package_with_id(&self, id: impl AsRef<str>) -> Option<&LockedPackage> {
         self.packages
             .iter()
             .find(|pkg| pkg.install_id() == id.as_ref())
     }
+
+    /// The locked URLs of all flake packages across all systems,
+    /// i.e. the flake revisions the environment pulls from.
+    ///
+    /// Each URL is listed once, in the order it first appears in the lockfile.
+    pub fn flake_inputs(&self) -> Vec<&str> {
+        let mut flake_inputs = Vec::new();
+        for package in &self.packages {
+            let LockedPackage::Flake(flake) = package else {
+                continue;
+            };
+            let locked_url = flake.locked_installable.locked_url.as_str();
+            if !flake_inputs.contains(&locked_url) {
+                flake_inputs.push(locked_url);
+            }
+        }
+        flake_inputs
+    }
 }
 
 impl FromStr for Lockfile {
@@ -284,6 +401,16 @@ pub enum PackageToList {
     StorePath(LockedPackageStorePath),
 }
 
+impl PackageToList {
+    pub fn install_id(&self) -> &str {
+        match self {
+            PackageToList::Catalog(_, pkg) => &pkg.install_id,
+            PackageToList::Flake(_, pkg) => &pkg.install_id,
+            PackageToList::StorePath(pkg) => &pkg.install_id,
+        }
+    }
+}
+
 #[cfg(any(test, feature = "tests"))]
 pub mod test_helpers {
     use std::path::Path;
@@ -314,6 +441,7 @@ pub mod test_helpers {
             version: None,
             priority: None,
             outputs: None,
+            allow_missing_builds: None,
         }
         .into();
 
@@ -469,6 +597,7 @@ pub mod test_helpers {
             version: None,
             systems: None,
             outputs: None,
+            allow_missing_builds: None,
         });
 
         locked_package.attr_path = "hello".to_string();
@@ -707,6 +836,81 @@ pub(crate) mod tests {
         assert_eq!(&actual, &expected);
     }
 
+    #[test]
+    fn validate_consistency_checks_descriptors_and_systems() {
+        let (foo_iid, foo_descriptor, foo_locked) = fake_catalog_package_lock("foo", None);
+        let (bar_iid, bar_descriptor, _) = fake_flake_installable_lock("bar");
+
+        let mut manifest = ManifestLatest::default();
+        manifest
+            .install
+            .inner_mut()
+            .insert(foo_iid.clone(), foo_descriptor);
+        let mut lockfile = Lockfile {
+            version: Version::<1>,
+            manifest: manifest.clone().as_typed_only(),
+            packages: vec![foo_locked.clone().into()],
+            compose: None,
+        };
+        lockfile.validate_consistency().unwrap();
+
+        // locked package without a descriptor
+        let (_, _, baz_locked) = fake_catalog_package_lock("baz", None);
+        lockfile.packages.push(baz_locked.into());
+        assert!(matches!(
+            lockfile.validate_consistency(),
+            Err(LockfileConsistencyError::MissingPackageDescriptor(iid)) if iid == "baz_install_id"
+        ));
+
+        // descriptor of a different kind than the locked package
+        let mut mismatched = foo_locked.clone();
+        mismatched.install_id = bar_iid.clone();
+        manifest
+            .install
+            .inner_mut()
+            .insert(bar_iid.clone(), bar_descriptor.into());
+        lockfile.manifest = manifest.clone().as_typed_only();
+        lockfile.packages = vec![foo_locked.into(), mismatched.into()];
+        assert!(matches!(
+            lockfile.validate_consistency(),
+            Err(LockfileConsistencyError::MissingPackageDescriptor(iid)) if iid == bar_iid
+        ));
+
+        // descriptor that isn't locked for one of the manifest's systems
+        lockfile.packages.pop();
+        assert!(matches!(
+            lockfile.validate_consistency(),
+            Err(LockfileConsistencyError::MissingLockedPackage { install_id, .. })
+                if install_id == bar_iid
+        ));
+    }
+
+    #[test]
+    fn flake_inputs_are_deduplicated_across_systems() {
+        let (_, _, foo_locked) = fake_flake_installable_lock("foo");
+        let mut foo_linux_locked = foo_locked.clone();
+        foo_linux_locked.locked_installable.system = PackageSystem::Aarch64Linux.to_string();
+        let (_, _, bar_locked) = fake_flake_installable_lock("bar");
+        let (_, _, catalog_locked) = fake_catalog_package_lock("baz", None);
+
+        let locked = Lockfile {
+            version: Version::<1>,
+            manifest: ManifestLatest::default().as_typed_only(),
+            packages: vec![
+                foo_locked.into(),
+                catalog_locked.into(),
+                bar_locked.into(),
+                foo_linux_locked.into(),
+            ],
+            compose: None,
+        };
+
+        assert_eq!(locked.flake_inputs(), vec![
+            "github:nowhere/exciting/affeaffeaffeaffeaffeaffeaffeaffeaffeaffe#foo",
+            "github:nowhere/exciting/affeaffeaffeaffeaffeaffeaffeaffeaffeaffe#bar",
+        ]);
+    }
+
     #[test]
     fn test_list_packages_store_path() {
         let (foo_iid, foo_descriptor, foo_locked) = fake_store_path_lock("foo");
@@ -741,4 +945,36 @@ pub(crate) mod tests {
 
         assert_eq!(&actual, &expected);
     }
+
+    #[test]
+    fn user_manifest_string_renders_composer_manifest() {
+        let (foo_iid, foo_descriptor, _) = fake_catalog_package_lock("foo", None);
+        let (bar_iid, bar_descriptor, _) = fake_catalog_package_lock("bar", None);
+
+        let mut composer = ManifestLatest::default();
+        composer
+            .install
+            .inner_mut()
+            .insert(foo_iid.clone(), foo_descriptor.clone());
+        let mut merged = composer.clone();
+        merged
+            .install
+            .inner_mut()
+            .insert(bar_iid.clone(), bar_descriptor);
+
+        let locked = Lockfile {
+            version: Version::<1>,
+            manifest: merged.as_typed_only(),
+            packages: vec![],
+            compose: Some(Compose {
+                composer: composer.as_typed_only(),
+                include: vec![],
+                warnings: vec![],
+            }),
+        };
+
+        let rendered = locked.user_manifest_string().unwrap();
+        assert!(rendered.contains(&foo_iid));
+        assert!(!rendered.contains(&bar_iid));
+    }
 }
@@ -3,6 +3,7 @@ use crate::lockfile::Lockfile;
 use crate::migrate::v1_10_0_to_v1_11_0::migrate_manifest_v1_10_0_to_v1_11_0;
 use crate::migrate::v1_11_0_to_v1_12_0::migrate_manifest_v1_11_0_to_v1_12_0;
 use crate::migrate::v1_12_0_to_v1_13_0::migrate_manifest_v1_12_0_to_v1_13_0;
+use crate::migrate::v1_13_0_to_v1_14_0::migrate_manifest_v1_13_0_to_v1_14_0;
 use crate::migrate::v1_to_v1_10_0::migrate_manifest_v1_to_v1_10_0;
 use crate::parsed::common::KnownSchemaVersion;
 use crate::raw::SyncTypedToRaw;
@@ -11,6 +12,7 @@ use crate::{Manifest, ManifestError, Migrated, MigratedTypedOnly, Parsed, TypedO
 mod v1_10_0_to_v1_11_0;
 mod v1_11_0_to_v1_12_0;
 mod v1_12_0_to_v1_13_0;
+mod v1_13_0_to_v1_14_0;
 mod v1_to_v1_10_0;
 
 #[derive(Debug, thiserror::Error)]
@@ -62,11 +64,15 @@ pub(crate) fn migrate_typed_only(
                 let migrated = migrate_manifest_v1_12_0_to_v1_13_0(manifest_v1_12_0)?;
                 inner = Parsed::V1_13_0(migrated);
             },
-            Parsed::V1_13_0(manifest_v1_13_0) => break Parsed::from_latest(manifest_v1_13_0),
+            Parsed::V1_13_0(manifest_v1_13_0) => {
+                let migrated = migrate_manifest_v1_13_0_to_v1_14_0(manifest_v1_13_0)?;
+                inner = Parsed::V1_14_0(migrated);
+            },
+            Parsed::V1_14_0(manifest_v1_14_0) => break Parsed::from_latest(manifest_v1_14_0),
         }
     };
     debug_assert_eq!(inner.schema_version(), KnownSchemaVersion::latest());
-    let Parsed::V1_13_0(migrated_manifest) = inner else {
+    let Parsed::V1_14_0(migrated_manifest) = inner else {
         unreachable!("already checked that manifest was latest schema version")
     };
     let migrated = Manifest {
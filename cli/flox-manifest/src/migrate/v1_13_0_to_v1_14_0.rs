@@ -0,0 +1,59 @@
+use crate::migrate::MigrationError;
+use crate::parsed::v1_13_0::ManifestV1_13_0;
+use crate::parsed::v1_14_0::ManifestV1_14_0;
+
+/// Migrate a v1.13.0 manifest to a v1.14.0 manifest.
+///
+/// This is a lossless migration: V1_14_0 adds the optional
+/// `[activation-profiles]` section, `hook.on-deactivate`,
+/// `options.allow.insecure` and `allow-missing-builds` on catalog package
+/// descriptors. All V1_13_0 manifests are valid V1_14_0 manifests without
+/// activation profiles and with all of the new fields set to `None`.
+pub(crate) fn migrate_manifest_v1_13_0_to_v1_14_0(
+    manifest: ManifestV1_13_0,
+) -> Result<ManifestV1_14_0, MigrationError> {
+    Ok(ManifestV1_14_0 {
+        schema_version: "1.14.0".to_string(),
+        minimum_cli_version: manifest.minimum_cli_version,
+        install: manifest.install.into(),
+        vars: manifest.vars,
+        hook: manifest.hook.map(Into::into),
+        profile: manifest.profile,
+        activation_profiles: Default::default(),
+        options: manifest.options.into(),
+        services: manifest.services,
+        build: manifest.build,
+        containerize: manifest.containerize,
+        include: manifest.include,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn migration_is_lossless_for_any_manifest(manifest in any::<ManifestV1_13_0>()) {
+            let migrated = migrate_manifest_v1_13_0_to_v1_14_0(manifest.clone()).unwrap();
+
+            let expected = ManifestV1_14_0 {
+                schema_version: "1.14.0".to_string(),
+                minimum_cli_version: manifest.minimum_cli_version,
+                install: manifest.install.into(),
+                vars: manifest.vars,
+                hook: manifest.hook.map(Into::into),
+                profile: manifest.profile,
+                activation_profiles: Default::default(),
+                options: manifest.options.into(),
+                services: manifest.services,
+                build: manifest.build,
+                containerize: manifest.containerize,
+                include: manifest.include,
+            };
+            prop_assert_eq!(migrated, expected);
+        }
+    }
+}
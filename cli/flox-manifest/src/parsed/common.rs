@@ -55,12 +55,13 @@ pub enum KnownSchemaVersion {
     V1_11_0,
     V1_12_0,
     V1_13_0,
+    V1_14_0,
 }
 
 impl KnownSchemaVersion {
     /// Returns the latest schema version.
     pub fn latest() -> Self {
-        KnownSchemaVersion::V1_13_0
+        KnownSchemaVersion::V1_14_0
     }
 
     /// Returns the oldest supported schema version.
@@ -76,6 +77,7 @@ impl KnownSchemaVersion {
             KnownSchemaVersion::V1_11_0,
             KnownSchemaVersion::V1_12_0,
             KnownSchemaVersion::V1_13_0,
+            KnownSchemaVersion::V1_14_0,
         ]
         .into_iter()
     }
@@ -101,6 +103,7 @@ impl TryFrom<VersionKind> for KnownSchemaVersion {
                 "1.11.0" => Ok(KnownSchemaVersion::V1_11_0),
                 "1.12.0" => Ok(KnownSchemaVersion::V1_12_0),
                 "1.13.0" => Ok(KnownSchemaVersion::V1_13_0),
+                "1.14.0" => Ok(KnownSchemaVersion::V1_14_0),
                 _ => Err(ManifestError::InvalidSchemaVersion(v.to_string())),
             },
         }
@@ -115,6 +118,7 @@ impl std::fmt::Display for KnownSchemaVersion {
             KnownSchemaVersion::V1_11_0 => write!(f, "1.11.0"),
             KnownSchemaVersion::V1_12_0 => write!(f, "1.12.0"),
             KnownSchemaVersion::V1_13_0 => write!(f, "1.13.0"),
+            KnownSchemaVersion::V1_14_0 => write!(f, "1.14.0"),
         }
     }
 }
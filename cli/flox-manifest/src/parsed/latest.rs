@@ -1,20 +1,18 @@
 use crate::interfaces::{AsLatestSchema, AsTypedOnlyManifest};
 use crate::lockfile::Lockfile;
 use crate::parsed::common::KnownSchemaVersion;
-pub use crate::parsed::v1_10_0::{
-    AllSentinel,
-    Install,
-    ManifestPackageDescriptor,
-    PackageDescriptorCatalog,
-    PackageDescriptorFlake,
-    SelectedOutputs,
-};
+pub use crate::parsed::v1_10_0::{AllSentinel, PackageDescriptorFlake, SelectedOutputs};
 pub use crate::parsed::v1_11_0::MinimumCliVersion;
 // BuildSandbox is version-specific from V1_13_0 on (it adds `warn`/`enforce`),
 // so the latest schema re-exports that copy rather than common's.
 pub use crate::parsed::v1_13_0::BuildSandbox;
+// Options are version-specific from V1_14_0 on (`allow.insecure` was added).
+pub use crate::parsed::v1_14_0::{Allows, Options};
+// Catalog descriptors are version-specific from V1_14_0 on
+// (`allow-missing-builds` was added).
+pub use crate::parsed::v1_14_0::{Install, ManifestPackageDescriptor, PackageDescriptorCatalog};
 use crate::{Manifest, ManifestError, TypedOnly};
-pub type ManifestLatest = crate::parsed::v1_13_0::ManifestV1_13_0;
+pub type ManifestLatest = crate::parsed::v1_14_0::ManifestV1_14_0;
 
 impl ManifestLatest {
     /// Try to return a manifest in its original schema
@@ -67,6 +65,15 @@ impl ManifestLatest {
                 untyped
             },
             KnownSchemaVersion::V1_13_0 => {
+                let mut untyped =
+                    serde_json::to_value(self).map_err(ManifestError::SerializeJson)?;
+                let map = untyped
+                    .as_object_mut()
+                    .expect("all valid manifests should serialize to JSON objects");
+                map.insert("schema-version".into(), "1.13.0".into());
+                untyped
+            },
+            KnownSchemaVersion::V1_14_0 => {
                 return Ok(Some(self.as_typed_only()));
             },
         };
@@ -104,6 +111,7 @@ impl ManifestLatest {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::path::PathBuf;
 
     use flox_core::data::environment_ref::RemoteEnvironmentRef;
@@ -114,17 +122,13 @@ mod tests {
 
     use super::*;
     use crate::ManifestError;
-    use crate::interfaces::{PackageLookup, SchemaVersion};
+    use crate::interfaces::{InstallableKind, PackageLookup, SchemaVersion};
     use crate::parsed::Inner;
-    use crate::parsed::common::{
-        BuildVersion,
-        Hook,
-        IncludeDescriptor,
-        PackageDescriptorStorePath,
-    };
+    use crate::parsed::common::{BuildVersion, IncludeDescriptor, PackageDescriptorStorePath};
     // ManifestLatest's build section is the version-specific Build (with
     // `sandbox-allow`), so build assertions use the latest schema's types.
     use crate::parsed::v1_13_0::{Build, BuildDescriptor, Profile, ProfileDeactivate};
+    use crate::parsed::v1_14_0::Hook;
     use crate::test_helpers::{with_latest_schema, with_schema};
 
     #[test]
@@ -183,6 +187,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn hook_on_deactivate_rejected_by_v1_13_0_schema() {
+        let manifest = with_schema(KnownSchemaVersion::V1_13_0, indoc! {r#"
+            [hook]
+            on-deactivate = "echo bye"
+        "#});
+
+        let err = Manifest::parse_toml_typed(&manifest)
+            .expect_err("'hook.on-deactivate' should be rejected by the v1.13.0 schema");
+
+        let ManifestError::Invalid(err) = err else {
+            panic!("expected ManifestError::Invalid, got: {err:?}");
+        };
+        assert!(
+            err.message()
+                .starts_with("unknown field `on-deactivate`, expected"),
+            "unexpected error message: {err}",
+        );
+    }
+
+    #[test]
+    fn allow_missing_builds_rejected_by_v1_13_0_schema() {
+        let manifest = with_schema(KnownSchemaVersion::V1_13_0, indoc! {r#"
+            [install]
+            hello.pkg-path = "hello"
+            hello.allow-missing-builds = true
+        "#});
+
+        let err = Manifest::parse_toml_typed(&manifest)
+            .expect_err("'allow-missing-builds' should be rejected by the v1.13.0 schema");
+
+        let ManifestError::Invalid(err) = err else {
+            panic!("expected ManifestError::Invalid, got: {err:?}");
+        };
+        assert!(
+            err.message()
+                .starts_with("Expected either a catalog package descriptor"),
+            "unexpected error message: {err}",
+        );
+    }
+
+    #[test]
+    fn activation_profile_layers_vars_and_hook() {
+        let manifest = with_latest_schema(indoc! {r#"
+            [vars]
+            FOO = "base"
+            BAR = "base"
+
+            [hook]
+            on-activate = "echo base"
+
+            [activation-profiles.ci.vars]
+            FOO = "ci"
+            CI = "1"
+
+            [activation-profiles.ci.hook]
+            on-activate = "echo ci"
+
+            [activation-profiles.dev.vars]
+            DEV = "1"
+        "#});
+        let parsed = toml_edit::de::from_str::<ManifestLatest>(&manifest).unwrap();
+
+        let ci = parsed.with_activation_profile("ci").unwrap();
+        assert_eq!(
+            ci.vars.inner(),
+            &BTreeMap::from_iter([
+                ("BAR".to_string(), "base".to_string()),
+                ("CI".to_string(), "1".to_string()),
+                ("FOO".to_string(), "ci".to_string()),
+            ])
+        );
+        assert_eq!(
+            ci.hook,
+            Some(Hook {
+                on_activate: Some("echo ci".to_string()),
+                on_deactivate: None,
+            })
+        );
+
+        // profiles without a hook keep the base hook
+        let dev = parsed.with_activation_profile("dev").unwrap();
+        assert_eq!(dev.hook, parsed.hook);
+        assert_eq!(dev.vars.inner().get("DEV"), Some(&"1".to_string()));
+
+        let err = parsed.with_activation_profile("staging").unwrap_err();
+        let ManifestError::ActivationProfileNotFound { name, available } = err else {
+            panic!("expected ManifestError::ActivationProfileNotFound, got: {err:?}");
+        };
+        assert_eq!(name, "staging");
+        assert_eq!(available, vec!["ci".to_string(), "dev".to_string()]);
+    }
+
+    #[test]
+    fn activation_profiles_rejected_by_v1_13_0_schema() {
+        let manifest = with_schema(KnownSchemaVersion::V1_13_0, indoc! {r#"
+            [activation-profiles.ci.vars]
+            CI = "1"
+        "#});
+
+        Manifest::parse_toml_typed(&manifest)
+            .expect_err("'activation-profiles' should be rejected by the v1.13.0 schema");
+    }
+
     #[test]
     fn profile_deactivate_parses_with_latest_schema() {
         let manifest = with_latest_schema(indoc! {r#"
@@ -249,7 +357,7 @@ mod tests {
             .as_maybe_backwards_compatible(KnownSchemaVersion::V1_12_0, None)
             .unwrap();
 
-        assert_eq!(compat.get_schema_version(), KnownSchemaVersion::V1_13_0);
+        assert_eq!(compat.get_schema_version(), KnownSchemaVersion::latest());
     }
 
     // FIXME
@@ -547,12 +655,39 @@ mod tests {
                     version: None,
                     systems: None,
                     outputs: None,
+                    allow_missing_builds: None,
                 }),
             );
         }
 
         typed_manifest_mock
     }
+
+    #[test]
+    fn installable_kind_classifies_each_descriptor_type() {
+        let manifest = with_latest_schema(indoc! {r#"
+            [install]
+            hello.pkg-path = "hello"
+            jobs.flake = "github:nix-community/nix-eval-jobs"
+            local.store-path = "/nix/store/00000000000000000000000000000000-local"
+        "#});
+        let manifest = toml_edit::de::from_str::<ManifestLatest>(&manifest).unwrap();
+
+        assert_eq!(
+            manifest.installable_kind("hello"),
+            Some(InstallableKind::Catalog)
+        );
+        assert_eq!(
+            manifest.installable_kind("jobs"),
+            Some(InstallableKind::Flake)
+        );
+        assert_eq!(
+            manifest.installable_kind("local"),
+            Some(InstallableKind::StorePath)
+        );
+        assert_eq!(manifest.installable_kind("missing"), None);
+    }
+
     /// Return the install ID if it matches the user input
     #[test]
     fn test_resolve_install_id_by_install_id() {
@@ -641,6 +776,7 @@ mod tests {
             version: None,
             systems: None,
             outputs: None,
+            allow_missing_builds: None,
         })
     }
 
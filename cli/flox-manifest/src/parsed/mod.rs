@@ -5,6 +5,7 @@ pub mod v1_10_0;
 pub mod v1_11_0;
 pub mod v1_12_0;
 pub mod v1_13_0;
+pub mod v1_14_0;
 
 /// An interface codifying how to access types that are just semantic wrappers
 /// around inner types. This impl may be generated with a macro.
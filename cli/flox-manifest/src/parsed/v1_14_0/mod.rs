@@ -0,0 +1,395 @@
+use std::collections::BTreeMap;
+
+use flox_core::data::System;
+#[cfg(any(test, feature = "tests"))]
+use flox_test_utils::proptest::{
+    alphanum_and_whitespace_string,
+    btree_map_strategy,
+    optional_vec_of_strings,
+};
+#[cfg(any(test, feature = "tests"))]
+use proptest::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+use crate::interfaces::{AsTypedOnlyManifest, SchemaVersion, impl_pkg_lookup};
+use crate::parsed::common::{
+    ActivateOptions,
+    Containerize,
+    Include,
+    KnownSchemaVersion,
+    SemverOptions,
+    Vars,
+};
+pub use crate::parsed::v1_11_0::MinimumCliVersion;
+pub use crate::parsed::v1_12_0::Services;
+pub use crate::parsed::v1_13_0::{
+    Build,
+    BuildDescriptor,
+    BuildSandbox,
+    Profile,
+    ProfileDeactivate,
+};
+use crate::parsed::{SkipSerializing, impl_into_inner};
+use crate::{Manifest, ManifestError, Parsed, TypedOnly};
+
+pub(crate) mod package_descriptor;
+pub use package_descriptor::*;
+
+/// Not meant for writing manifest files, only for reading them.
+/// Modifications should be made using `manifest::raw`.
+
+// We use `skip_serializing_none` and `skip_serializing_if` throughout to reduce
+// the size of the lockfile and improve backwards compatibility when we
+// introduce fields.
+//
+// It would be better if we could deny_unknown_fields when we're deserializing
+// the user provided manifest but allow unknown fields when deserializing the
+// lockfile, but that doesn't seem worth the effort at the moment.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[cfg_attr(any(test, feature = "tests"), derive(proptest_derive::Arbitrary))]
+#[serde(deny_unknown_fields)]
+pub struct ManifestV1_14_0 {
+    /// Which schema version this manifest adheres to.
+    ///
+    /// Must be a valid Flox CLI version listed in [`KnownSchemaVersion`].
+    #[serde(rename = "schema-version")]
+    pub schema_version: String,
+    /// The minimum CLI version that can activate this environment.
+    #[serde(rename = "minimum-cli-version")]
+    pub minimum_cli_version: Option<MinimumCliVersion>,
+    /// The packages to install in the form of a map from install_id
+    /// to package descriptor.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Install::skip_serializing")]
+    pub install: Install,
+    /// Variables that are exported to the shell environment upon activation.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vars::skip_serializing")]
+    pub vars: Vars,
+    /// Hooks that are run at various times during the lifecycle of the manifest
+    /// in a known shell environment.
+    #[serde(default)]
+    pub hook: Option<Hook>,
+    /// Profile scripts that are run in the user's shell upon activation
+    /// (and, optionally, upon deactivation).
+    #[serde(default)]
+    pub profile: Option<Profile>,
+    /// Named sets of `vars` and `hook` overrides that are layered onto the
+    /// environment when activating with `--profile <name>`.
+    #[serde(rename = "activation-profiles")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "ActivationProfiles::skip_serializing")]
+    pub activation_profiles: ActivationProfiles,
+    /// Options that control the behavior of the manifest.
+    #[serde(default)]
+    pub options: Options,
+    /// Service definitions
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Services::skip_serializing")]
+    pub services: Services,
+    /// Package build definitions
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Build::skip_serializing")]
+    pub build: Build,
+    #[serde(default)]
+    pub containerize: Option<Containerize>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Include::skip_serializing")]
+    pub include: Include,
+}
+impl_pkg_lookup!(crate::parsed::v1_14_0, ManifestV1_14_0);
+
+// You can't derive `Default` because `schema-version` is a `String`,
+// which just defaults to an empty string.
+impl Default for ManifestV1_14_0 {
+    fn default() -> Self {
+        Self {
+            schema_version: "1.14.0".into(),
+            minimum_cli_version: Default::default(),
+            install: Default::default(),
+            vars: Default::default(),
+            hook: Default::default(),
+            profile: Default::default(),
+            activation_profiles: Default::default(),
+            options: Default::default(),
+            services: Default::default(),
+            build: Default::default(),
+            containerize: Default::default(),
+            include: Default::default(),
+        }
+    }
+}
+
+impl AsTypedOnlyManifest for ManifestV1_14_0 {
+    fn as_typed_only(&self) -> crate::Manifest<TypedOnly> {
+        Manifest {
+            inner: TypedOnly {
+                parsed: Parsed::V1_14_0(self.clone()),
+            },
+        }
+    }
+}
+
+impl SchemaVersion for ManifestV1_14_0 {
+    fn get_schema_version(&self) -> KnownSchemaVersion {
+        KnownSchemaVersion::V1_14_0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[cfg_attr(any(test, feature = "tests"), derive(proptest_derive::Arbitrary))]
+pub struct Install(
+    #[cfg_attr(
+        any(test, feature = "tests"),
+        proptest(strategy = "btree_map_strategy::<ManifestPackageDescriptor>(10, 3)")
+    )]
+    pub(crate) BTreeMap<String, ManifestPackageDescriptor>,
+);
+
+impl From<BTreeMap<String, ManifestPackageDescriptor>> for Install {
+    fn from(value: BTreeMap<String, ManifestPackageDescriptor>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<crate::parsed::v1_10_0::Install> for Install {
+    fn from(value: crate::parsed::v1_10_0::Install) -> Self {
+        Self(
+            value
+                .0
+                .into_iter()
+                .map(|(id, descriptor)| (id, descriptor.into()))
+                .collect(),
+        )
+    }
+}
+
+impl SkipSerializing for Install {
+    fn skip_serializing(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl_into_inner!(Install, BTreeMap<String, ManifestPackageDescriptor>);
+
+/// Hooks for V1_14_0.
+///
+/// This is a version-specific copy of `common::Hook` because V1_14_0 adds the
+/// `on-deactivate` script; `on-activate` is the same as in earlier versions.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash, JsonSchema)]
+#[cfg_attr(any(test, feature = "tests"), derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct Hook {
+    /// A script that is run at activation time,
+    /// in a flox provided bash shell
+    #[cfg_attr(
+        any(test, feature = "tests"),
+        proptest(strategy = "proptest::option::of(alphanum_and_whitespace_string(5))")
+    )]
+    pub(crate) on_activate: Option<String>,
+    /// A script that is run once the last shell of an activation exits,
+    /// in a flox provided bash shell
+    #[cfg_attr(
+        any(test, feature = "tests"),
+        proptest(strategy = "proptest::option::of(alphanum_and_whitespace_string(5))")
+    )]
+    pub(crate) on_deactivate: Option<String>,
+}
+
+// Used by the V1_13_0 -> V1_14_0 migration.
+// Earlier versions can't define `on-deactivate`, so it defaults to None.
+impl From<crate::parsed::common::Hook> for Hook {
+    fn from(hook: crate::parsed::common::Hook) -> Self {
+        let crate::parsed::common::Hook { on_activate } = hook;
+        Hook {
+            on_activate,
+            on_deactivate: None,
+        }
+    }
+}
+
+/// Options for V1_14_0.
+///
+/// This is a version-specific copy of `common::Options` because V1_14_0 adds
+/// `insecure` to [Allows]; all other options are the same.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash, JsonSchema)]
+#[cfg_attr(any(test, feature = "tests"), derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct Options {
+    /// A list of systems that each package is resolved for.
+    #[cfg_attr(
+        any(test, feature = "tests"),
+        proptest(strategy = "optional_vec_of_strings(3, 4)")
+    )]
+    pub systems: Option<Vec<System>>,
+    /// Options that control what types of packages are allowed.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Allows::skip_serializing")]
+    pub allow: Allows,
+    /// Options that control how semver versions are resolved.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SemverOptions::skip_serializing")]
+    pub semver: SemverOptions,
+    /// Whether to detect CUDA devices and libs during activation.
+    // TODO: Migrate to `ActivateOptions`.
+    pub cuda_detection: Option<bool>,
+    /// Options that control the behavior of activations.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "ActivateOptions::skip_serializing")]
+    pub activate: ActivateOptions,
+}
+
+/// Allowed package types for V1_14_0: adds `insecure` to the fields of
+/// `common::Allows`.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, Hash, JsonSchema)]
+#[cfg_attr(any(test, feature = "tests"), derive(proptest_derive::Arbitrary))]
+#[serde(deny_unknown_fields)]
+pub struct Allows {
+    /// Whether to allow packages that are marked as `unfree`
+    pub unfree: Option<bool>,
+    /// Whether to allow packages that are marked as `broken`
+    pub broken: Option<bool>,
+    /// A list of license descriptors that are allowed
+    #[serde(default)]
+    #[cfg_attr(
+        any(test, feature = "tests"),
+        proptest(strategy = "optional_vec_of_strings(3, 4)")
+    )]
+    pub licenses: Option<Vec<String>>,
+    /// Install IDs of packages that are allowed to be marked as `insecure`
+    #[serde(default)]
+    #[cfg_attr(
+        any(test, feature = "tests"),
+        proptest(strategy = "optional_vec_of_strings(3, 4)")
+    )]
+    pub insecure: Option<Vec<String>>,
+}
+
+impl SkipSerializing for Allows {
+    fn skip_serializing(&self) -> bool {
+        // Destructuring here prevents us from missing new fields if they're
+        // added in the future.
+        let Allows {
+            unfree,
+            broken,
+            licenses,
+            insecure,
+        } = self;
+        unfree.is_none() && broken.is_none() && licenses.is_none() && insecure.is_none()
+    }
+}
+
+// Conversions from the common types, used by the V1_13_0 -> V1_14_0 migration.
+// The new `insecure` field defaults to None, which is what makes the migration
+// lossless.
+impl From<crate::parsed::common::Allows> for Allows {
+    fn from(allows: crate::parsed::common::Allows) -> Self {
+        let crate::parsed::common::Allows {
+            unfree,
+            broken,
+            licenses,
+        } = allows;
+        Allows {
+            unfree,
+            broken,
+            licenses,
+            insecure: None,
+        }
+    }
+}
+
+impl From<crate::parsed::common::Options> for Options {
+    fn from(options: crate::parsed::common::Options) -> Self {
+        let crate::parsed::common::Options {
+            systems,
+            allow,
+            semver,
+            cuda_detection,
+            activate,
+        } = options;
+        Options {
+            systems,
+            allow: allow.into(),
+            semver,
+            cuda_detection,
+            activate,
+        }
+    }
+}
+
+/// A map of activation profile names to the overrides they apply.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(any(test, feature = "tests"), derive(proptest_derive::Arbitrary))]
+pub struct ActivationProfiles(
+    #[cfg_attr(
+        any(test, feature = "tests"),
+        proptest(strategy = "btree_map_strategy::<ActivationProfile>(5, 3)")
+    )]
+    pub(crate) BTreeMap<String, ActivationProfile>,
+);
+
+impl SkipSerializing for ActivationProfiles {
+    fn skip_serializing(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl_into_inner!(ActivationProfiles, BTreeMap<String, ActivationProfile>);
+
+/// Overrides for an environment that are applied by `flox activate --profile`.
+///
+/// `vars` are added to, or replace, the environment's `[vars]`,
+/// `hook.on-activate` replaces the environment's `hook.on-activate`.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(any(test, feature = "tests"), derive(proptest_derive::Arbitrary))]
+#[serde(deny_unknown_fields)]
+pub struct ActivationProfile {
+    /// Variables that are exported in addition to, or instead of, `[vars]`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vars::skip_serializing")]
+    pub vars: Vars,
+    /// Hooks that replace the environment's `[hook]` section.
+    #[serde(default)]
+    pub hook: Option<crate::parsed::common::Hook>,
+}
+
+impl ManifestV1_14_0 {
+    /// Return a copy of this manifest with the activation profile `name`
+    /// layered on top.
+    ///
+    /// Fails if the manifest doesn't define the profile.
+    pub fn with_activation_profile(&self, name: &str) -> Result<Self, ManifestError> {
+        let Some(activation_profile) = self.activation_profiles.0.get(name) else {
+            return Err(ManifestError::ActivationProfileNotFound {
+                name: name.to_string(),
+                available: self.activation_profiles.0.keys().cloned().collect(),
+            });
+        };
+
+        let mut manifest = self.clone();
+        manifest.vars.0.extend(activation_profile.vars.0.clone());
+        if let Some(on_activate) = activation_profile
+            .hook
+            .as_ref()
+            .and_then(|hook| hook.on_activate.clone())
+        {
+            manifest.hook = Some(Hook {
+                on_activate: Some(on_activate),
+                on_deactivate: manifest
+                    .hook
+                    .as_ref()
+                    .and_then(|hook| hook.on_deactivate.clone()),
+            });
+        }
+        Ok(manifest)
+    }
+}
@@ -15,7 +15,7 @@ use crate::interfaces::CommonFields;
 use crate::parsed::common::{self, KnownSchemaVersion, VersionKind};
 use crate::parsed::latest::ManifestPackageDescriptor;
 use crate::parsed::v1_10_0::SelectedOutputs;
-use crate::parsed::{Inner, v1, v1_10_0};
+use crate::parsed::{Inner, v1, v1_10_0, v1_14_0};
 use crate::util::is_custom_package;
 use crate::{Manifest, ManifestError, Migrated, Parsed, Validated};
 
@@ -187,6 +187,12 @@ pub enum TomlEditError {
     MalformedOptionsTable(String),
     #[error("'options' must be an array, but found {0} instead")]
     MalformedOptionsSystemsArray(String),
+    #[error("'include' must be a table, but found {0} instead")]
+    MalformedIncludeTable(String),
+    #[error("'include.environments' must be an array, but found {0} instead")]
+    MalformedIncludeEnvironmentsArray(String),
+    #[error("couldn't find included environment number {0} in the manifest")]
+    IncludeNotFound(usize),
 
     #[error("'{0}' is not a supported attribute in manifest version 1")]
     UnsupportedAttributeV1(String),
@@ -422,6 +428,13 @@ pub struct CatalogPackage {
     /// If `None`, the default outputs are installed.
     /// This can be parsed from the shorthand descriptor using the `^` syntax.
     pub outputs: Option<RawSelectedOutputs>,
+    /// Package group to resolve the package in.
+    /// If `None`, the package is added to the default group,
+    /// or to a group named after its id if it is from a custom catalog.
+    pub pkg_group: Option<String>,
+    /// Lock the package even if a build is missing for some systems,
+    /// i.e. set `allow-missing-builds` in its descriptor.
+    pub allow_missing_builds: bool,
 }
 
 impl CatalogPackage {
@@ -538,6 +551,8 @@ impl FromStr for CatalogPackage {
             version,
             systems: None,
             outputs,
+            pkg_group: None,
+            allow_missing_builds: false,
         })
     }
 }
@@ -717,6 +732,15 @@ impl From<&CatalogPackage> for InlineTable {
         if let Some(ref version) = val.version {
             table.insert("version", Value::String(Formatted::new(version.clone())));
         }
+        if let Some(ref pkg_group) = val.pkg_group {
+            table.insert(
+                "pkg-group",
+                Value::String(Formatted::new(pkg_group.clone())),
+            );
+        }
+        if val.allow_missing_builds {
+            table.insert("allow-missing-builds", Value::Boolean(Formatted::new(true)));
+        }
         if let Some(ref systems) = val.systems {
             table.insert(
                 "systems",
@@ -769,20 +793,20 @@ impl Manifest<Migrated> {
     ) {
         match pkg {
             PackageToInstall::Catalog(pkg_raw) => {
-                let pkg_group = if pkg_raw.is_custom_catalog() {
-                    Some(pkg.id().to_string())
-                } else {
-                    None
-                };
-                let catalog_descriptor = v1_10_0::PackageDescriptorCatalog {
+                let pkg_group = pkg_raw
+                    .pkg_group
+                    .clone()
+                    .or_else(|| pkg_raw.is_custom_catalog().then(|| pkg.id().to_string()));
+                let catalog_descriptor = v1_14_0::PackageDescriptorCatalog {
                     pkg_path: pkg_raw.pkg_path.clone(),
                     pkg_group,
                     priority: None,
                     version: pkg_raw.version.clone(),
                     systems: pkg_raw.systems.clone(),
                     outputs: pkg_raw.outputs.clone().map(|outputs| outputs.into()),
+                    allow_missing_builds: pkg_raw.allow_missing_builds.then_some(true),
                 };
-                let descriptor = v1_10_0::ManifestPackageDescriptor::Catalog(catalog_descriptor);
+                let descriptor = ManifestPackageDescriptor::Catalog(catalog_descriptor);
                 pkg_map.insert(pkg.id().to_string(), descriptor);
                 debug!(
                     "package newly installed: id={}, pkg-path={}",
@@ -796,7 +820,7 @@ impl Manifest<Migrated> {
                     systems: pkg.systems(),
                     outputs: flake_raw.outputs.as_ref().map(|o| o.into()),
                 };
-                let descriptor = v1_10_0::ManifestPackageDescriptor::FlakeRef(flake_descriptor);
+                let descriptor = ManifestPackageDescriptor::FlakeRef(flake_descriptor);
                 pkg_map.insert(pkg.id().to_string(), descriptor);
                 debug!(
                     "package newly installed: id={}, flakeref={}",
@@ -810,8 +834,7 @@ impl Manifest<Migrated> {
                     systems: None,
                     priority: None,
                 };
-                let descriptor =
-                    v1_10_0::ManifestPackageDescriptor::StorePath(store_path_descriptor);
+                let descriptor = ManifestPackageDescriptor::StorePath(store_path_descriptor);
                 pkg_map.insert(pkg.id().to_string(), descriptor);
                 debug!(id=pkg.id(), store_path=%store_path_raw.store_path.display(),
                     "store path newly installed"
@@ -890,11 +913,8 @@ impl SyncTypedToRaw for Manifest<Validated> {
     }
 
     fn update_systems(&mut self) -> Result<(), ManifestError> {
-        update_systems(
-            &mut self.inner.raw,
-            self.inner.parsed.options().systems.as_ref(),
-        )
-        .map_err(ManifestError::TomlEdit)
+        update_systems(&mut self.inner.raw, self.inner.parsed.systems())
+            .map_err(ManifestError::TomlEdit)
     }
 
     fn update_raw_packages_from_typed_manifest(&mut self) -> Result<(), ManifestError> {
@@ -1011,6 +1031,12 @@ fn update_raw_packages_from_typed_manifest(
             .keys()
             .cloned()
             .collect::<HashSet<String>>(),
+        Parsed::V1_14_0(manifest) => manifest
+            .install
+            .inner()
+            .keys()
+            .cloned()
+            .collect::<HashSet<String>>(),
     };
 
     // Don't create an [install] table if there are no packages in either
@@ -1147,6 +1173,19 @@ fn update_descriptor(
                 StorePath(d) => update_store_path_descriptor(raw, d),
             }
         },
+        Parsed::V1_14_0(manifest) => {
+            let typed = manifest
+                .install
+                .inner()
+                .get(install_id)
+                .ok_or(TomlEditError::PackageNotFound(install_id.to_string()))?;
+            use crate::parsed::v1_14_0::ManifestPackageDescriptor::*;
+            match typed {
+                Catalog(d) => update_v1_14_0_catalog_descriptor(raw, d),
+                FlakeRef(d) => update_v1_10_0_flake_descriptor(raw, d),
+                StorePath(d) => update_store_path_descriptor(raw, d),
+            }
+        },
     }
     Ok(())
 }
@@ -1409,6 +1448,39 @@ fn update_v1_10_0_catalog_descriptor(
     }
 }
 
+fn update_v1_14_0_catalog_descriptor(
+    raw: &mut dyn TableLike,
+    descriptor: &v1_14_0::PackageDescriptorCatalog,
+) {
+    let v1_14_0::PackageDescriptorCatalog {
+        pkg_path,
+        pkg_group,
+        priority,
+        version,
+        systems,
+        outputs,
+        allow_missing_builds,
+    } = descriptor;
+    // Everything but `allow-missing-builds` is shared with V1_10_0
+    update_v1_10_0_catalog_descriptor(raw, &v1_10_0::PackageDescriptorCatalog {
+        pkg_path: pkg_path.clone(),
+        pkg_group: pkg_group.clone(),
+        priority: *priority,
+        version: version.clone(),
+        systems: systems.clone(),
+        outputs: outputs.clone(),
+    });
+    if let Some(allow_missing_builds) = allow_missing_builds {
+        table_like_set(
+            raw,
+            "allow-missing-builds",
+            Value::Boolean(Formatted::new(*allow_missing_builds)).into(),
+        );
+    } else {
+        table_like_remove(raw, "allow-missing-builds");
+    }
+}
+
 fn update_v1_10_0_flake_descriptor(
     raw: &mut dyn TableLike,
     descriptor: &v1_10_0::PackageDescriptorFlake,
@@ -1444,6 +1516,127 @@ fn update_v1_10_0_flake_descriptor(
     }
 }
 
+impl Manifest<Validated> {
+    /// Render the manifest in a canonical layout, see [format_toml].
+    ///
+    /// Fails if the formatted manifest doesn't parse to the same manifest,
+    /// which would indicate a bug in the formatter.
+    pub fn formatted(&self) -> Result<String, ManifestError> {
+        let formatted = format_toml(&self.inner.raw);
+        let reparsed = Manifest::parse_toml_typed(&formatted)?;
+        if reparsed.inner.parsed != self.inner.parsed {
+            return Err(ManifestError::Other(
+                "formatting would change the contents of the manifest".to_string(),
+            ));
+        }
+        Ok(formatted)
+    }
+}
+
+/// Render a manifest in a canonical layout without changing its contents:
+///
+/// * keys and values are separated by ` = `
+/// * indentation before keys and trailing whitespace are removed
+/// * runs of blank lines are collapsed into a single blank line
+/// * tables are separated by a single blank line
+/// * packages in `[install]` are sorted by install ID
+///
+/// Comments are preserved and move together with the entry they precede.
+/// Inline tables and arrays are left as they are.
+fn format_toml(doc: &DocumentMut) -> String {
+    let mut doc = doc.clone();
+    if let Some(install) = doc.get_mut("install").and_then(Item::as_table_mut) {
+        install.sort_values();
+    }
+    format_table_entries(doc.as_table_mut(), true);
+
+    let formatted = doc.to_string();
+    format!("{}\n", formatted.trim_start_matches('\n').trim_end())
+}
+
+/// Normalize the layout of the entries of `table` and of its subtables.
+///
+/// If `leading` is set, blank lines before the first entry are removed.
+fn format_table_entries(table: &mut Table, leading: bool) {
+    for (index, (mut key, item)) in table.iter_mut().enumerate() {
+        let leading = leading && index == 0;
+        match item {
+            Item::Value(value) => {
+                let decor = key.leaf_decor_mut();
+                let prefix = decor.prefix().and_then(|p| p.as_str()).unwrap_or_default();
+                decor.set_prefix(format_prefix(prefix, leading));
+                decor.set_suffix(" ");
+
+                let decor = value.decor_mut();
+                let suffix = decor.suffix().and_then(|s| s.as_str()).unwrap_or_default();
+                decor.set_suffix(format_suffix(suffix));
+                decor.set_prefix(" ");
+            },
+            // Dotted keys, e.g. `hook.on-activate = "..."`,
+            // are rendered as entries of the surrounding table.
+            Item::Table(table) if table.is_dotted() => format_table_entries(table, leading),
+            Item::Table(table) => {
+                key.leaf_decor_mut().clear();
+                format_table(table);
+            },
+            Item::ArrayOfTables(tables) => {
+                key.leaf_decor_mut().clear();
+                tables.iter_mut().for_each(format_table);
+            },
+            Item::None => {},
+        }
+    }
+}
+
+/// Normalize the layout of a table header and its entries.
+///
+/// Every table header is preceded by a blank line,
+/// the blank line before the first table is removed by [format_toml].
+fn format_table(table: &mut Table) {
+    if !table.is_implicit() {
+        let decor = table.decor_mut();
+        let prefix = decor.prefix().and_then(|p| p.as_str()).unwrap_or_default();
+        let prefix = format!("\n{}", format_prefix(prefix, true));
+        let suffix = decor.suffix().and_then(|s| s.as_str()).unwrap_or_default();
+        let suffix = format_suffix(suffix);
+        decor.set_prefix(prefix);
+        decor.set_suffix(suffix);
+    }
+    format_table_entries(table, true);
+}
+
+/// Normalize the lines preceding a key or table header,
+/// i.e. blank lines and comments.
+///
+/// Indentation is removed and runs of blank lines are collapsed.
+/// If `leading` is set, blank lines at the start are removed.
+fn format_prefix(prefix: &str, leading: bool) -> String {
+    let mut lines: Vec<&str> = vec![];
+    // The last segment is the indentation of the key itself.
+    let Some((lines_before, _indentation)) = prefix.rsplit_once('\n') else {
+        return String::new();
+    };
+    for line in lines_before.split('\n').map(str::trim) {
+        let previous_is_blank = lines.last().map(|line| line.is_empty());
+        if line.is_empty() && (previous_is_blank.unwrap_or(leading)) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+/// Normalize what follows a value or table header on the same line,
+/// i.e. an optional comment.
+fn format_suffix(suffix: &str) -> String {
+    let comment = suffix.trim();
+    if comment.is_empty() {
+        String::new()
+    } else {
+        format!(" {comment}")
+    }
+}
+
 /// Add a `system` to the `[options.systems]` array of a manifest
 pub fn add_system(toml: &str, system: &str) -> Result<DocumentMut, TomlEditError> {
     let mut doc = toml
@@ -1487,6 +1680,128 @@ pub fn add_system(toml: &str, system: &str) -> Result<DocumentMut, TomlEditError
     Ok(doc)
 }
 
+/// Set the `version` of the package `install_id` in the `[install]` table
+/// of a manifest, keeping the rest of the manifest as is
+pub fn set_package_version(
+    toml: &str,
+    install_id: &str,
+    version: &str,
+) -> Result<DocumentMut, TomlEditError> {
+    let mut doc = toml
+        .parse::<DocumentMut>()
+        .map_err(TomlEditError::ParseToml)?;
+
+    let install_table = doc
+        .get_mut("install")
+        .ok_or(TomlEditError::MissingInstallTable)?;
+    let install_table_type = install_table.type_name().into();
+    let install_table = install_table
+        .as_table_like_mut()
+        .ok_or(TomlEditError::MalformedInstallTable(install_table_type))?;
+
+    let descriptor = install_table
+        .get_mut(install_id)
+        .and_then(|descriptor| descriptor.as_table_like_mut())
+        .ok_or(TomlEditError::PackageNotFound(install_id.to_string()))?;
+    descriptor.insert("version", toml_edit::value(version));
+
+    Ok(doc)
+}
+
+/// Append an environment to the `include.environments` array of a manifest,
+/// keeping the rest of the manifest as is
+///
+/// No check is made whether the environment is already included,
+/// callers are expected to have done that on the typed manifest.
+pub fn add_include(
+    toml: &str,
+    descriptor: &common::IncludeDescriptor,
+) -> Result<DocumentMut, TomlEditError> {
+    let mut doc = toml
+        .parse::<DocumentMut>()
+        .map_err(TomlEditError::ParseToml)?;
+
+    // extract the `[include]` table
+    let include_table = doc
+        .entry(MANIFEST_INCLUDE_KEY)
+        .or_insert(Item::Table(Table::default()));
+    let include_table_type = include_table.type_name().into();
+    let include_table = include_table
+        .as_table_like_mut()
+        .ok_or(TomlEditError::MalformedIncludeTable(include_table_type))?;
+
+    // extract the `include.environments` array
+    let environments = include_table
+        .entry("environments")
+        .or_insert(Item::Value(Value::Array(Array::default())));
+    let environments_type = environments.type_name().into();
+    let environments =
+        environments
+            .as_array_mut()
+            .ok_or(TomlEditError::MalformedIncludeEnvironmentsArray(
+                environments_type,
+            ))?;
+
+    let mut entry = InlineTable::new();
+    let name = match descriptor {
+        common::IncludeDescriptor::Local { dir, name } => {
+            entry.insert("dir", dir.to_string_lossy().as_ref().into());
+            name
+        },
+        common::IncludeDescriptor::Remote {
+            remote,
+            name,
+            generation,
+        } => {
+            entry.insert("remote", remote.to_string().into());
+            if let Some(generation) = generation {
+                entry.insert("generation", (*generation as i64).into());
+            }
+            name
+        },
+    };
+    if let Some(name) = name {
+        entry.insert("name", name.as_str().into());
+    }
+    environments.push(entry);
+
+    Ok(doc)
+}
+
+/// Remove the environment at `index` from the `include.environments` array
+/// of a manifest, keeping the rest of the manifest as is
+pub fn remove_include(toml: &str, index: usize) -> Result<DocumentMut, TomlEditError> {
+    let mut doc = toml
+        .parse::<DocumentMut>()
+        .map_err(TomlEditError::ParseToml)?;
+
+    let include_table = doc
+        .get_mut(MANIFEST_INCLUDE_KEY)
+        .ok_or(TomlEditError::IncludeNotFound(index))?;
+    let include_table_type = include_table.type_name().into();
+    let include_table = include_table
+        .as_table_like_mut()
+        .ok_or(TomlEditError::MalformedIncludeTable(include_table_type))?;
+
+    let environments = include_table
+        .get_mut("environments")
+        .ok_or(TomlEditError::IncludeNotFound(index))?;
+    let environments_type = environments.type_name().into();
+    let environments =
+        environments
+            .as_array_mut()
+            .ok_or(TomlEditError::MalformedIncludeEnvironmentsArray(
+                environments_type,
+            ))?;
+
+    if index >= environments.len() {
+        return Err(TomlEditError::IncludeNotFound(index));
+    }
+    environments.remove(index);
+
+    Ok(doc)
+}
+
 #[cfg(any(test, feature = "tests"))]
 pub mod test_helpers {
     use toml_edit::DocumentMut;
@@ -1668,6 +1983,8 @@ mod test {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1678,6 +1995,8 @@ mod test {
             version: Some("=1.2.3".to_string()),
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1688,6 +2007,8 @@ mod test {
             version: Some("23.11".to_string()),
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1698,6 +2019,8 @@ mod test {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1709,6 +2032,8 @@ mod test {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1725,6 +2050,8 @@ mod test {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1736,6 +2063,8 @@ mod test {
             version: Some("1.2.3".to_string()),
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1748,6 +2077,8 @@ mod test {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1761,6 +2092,8 @@ mod test {
             version: Some("version".to_string()),
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), false);
 
@@ -1772,6 +2105,8 @@ mod test {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), true);
 
@@ -1783,6 +2118,8 @@ mod test {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), true);
 
@@ -1794,6 +2131,8 @@ mod test {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
         assert_eq!(parsed.is_custom_catalog(), true);
 
@@ -1840,6 +2179,62 @@ curl.outputs = [\"bin\", \"man\"]
         );
     }
 
+    #[test]
+    fn manifest_is_updated_correctly_with_pkg_group() {
+        let package = PackageToInstall::Catalog(CatalogPackage {
+            pkg_group: Some("tools".to_string()),
+            ..CatalogPackage::from_str("curl").unwrap()
+        });
+        let contents = "
+schema-version = \"1.10.0\"
+        ";
+        let manifest = mk_test_manifest_from_contents(contents);
+        let new_manifest = manifest
+            .modify_packages(&[PackageToModify {
+                install_id: package.id().to_string(),
+                modification: PackageModification::Add(package),
+            }])
+            .expect("couldn't add package");
+        assert_eq!(
+            new_manifest.inner.migrated_raw.to_string(),
+            "
+schema-version = \"1.10.0\"
+
+[install]
+curl.pkg-path = \"curl\"
+curl.pkg-group = \"tools\"
+        "
+        );
+    }
+
+    #[test]
+    fn manifest_is_updated_correctly_with_allow_missing_builds() {
+        let package = PackageToInstall::Catalog(CatalogPackage {
+            allow_missing_builds: true,
+            ..CatalogPackage::from_str("curl").unwrap()
+        });
+        let contents = "
+schema-version = \"1.14.0\"
+        ";
+        let manifest = mk_test_manifest_from_contents(contents);
+        let new_manifest = manifest
+            .modify_packages(&[PackageToModify {
+                install_id: package.id().to_string(),
+                modification: PackageModification::Add(package),
+            }])
+            .expect("couldn't add package");
+        assert_eq!(
+            new_manifest.inner.migrated_raw.to_string(),
+            "
+schema-version = \"1.14.0\"
+
+[install]
+curl.pkg-path = \"curl\"
+curl.allow-missing-builds = true
+        "
+        );
+    }
+
     #[test]
     fn catalog_parses_descriptors_with_outputs() {
         // Package with specific outputs
@@ -1853,6 +2248,8 @@ curl.outputs = [\"bin\", \"man\"]
                 "bin".to_string(),
                 "man".to_string()
             ])),
+            pkg_group: None,
+            allow_missing_builds: false,
         });
 
         // Package with all outputs
@@ -1863,6 +2260,8 @@ curl.outputs = [\"bin\", \"man\"]
             version: None,
             systems: None,
             outputs: Some(RawSelectedOutputs::All),
+            pkg_group: None,
+            allow_missing_builds: false,
         });
 
         // Package with version containing special characters
@@ -1873,6 +2272,8 @@ curl.outputs = [\"bin\", \"man\"]
             version: Some("^5.0.0".to_string()),
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
 
         // Invalid package with version and outputs
@@ -1883,6 +2284,8 @@ curl.outputs = [\"bin\", \"man\"]
             version: Some("5.0^bin,man,dev".to_string()),
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         });
 
         // Package with outputs containing spaces (should be trimmed)
@@ -1897,6 +2300,8 @@ curl.outputs = [\"bin\", \"man\"]
                 "man".to_string(),
                 "dev".to_string()
             ])),
+            pkg_group: None,
+            allow_missing_builds: false,
         });
 
         // Error: empty outputs specification
@@ -2104,7 +2509,7 @@ curl.outputs = [\"bin\", \"man\"]
         "#});
         let mut manifest = Manifest::parse_toml_typed(&toml_str).unwrap();
         let systems = vec!["x86_64-linux".to_string()];
-        manifest.inner.parsed.options_mut().systems = Some(systems.clone());
+        *manifest.inner.parsed.systems_mut() = Some(systems.clone());
         manifest.update_systems().unwrap();
         let updated_systems = manifest.inner.raw["options"]["systems"]
             .as_array()
@@ -2123,7 +2528,7 @@ curl.outputs = [\"bin\", \"man\"]
             allow.unfree = true
         "#});
         let mut manifest = Manifest::parse_toml_typed(&toml_str).unwrap();
-        manifest.inner.parsed.options_mut().systems = None;
+        *manifest.inner.parsed.systems_mut() = None;
         manifest.update_systems().unwrap();
         let opts = manifest.inner.raw["options"].clone();
         assert!(opts["allow"]["unfree"].as_bool().unwrap());
@@ -2176,6 +2581,9 @@ curl.outputs = [\"bin\", \"man\"]
             Parsed::V1_13_0(m) => {
                 m.install.inner_mut().remove(id);
             },
+            Parsed::V1_14_0(m) => {
+                m.install.inner_mut().remove(id);
+            },
         }
     }
 
@@ -2198,6 +2606,11 @@ curl.outputs = [\"bin\", \"man\"]
             Parsed::V1_13_0(m) => {
                 m.install.inner_mut().insert(id.to_string(), descriptor);
             },
+            Parsed::V1_14_0(m) => {
+                m.install
+                    .inner_mut()
+                    .insert(id.to_string(), descriptor.into());
+            },
             _ => panic!("expected v1_10_0 or later manifest"),
         }
     }
@@ -2224,7 +2637,22 @@ curl.outputs = [\"bin\", \"man\"]
                 v1_10_0::ManifestPackageDescriptor::Catalog(desc) => Some(desc),
                 _ => None,
             },
-            _ => panic!("expected v1_10_0 or later manifest"),
+            _ => panic!("expected v1_10_0 to v1_13_0 manifest"),
+        }
+    }
+
+    /// Helper to get a mutable reference to a catalog descriptor of the latest
+    /// schema.
+    fn get_latest_catalog_descriptor_mut<'a>(
+        manifest: &'a mut Manifest<Validated>,
+        id: &str,
+    ) -> Option<&'a mut v1_14_0::PackageDescriptorCatalog> {
+        match &mut manifest.inner.parsed {
+            Parsed::V1_14_0(m) => match m.install.inner_mut().get_mut(id)? {
+                v1_14_0::ManifestPackageDescriptor::Catalog(desc) => Some(desc),
+                _ => None,
+            },
+            _ => panic!("expected latest manifest"),
         }
     }
 
@@ -2245,7 +2673,7 @@ curl.outputs = [\"bin\", \"man\"]
         manifest.update_raw_packages_from_typed_manifest().unwrap();
         let output = manifest.inner.raw.to_string();
         expect![[r#"
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
             [install]
 
@@ -2283,7 +2711,7 @@ curl.outputs = [\"bin\", \"man\"]
         manifest.update_raw_packages_from_typed_manifest().unwrap();
         let output = manifest.inner.raw.to_string();
         expect![[r#"
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
             [install]
             # my favorite greeting program
@@ -2309,13 +2737,13 @@ curl.outputs = [\"bin\", \"man\"]
             ripgrep.pkg-path = "ripgrep"
         "#});
         let mut manifest = Manifest::parse_toml_typed(&toml_str).unwrap();
-        get_catalog_descriptor_mut(&mut manifest, "hello")
+        get_latest_catalog_descriptor_mut(&mut manifest, "hello")
             .unwrap()
             .version = Some("2.0".to_string());
         manifest.update_raw_packages_from_typed_manifest().unwrap();
         let output = manifest.inner.raw.to_string();
         expect![[r#"
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
             [install]
             # keep this comment about hello
@@ -2337,13 +2765,13 @@ curl.outputs = [\"bin\", \"man\"]
             hello.version = "1.0" # pin the version
         "#});
         let mut manifest = Manifest::parse_toml_typed(&toml_str).unwrap();
-        get_catalog_descriptor_mut(&mut manifest, "hello")
+        get_latest_catalog_descriptor_mut(&mut manifest, "hello")
             .unwrap()
             .version = Some("2.0".to_string());
         manifest.update_raw_packages_from_typed_manifest().unwrap();
         let output = manifest.inner.raw.to_string();
         expect![[r#"
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
             [install]
             hello.pkg-path = "hello" # this is important
@@ -2415,7 +2843,7 @@ curl.outputs = [\"bin\", \"man\"]
         manifest.update_raw_packages_from_typed_manifest().unwrap();
         let output = manifest.inner.raw.to_string();
         expect![[r#"
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
             [install]
             # this comment is above hello
@@ -2454,14 +2882,14 @@ curl.outputs = [\"bin\", \"man\"]
             ]
         "#});
         let mut manifest = Manifest::parse_toml_typed(&toml_str).unwrap();
-        manifest.inner.parsed.options_mut().systems = Some(vec![
+        *manifest.inner.parsed.systems_mut() = Some(vec![
             "aarch64-darwin".to_string(),
             "x86_64-linux".to_string(),
         ]);
         manifest.update_systems().unwrap();
         let output = manifest.inner.raw.to_string();
         expect![[r#"
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
             [options]
             systems = ["aarch64-darwin", "x86_64-linux"]
@@ -2508,7 +2936,7 @@ curl.outputs = [\"bin\", \"man\"]
             ]
         "#});
         let mut manifest = Manifest::parse_toml_typed(&toml_str).unwrap();
-        let desc = get_catalog_descriptor_mut(&mut manifest, "hello").unwrap();
+        let desc = get_latest_catalog_descriptor_mut(&mut manifest, "hello").unwrap();
         desc.systems = Some(vec![
             "aarch64-darwin".to_string(),
             "aarch64-linux".to_string(),
@@ -2517,7 +2945,7 @@ curl.outputs = [\"bin\", \"man\"]
         manifest.update_raw_packages_from_typed_manifest().unwrap();
         let output = manifest.inner.raw.to_string();
         expect![[r#"
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
             [install]
             hello.pkg-path = "hello"
@@ -2542,7 +2970,7 @@ curl.outputs = [\"bin\", \"man\"]
         let output = migrated.inner.migrated_raw.to_string();
         expect![[r##"
             # this comment is above version
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
             [install]
             hello.pkg-path = "hello"
@@ -2745,4 +3173,166 @@ curl.outputs = [\"bin\", \"man\"]
             .assert_eq(&doc.to_string());
         }
     }
+
+    #[test]
+    fn formatted_normalizes_layout_and_preserves_comments() {
+        let manifest = Manifest::parse_toml_typed(indoc! {r#"
+            version = 1
+            [install]
+              # editor
+              vim.pkg-path   =   "vim"
+            curl.pkg-path = "curl"    # for downloads
+
+
+
+            [vars]
+
+
+            FOO="bar"
+            [hook]
+            on-activate = """
+              echo hi
+            """
+        "#})
+        .unwrap();
+
+        let formatted = manifest.formatted().unwrap();
+        assert_eq!(formatted, indoc! {r#"
+            version = 1
+
+            [install]
+            curl.pkg-path = "curl" # for downloads
+            # editor
+            vim.pkg-path = "vim"
+
+            [vars]
+            FOO = "bar"
+
+            [hook]
+            on-activate = """
+              echo hi
+            """
+        "#});
+
+        // Formatting is idempotent
+        let reformatted = Manifest::parse_toml_typed(&formatted)
+            .unwrap()
+            .formatted()
+            .unwrap();
+        assert_eq!(reformatted, formatted);
+    }
+
+    #[test]
+    fn set_package_version_updates_any_descriptor_layout() {
+        let toml = indoc! {r#"
+            version = 1
+
+            [install]
+            # greeter
+            hello.pkg-path = "hello"
+            hello.version = "2.10"
+            rg = { pkg-path = "ripgrep" }
+
+            [install.bat]
+            pkg-path = "bat"
+        "#};
+
+        let doc = set_package_version(toml, "hello", "2.12").unwrap();
+        let doc = set_package_version(&doc.to_string(), "rg", "14.1.1").unwrap();
+        let doc = set_package_version(&doc.to_string(), "bat", "0.24.0").unwrap();
+
+        let contents = doc.to_string();
+        assert!(contents.contains("# greeter\nhello.pkg-path"), "{contents}");
+        let install = &doc["install"];
+        assert_eq!(install["hello"]["version"].as_str(), Some("2.12"));
+        assert_eq!(install["rg"]["version"].as_str(), Some("14.1.1"));
+        assert_eq!(install["rg"]["pkg-path"].as_str(), Some("ripgrep"));
+        assert_eq!(install["bat"]["version"].as_str(), Some("0.24.0"));
+
+        assert!(matches!(
+            set_package_version(toml, "vim", "9.1"),
+            Err(TomlEditError::PackageNotFound(id)) if id == "vim"
+        ));
+        assert!(matches!(
+            set_package_version("version = 1", "vim", "9.1"),
+            Err(TomlEditError::MissingInstallTable)
+        ));
+    }
+
+    #[test]
+    fn add_include_appends_to_environments() {
+        let toml = indoc! {r#"
+            version = 1
+
+            [include]
+            # shared tools
+            environments = [{ dir = "../tools" }]
+        "#};
+
+        let doc = add_include(toml, &common::IncludeDescriptor::Remote {
+            remote: "owner/db".parse().unwrap(),
+            name: Some("db".to_string()),
+            generation: None,
+        })
+        .unwrap();
+
+        let contents = doc.to_string();
+        assert!(contents.contains("# shared tools\n"), "{contents}");
+        let environments = doc["include"]["environments"].as_array().unwrap();
+        assert_eq!(environments.len(), 2);
+        let added = environments.get(1).unwrap().as_inline_table().unwrap();
+        assert_eq!(added.get("remote").unwrap().as_str(), Some("owner/db"));
+        assert_eq!(added.get("name").unwrap().as_str(), Some("db"));
+        assert!(added.get("generation").is_none());
+
+        let doc = add_include("version = 1", &common::IncludeDescriptor::Local {
+            dir: PathBuf::from("../tools"),
+            name: None,
+        })
+        .unwrap();
+        let environments = doc["include"]["environments"].as_array().unwrap();
+        assert_eq!(environments.len(), 1);
+        let added = environments.get(0).unwrap().as_inline_table().unwrap();
+        assert_eq!(added.get("dir").unwrap().as_str(), Some("../tools"));
+        assert!(added.get("name").is_none());
+
+        assert!(matches!(
+            add_include("include = 1", &common::IncludeDescriptor::Local {
+                dir: PathBuf::from("../tools"),
+                name: None,
+            }),
+            Err(TomlEditError::MalformedIncludeTable(_))
+        ));
+    }
+
+    #[test]
+    fn remove_include_keeps_other_environments() {
+        let toml = indoc! {r#"
+            version = 1
+
+            [include]
+            # shared tools
+            environments = [
+                { dir = "../tools" },
+                { remote = "owner/db", name = "db" },
+            ]
+        "#};
+
+        let doc = remove_include(toml, 0).unwrap();
+        let contents = doc.to_string();
+        assert!(contents.contains("# shared tools\n"), "{contents}");
+        let environments = doc["include"]["environments"].as_array().unwrap();
+        assert_eq!(environments.len(), 1);
+        let remaining = environments.get(0).unwrap().as_inline_table().unwrap();
+        assert_eq!(remaining.get("remote").unwrap().as_str(), Some("owner/db"));
+
+        assert!(matches!(
+            remove_include(toml, 2),
+            Err(TomlEditError::IncludeNotFound(2))
+        ));
+        assert!(matches!(
+            remove_include("version = 1", 0),
+            Err(TomlEditError::IncludeNotFound(0))
+        ));
+    }
 }
@@ -1,3 +1,5 @@
+use similar::TextDiff;
+
 /// Custom packages are of the form "<prefix>/<suffix>" where the prefix is not
 /// allowed to contain a '.' character. This is a quick and dirty way of
 /// identifying custom packages using that logic.
@@ -8,3 +10,94 @@ pub fn is_custom_package(pkg_path: &str) -> bool {
     let is_base_catalog_pkg = parts.len() == 1 || parts.first().is_some_and(|p| p.contains('.'));
     !is_base_catalog_pkg
 }
+
+/// Number of unchanged lines shown around each change in [unified_diff].
+const DIFF_CONTEXT_LINES: usize = 3;
+
+/// Render a line based unified diff between `old` and `new`,
+/// labelling the sides with `old_label` and `new_label`.
+///
+/// Returns an empty string if the contents are identical.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(DIFF_CONTEXT_LINES)
+        .header(old_label, new_label)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn unified_diff_identical_is_empty() {
+        let contents = "version = 1\n";
+        assert_eq!(unified_diff(contents, contents, "a", "b"), "");
+    }
+
+    #[test]
+    fn unified_diff_groups_changes_into_hunks() {
+        let old = indoc! {r#"
+            version = 1
+            [install]
+            hello.pkg-path = "hello"
+            a = 1
+            b = 2
+            c = 3
+            d = 4
+            e = 5
+            f = 6
+            g = 7
+            [vars]
+            foo = "bar"
+        "#};
+        let new = indoc! {r#"
+            version = 1
+            [install]
+            hello.pkg-path = "hello"
+            curl.pkg-path = "curl"
+            a = 1
+            b = 2
+            c = 3
+            d = 4
+            e = 5
+            f = 6
+            g = 7
+            [vars]
+            foo = "baz"
+        "#};
+
+        assert_eq!(unified_diff(old, new, "old", "new"), indoc! {r#"
+            --- old
+            +++ new
+            @@ -1,6 +1,7 @@
+             version = 1
+             [install]
+             hello.pkg-path = "hello"
+            +curl.pkg-path = "curl"
+             a = 1
+             b = 2
+             c = 3
+            @@ -9,4 +10,4 @@
+             f = 6
+             g = 7
+             [vars]
+            -foo = "bar"
+            +foo = "baz"
+        "#});
+    }
+
+    #[test]
+    fn unified_diff_from_empty() {
+        assert_eq!(unified_diff("", "version = 1\n", "old", "new"), indoc! {"
+            --- old
+            +++ new
+            @@ -0,0 +1 @@
+            +version = 1
+        "});
+    }
+}
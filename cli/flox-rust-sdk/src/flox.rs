@@ -1,11 +1,14 @@
-use std::path::PathBuf;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::LazyLock;
 
+use flox_core::data::environment_ref::RemoteEnvironmentRef;
 use flox_core::vars::FLOX_VERSION_STRING;
 pub use floxhub_client::{AuthContext, AuthnMode, FloxhubToken, FloxhubTokenError};
 use floxhub_client::{FloxhubClient, FloxhubClientError};
 use serde::{Deserialize, Serialize};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
 use thiserror::Error;
 use url::Url;
 use uuid::Uuid;
@@ -65,6 +68,9 @@ pub struct Flox {
     /// Feature flags
     pub features: Features,
 
+    /// The environment to fall back to when no environment is found or active
+    pub default_environment: Option<DefaultEnvironment>,
+
     pub verbosity: i32,
 
     /// Device UUID for telemetry correlation.
@@ -99,6 +105,39 @@ pub struct Features {
     pub auto_activate: bool,
 }
 
+/// An environment that commands fall back to,
+/// set with `flox config --set-default-environment`
+#[derive(Clone, Debug, PartialEq, Eq, SerializeDisplay, DeserializeFromStr)]
+pub enum DefaultEnvironment {
+    /// The environment in a directory, stored as an absolute path
+    Path(PathBuf),
+    /// A FloxHub environment
+    Remote(RemoteEnvironmentRef),
+}
+
+impl Display for DefaultEnvironment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefaultEnvironment::Path(path) => write!(f, "{}", path.display()),
+            DefaultEnvironment::Remote(env_ref) => write!(f, "{env_ref}"),
+        }
+    }
+}
+
+impl FromStr for DefaultEnvironment {
+    type Err = String;
+
+    /// Absolute paths are directories, anything else has to be `owner/name`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if Path::new(s).is_absolute() {
+            return Ok(DefaultEnvironment::Path(PathBuf::from(s)));
+        }
+        s.parse().map(DefaultEnvironment::Remote).map_err(|_| {
+            format!("invalid default environment '{s}', expected an absolute path or owner/name")
+        })
+    }
+}
+
 pub static DEFAULT_FLOXHUB_URL: LazyLock<Url> =
     LazyLock::new(|| Url::parse("https://hub.flox.dev").unwrap());
 
@@ -325,6 +364,7 @@ pub mod test_helpers {
             floxhub_client: floxhub_client::client::test_helpers::new_noop(),
             installable_locker: InstallableLockerImpl::Mock(InstallableLockerMock::new()),
             features: Default::default(),
+            default_environment: None,
             verbosity: 0,
             metrics_device_uuid: None,
         };
@@ -1,5 +1,5 @@
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use flox_core::{Version, WriteError, serialize_atomically, traceable_path};
 use fslock::LockFile;
@@ -121,10 +121,29 @@ impl EnvRegistry {
     }
 
     /// Prunes environments that no longer exist on disk from the Registry and FloxMeta.
-    fn prune_nonexistent(&mut self, flox: &Flox) -> Result<(), EnvRegistryError> {
+    ///
+    /// If `registered_before` is provided (in seconds since the Unix Epoch),
+    /// only entries whose latest environment was registered before then are pruned.
+    /// Entries with a path in `excluded` are never pruned.
+    ///
+    /// Returns the pruned entries.
+    fn prune_nonexistent(
+        &mut self,
+        flox: &Flox,
+        registered_before: Option<u64>,
+        excluded: &[PathBuf],
+    ) -> Result<Vec<RegistryEntry>, EnvRegistryError> {
+        let collectable = |entry: &RegistryEntry| {
+            !entry.exists()
+                && !excluded.contains(&entry.path)
+                && registered_before.is_none_or(|cutoff| {
+                    entry.latest_env().is_none_or(|env| env.created_at < cutoff)
+                })
+        };
+
         self.entries
             .iter()
-            .filter(|entry| !entry.exists())
+            .filter(|entry| collectable(entry))
             .try_for_each(|entry| {
                 for env in entry.envs.iter() {
                     // Prune floxmeta branches for managed environments
@@ -143,9 +162,12 @@ impl EnvRegistry {
         // The environment registry is the only method we have of determining
         // whether a branch in floxmeta should be garbage collected, so only
         // remove entries after pruning floxmeta
-        self.entries.retain(|entry| entry.exists());
+        let (pruned, retained) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(|entry| collectable(entry));
+        self.entries = retained;
 
-        Ok(())
+        Ok(pruned)
     }
 }
 
@@ -335,12 +357,38 @@ pub fn deregister(
 /// second read by any consumers.
 #[instrument(skip_all, fields(progress = "Garbage collecting stale environments"))]
 pub fn garbage_collect(flox: &Flox) -> Result<EnvRegistry, EnvRegistryError> {
+    let (reg, _) = garbage_collect_older_than(flox, None, &[])?;
+    Ok(reg)
+}
+
+/// Like [garbage_collect], but if `older_than` is provided,
+/// only collects nonexistent environments that were last registered more
+/// than `older_than` ago.
+/// Entries whose `.flox` directory is in `excluded` are kept regardless.
+///
+/// Returns the removed entries in addition to the updated registry.
+#[instrument(skip_all, fields(progress = "Garbage collecting stale environments"))]
+pub fn garbage_collect_older_than(
+    flox: &Flox,
+    older_than: Option<Duration>,
+    excluded: &[PathBuf],
+) -> Result<(EnvRegistry, Vec<RegistryEntry>), EnvRegistryError> {
     let reg_path = env_registry_path(flox);
     let lock = acquire_env_registry_lock(&reg_path)?;
     let mut reg = read_environment_registry(&reg_path)?.ok_or(EnvRegistryError::NoEnvRegistry)?;
-    reg.prune_nonexistent(flox)?;
+    let removed = reg.prune_nonexistent(flox, older_than.map(registered_before), excluded)?;
     write_environment_registry(&reg, &reg_path, lock)?;
-    Ok(reg)
+    Ok((reg, removed))
+}
+
+/// The time `older_than` ago, in seconds since the Unix Epoch.
+fn registered_before(older_than: Duration) -> u64 {
+    SystemTime::now()
+        .checked_sub(older_than)
+        .unwrap_or(UNIX_EPOCH)
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -548,4 +596,66 @@ mod test {
             reg_read
         );
     }
+
+    #[test]
+    fn garbage_collect_older_than_returns_removed_entries() {
+        let (flox, _temp_dir) = flox_instance();
+
+        // This also registers the environment.
+        let env = new_path_environment(&flox, "version = 1");
+        let env_hash = path_hash(&env.path);
+
+        let (_, removed) = garbage_collect_older_than(&flox, None, &[]).unwrap();
+        assert_eq!(removed, vec![]);
+
+        std::fs::remove_dir_all(&env.path).unwrap();
+
+        let excluded = [env.path.to_path_buf()];
+        let (_, removed) = garbage_collect_older_than(&flox, None, &excluded).unwrap();
+        assert_eq!(removed, vec![], "excluded environments should survive GC");
+
+        let (_, removed) = garbage_collect_older_than(&flox, None, &[]).unwrap();
+        assert_eq!(
+            removed
+                .iter()
+                .map(|entry| entry.path_hash.as_str())
+                .collect::<Vec<_>>(),
+            vec![env_hash.as_str()]
+        );
+    }
+
+    #[test]
+    fn garbage_collect_older_than_keeps_recent_envs() {
+        let (flox, _temp_dir) = flox_instance();
+        let reg_path = env_registry_path(&flox);
+
+        // This also registers the environment.
+        let env = new_path_environment(&flox, "version = 1");
+        let env_hash = path_hash(&env.path);
+        std::fs::remove_dir_all(&env.path).unwrap();
+
+        garbage_collect_older_than(&flox, Some(Duration::from_secs(60 * 60)), &[]).unwrap();
+        let reg_read = read_environment_registry(&reg_path).unwrap().unwrap();
+        assert!(
+            reg_read.entry_for_hash(&env_hash).is_some(),
+            "recently registered env should survive GC, reg: {:#?}",
+            reg_read
+        );
+
+        // Pretend the environment was registered two hours ago
+        let lock = acquire_env_registry_lock(&reg_path).unwrap();
+        let mut reg = read_environment_registry(&reg_path).unwrap().unwrap();
+        for registered in reg.entry_for_hash_mut(&env_hash).unwrap().envs.iter_mut() {
+            registered.created_at -= 2 * 60 * 60;
+        }
+        write_environment_registry(&reg, &reg_path, lock).unwrap();
+
+        garbage_collect_older_than(&flox, Some(Duration::from_secs(60 * 60)), &[]).unwrap();
+        let reg_read = read_environment_registry(&reg_path).unwrap().unwrap();
+        assert!(
+            reg_read.entry_for_hash(&env_hash).is_none(),
+            "deleted env registered before the cutoff should not survive GC, reg: {:#?}",
+            reg_read
+        );
+    }
 }
@@ -38,7 +38,13 @@ use crate::data::CanonicalPath;
 use crate::flox::Flox;
 use crate::models::environment::install::compute_install_modifications;
 use crate::providers::buildenv::{BuildEnv, BuildEnvError, BuildEnvNix, BuildEnvOutputs};
-use crate::providers::lock_manifest::{LockManifest, LockResult, ResolutionFailure, ResolveError};
+use crate::providers::lock_manifest::{
+    LockManifest,
+    LockResult,
+    ResolutionFailure,
+    ResolutionOverrides,
+    ResolveError,
+};
 use crate::providers::nix_auth::{AuthError, NixAuth};
 use crate::providers::services::process_compose::{ServiceError, maybe_make_service_config_file};
 
@@ -147,10 +153,16 @@ impl<State> CoreEnvironment<State> {
     /// since pkgdb manifests can no longer be locked.
     ///
     /// TODO: consider removing this
-    pub fn ensure_locked(&mut self, flox: &Flox) -> Result<LockResult, EnvironmentError> {
+    ///
+    /// `overrides` only apply if the environment has to be locked.
+    pub fn ensure_locked(
+        &mut self,
+        flox: &Flox,
+        overrides: &ResolutionOverrides,
+    ) -> Result<LockResult, EnvironmentError> {
         match self.lockfile_if_up_to_date()? {
             Some(lock) => Ok(LockResult::Unchanged(lock)),
-            None => self.lock(flox),
+            None => self.lock_with_overrides(flox, overrides),
         }
     }
 
@@ -187,6 +199,17 @@ impl<State> CoreEnvironment<State> {
     /// It's included in the [ReadOnly] struct for ergonomic reasons
     /// and because it doesn't modify the manifest.
     pub fn lock(&mut self, flox: &Flox) -> Result<LockResult, EnvironmentError> {
+        self.lock_with_overrides(flox, &ResolutionOverrides::default())
+    }
+
+    /// Lock the environment, applying `overrides` for resolution only.
+    ///
+    /// See [Self::lock].
+    pub fn lock_with_overrides(
+        &mut self,
+        flox: &Flox,
+        overrides: &ResolutionOverrides,
+    ) -> Result<LockResult, EnvironmentError> {
         let manifest_without_migrating = self.manifest_without_migrating()?.as_typed_only();
         let original_schema = manifest_without_migrating.get_schema_version();
 
@@ -199,11 +222,14 @@ impl<State> CoreEnvironment<State> {
         // This is `mut` because we may need to update the on-disk manifest to match the
         // schema of the merged manifest, and then we'll also have to update the
         // `compose.composer` to match.
-        let mut lockfile = LockManifest::lock_manifest(
+        let mut lockfile = LockManifest::lock_manifest_with_include_upgrades(
             flox,
             &migrated_manifest_for_locking,
             existing_lockfile.as_ref(),
             &self.include_fetcher,
+            None,
+            false,
+            overrides,
         )
         .block_on()?;
 
@@ -242,18 +268,26 @@ impl<State> CoreEnvironment<State> {
     /// Note: does not handle updating the on-disk manifest or lockfile,
     /// and does not handle ensuring that the schemas of the on-disk
     /// manifest and the lockfile's manifest are in sync.
+    ///
+    /// With `locked_includes_only`, included environments that are already
+    /// locked are not fetched again, even if they have been renamed.
     pub fn lock_without_writing(
         &mut self,
         flox: &Flox,
         manifest: &Manifest<Migrated>,
         existing_lockfile: Option<&Lockfile>,
+        locked_includes_only: bool,
+        overrides: &ResolutionOverrides,
     ) -> Result<LockResult, EnvironmentError> {
         // If a lockfile exists, it is used as a base.
-        let lockfile = LockManifest::lock_manifest(
+        let lockfile = LockManifest::lock_manifest_with_include_upgrades(
             flox,
             &manifest.as_migrated_typed_only(),
             existing_lockfile,
             &self.include_fetcher,
+            None,
+            locked_includes_only,
+            overrides,
         )
         .block_on()?;
 
@@ -304,6 +338,41 @@ impl<State> CoreEnvironment<State> {
     }
 }
 
+/// Build a lockfile other than an environment's own lockfile,
+/// e.g. a lockfile with an activation profile applied.
+///
+/// The lockfile is written to `lockfile_path` first, as the builder reads
+/// lockfiles from disk.
+/// Activation symlinks are created at `out_link_prefix` as in
+/// [CoreEnvironment::build].
+pub fn build_lockfile(
+    flox: &Flox,
+    lockfile: &Lockfile,
+    lockfile_path: &Path,
+    out_link_prefix: &Path,
+) -> Result<BuildEnvOutputs, CoreEnvironmentError> {
+    debug!("writing lockfile to {}", lockfile_path.display());
+    let mut contents =
+        serde_json::to_string_pretty(lockfile).expect("lockfile contents should be valid JSON");
+    contents.push('\n');
+    write_atomically(lockfile_path, contents)
+        .map_err(CoreEnvironmentError::WriteLockfileAtomically)?;
+
+    let service_config_path = maybe_make_service_config_file(flox, lockfile)?;
+
+    let auth = NixAuth::from_flox(flox).map_err(CoreEnvironmentError::Auth)?;
+
+    let catalog = &flox.floxhub_client;
+    let outputs = BuildEnvNix::new(auth).build(
+        catalog,
+        lockfile_path,
+        service_config_path,
+        Some(out_link_prefix),
+    )?;
+    debug!(?outputs, "built lockfile");
+    Ok(outputs)
+}
+
 /// Environment modifying methods accept an optional `out_link_prefix` and,
 /// when supplied, write activation symlinks as part of the in-transaction build.
 /// Since files referenced by the environment are ingested into the nix store,
@@ -323,25 +392,34 @@ impl CoreEnvironment<ReadOnly> {
 
     pub(crate) fn manifest(&mut self, flox: &Flox) -> Result<Manifest<Migrated>, EnvironmentError> {
         let manifest = self.manifest_without_migrating()?;
-        let lockfile = self.ensure_locked(flox)?.into();
+        let lockfile = self
+            .ensure_locked(flox, &ResolutionOverrides::default())?
+            .into();
         let migrated = manifest.migrate(Some(&lockfile))?;
         Ok(migrated)
     }
 
     /// Install packages to the environment atomically
     ///
+    /// The modified manifest is locked and built in a temporary copy of the
+    /// environment and only replaces the original once both succeeded,
+    /// so a failed install (e.g. a package that can't be resolved)
+    /// leaves the manifest and lockfile as they were.
+    ///
     /// Skips rebuilding if all packages are already installed
     pub fn install(
         &mut self,
         packages: &[PackageToInstall],
         flox: &Flox,
         out_link_prefix: Option<&Path>,
+        overrides: &ResolutionOverrides,
     ) -> Result<InstallationAttempt, EnvironmentError> {
-        let manifest = self.manifest(flox)?;
         // TODO: this could lead to double resolution and surprising errors
         // (e.g. if you try to install a package and we fail to resolve a different package)
         // We need a lockfile for logic about output merging
-        let lockfile: Lockfile = self.lock(flox)?.into();
+        let lockfile: Lockfile = self.lock_with_overrides(flox, overrides)?.into();
+        // Lock with the overrides first, so that this doesn't lock without them
+        let manifest = self.manifest(flox)?;
 
         let modifications = compute_install_modifications(packages, &manifest, &lockfile)?;
 
@@ -350,7 +428,7 @@ impl CoreEnvironment<ReadOnly> {
         } else {
             let new_manifest = manifest.modify_packages(&modifications)?;
             let (built_environments, _) =
-                self.transact_with_manifest(&new_manifest, flox, out_link_prefix)?;
+                self.transact_with_manifest(&new_manifest, flox, out_link_prefix, overrides)?;
             Some(built_environments)
         };
 
@@ -360,6 +438,40 @@ impl CoreEnvironment<ReadOnly> {
         })
     }
 
+    /// Add packages to the manifest without locking or building the environment
+    ///
+    /// The lockfile is left as is, so the environment is out of date
+    /// until it is locked again, e.g. when it is activated.
+    /// Outputs of packages that are already installed are merged
+    /// based on the existing lockfile.
+    pub fn stage_install(
+        &mut self,
+        packages: &[PackageToInstall],
+    ) -> Result<InstallationAttempt, EnvironmentError> {
+        let up_to_date_lockfile = self.lockfile_if_up_to_date()?;
+        let manifest = self
+            .manifest_without_migrating()?
+            .migrate(up_to_date_lockfile.as_ref())?;
+        let lockfile = self.existing_lockfile()?.unwrap_or_default();
+
+        let modifications = compute_install_modifications(packages, &manifest, &lockfile)?;
+        if !modifications.is_empty() {
+            let new_manifest = manifest.modify_packages(&modifications)?;
+            debug!(
+                "writing staged manifest to {}",
+                self.manifest_path().display()
+            );
+            new_manifest
+                .as_writable()
+                .write_to_file(self.manifest_path())?;
+        }
+
+        Ok(InstallationAttempt {
+            modifications,
+            built_environments: None,
+        })
+    }
+
     /// Uninstall packages and or package outputs from the environment atomically
     ///
     /// Locks the environment first in order to detect and resolve any composition.
@@ -383,7 +495,12 @@ impl CoreEnvironment<ReadOnly> {
         let modifications = resolve_specs_to_modifications(&uninstall_specs, &manifest, &lockfile)?;
 
         let new_manifest = manifest.modify_packages(&modifications)?;
-        let (store_path, _) = self.transact_with_manifest(&new_manifest, flox, out_link_prefix)?;
+        let (store_path, _) = self.transact_with_manifest(
+            &new_manifest,
+            flox,
+            out_link_prefix,
+            &ResolutionOverrides::default(),
+        )?;
 
         // Collect the modified install ids that are still installed through includes
         let still_included = if let Some(compose) = &lockfile.compose {
@@ -406,11 +523,19 @@ impl CoreEnvironment<ReadOnly> {
     }
 
     /// Atomically edit this environment, ensuring that it still builds
+    ///
+    /// With `locked_includes_only`, included environments that are already
+    /// locked are not fetched again, even if they have been renamed.
+    ///
+    /// With `skip_ineffective_edits`, edits that lock to the existing lockfile
+    /// are written to the manifest, but the environment isn't rebuilt.
     pub fn edit(
         &mut self,
         flox: &Flox,
         contents: String,
         out_link_prefix: Option<&Path>,
+        locked_includes_only: bool,
+        skip_ineffective_edits: bool,
     ) -> Result<EditResult, EnvironmentError> {
         let maybe_up_to_date_lockfile = self.lockfile_if_up_to_date()?;
 
@@ -435,8 +560,31 @@ impl CoreEnvironment<ReadOnly> {
                 (None, migrated)
             };
 
-        let (store_path, new_lockfile) =
-            self.transact_with_manifest(&migrated_manifest, flox, out_link_prefix)?;
+        let lock_result = self.lock_for_transaction(
+            &migrated_manifest,
+            flox,
+            locked_includes_only,
+            &ResolutionOverrides::default(),
+        )?;
+        if skip_ineffective_edits
+            && lockfile_is_up_to_date
+            && matches!(lock_result, LockResult::Unchanged(_))
+        {
+            // Keep the user's comments and formatting,
+            // but skip rebuilding the environment.
+            debug!("edit doesn't change the lockfile, only writing the manifest");
+            new_manifest
+                .as_writable()
+                .write_to_file(self.manifest_path())?;
+            return Ok(EditResult::Unchanged);
+        }
+
+        let (store_path, new_lockfile) = self.transact_with_locked_manifest(
+            &migrated_manifest,
+            lock_result.into(),
+            flox,
+            out_link_prefix,
+        )?;
 
         Ok(EditResult::Changed {
             old_lockfile: Box::new(old_lockfile),
@@ -524,6 +672,8 @@ impl CoreEnvironment<ReadOnly> {
     /// First resolve a new lockfile with upgraded packages using the catalog client.
     /// Then verify the new lockfile by building the environment.
     ///
+    /// Packages in `frozen` are kept locked as they are.
+    ///
     /// Finally if `write_lockfile` is true,
     /// replace the existing environment with the new, upgraded one.
     /// Otherwise, validate the upgrade by writing the new lockfile to a temporary file
@@ -532,6 +682,7 @@ impl CoreEnvironment<ReadOnly> {
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
         write_lockfile: bool,
         out_link_prefix: Option<&Path>,
     ) -> Result<UpgradeResult, EnvironmentError> {
@@ -539,9 +690,11 @@ impl CoreEnvironment<ReadOnly> {
         let manifest = self.manifest(flox)?;
 
         Self::ensure_valid_upgrade(groups_or_iids, &manifest)?;
+        Self::ensure_valid_frozen_packages(frozen, &manifest)?;
         tracing::debug!("using catalog client to upgrade");
 
-        let mut result = self.upgrade_with_catalog_client(flox, groups_or_iids, &manifest)?;
+        let mut result =
+            self.upgrade_with_catalog_client(flox, groups_or_iids, frozen, &manifest)?;
 
         // SAFETY: serde_json::to_string_pretty is only documented to fail if
         // the "Serialize decides to fail, or if T contains a map with non-string keys",
@@ -624,14 +777,31 @@ impl CoreEnvironment<ReadOnly> {
         Ok(())
     }
 
+    /// Check that all frozen packages are packages in the manifest.
+    fn ensure_valid_frozen_packages(
+        frozen: &[&str],
+        manifest: &Manifest<Migrated>,
+    ) -> Result<(), CoreEnvironmentError> {
+        for id in frozen {
+            if manifest.pkg_descriptor_with_id(id).is_none() {
+                return Err(CoreEnvironmentError::UpgradeFailedCatalog(
+                    UpgradeError::PkgNotFound(ManifestError::PackageNotFound(id.to_string())),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Upgrade the given groups or install ids in the environment using the catalog client.
     /// The environment is upgraded by locking the existing manifest
     /// using [LockedManifestCatalog::lock_manifest] with the existing lockfile as a seed,
     /// where the upgraded packages have been filtered out causing them to be re-resolved.
+    /// Packages in `frozen` are never filtered out.
     fn upgrade_with_catalog_client(
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
         manifest: &Manifest<Migrated>,
     ) -> Result<UpgradeResult, EnvironmentError> {
         tracing::debug!(to_upgrade = groups_or_iids.join(","), "upgrading");
@@ -646,7 +816,11 @@ impl CoreEnvironment<ReadOnly> {
         // all packages matching the given groups or iids.
         // If no groups or iids are provided, all packages are unlocked.
         let seed_lockfile = existing_lockfile.clone().map(|mut lockfile| {
-            LockManifest::unlock_specified_packages_or_groups(&mut lockfile, groups_or_iids);
+            LockManifest::unlock_specified_packages_or_groups(
+                &mut lockfile,
+                groups_or_iids,
+                frozen,
+            );
             lockfile
         });
 
@@ -710,6 +884,8 @@ impl CoreEnvironment<ReadOnly> {
             existing_lockfile.as_ref(),
             &self.include_fetcher,
             Some(to_upgrade),
+            false,
+            &ResolutionOverrides::default(),
         )
         .block_on()?;
 
@@ -826,10 +1002,46 @@ impl CoreEnvironment<ReadOnly> {
         manifest: &Manifest<Migrated>,
         flox: &Flox,
         out_link_prefix: Option<&Path>,
+        overrides: &ResolutionOverrides,
     ) -> Result<(BuildEnvOutputs, Lockfile), EnvironmentError> {
+        let lockfile = self
+            .lock_for_transaction(manifest, flox, false, overrides)?
+            .into();
+        self.transact_with_locked_manifest(manifest, lockfile, flox, out_link_prefix)
+    }
+
+    /// Validate `manifest` and lock it, using the existing lockfile as a base
+    fn lock_for_transaction(
+        &mut self,
+        manifest: &Manifest<Migrated>,
+        flox: &Flox,
+        locked_includes_only: bool,
+        overrides: &ResolutionOverrides,
+    ) -> Result<LockResult, EnvironmentError> {
         debug!("transaction: validating services block");
         manifest.as_latest_schema().services.validate()?;
 
+        debug!("transaction: locking environment");
+        let existing_lockfile = self.existing_lockfile()?;
+        self.lock_without_writing(
+            flox,
+            manifest,
+            existing_lockfile.as_ref(),
+            locked_includes_only,
+            overrides,
+        )
+    }
+
+    /// Attempt to transactionally replace the manifest with `manifest`
+    /// and the lockfile with `lockfile`, the result of locking `manifest`
+    #[must_use = "don't discard the store path of built environments"]
+    fn transact_with_locked_manifest(
+        &mut self,
+        manifest: &Manifest<Migrated>,
+        mut lockfile: Lockfile,
+        flox: &Flox,
+        out_link_prefix: Option<&Path>,
+    ) -> Result<(BuildEnvOutputs, Lockfile), EnvironmentError> {
         let tempdir = tempfile::tempdir_in(&flox.temp_dir)
             .map_err(CoreEnvironmentError::MakeSandbox)?
             .keep();
@@ -840,12 +1052,6 @@ impl CoreEnvironment<ReadOnly> {
         );
         let mut temp_env = self.writable(&tempdir)?;
 
-        debug!("transaction: locking environment");
-        let existing_lockfile = temp_env.existing_lockfile()?;
-        let mut lockfile: Lockfile = temp_env
-            .lock_without_writing(flox, manifest, existing_lockfile.as_ref())?
-            .into();
-
         debug!("transaction: ensuring manifest schemas match");
         if lockfile.manifest.get_schema_version() != manifest.original_schema()
             && let Some(compose) = lockfile.compose.as_mut()
@@ -1361,7 +1567,9 @@ mod tests {
 
         flox.floxhub_client =
             catalog_replay_client(GENERATED_DATA.join("resolve/hello.yaml")).await;
-        env_view.edit(&flox, new_env_str.to_string(), None).unwrap();
+        env_view
+            .edit(&flox, new_env_str.to_string(), None, false, false)
+            .unwrap();
 
         assert_eq!(
             env_view
@@ -1383,7 +1591,9 @@ mod tests {
         let mut env_view = new_core_environment(&flox, &same_manifest);
         env_view.lock(&flox).unwrap(); // Explicit lock
 
-        let result = env_view.edit(&flox, same_manifest, None).unwrap();
+        let result = env_view
+            .edit(&flox, same_manifest, None, false, false)
+            .unwrap();
         assert_eq!(result, EditResult::Unchanged);
     }
 
@@ -1398,11 +1608,116 @@ mod tests {
         env_view.lock(&flox).unwrap(); // Explicit lock
 
         let result = env_view
-            .edit(&flox, same_manifest.to_string(), None)
+            .edit(&flox, same_manifest.to_string(), None, false, false)
             .unwrap();
         assert_eq!(result, EditResult::Unchanged);
     }
 
+    /// With `skip_ineffective_edits`, an edit that only changes comments
+    /// and whitespace returns EditResult::Unchanged,
+    /// but the new manifest is still written.
+    #[test]
+    fn edit_skips_ineffective_edits() {
+        let (flox, _temp_dir_handle) = flox_instance();
+
+        let manifest = with_latest_schema("");
+        let mut env_view = new_core_environment(&flox, &manifest);
+        env_view.lock(&flox).unwrap();
+
+        let commented = format!("# a comment\n\n{manifest}\n");
+        let result = env_view
+            .edit(&flox, commented.clone(), None, false, true)
+            .unwrap();
+        assert_eq!(result, EditResult::Unchanged);
+        assert!(
+            env_view
+                .manifest_without_migrating()
+                .unwrap()
+                .contents_match(&commented)
+        );
+
+        let reformatted = format!("# another comment\n\n{manifest}\n");
+        let result = env_view
+            .edit(&flox, reformatted.clone(), None, false, false)
+            .unwrap();
+        assert!(matches!(result, EditResult::Changed { .. }));
+        assert!(
+            env_view
+                .manifest_without_migrating()
+                .unwrap()
+                .contents_match(&reformatted)
+        );
+    }
+
+    /// Staging a package writes it to the manifest but doesn't lock it
+    #[test]
+    fn stage_install_writes_manifest_without_locking() {
+        let (flox, _temp_dir_handle) = flox_instance();
+
+        let mut env_view = new_core_environment(&flox, &with_latest_schema(""));
+        env_view.lock(&flox).unwrap();
+        let lockfile_before = env_view.existing_lockfile_contents().unwrap();
+
+        let attempt = env_view
+            .stage_install(&[PackageToInstall::parse(&flox.system, "hello").unwrap()])
+            .unwrap();
+        assert_eq!(attempt.modifications.len(), 1);
+        assert_eq!(attempt.built_environments, None);
+
+        let manifest = env_view
+            .manifest_without_migrating()
+            .unwrap()
+            .migrate(None)
+            .unwrap();
+        assert!(manifest.pkg_descriptor_with_id("hello").is_some());
+        assert_eq!(
+            env_view.existing_lockfile_contents().unwrap(),
+            lockfile_before
+        );
+        assert!(env_view.lockfile_if_up_to_date().unwrap().is_none());
+
+        // Staging the same package again is a no-op
+        let attempt = env_view
+            .stage_install(&[PackageToInstall::parse(&flox.system, "hello").unwrap()])
+            .unwrap();
+        assert!(attempt.modifications.is_empty());
+    }
+
+    /// A package that fails to resolve leaves the manifest and lockfile untouched
+    #[tokio::test(flavor = "multi_thread")]
+    async fn install_leaves_environment_unchanged_if_locking_fails() {
+        let (mut flox, _temp_dir_handle) = flox_instance();
+
+        let mut env_view = new_core_environment(&flox, &with_latest_schema(""));
+        env_view.lock(&flox).unwrap();
+        let manifest_before = fs::read(env_view.manifest_path()).unwrap();
+        let lockfile_before = fs::read(env_view.lockfile_path()).unwrap();
+
+        flox.floxhub_client =
+            catalog_replay_client(GENERATED_DATA.join("resolve/badpkg.yaml")).await;
+        let err = env_view
+            .install(
+                &[PackageToInstall::parse(&flox.system, "badpkg").unwrap()],
+                &flox,
+                None,
+                &ResolutionOverrides::default(),
+            )
+            .unwrap_err();
+
+        assert!(
+            matches!(
+                err,
+                EnvironmentError::Core(CoreEnvironmentError::Resolve(
+                    ResolveError::ResolutionFailed(_)
+                ))
+            ),
+            "{err:?}"
+        );
+        assert_eq!(fs::read(env_view.manifest_path()).unwrap(), manifest_before);
+        assert_eq!(fs::read(env_view.lockfile_path()).unwrap(), lockfile_before);
+        assert!(!env_view.env_dir.with_extension("tmp").exists());
+    }
+
     /// A no-op with edit against an unlocked environment returns EditResult::Changed
     #[test]
     fn edit_no_op_unlocked_returns_changed() {
@@ -1412,7 +1727,7 @@ mod tests {
         let mut env_view = new_core_environment(&flox, same_manifest);
 
         let result = env_view
-            .edit(&flox, same_manifest.to_string(), None)
+            .edit(&flox, same_manifest.to_string(), None, false, false)
             .unwrap();
         assert!(matches!(result, EditResult::Changed { .. }));
     }
@@ -1473,7 +1788,9 @@ mod tests {
 
         flox.floxhub_client =
             catalog_replay_client(GENERATED_DATA.join("resolve/hello.yaml")).await;
-        let result = env_view.edit(&flox, new_env_str.to_string(), None).unwrap();
+        let result = env_view
+            .edit(&flox, new_env_str.to_string(), None, false, false)
+            .unwrap();
 
         assert!(matches!(result, EditResult::Changed { .. }));
         assert!(!result.reactivate_required().unwrap());
@@ -1491,7 +1808,9 @@ mod tests {
         on-activate = ""
         "#;
 
-        let result = env_view.edit(&flox, new_env_str.to_string(), None).unwrap();
+        let result = env_view
+            .edit(&flox, new_env_str.to_string(), None, false, false)
+            .unwrap();
 
         assert!(result.reactivate_required().unwrap());
     }
@@ -1510,6 +1829,7 @@ mod tests {
                 )],
                 &flox,
                 None,
+                &ResolutionOverrides::default(),
             )
             .unwrap();
 
@@ -1518,7 +1838,7 @@ mod tests {
 
         let manifest = env_view.manifest(&flox).unwrap();
         let upgraded_packages = env_view
-            .upgrade_with_catalog_client(&flox, &[], &manifest)
+            .upgrade_with_catalog_client(&flox, &[], &[], &manifest)
             .unwrap()
             .diff();
 
@@ -1765,7 +2085,8 @@ mod tests {
             .get_mut("bad")
             .unwrap()
             .shutdown = None;
-        let res = env.transact_with_manifest(&manifest, &flox, None);
+        let res =
+            env.transact_with_manifest(&manifest, &flox, None, &ResolutionOverrides::default());
         assert!(matches!(
             res,
             Err(EnvironmentError::ManifestError(
@@ -14,6 +14,14 @@ use crate::models::environment::remote_environment::RemoteEnvironment;
 use crate::models::environment::{Environment, ManagedPointer};
 use crate::providers::lock_manifest::RecoverableMergeError;
 
+/// The live generation of an environment that tracks generations
+fn current_generation(environment: &impl GenerationsExt) -> Result<Option<u64>, EnvironmentError> {
+    let metadata = environment
+        .generations_metadata()
+        .map_err(ManagedEnvironmentError::Generations)?;
+    Ok(metadata.current_gen().map(|generation| *generation as u64))
+}
+
 /// Context required to fetch an environment include
 #[derive(Clone, Debug)]
 pub struct IncludeFetcher {
@@ -26,7 +34,7 @@ impl IncludeFetcher {
         flox: &Flox,
         include_environment: &IncludeDescriptor,
     ) -> Result<LockedInclude, EnvironmentError> {
-        let (manifest, name) = match include_environment {
+        let (manifest, name, generation) = match include_environment {
             IncludeDescriptor::Local { dir, name } => self.fetch_local(flox, dir, name),
             IncludeDescriptor::Remote {
                 remote,
@@ -39,16 +47,19 @@ impl IncludeFetcher {
             manifest,
             name,
             descriptor: include_environment.clone(),
+            generation,
         })
     }
 
     /// Fetch a local (path or managed) environment, only if it's already locked.
+    ///
+    /// For managed environments, also return the current generation.
     fn fetch_local(
         &self,
         flox: &Flox,
         dir: impl AsRef<Path>,
         name: &Option<String>,
-    ) -> Result<(Manifest<TypedOnly>, String), EnvironmentError> {
+    ) -> Result<(Manifest<TypedOnly>, String, Option<u64>), EnvironmentError> {
         if self.base_directory.is_none() {
             return Err(EnvironmentError::Recoverable(
                 RecoverableMergeError::RemoteCannotIncludeLocal,
@@ -63,11 +74,11 @@ impl IncludeFetcher {
             .clone()
             .unwrap_or_else(|| environment.name().to_string());
 
-        let lockfile = match environment {
+        let (lockfile, generation) = match environment {
             ConcreteEnvironment::Path(environment) => {
                 let core_environment = environment.into_core_environment()?;
                 if let Some(lockfile) = core_environment.lockfile_if_up_to_date()? {
-                    lockfile
+                    (lockfile, None)
                 } else {
                     return Err(EnvironmentError::Recoverable(
                         RecoverableMergeError::PathOutOfSync(path),
@@ -81,7 +92,7 @@ impl IncludeFetcher {
                     ));
                 };
                 if !environment.has_local_changes(flox)? {
-                    lockfile
+                    (lockfile, current_generation(&environment)?)
                 } else {
                     return Err(EnvironmentError::Recoverable(
                         RecoverableMergeError::ManagedOutOfSync(path),
@@ -95,19 +106,21 @@ impl IncludeFetcher {
 
         let manifest = lockfile.manifest;
 
-        Ok((manifest, name))
+        Ok((manifest, name, generation))
     }
 
     /// Fetch a remote environment.
     /// If `generation` is not [None], retrieve the lockfile for the named generation,
     /// instead of the "live" generation.
+    ///
+    /// Also returns the generation that was fetched.
     fn fetch_remote(
         &self,
         flox: &Flox,
         remote: &RemoteEnvironmentRef,
         name: &Option<String>,
         generation: Option<usize>,
-    ) -> Result<(Manifest<TypedOnly>, String), EnvironmentError> {
+    ) -> Result<(Manifest<TypedOnly>, String, Option<u64>), EnvironmentError> {
         let pointer =
             ManagedPointer::new(remote.owner().clone(), remote.name().clone(), &flox.floxhub);
 
@@ -116,23 +129,29 @@ impl IncludeFetcher {
             tempfile::tempdir_in(&flox.temp_dir).map_err(EnvironmentError::CreateTempDir)?;
         let environment = RemoteEnvironment::new_in(flox, tempdir.path(), pointer, None)?;
 
-        let lockfile = match generation {
+        let (lockfile, generation) = match generation {
             Some(generation) => {
                 let lockfile_content = environment
                     .lockfile_contents_for_generation(generation)
                     .map_err(ManagedEnvironmentError::Generations)?;
-                Lockfile::from_str(&lockfile_content)?
+                (
+                    Lockfile::from_str(&lockfile_content)?,
+                    Some(generation as u64),
+                )
             },
-            None => environment
-                .existing_lockfile(flox)?
-                .expect("remote environments should always be locked"),
+            None => (
+                environment
+                    .existing_lockfile(flox)?
+                    .expect("remote environments should always be locked"),
+                current_generation(&environment)?,
+            ),
         };
         let manifest = lockfile.manifest;
         let name = name
             .clone()
             .unwrap_or_else(|| environment.name().to_string());
 
-        Ok((manifest, name))
+        Ok((manifest, name, generation))
     }
 
     /// For directories that aren't absolute, join them to the base_directory
@@ -209,6 +228,7 @@ mod test {
             manifest,
             name: "environment".to_string(),
             descriptor: include_descriptor,
+            generation: None,
         })
     }
 
@@ -239,6 +259,7 @@ mod test {
             manifest,
             name: "environment".to_string(),
             descriptor: include_descriptor,
+            generation: None,
         })
     }
 
@@ -382,7 +403,7 @@ mod test {
         "#});
         let manifest = toml_edit::de::from_str(&manifest_contents).unwrap();
         remote_env
-            .edit(&flox, manifest_contents.to_string())
+            .edit(&flox, manifest_contents.to_string(), false, false)
             .unwrap();
         remote_env.push(&flox, true).unwrap();
 
@@ -402,6 +423,11 @@ mod test {
                 manifest,
                 name: "name".to_string(),
                 descriptor: include_descriptor,
+                generation: remote_env
+                    .generations_metadata()
+                    .unwrap()
+                    .current_gen()
+                    .map(|generation| *generation as u64),
             },
             "fetch should get the new generation"
         );
@@ -452,6 +478,7 @@ mod test {
             manifest: initial_generation_manifest.as_typed_only(),
             name: "name".to_string(),
             descriptor: include_descriptor.clone(),
+            generation: Some(*initial_generation as u64),
         });
 
         // Modify the remote environment to create a new generation.
@@ -459,7 +486,9 @@ mod test {
             [vars]
             foo = "bar"
         "#});
-        remote_env.edit(&flox, manifest_contents.clone()).unwrap();
+        remote_env
+            .edit(&flox, manifest_contents.clone(), false, false)
+            .unwrap();
 
         let fetched_after_upstream_changes =
             include_fetcher.fetch(&flox, &include_descriptor).unwrap();
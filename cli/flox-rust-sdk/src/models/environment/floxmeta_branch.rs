@@ -763,6 +763,7 @@ mod tests {
             floxhub_git_url_override: Some(
                 Url::from_directory_path(mock_floxhub_git_path).unwrap(),
             ),
+            read_only: false,
             version: flox_core::Version::<1> {},
         }
     }
@@ -407,6 +407,43 @@ impl Generations<ReadWrite<'_>> {
 
         Ok(())
     }
+
+    /// Delete a generation that is not live.
+    ///
+    /// The files of the generation are removed,
+    /// and the deletion is recorded in the history,
+    /// so that the generation can no longer be listed or switched to.
+    ///
+    /// Fails if the generation does not exist or is the current generation.
+    pub fn delete_generation(&mut self, generation: GenerationId) -> Result<(), GenerationsError> {
+        let mut metadata = self.metadata()?;
+
+        let history_item = metadata.delete_generation(DeleteGenerationOptions {
+            author: self._state.author.clone(),
+            hostname: self._state.hostname.clone(),
+            argv: self._state.argv.clone(),
+            timestamp: Utc::now(),
+            generation,
+        })?;
+        let summary = history_item.summary();
+
+        write_metadata_file(metadata, self.repo.path())?;
+
+        self.repo
+            .rm(&[Path::new(&generation.to_string())], true, true, false)
+            .map_err(GenerationsError::StageChanges)?;
+        self.repo
+            .add(&[Path::new(GENERATIONS_METADATA_FILE)])
+            .map_err(GenerationsError::StageChanges)?;
+        self.repo
+            .commit(&summary)
+            .map_err(GenerationsError::CommitChanges)?;
+        self.repo
+            .push("origin", false)
+            .map_err(GenerationsError::CompleteTransaction)?;
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Error)]
@@ -467,6 +504,8 @@ pub enum GenerationsError {
     NoGenerations,
     #[error("Cannot switch to live generation")]
     SwitchToLiveGeneration,
+    #[error("Cannot delete generation {0} because it is the live generation")]
+    DeleteLiveGeneration(GenerationId),
     // endregion
 
     // region: repo/transaction
@@ -549,9 +588,7 @@ fn parse_metadata<'de>(
             migrated.into()
         },
         MetadataVersionCompat::V2(all_generations_metadata) => all_generations_metadata,
-        MetadataVersionCompat::VX { version }
-            if version == Value::Number(1.into()) || version == Value::Number(2.into()) =>
-        {
+        MetadataVersionCompat::VX { version } if [1, 2, 3].map(Value::from).contains(&version) => {
             Err(GenerationsError::InvalidSchema(version))?
         },
 
@@ -599,6 +636,26 @@ pub trait GenerationsExt {
         generation: GenerationId,
     ) -> Result<(), EnvironmentError>;
 
+    /// Delete a generation other than the live one,
+    /// and remove the links that keep its build from being garbage collected.
+    ///
+    /// Returns the removed links.
+    fn delete_generation(
+        &mut self,
+        flox: &Flox,
+        generation: GenerationId,
+    ) -> Result<Vec<PathBuf>, EnvironmentError>;
+
+    /// Return the links on this system that keep the build of `generation`
+    /// from being garbage collected.
+    ///
+    /// Generations that were never built on this system have no links.
+    fn generation_links(
+        &self,
+        flox: &Flox,
+        generation: GenerationId,
+    ) -> Result<Vec<PathBuf>, EnvironmentError>;
+
     /// Return the lockfile from FloxHub stored on the sync branch.
     fn remote_lockfile_contents_for_current_generation(&self) -> Result<String, GenerationsError>;
     /// Return the manifest from FloxHub stored on the sync branch.
@@ -708,11 +765,46 @@ impl TryFrom<ConcreteEnvironment> for GenerationsEnvironment {
 #[skip_serializing_none]
 pub struct AllGenerationsMetadata {
     /// Schema version of the metadata file
-    version: Version<2>,
+    version: MetadataVersion,
     history: History,
     total_generations: usize,
 }
 
+/// Schema version of [AllGenerationsMetadata]
+///
+/// Version 3 is written once a generation has been deleted,
+/// so that older versions of Flox, which don't know about
+/// [HistoryKind::DeleteGeneration], refuse to read the metadata
+/// instead of listing and switching to deleted generations.
+/// Metadata without deletions remains at version 2.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum MetadataVersion {
+    V2(Version<2>),
+    V3(Version<3>),
+}
+
+impl Default for MetadataVersion {
+    fn default() -> Self {
+        MetadataVersion::V2(Version)
+    }
+}
+
+// [Version] uses the same schema name for every version,
+// so a derived schema would describe both variants as the same definition.
+impl JsonSchema for MetadataVersion {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "version".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "integer",
+            "enum": [2, 3],
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AddGenerationOptions {
     pub author: String,
@@ -731,6 +823,15 @@ pub struct SwitchGenerationOptions {
     pub next_generation: GenerationId,
 }
 
+#[derive(Debug, Clone)]
+pub struct DeleteGenerationOptions {
+    pub author: String,
+    pub hostname: String,
+    pub argv: Vec<String>,
+    pub timestamp: DateTime<Utc>,
+    pub generation: GenerationId,
+}
+
 impl AllGenerationsMetadata {
     /// Add metadata for a new generation, as well as consistent history.
     /// The return provides the [GenerationId] of the added generation metadata,
@@ -804,7 +905,10 @@ impl AllGenerationsMetadata {
         }
 
         // we assume to track generations consecutively, 1 ..= total_generations
-        if *next_generation > self.total_generations {
+        // minus any deleted generations
+        if *next_generation > self.total_generations
+            || !self.generations().contains_key(&next_generation)
+        {
             return Err(GenerationsError::GenerationNotFound(*next_generation));
         };
 
@@ -831,6 +935,54 @@ impl AllGenerationsMetadata {
         Ok((next_generation, history_ref))
     }
 
+    /// Delete `generation`, which must exist and must not be the current generation.
+    ///
+    /// The deletion is recorded as a history item of type
+    /// [HistoryKind::DeleteGeneration],
+    /// which leaves the current generation unchanged.
+    pub fn delete_generation(
+        &mut self,
+        DeleteGenerationOptions {
+            author,
+            hostname,
+            argv,
+            timestamp,
+            generation,
+        }: DeleteGenerationOptions,
+    ) -> Result<&HistorySpec, GenerationsError> {
+        let current_generation = self.current_gen().ok_or(GenerationsError::NoGenerations)?;
+
+        if generation == current_generation {
+            return Err(GenerationsError::DeleteLiveGeneration(generation));
+        }
+
+        if !self.generations().contains_key(&generation) {
+            return Err(GenerationsError::GenerationNotFound(*generation));
+        }
+
+        let history_spec = HistorySpec {
+            author,
+            hostname,
+            command: Self::parse_argv(argv),
+            timestamp,
+            previous_generation: Some(current_generation),
+            current_generation,
+            kind: HistoryKind::DeleteGeneration { generation },
+        };
+
+        self.history.0.push(history_spec.into());
+        self.version = MetadataVersion::V3(Version);
+
+        let history_ref = self
+            .history
+            .0
+            .iter()
+            .next_back()
+            .expect("history event should have been inserted");
+
+        Ok(history_ref)
+    }
+
     /// Parse ARGV to store in a `HistorySpec`.
     ///
     /// If empty, as invoked from a unit test, return `None`.
@@ -867,8 +1019,9 @@ impl AllGenerationsMetadata {
     /// * exactly one generation creating event for every generation [1..]
     /// * previous_generation correctly refers to the previously live generation
     ///
-    /// These invariants are maintained when using [Self::add_generation]
-    /// and [Self::switch_generation].
+    /// These invariants are maintained when using [Self::add_generation],
+    /// [Self::switch_generation], and [Self::delete_generation].
+    /// Deleted generations are omitted.
     pub fn generations(&self) -> BTreeMap<GenerationId, SingleGenerationMetadata> {
         let mut map: BTreeMap<GenerationId, SingleGenerationMetadata> = BTreeMap::new();
         for spec in self.history.iter() {
@@ -885,6 +1038,9 @@ impl AllGenerationsMetadata {
                         .expect("there must be a current generation by construction");
                     new.last_live = None;
                 },
+                HistoryKind::DeleteGeneration { generation } => {
+                    map.remove(&generation);
+                },
                 _ => {
                     // Adding a generation performs an implicit switch.
                     // Hence, record that the previous generation was only live
@@ -1000,6 +1156,9 @@ pub enum HistoryKind {
     #[schemars(title = "SwitchGeneration")]
     SwitchGeneration,
 
+    #[schemars(title = "DeleteGeneration")]
+    DeleteGeneration { generation: GenerationId },
+
     #[schemars(title = "Other")]
     Other { summary: String },
 
@@ -1154,6 +1313,9 @@ impl HistorySpec {
                     "switch implementation prevents switches without a current live generation"
                 ),
             },
+            HistoryKind::DeleteGeneration { generation } => {
+                format!("deleted generation {generation}")
+            },
             HistoryKind::Other { summary } => summary.to_string(),
             HistoryKind::Unknown { kind } => format!("performed unknown {kind} operation"),
         }
@@ -1353,7 +1515,13 @@ pub mod test_helpers {
 
     use chrono::Utc;
 
-    use super::{AddGenerationOptions, GenerationId, HistoryKind, SwitchGenerationOptions};
+    use super::{
+        AddGenerationOptions,
+        DeleteGenerationOptions,
+        GenerationId,
+        HistoryKind,
+        SwitchGenerationOptions,
+    };
 
     pub const AUTHOR: &str = "author";
     pub const HOSTNAME: &str = "host";
@@ -1383,6 +1551,16 @@ pub mod test_helpers {
             next_generation,
         }
     }
+
+    pub fn default_delete_generation_options(generation: GenerationId) -> DeleteGenerationOptions {
+        DeleteGenerationOptions {
+            author: AUTHOR.into(),
+            hostname: HOSTNAME.into(),
+            argv: ARGV.clone(),
+            timestamp: Utc::now(),
+            generation,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1401,6 +1579,7 @@ mod tests {
 
     mod metadata {
         use chrono::Utc;
+        use flox_core::Version;
         use pretty_assertions::{assert_eq, assert_str_eq};
         use serde_json::{Value, json};
 
@@ -1409,6 +1588,7 @@ mod tests {
             AUTHOR,
             HOSTNAME,
             default_add_generation_options,
+            default_delete_generation_options,
             default_switch_generation_options,
         };
         use crate::models::environment::generations::{
@@ -1417,8 +1597,10 @@ mod tests {
             GenerationsError,
             HistoryKind,
             HistorySpec,
+            MetadataVersion,
             SwitchGenerationOptions,
             WithOtherFields,
+            parse_metadata,
         };
 
         /// Adding a generation adds consistent metadata, ie.
@@ -1575,6 +1757,84 @@ mod tests {
             )
         }
 
+        /// Deleting a generation removes it from the tracked generations,
+        /// keeps the current generation, and prevents switching to it.
+        #[test]
+        fn delete_generation_removes_generation() {
+            let mut metadata = AllGenerationsMetadata::default();
+            let (first_gen_id, ..) = metadata.add_generation(default_add_generation_options());
+            let (second_gen_id, ..) = metadata.add_generation(default_add_generation_options());
+            assert_eq!(metadata.version, MetadataVersion::V2(Version));
+
+            let history = metadata
+                .delete_generation(default_delete_generation_options(first_gen_id))
+                .unwrap()
+                .clone();
+
+            // older versions of Flox must not read metadata with deletions
+            assert_eq!(metadata.version, MetadataVersion::V3(Version));
+            let serialized = serde_json::to_string(&metadata).unwrap();
+            let parsed =
+                parse_metadata(&mut serde_json::Deserializer::from_str(&serialized)).unwrap();
+            assert_eq!(*parsed, metadata);
+
+            assert_eq!(history.kind, HistoryKind::DeleteGeneration {
+                generation: first_gen_id
+            });
+            assert_eq!(history.current_generation, second_gen_id);
+            assert_eq!(metadata.current_gen(), Some(second_gen_id));
+            assert_eq!(metadata.generations().keys().collect::<Vec<_>>(), vec![
+                &second_gen_id
+            ]);
+
+            let result =
+                metadata.switch_generation(default_switch_generation_options(first_gen_id));
+            assert!(
+                matches!(result, Err(GenerationsError::GenerationNotFound(1))),
+                "unexpected result {:?}",
+                result
+            );
+
+            // generation numbers are not reused
+            let (third_gen_id, ..) = metadata.add_generation(default_add_generation_options());
+            assert_eq!(third_gen_id, GenerationId(3));
+        }
+
+        #[test]
+        fn delete_generation_does_not_allow_current_generation() {
+            let mut metadata = AllGenerationsMetadata::default();
+            let (generation_id, ..) = metadata.add_generation(default_add_generation_options());
+
+            let result =
+                metadata.delete_generation(default_delete_generation_options(generation_id));
+
+            assert!(
+                matches!(result, Err(GenerationsError::DeleteLiveGeneration(id)) if id == generation_id),
+                "unexpected result {:?}",
+                result
+            )
+        }
+
+        #[test]
+        fn delete_generation_requires_existing_generation() {
+            let mut metadata = AllGenerationsMetadata::default();
+            let (first_gen_id, ..) = metadata.add_generation(default_add_generation_options());
+            metadata.add_generation(default_add_generation_options());
+            metadata
+                .delete_generation(default_delete_generation_options(first_gen_id))
+                .unwrap();
+
+            for absent_gen_id in [first_gen_id, GenerationId(3)] {
+                let result =
+                    metadata.delete_generation(default_delete_generation_options(absent_gen_id));
+                assert!(
+                    matches!(result, Err(GenerationsError::GenerationNotFound(n)) if n == *absent_gen_id),
+                    "unexpected result {:?}",
+                    result
+                )
+            }
+        }
+
         #[test]
         fn history_summaries() {
             let all_targets = [];
@@ -1905,15 +2165,30 @@ mod tests {
         }
 
         #[test]
-        fn parse_v3_unknown() {
+        fn parse_v3_invalid() {
             let metadata = json!({
                 "version": 3,
+                // Missing required fields.
             });
             let serialized = serde_json::to_string_pretty(&metadata).unwrap();
             let err = parse_metadata(&mut serde_json::Deserializer::from_str(&serialized))
-                .expect_err("unknown v3 schema should fail to parse");
+                .expect_err("invalid v3 schema should fail to parse");
+            assert_eq!(
+                err.to_string(),
+                "Environment metadata of version '3' could not be parsed into its expected schema."
+            );
+        }
+
+        #[test]
+        fn parse_v4_unknown() {
+            let metadata = json!({
+                "version": 4,
+            });
+            let serialized = serde_json::to_string_pretty(&metadata).unwrap();
+            let err = parse_metadata(&mut serde_json::Deserializer::from_str(&serialized))
+                .expect_err("unknown v4 schema should fail to parse");
             assert_eq!(err.to_string(), indoc! {"
-                Environment metadata of version '3' is not supported
+                Environment metadata of version '4' is not supported
 
                 This environment appears to have been modified by a newer version of Flox.
                 Please upgrade to the latest version of Flox and try again."});
@@ -2163,4 +2438,32 @@ mod tests {
             res
         );
     }
+
+    #[test]
+    fn delete_generation_removes_generation_files() {
+        let (mut generations, tempdir) = setup_two_generations();
+        let mut generations_rw = generations
+            .writable(&tempdir, AUTHOR, HOSTNAME, &ARGV)
+            .unwrap();
+
+        generations_rw.delete_generation(GEN_ID_1).unwrap();
+
+        let metadata = generations_rw.metadata().unwrap();
+        assert_eq!(metadata.current_gen(), Some(GEN_ID_2));
+        assert!(!metadata.generations().contains_key(&GEN_ID_1));
+        assert!(
+            !generations_rw
+                .repo
+                .path()
+                .join(GEN_ID_1.to_string())
+                .exists()
+        );
+        assert!(
+            generations_rw
+                .repo
+                .path()
+                .join(GEN_ID_2.to_string())
+                .exists()
+        );
+    }
 }
@@ -175,6 +175,8 @@ mod tests {
             version: None,
             systems: None,
             outputs,
+            pkg_group: None,
+            allow_missing_builds: false,
         })
     }
 
@@ -60,7 +60,7 @@ use crate::models::environment::generations::SyncToGenerationResult;
 use crate::models::floxmeta::{FloxMetaError, floxmeta_git_options};
 use crate::providers::buildenv::BuildEnvOutputs;
 use crate::providers::git::{GitCommandError, GitProvider, GitRemoteCommandError, PushFlag};
-use crate::providers::lock_manifest::LockResult;
+use crate::providers::lock_manifest::{LockResult, ResolutionOverrides};
 
 pub const GENERATION_LOCK_FILENAME: &str = "env.lock";
 
@@ -115,6 +115,13 @@ pub enum ManagedEnvironmentError {
     Diverged(DivergedMetadata),
     #[error("access to floxmeta repository was denied")]
     AccessDenied,
+    #[error(
+        "Environment '{0}' was pulled as read-only and can't be modified.\n\
+         \n\
+         To make changes, pull it again without '--read-only':\n\
+         'flox pull --force {0}'"
+    )]
+    ReadOnly(RemoteEnvironmentRef),
     #[error("environment '{env_ref}' does not exist at upstream '{upstream}'")]
     UpstreamNotFound {
         env_ref: RemoteEnvironmentRef,
@@ -165,6 +172,12 @@ pub enum ManagedEnvironmentError {
     #[error("could not commit generation")]
     CommitGeneration(#[source] GenerationsError),
 
+    #[error("could not delete generation")]
+    DeleteGeneration(#[source] GenerationsError),
+
+    #[error("couldn't remove links of deleted generation: {0}")]
+    RemoveLinks(std::io::Error),
+
     #[error("could not build environment")]
     Build(#[source] CoreEnvironmentError),
 
@@ -199,7 +212,11 @@ pub struct DivergedMetadata {
 
 impl Environment for ManagedEnvironment {
     /// This will lock if there is an out of sync local checkout
-    fn lockfile(&mut self, flox: &Flox) -> Result<LockResult, EnvironmentError> {
+    fn lockfile_with_overrides(
+        &mut self,
+        flox: &Flox,
+        overrides: &ResolutionOverrides,
+    ) -> Result<LockResult, EnvironmentError> {
         if let Some(generation) = self.generation {
             let lockfile_contents = self
                 .generations()
@@ -213,7 +230,7 @@ impl Environment for ManagedEnvironment {
         }
 
         let mut local_checkout = self.local_env_or_copy_current_generation(flox)?;
-        self.ensure_locked(flox, &mut local_checkout)
+        self.ensure_locked(flox, &mut local_checkout, overrides)
     }
 
     /// Returns the lockfile if it already exists.
@@ -277,8 +294,10 @@ impl Environment for ManagedEnvironment {
         &mut self,
         packages: &[PackageToInstall],
         flox: &Flox,
+        overrides: &ResolutionOverrides,
     ) -> Result<InstallationAttempt, EnvironmentError> {
         self.guard_generation_immutable()?;
+        self.guard_read_only()?;
 
         let mut generations = self.generations();
         let mut generations = generations
@@ -318,7 +337,7 @@ impl Environment for ManagedEnvironment {
             .collect();
 
         let out_link_prefix = self.rendered_env_links.out_link_prefix();
-        let result = local_checkout.install(packages, flox, Some(out_link_prefix))?;
+        let result = local_checkout.install(packages, flox, Some(out_link_prefix), overrides)?;
         if !result.modifications.is_empty() {
             let change = HistoryKind::Install { targets };
             generations
@@ -340,6 +359,7 @@ impl Environment for ManagedEnvironment {
         flox: &Flox,
     ) -> Result<UninstallationAttempt, EnvironmentError> {
         self.guard_generation_immutable()?;
+        self.guard_read_only()?;
 
         let mut generations = self.generations();
         let mut generations = generations
@@ -378,8 +398,15 @@ impl Environment for ManagedEnvironment {
     }
 
     /// Atomically edit this environment, ensuring that it still builds
-    fn edit(&mut self, flox: &Flox, contents: String) -> Result<EditResult, EnvironmentError> {
+    fn edit(
+        &mut self,
+        flox: &Flox,
+        contents: String,
+        locked_includes_only: bool,
+        skip_ineffective_edits: bool,
+    ) -> Result<EditResult, EnvironmentError> {
         self.guard_generation_immutable()?;
+        self.guard_read_only()?;
 
         let mut generations = self.generations();
         let mut generations = generations
@@ -394,7 +421,13 @@ impl Environment for ManagedEnvironment {
         let mut local_checkout = self.local_env_or_copy_current_generation(flox)?;
 
         let out_link_prefix = self.rendered_env_links.out_link_prefix();
-        let result = local_checkout.edit(flox, contents, Some(out_link_prefix))?;
+        let result = local_checkout.edit(
+            flox,
+            contents,
+            Some(out_link_prefix),
+            locked_includes_only,
+            skip_ineffective_edits,
+        )?;
 
         match &result {
             EditResult::Changed { .. } => {
@@ -416,9 +449,10 @@ impl Environment for ManagedEnvironment {
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
     ) -> Result<UpgradeResult, EnvironmentError> {
         let mut local_checkout = self.local_env_or_copy_current_generation(flox)?;
-        let result = local_checkout.upgrade(flox, groups_or_iids, false, None)?; // dry-run: no out-link
+        let result = local_checkout.upgrade(flox, groups_or_iids, frozen, false, None)?; // dry-run: no out-link
         Ok(result)
     }
 
@@ -427,8 +461,10 @@ impl Environment for ManagedEnvironment {
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
     ) -> Result<UpgradeResult, EnvironmentError> {
         self.guard_generation_immutable()?;
+        self.guard_read_only()?;
 
         let mut generations = self.generations();
         let mut generations = generations
@@ -449,7 +485,8 @@ impl Environment for ManagedEnvironment {
         }
 
         let out_link_prefix = self.rendered_env_links.out_link_prefix();
-        let result = local_checkout.upgrade(flox, groups_or_iids, true, Some(out_link_prefix))?;
+        let result =
+            local_checkout.upgrade(flox, groups_or_iids, frozen, true, Some(out_link_prefix))?;
         if !result.diff().is_empty() {
             let change = HistoryKind::Upgrade {
                 targets: result.packages().collect(),
@@ -473,6 +510,7 @@ impl Environment for ManagedEnvironment {
         to_upgrade: Vec<String>,
     ) -> Result<UpgradeResult, EnvironmentError> {
         self.guard_generation_immutable()?;
+        self.guard_read_only()?;
 
         let mut generations = self.generations();
         let mut generations = generations
@@ -520,7 +558,7 @@ impl Environment for ManagedEnvironment {
         }
 
         let mut local_checkout = self.local_env_or_copy_current_generation(flox)?;
-        self.ensure_locked(flox, &mut local_checkout)?;
+        self.ensure_locked(flox, &mut local_checkout, &ResolutionOverrides::default())?;
 
         let lockfile_contents = local_checkout
             .existing_lockfile_contents()
@@ -686,6 +724,62 @@ impl GenerationsExt for ManagedEnvironment {
         Ok(())
     }
 
+    fn delete_generation(
+        &mut self,
+        flox: &Flox,
+        generation: GenerationId,
+    ) -> Result<Vec<PathBuf>, EnvironmentError> {
+        let mut generations = self
+            .generations()
+            .writable(
+                &flox.temp_dir,
+                &flox.system_user_name,
+                &flox.system_hostname,
+                &flox.argv,
+            )
+            .map_err(ManagedEnvironmentError::Generations)?;
+
+        generations
+            .delete_generation(generation)
+            .map_err(ManagedEnvironmentError::DeleteGeneration)?;
+
+        self.lock_pointer()?;
+
+        // Remove the links that keep the build of the deleted generation alive,
+        // so that it can be garbage collected.
+        let links = self.generation_links(flox, generation)?;
+        for link in &links {
+            std::fs::remove_file(link).map_err(ManagedEnvironmentError::RemoveLinks)?;
+        }
+
+        Ok(links)
+    }
+
+    fn generation_links(
+        &self,
+        flox: &Flox,
+        generation: GenerationId,
+    ) -> Result<Vec<PathBuf>, EnvironmentError> {
+        let run_dir = self.path.join(GCROOTS_DIR_NAME);
+        let Ok(base_dir) = CanonicalPath::new(&run_dir) else {
+            return Ok(vec![]);
+        };
+        let rendered_env_links =
+            RenderedEnvironmentLinks::new_in_base_dir_with_name_system_and_generation(
+                &base_dir,
+                self.name().as_ref(),
+                &flox.system,
+                generation,
+            );
+
+        let links = [&rendered_env_links.dev, &rendered_env_links.run]
+            .into_iter()
+            .filter(|link| link.symlink_metadata().is_ok())
+            .map(|link| link.to_path_buf())
+            .collect();
+        Ok(links)
+    }
+
     fn remote_lockfile_contents_for_current_generation(&self) -> Result<String, GenerationsError> {
         self.floxmeta_branch
             .remote_generations()
@@ -788,6 +882,18 @@ impl ManagedEnvironment {
         Ok(())
     }
 
+    /// Guard against modifying an environment that was pulled with
+    /// `flox pull --read-only`.
+    fn guard_read_only(&self) -> Result<(), EnvironmentError> {
+        if self.pointer.read_only {
+            return Err(EnvironmentError::ManagedEnvironment(
+                ManagedEnvironmentError::ReadOnly(self.env_ref()),
+            ));
+        }
+
+        Ok(())
+    }
+
     /// If there's an out of sync local checkout, ensure it's locked.
     /// If the checkout is in sync, return it's lock contents.
     ///
@@ -797,11 +903,12 @@ impl ManagedEnvironment {
         &mut self,
         flox: &Flox,
         local_checkout: &mut CoreEnvironment,
+        overrides: &ResolutionOverrides,
     ) -> Result<LockResult, EnvironmentError> {
         // Otherwise, there would be a generation without a lockfile, which is a bad state,
         // and we error.
         if !Self::validate_checkout(local_checkout, &self.generations())? {
-            Ok(local_checkout.ensure_locked(flox)?)
+            Ok(local_checkout.ensure_locked(flox, overrides)?)
         } else {
             match local_checkout.existing_lockfile()? {
                 Some(lockfile) => Ok(LockResult::Unchanged(lockfile)),
@@ -1277,7 +1384,9 @@ impl ManagedEnvironment {
         // if the environment was modified primarily through editing the manifest manually.
         // Call `ensure_locked` to avoid locking of v0 manifests,
         // but permit pushing old manifests that are already locked.
-        let lockfile: Lockfile = core_environment.ensure_locked(flox)?.into();
+        let lockfile: Lockfile = core_environment
+            .ensure_locked(flox, &ResolutionOverrides::default())?
+            .into();
 
         // Ensure the environment builds before we push it
         core_environment
@@ -1433,7 +1542,7 @@ impl ManagedEnvironment {
             // we should already be locked here,
             // as a valid lockfile is a precondition for creating a generation.
             let lockfile: Lockfile = local_checkout
-                .ensure_locked(flox)
+                .ensure_locked(flox, &ResolutionOverrides::default())
                 .map_err(|_| ManagedEnvironmentError::CheckoutOutOfSync)?
                 .into();
             local_checkout
@@ -1788,6 +1897,7 @@ mod test {
             floxhub_git_url_override: Some(
                 Url::from_directory_path(mock_floxhub_git_path).unwrap(),
             ),
+            read_only: false,
             version: Version::<1> {},
         }
     }
@@ -2049,6 +2159,7 @@ mod test {
                 version: None,
                 systems: None,
                 outputs: None,
+                allow_missing_builds: None,
             }
             .into(),
         );
@@ -2467,7 +2578,12 @@ mod test {
         "#};
 
         composer
-            .edit(&flox, composer_manifest_contents_with_include.to_string())
+            .edit(
+                &flox,
+                composer_manifest_contents_with_include.to_string(),
+                false,
+                false,
+            )
             .unwrap();
 
         // Check lockfile
@@ -2507,14 +2623,16 @@ mod test {
             CatalogPackage::from_str("hello").unwrap(),
         )];
 
-        env.install(&packages, &flox).unwrap();
+        env.install(&packages, &flox, &ResolutionOverrides::default())
+            .unwrap();
         assert_eq!(
             env.generations_metadata().unwrap().current_gen().as_deref(),
             Some(&2),
             "installing a package should create a new generation"
         );
 
-        env.install(&packages, &flox).unwrap();
+        env.install(&packages, &flox, &ResolutionOverrides::default())
+            .unwrap();
         assert_eq!(
             env.generations_metadata().unwrap().current_gen().as_deref(),
             Some(&2),
@@ -2593,14 +2711,16 @@ mod test {
             version = 1
         "#};
 
-        env.edit(&flox, manifest_updated.to_string()).unwrap();
+        env.edit(&flox, manifest_updated.to_string(), false, false)
+            .unwrap();
         assert_eq!(
             env.generations_metadata().unwrap().current_gen().as_deref(),
             Some(&2),
             "edit with manifest changes should create a new generation"
         );
 
-        env.edit(&flox, manifest_updated.to_string()).unwrap();
+        env.edit(&flox, manifest_updated.to_string(), false, false)
+            .unwrap();
         assert_eq!(
             env.generations_metadata().unwrap().current_gen().as_deref(),
             Some(&2),
@@ -2608,6 +2728,49 @@ mod test {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn read_only_environment_refuses_modifications() {
+        let owner = "owner".parse().unwrap();
+        let (flox, temp_dir) = flox_instance_with_optional_floxhub(Some(&owner));
+
+        let mut env = mock_managed_environment_in(&flox, "version = 1", owner, &temp_dir, None);
+        env.pointer.read_only = true;
+
+        let err = env
+            .edit(&flox, "# updated\nversion = 1\n".to_string(), false, false)
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                EnvironmentError::ManagedEnvironment(ManagedEnvironmentError::ReadOnly(_))
+            ),
+            "{err:?}"
+        );
+
+        let err = env
+            .uninstall(
+                vec![UninstallSpec {
+                    package_ref: "hello".to_string(),
+                    outputs: None,
+                    version: None,
+                }],
+                &flox,
+            )
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                EnvironmentError::ManagedEnvironment(ManagedEnvironmentError::ReadOnly(_))
+            ),
+            "{err:?}"
+        );
+        assert_eq!(
+            env.generations_metadata().unwrap().current_gen().as_deref(),
+            Some(&1),
+            "a read-only environment should not get new generations"
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn upgrade_generations() {
         let owner = "owner".parse().unwrap();
@@ -2633,14 +2796,14 @@ mod test {
         flox.floxhub_client =
             catalog_replay_client(GENERATED_DATA.join("resolve/hello.yaml")).await;
 
-        env.upgrade(&flox, &[]).unwrap();
+        env.upgrade(&flox, &[], &[]).unwrap();
         assert_eq!(
             env.generations_metadata().unwrap().current_gen().as_deref(),
             Some(&2),
             "upgrade with changes should create a new generation"
         );
 
-        env.upgrade(&flox, &[]).unwrap();
+        env.upgrade(&flox, &[], &[]).unwrap();
         assert_eq!(
             env.generations_metadata().unwrap().current_gen().as_deref(),
             Some(&2),
@@ -2732,6 +2895,7 @@ mod test {
             .install(
                 &[PackageToInstall::parse(&flox.system, "hello").unwrap()],
                 &flox,
+                &ResolutionOverrides::default(),
             )
             .unwrap();
 
@@ -2802,7 +2966,12 @@ mod compare_remote_tests {
         assert_eq!(env_a.compare_remote().unwrap(), BranchOrd::Equal);
 
         env_a
-            .edit(&flox, "version = 1\n\n# local change".to_string())
+            .edit(
+                &flox,
+                "version = 1\n\n# local change".to_string(),
+                false,
+                false,
+            )
             .unwrap();
         assert_eq!(env_a.compare_remote().unwrap(), BranchOrd::Ahead);
 
@@ -2829,7 +2998,7 @@ mod compare_remote_tests {
 
         // Ahead also returns UpToDate
         env_a
-            .edit(&flox, "version = 1\n\n# local".to_string())
+            .edit(&flox, "version = 1\n\n# local".to_string(), false, false)
             .unwrap();
         assert_eq!(env_a.compare_remote().unwrap(), BranchOrd::Ahead);
         assert_eq!(env_a.pull(&flox, false).unwrap(), PullResult::UpToDate);
@@ -2846,7 +3015,12 @@ mod compare_remote_tests {
 
         // A pushes a change
         env_a
-            .edit(&flox, "version = 1\n\n# modified by A".to_string())
+            .edit(
+                &flox,
+                "version = 1\n\n# modified by A".to_string(),
+                false,
+                false,
+            )
             .unwrap();
         assert_eq!(env_a.push(&flox, false).unwrap(), PushResult::Updated);
         assert_eq!(env_a.compare_remote().unwrap(), BranchOrd::Equal);
@@ -2869,10 +3043,20 @@ mod compare_remote_tests {
 
         // Both make conflicting changes
         env_a
-            .edit(&flox, "version = 1\n\n# change by A".to_string())
+            .edit(
+                &flox,
+                "version = 1\n\n# change by A".to_string(),
+                false,
+                false,
+            )
             .unwrap();
         env_b
-            .edit(&flox, "version = 1\n\n# change by B".to_string())
+            .edit(
+                &flox,
+                "version = 1\n\n# change by B".to_string(),
+                false,
+                false,
+            )
             .unwrap();
 
         env_a.push(&flox, false).unwrap();
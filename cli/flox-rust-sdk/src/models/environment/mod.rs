@@ -41,7 +41,7 @@ use crate::providers::git::{
     GitDiscoverError,
     GitProvider,
 };
-use crate::providers::lock_manifest::{LockResult, RecoverableMergeError};
+use crate::providers::lock_manifest::{LockResult, RecoverableMergeError, ResolutionOverrides};
 use crate::providers::manifest_init::ManifestInitError;
 use crate::providers::nix_auth::AuthError;
 use crate::utils::copy_file_without_permissions;
@@ -79,6 +79,8 @@ pub const CACHE_DIR_NAME: &str = "cache";
 pub const LIB_DIR_NAME: &str = "lib";
 pub const LOG_DIR_NAME: &str = "log";
 pub const ENV_DIR_NAME: &str = "env";
+/// Directory within the environment cache holding activation profile lockfiles
+pub const ACTIVATION_PROFILES_DIR_NAME: &str = "activation-profiles";
 
 /// This variable is used in tests to override what path to use for the socket.
 pub const FLOX_SERVICES_SOCKET_OVERRIDE_VAR: &str = "_FLOX_SERVICES_SOCKET_OVERRIDE";
@@ -110,10 +112,13 @@ pub struct UninstallationAttempt {
 #[enum_dispatch]
 pub trait Environment: Send {
     /// Install packages to the environment atomically
+    ///
+    /// `overrides` apply to locking for this install only.
     fn install(
         &mut self,
         packages: &[PackageToInstall],
         flox: &Flox,
+        overrides: &ResolutionOverrides,
     ) -> Result<InstallationAttempt, EnvironmentError>;
 
     /// Uninstall packages from the environment atomically
@@ -124,20 +129,41 @@ pub trait Environment: Send {
     ) -> Result<UninstallationAttempt, EnvironmentError>;
 
     /// Atomically edit this environment, ensuring that it still builds
-    fn edit(&mut self, flox: &Flox, contents: String) -> Result<EditResult, EnvironmentError>;
+    ///
+    /// With `locked_includes_only`, included environments that are already
+    /// locked are not fetched again, even if they have been renamed.
+    ///
+    /// With `skip_ineffective_edits`, edits that lock to the existing lockfile,
+    /// i.e. edits that only change whitespace, comments, or formatting,
+    /// are written to the manifest but reported as unchanged,
+    /// without rebuilding the environment or creating a generation.
+    fn edit(
+        &mut self,
+        flox: &Flox,
+        contents: String,
+        locked_includes_only: bool,
+        skip_ineffective_edits: bool,
+    ) -> Result<EditResult, EnvironmentError>;
 
     /// Upgrade packages in this environment without modifying the environment on disk.
+    ///
+    /// Packages in `frozen` are kept locked as they are.
     fn dry_upgrade(
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
     ) -> Result<UpgradeResult, EnvironmentError>;
 
     /// Atomically upgrade packages in this environment
+    ///
+    /// Packages in `frozen` are kept locked as they are,
+    /// even if all packages or their group are upgraded.
     fn upgrade(
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
     ) -> Result<UpgradeResult, EnvironmentError>;
 
     /// Upgrade environment with latest changes to included environments.
@@ -151,7 +177,18 @@ pub trait Environment: Send {
     ///
     /// Some implementations error if the lock does not already exist, while
     /// others call lock.
-    fn lockfile(&mut self, flox: &Flox) -> Result<LockResult, EnvironmentError>;
+    fn lockfile(&mut self, flox: &Flox) -> Result<LockResult, EnvironmentError> {
+        self.lockfile_with_overrides(flox, &ResolutionOverrides::default())
+    }
+
+    /// Return the lockfile, applying `overrides` if the environment has to be locked.
+    ///
+    /// See [Self::lockfile].
+    fn lockfile_with_overrides(
+        &mut self,
+        flox: &Flox,
+        overrides: &ResolutionOverrides,
+    ) -> Result<LockResult, EnvironmentError>;
 
     /// Reads the manifest from disk without performing the migration.
     ///
@@ -281,6 +318,47 @@ impl TryFrom<&ConcreteEnvironment> for ActivateEnvironmentRef {
     }
 }
 
+/// Build the environment locked in `lockfile` with the activation profile
+/// `profile` layered on top, and return links to the result.
+///
+/// The layered lockfile is written to the environment's cache directory.
+/// Each profile is built to its own links
+/// (see [RenderedEnvironmentLinks::for_activation_profile]),
+/// so activations of different profiles don't share a store path.
+pub fn build_activation_profile(
+    flox: &Flox,
+    environment: &ConcreteEnvironment,
+    lockfile: &Lockfile,
+    rendered_env_links: &RenderedEnvironmentLinks,
+    profile: &str,
+) -> Result<RenderedEnvironmentLinks, EnvironmentError> {
+    let valid_name = !profile.is_empty()
+        && profile
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid_name {
+        return Err(EnvironmentError::InvalidActivationProfileName(
+            profile.to_string(),
+        ));
+    }
+
+    let layered_lockfile = lockfile
+        .with_activation_profile(profile)
+        .map_err(CoreEnvironmentError::Lockfile)?;
+
+    let profiles_dir = environment.cache_path()?.join(ACTIVATION_PROFILES_DIR_NAME);
+    fs::create_dir_all(&profiles_dir).map_err(EnvironmentError::CreateCacheDir)?;
+
+    let links = rendered_env_links.for_activation_profile(profile);
+    core_environment::build_lockfile(
+        flox,
+        &layered_lockfile,
+        &profiles_dir.join(format!("{profile}.lock")),
+        links.out_link_prefix(),
+    )?;
+    Ok(links)
+}
+
 /// A link to a built environment in the Nix store.
 ///
 /// The path may not exist if the environment has never been built and linked.
@@ -356,6 +434,16 @@ impl RenderedEnvironmentLinks {
         Self::new_unchecked(prefix)
     }
 
+    /// Links for the environment built with the activation profile `profile`,
+    /// next to the links of the environment itself,
+    /// e.g. `<system>.<name>.profile-ci-dev`.
+    pub fn for_activation_profile(&self, profile: &str) -> Self {
+        Self::new_unchecked(append_output_suffix(
+            &self.out_link_prefix,
+            &format!(".profile-{profile}"),
+        ))
+    }
+
     /// Returns the `--out-link` prefix for `nix build`.
     ///
     /// With outputs named `"dev"` and `"run"`, nix creates `<prefix>-dev` and
@@ -478,6 +566,10 @@ pub struct ManagedPointer {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg_attr(test, proptest(value = "None"))]
     pub floxhub_git_url_override: Option<Url>,
+    /// Whether the environment was pulled with `flox pull --read-only`
+    /// and must not be modified locally.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
     version: Version<1>,
 }
 
@@ -489,6 +581,7 @@ impl ManagedPointer {
             owner,
             floxhub_base_url: floxhub.base_url().clone(),
             floxhub_git_url_override: floxhub.git_url_override().cloned(),
+            read_only: false,
             version: Version::<1>,
         }
     }
@@ -860,6 +953,11 @@ pub enum EnvironmentError {
     #[error("failed to create cache directory")]
     CreateCacheDir(#[source] std::io::Error),
 
+    #[error(
+        "Activation profile name '{0}' is invalid.\nActivation profile names may only contain alphanumeric characters, '.', '_', and '-'."
+    )]
+    InvalidActivationProfileName(String),
+
     #[error("failed to create log directory")]
     CreateLogDir(#[source] std::io::Error),
 
@@ -1161,6 +1259,7 @@ mod test {
             owner: EnvironmentOwner::from_str("owner").unwrap(),
             floxhub_base_url: DEFAULT_FLOXHUB_URL.clone(),
             floxhub_git_url_override: None,
+            read_only: false,
             version: Version::<1> {},
         })
     });
@@ -1172,6 +1271,7 @@ mod test {
             owner: EnvironmentOwner::from_str("owner").unwrap(),
             floxhub_base_url: DEFAULT_FLOXHUB_URL.clone(),
             floxhub_git_url_override: None,
+            read_only: false,
             version: Version::<1> {},
         });
 
@@ -1188,6 +1288,24 @@ mod test {
         assert_eq!(managed_pointer, *MANAGED_ENV_POINTER);
     }
 
+    #[test]
+    fn read_only_managed_environment_pointer_roundtrips() {
+        let EnvironmentPointer::Managed(pointer) = &*MANAGED_ENV_POINTER else {
+            unreachable!()
+        };
+        let read_only_pointer = ManagedPointer {
+            read_only: true,
+            ..pointer.clone()
+        };
+
+        let json = serde_json::to_value(&read_only_pointer).unwrap();
+        assert_eq!(json["read_only"], serde_json::Value::Bool(true));
+        assert_eq!(
+            serde_json::from_value::<ManagedPointer>(json).unwrap(),
+            read_only_pointer
+        );
+    }
+
     #[test]
     fn serializes_path_environment_pointer() {
         let path_pointer = EnvironmentPointer::Path(PathPointer {
@@ -1221,6 +1339,7 @@ mod test {
             owner: EnvironmentOwner::from_str("owner").unwrap(),
             floxhub_base_url: Url::from_str("https://example.com/").unwrap(),
             floxhub_git_url_override: None,
+            read_only: false,
             version: Version::<1> {},
         };
         assert_eq!(
@@ -1571,6 +1690,25 @@ mod test {
         ));
     }
 
+    /// Activation profile links are siblings of the environment's own links.
+    #[test]
+    fn rendered_env_links_for_activation_profile_are_siblings() {
+        let links = RenderedEnvironmentLinks::new_unchecked(PathBuf::from("/run/x86_64-linux.env"));
+        let profile_links = links.for_activation_profile("ci");
+        assert_eq!(
+            profile_links.out_link_prefix(),
+            Path::new("/run/x86_64-linux.env.profile-ci")
+        );
+        assert_eq!(
+            profile_links.dev.as_path(),
+            Path::new("/run/x86_64-linux.env.profile-ci-dev")
+        );
+        assert_eq!(
+            profile_links.run.as_path(),
+            Path::new("/run/x86_64-linux.env.profile-ci-run")
+        );
+    }
+
     /// The dev and run links are the `--out-link` prefix with the nix output
     /// names appended, and `out_link_prefix()` returns that prefix verbatim
     /// rather than re-deriving it from the dev link.
@@ -1644,7 +1782,7 @@ mod migration_tests {
             .to_string();
 
         expect![[r#"
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
         "#]]
         .assert_eq(&manifest_contents);
     }
@@ -1808,7 +1946,9 @@ mod migration_tests {
 
         flox.floxhub_client = catalog_replay_client(GENERATED_DATA.join("envs/bash.yaml")).await;
 
-        included_env.edit(&flox, updated_included_manifest).unwrap();
+        included_env
+            .edit(&flox, updated_included_manifest, false, false)
+            .unwrap();
 
         // Upgrade all includes on the composer.
         let new_lockfile = composer
@@ -1875,7 +2015,7 @@ mod migration_tests {
 
         flox.floxhub_client = catalog_replay_client(GENERATED_DATA.join("envs/bash.yaml")).await;
 
-        let result = composer.edit(&flox, edited_manifest).unwrap();
+        let result = composer.edit(&flox, edited_manifest, false, false).unwrap();
         let EditResult::Changed { new_lockfile, .. } = result else {
             panic!("expected EditResult::Changed");
         };
@@ -1967,8 +2107,11 @@ mod migration_tests {
                 version: None,
                 systems: None,
                 outputs: None,
+                pkg_group: None,
+                allow_missing_builds: false,
             })],
             &flox,
+            &ResolutionOverrides::default(),
         )
         .unwrap();
         assert_eq!(
@@ -2001,8 +2144,11 @@ mod migration_tests {
                 version: None,
                 systems: None,
                 outputs: None,
+                pkg_group: None,
+                allow_missing_builds: false,
             })],
             &flox,
+            &ResolutionOverrides::default(),
         )
         .unwrap();
         assert_eq!(
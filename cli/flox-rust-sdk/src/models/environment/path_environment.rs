@@ -55,7 +55,7 @@ use crate::flox::Flox;
 use crate::models::env_registry::{deregister, ensure_registered};
 use crate::models::environment::{ENV_DIR_NAME, create_dot_flox_gitignore};
 use crate::providers::buildenv::BuildEnvOutputs;
-use crate::providers::lock_manifest::LockResult;
+use crate::providers::lock_manifest::{LockResult, ResolutionOverrides};
 use crate::providers::manifest_init::ManifestInitializer;
 
 /// Struct representing a local environment
@@ -192,9 +192,13 @@ impl PathEnvironment {
 
 impl Environment for PathEnvironment {
     /// This will lock the environment if it is not already locked.
-    fn lockfile(&mut self, flox: &Flox) -> Result<LockResult, EnvironmentError> {
+    fn lockfile_with_overrides(
+        &mut self,
+        flox: &Flox,
+        overrides: &ResolutionOverrides,
+    ) -> Result<LockResult, EnvironmentError> {
         let mut env_view = self.as_core_environment_mut()?;
-        env_view.ensure_locked(flox)
+        env_view.ensure_locked(flox, overrides)
     }
 
     /// Returns the lockfile if it already exists.
@@ -229,10 +233,11 @@ impl Environment for PathEnvironment {
         &mut self,
         packages: &[PackageToInstall],
         flox: &Flox,
+        overrides: &ResolutionOverrides,
     ) -> Result<InstallationAttempt, EnvironmentError> {
         let mut env_view = self.as_core_environment_mut()?;
         let out_link_prefix = self.rendered_env_links.out_link_prefix();
-        let result = env_view.install(packages, flox, Some(out_link_prefix))?;
+        let result = env_view.install(packages, flox, Some(out_link_prefix), overrides)?;
         if result.built_environments.is_some() {
             self.rendered_env_links.replace_legacy_links();
         }
@@ -259,10 +264,22 @@ impl Environment for PathEnvironment {
     }
 
     /// Atomically edit this environment, ensuring that it still builds
-    fn edit(&mut self, flox: &Flox, contents: String) -> Result<EditResult, EnvironmentError> {
+    fn edit(
+        &mut self,
+        flox: &Flox,
+        contents: String,
+        locked_includes_only: bool,
+        skip_ineffective_edits: bool,
+    ) -> Result<EditResult, EnvironmentError> {
         let mut env_view = self.as_core_environment_mut()?;
         let out_link_prefix = self.rendered_env_links.out_link_prefix();
-        let result = env_view.edit(flox, contents, Some(out_link_prefix))?;
+        let result = env_view.edit(
+            flox,
+            contents,
+            Some(out_link_prefix),
+            locked_includes_only,
+            skip_ineffective_edits,
+        )?;
         if matches!(&result, EditResult::Changed { .. }) {
             self.rendered_env_links.replace_legacy_links();
         }
@@ -274,9 +291,10 @@ impl Environment for PathEnvironment {
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
     ) -> Result<UpgradeResult, EnvironmentError> {
         let mut env_view = self.as_core_environment_mut()?;
-        let result = env_view.upgrade(flox, groups_or_iids, false, None)?; // dry-run: no out-link
+        let result = env_view.upgrade(flox, groups_or_iids, frozen, false, None)?; // dry-run: no out-link
         Ok(result)
     }
 
@@ -285,11 +303,12 @@ impl Environment for PathEnvironment {
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
     ) -> Result<UpgradeResult, EnvironmentError> {
         tracing::debug!(to_upgrade = groups_or_iids.join(","), "upgrading");
         let mut env_view = self.as_core_environment_mut()?;
         let out_link_prefix = self.rendered_env_links.out_link_prefix();
-        let result = env_view.upgrade(flox, groups_or_iids, true, Some(out_link_prefix))?;
+        let result = env_view.upgrade(flox, groups_or_iids, frozen, true, Some(out_link_prefix))?;
         if result.store_path.is_some() {
             self.rendered_env_links.replace_legacy_links();
         }
@@ -353,7 +372,7 @@ impl Environment for PathEnvironment {
     fn build(&mut self, flox: &Flox) -> Result<BuildEnvOutputs, EnvironmentError> {
         let mut env_view = self.as_core_environment_mut()?;
         let out_link_prefix = self.rendered_env_links.out_link_prefix();
-        env_view.ensure_locked(flox)?;
+        env_view.ensure_locked(flox, &ResolutionOverrides::default())?;
         let store_paths = env_view.build(flox, Some(out_link_prefix))?;
         self.rendered_env_links.replace_legacy_links();
         Ok(store_paths)
@@ -621,6 +640,16 @@ impl PathEnvironment {
         Ok(false)
     }
 
+    /// Add packages to the manifest without locking or building the environment
+    ///
+    /// See [CoreEnvironment::stage_install].
+    pub fn stage_install(
+        &mut self,
+        packages: &[PackageToInstall],
+    ) -> Result<InstallationAttempt, EnvironmentError> {
+        self.as_core_environment_mut()?.stage_install(packages)
+    }
+
     /// The environment is locked,
     /// and the manifest in the lockfile matches that in the manifest.
     /// Note that the manifest could have whitespace or comment differences from
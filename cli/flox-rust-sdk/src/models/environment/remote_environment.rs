@@ -46,7 +46,7 @@ use crate::models::environment::floxmeta_branch::{
 use crate::models::environment::generations::SyncToGenerationResult;
 use crate::models::environment::managed_environment::GENERATION_LOCK_FILENAME;
 use crate::models::environment::path_environment::{InitCustomization, PathEnvironment};
-use crate::providers::lock_manifest::LockResult;
+use crate::providers::lock_manifest::{LockResult, ResolutionOverrides};
 
 const REMOTE_ENVIRONMENT_BASE_DIR: &str = "remote";
 
@@ -358,8 +358,12 @@ impl RemoteEnvironment {
 impl Environment for RemoteEnvironment {
     /// Return the lockfile content,
     /// or error if the lockfile doesn't exist.
-    fn lockfile(&mut self, flox: &Flox) -> Result<LockResult, EnvironmentError> {
-        self.inner.lockfile(flox)
+    fn lockfile_with_overrides(
+        &mut self,
+        flox: &Flox,
+        overrides: &ResolutionOverrides,
+    ) -> Result<LockResult, EnvironmentError> {
+        self.inner.lockfile_with_overrides(flox, overrides)
     }
 
     /// Returns the lockfile if it exists.
@@ -383,8 +387,9 @@ impl Environment for RemoteEnvironment {
         &mut self,
         packages: &[PackageToInstall],
         flox: &Flox,
+        overrides: &ResolutionOverrides,
     ) -> Result<InstallationAttempt, EnvironmentError> {
-        let result = self.inner.install(packages, flox)?;
+        let result = self.inner.install(packages, flox, overrides)?;
         // TODO: clean up git branch for temporary environment
         Ok(result)
     }
@@ -401,8 +406,16 @@ impl Environment for RemoteEnvironment {
     }
 
     /// Atomically edit this environment, ensuring that it still builds
-    fn edit(&mut self, flox: &Flox, contents: String) -> Result<EditResult, EnvironmentError> {
-        let result = self.inner.edit(flox, contents)?;
+    fn edit(
+        &mut self,
+        flox: &Flox,
+        contents: String,
+        locked_includes_only: bool,
+        skip_ineffective_edits: bool,
+    ) -> Result<EditResult, EnvironmentError> {
+        let result =
+            self.inner
+                .edit(flox, contents, locked_includes_only, skip_ineffective_edits)?;
         if result == EditResult::Unchanged {
             return Ok(result);
         }
@@ -414,8 +427,9 @@ impl Environment for RemoteEnvironment {
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
     ) -> Result<UpgradeResult, EnvironmentError> {
-        self.inner.dry_upgrade(flox, groups_or_iids)
+        self.inner.dry_upgrade(flox, groups_or_iids, frozen)
     }
 
     /// Atomically upgrade packages in this environment
@@ -423,8 +437,9 @@ impl Environment for RemoteEnvironment {
         &mut self,
         flox: &Flox,
         groups_or_iids: &[&str],
+        frozen: &[&str],
     ) -> Result<UpgradeResult, EnvironmentError> {
-        let result = self.inner.upgrade(flox, groups_or_iids)?;
+        let result = self.inner.upgrade(flox, groups_or_iids, frozen)?;
 
         Ok(result)
     }
@@ -523,6 +538,22 @@ impl GenerationsExt for RemoteEnvironment {
         Ok(())
     }
 
+    fn delete_generation(
+        &mut self,
+        flox: &Flox,
+        generation: GenerationId,
+    ) -> Result<Vec<PathBuf>, EnvironmentError> {
+        self.inner.delete_generation(flox, generation)
+    }
+
+    fn generation_links(
+        &self,
+        flox: &Flox,
+        generation: GenerationId,
+    ) -> Result<Vec<PathBuf>, EnvironmentError> {
+        self.inner.generation_links(flox, generation)
+    }
+
     fn remote_lockfile_contents_for_current_generation(&self) -> Result<String, GenerationsError> {
         self.inner.remote_lockfile_contents_for_current_generation()
     }
@@ -680,7 +711,7 @@ mod tests {
         ]
         "#};
         let err = environment
-            .edit(&flox, manifest_edited_contents.to_string())
+            .edit(&flox, manifest_edited_contents.to_string(), false, false)
             .unwrap_err();
 
         let EnvironmentError::Recoverable(RecoverableMergeError::Fetch { err, .. }) = err else {
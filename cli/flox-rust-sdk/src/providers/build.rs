@@ -209,6 +209,12 @@ pub struct FloxBuildMk<'args> {
 
     // fetchable ref for nix expression builds
     expression_ref: &'args NixFlakeref,
+    // Directory for intermediate build artifacts,
+    // defaults to the makefile's TMPDIR
+    cache_dir: Option<&'args Path>,
+    // Build all manifest builds with `sandbox = "off"`,
+    // regardless of their `sandbox` setting
+    disable_sandbox: bool,
     // Optional buffers that collect output.
     // Without these set std{out,err} of the underlying make call
     // are inherited from the current process.
@@ -229,6 +235,8 @@ impl FloxBuildMk<'_> {
             base_dir,
             expression_ref,
             built_environments,
+            cache_dir: None,
+            disable_sandbox: false,
             stdout_buffer: None,
             stderr_buffer: None,
         }
@@ -252,6 +260,8 @@ impl FloxBuildMk<'_> {
             base_dir,
             expression_ref,
             built_environments,
+            cache_dir: None,
+            disable_sandbox: false,
             stdout_buffer: Some(stdout),
             stderr_buffer: Some(stderr),
         }
@@ -266,11 +276,39 @@ impl FloxBuildMk<'_> {
         if self.verbosity <= 0 {
             command.arg("--no-print-directory"); // Only print directory with -v.
         }
+        if let Some(cache_dir) = self.cache_dir {
+            command.arg(format!("TMPDIR={}", cache_dir.display()));
+        }
 
         command
     }
 }
 
+impl<'args> FloxBuildMk<'args> {
+    /// Write intermediate build artifacts to `cache_dir`
+    /// rather than the default temporary directory.
+    ///
+    /// The directory is expected to exist and be writable.
+    pub fn with_cache_dir(self, cache_dir: &'args Path) -> Self {
+        FloxBuildMk {
+            cache_dir: Some(cache_dir),
+            ..self
+        }
+    }
+
+    /// Build manifest builds without a sandbox,
+    /// as if they all set `sandbox = "off"`.
+    ///
+    /// Builds may then access the network and files outside their closure,
+    /// so their results may not be reproducible.
+    pub fn with_sandbox_disabled(self) -> Self {
+        FloxBuildMk {
+            disable_sandbox: true,
+            ..self
+        }
+    }
+}
+
 impl ManifestBuilder for FloxBuildMk<'_> {
     /// Build `packages` defined in the environment rendered at
     /// `flox_env` using the [FLOX_BUILD_MK] makefile.
@@ -353,6 +391,10 @@ impl ManifestBuilder for FloxBuildMk<'_> {
             command.arg("DISABLE_BUILDCACHE=true");
         }
 
+        if self.disable_sandbox {
+            command.arg("DISABLE_SANDBOX=true");
+        }
+
         if self.stdout_buffer.is_some() {
             command.stdout(Stdio::piped());
         }
@@ -1418,6 +1460,43 @@ mod tests {
         );
     }
 
+    /// [FloxBuildMk::with_sandbox_disabled] overrides `sandbox = "enforce"`,
+    /// so the out-of-closure read that would otherwise be blocked succeeds.
+    #[test]
+    fn build_sandbox_disabled_overrides_enforce() {
+        let pname = String::from("foo");
+        let (flox, _temp_dir_handle) = flox_instance();
+        let mut env = new_path_environment(&flox, &sandbox_manifest(&pname, "enforce"));
+
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let result = FloxBuildMk::new_with_buffers(
+            &flox,
+            &env.parent_path().unwrap(),
+            &NixFlakeref::from_path(env.dot_flox_path()).unwrap(),
+            &env.build(&flox).unwrap(),
+            &mut stdout,
+            &mut stderr,
+        )
+        .with_sandbox_disabled()
+        .build(
+            &COMMON_NIXPKGS_URL,
+            &env.rendered_env_links(&flox).unwrap().dev,
+            &[PackageTargetName::new_unchecked(&pname)],
+            None,
+            None,
+        );
+
+        assert!(
+            result.is_ok(),
+            "expected build to succeed; stderr:\n{stderr}"
+        );
+        assert!(
+            !stderr.contains("not in the sandbox"),
+            "disabling the sandbox must not engage the virtual sandbox; stderr:\n{stderr}"
+        );
+    }
+
     /// `sandbox-allow` permits a specific out-of-closure path even under
     /// enforce. The build reads `/etc/hosts` (out of closure) but allow-lists
     /// it, so — unlike build_sandbox_enforce_blocks_out_of_closure_access —
@@ -2148,7 +2227,9 @@ mod tests {
         assert_build_file(&env_path, &package_name, &file_name, content_before);
 
         let _ = env
-            .edit(&flox, formatdoc! {r#"
+            .edit(
+                &flox,
+                formatdoc! {r#"
             version = 1
 
             [build.{package_name}]
@@ -2156,7 +2237,10 @@ mod tests {
                 mkdir -p $out
                 echo -n "{content_after}" > $out/{file_name}
             """
-        "#})
+        "#},
+                false,
+                false,
+            )
             .unwrap();
         assert_build_status(&flox, &mut env, &package_name, None, true);
         assert_build_file(&env_path, &package_name, &file_name, content_after);
@@ -2359,7 +2443,7 @@ mod tests {
                 cp main $out/bin/{bin_name}
             """
         "#};
-        env.edit(&flox, build_manifest).unwrap();
+        env.edit(&flox, build_manifest, false, false).unwrap();
 
         let expected_message = "Hello from C!";
         // Literal `{` and `}` are escaped as `{{` and `}}`.
@@ -2440,7 +2524,7 @@ mod tests {
             runtime-packages = [ "boost", "gcc", "gcc-unwrapped" ]
             sandbox = "{}"
             "#, if sandbox { "pure" } else { "off" }};
-        env.edit(&flox, build_manifest).unwrap();
+        env.edit(&flox, build_manifest, false, false).unwrap();
 
         // From <https://www.boost.org/doc/libs/latest/libs/exception/doc/tutorial_transporting_data.html>
         let source_code = indoc! {r#"
@@ -2527,7 +2611,7 @@ mod tests {
             runtime-packages = [ "gcc", "gcc-unwrapped" ]
             sandbox = "{}"
             "#, if sandbox { "pure" } else { "off" }};
-        env.edit(&flox, build_manifest).unwrap();
+        env.edit(&flox, build_manifest, false, false).unwrap();
 
         let source_code = indoc! {r#"
             #include <iostream>
@@ -3159,7 +3243,7 @@ mod tests {
             """
             sandbox = "{}"
         "#, if sandbox { "pure" } else { "off" }};
-        env.edit(&flox, build_manifest).unwrap();
+        env.edit(&flox, build_manifest, false, false).unwrap();
 
         if sandbox {
             let _git = GitCommandProvider::init(&env_path, false).unwrap();
@@ -3282,7 +3366,7 @@ mod tests {
             """
             sandbox = "{}"
         "##, if sandbox { "pure" } else { "off" }};
-        env.edit(&flox, build_manifest).unwrap();
+        env.edit(&flox, build_manifest, false, false).unwrap();
 
         if sandbox {
             let _git = GitCommandProvider::init(&env_path, false).unwrap();
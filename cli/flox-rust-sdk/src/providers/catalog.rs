@@ -3,6 +3,7 @@ use std::fmt::Debug;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, LazyLock, Mutex};
 
+pub use floxhub_client::CatalogQoS;
 use floxhub_client::{
     ApiResponseValue,
     BaseCatalogInfo,
@@ -110,40 +111,6 @@ pub enum MockDataError {
 //     Ok(responses)
 // }
 
-#[derive(Clone, Copy, Debug, Default, derive_more::Display, PartialEq)]
-/// The QoS class of a catalog request.
-///
-/// Referencing macos performance classes, described [1].
-///
-/// [1]: <https://blog.xoria.org/macos-tips-threading/>
-pub enum CatalogQoS {
-    /// your app’s user interface will stutter if this work is preempted
-    #[display("user_interactive")]
-    UserInteractive,
-    /// the user must wait for this work to finish before they can keep using your app, e.g. loading the contents of a document that was just opened
-    #[display("user_initiated")]
-    UserInitiated,
-    /// used as a fallback for threads which don’t have a QoS class assigned
-    #[default]
-    #[display("default")]
-    Default,
-    /// the user knows this work is happening but doesn’t wait for it to finish because they can keep using your app while it’s in progress, e.g. exporting in a video editor, downloading a file in a web browser
-    #[display("utility")]
-    Utility,
-    /// the user doesn’t know this work is happening, e.g. search indexing
-    #[display("background")]
-    Background,
-    /// the user doesn’t know this work is happening, e.g. garbage collection?
-    #[display("maintenance")]
-    Maintenance,
-}
-
-impl CatalogQoS {
-    pub fn as_header_pair(&self) -> (String, String) {
-        ("X-Flox-QoS-Context".to_string(), self.to_string())
-    }
-}
-
 /// A catalog client that can be seeded with mock responses
 ///
 /// This is being deprecated in favour of httpmock and no longer supports
@@ -230,10 +197,7 @@ impl CatalogClientTrait for MockClient {
             // deserializing, so we might get a Packages response here as that variant is tried
             // first. That's okay. But if it has actual results of the wrong type, then it's an
             // error.
-            Some(Response::Packages(PackageDetails {
-                results: _,
-                count: Some(0),
-            })) => Ok(SearchResults {
+            Some(Response::Packages(PackageDetails { count: Some(0), .. })) => Ok(SearchResults {
                 results: vec![],
                 count: Some(0),
             }),
@@ -247,6 +211,31 @@ impl CatalogClientTrait for MockClient {
         }
     }
 
+    /// Returns the mocked search response,
+    /// keeping only the results from `catalog_name`.
+    async fn search_in_catalog(
+        &self,
+        search_term: impl AsRef<str> + Send + Sync,
+        catalog_name: impl AsRef<str> + Send + Sync,
+        system: PackageSystem,
+        limit: SearchLimit,
+    ) -> Result<SearchResults, SearchError> {
+        let mut search_results = self.search(search_term, system, limit).await?;
+        search_results
+            .results
+            .retain(|result| result.catalog.as_deref() == Some(catalog_name.as_ref()));
+        search_results.count = Some(search_results.results.len() as u64);
+        Ok(search_results)
+    }
+
+    /// The mock client doesn't track catalogs, so every catalog exists.
+    async fn catalog_exists(
+        &self,
+        _catalog_name: impl AsRef<str> + Send + Sync,
+    ) -> Result<bool, FloxhubClientError> {
+        Ok(true)
+    }
+
     async fn package_versions(
         &self,
         _attr_path: impl AsRef<str> + Send + Sync,
@@ -373,6 +362,13 @@ impl CatalogClientTrait for MockClient {
         Ok(resp)
     }
 
+    async fn catalog_store_config(
+        &self,
+        _catalog_name: impl AsRef<str> + Send + Sync,
+    ) -> Result<CatalogStoreConfig, FloxhubClientError> {
+        unimplemented!("catalog_store_config is not supported in MockClient")
+    }
+
     async fn get_catalog_locked_sources(
         &self,
         _catalog_name: impl AsRef<str> + Send + Sync,
@@ -578,6 +574,7 @@ pub mod test_helpers {
             mock_mode: FloxhubMockMode::Replay(path.as_ref().to_path_buf()),
             auth_context: AuthContext::from_mode(&AuthnMode::Auth0, None),
             user_agent: None,
+            qos: Default::default(),
         };
         FloxhubClient::new(catalog_config).expect("failed to create catalog client")
     }
@@ -669,6 +666,7 @@ pub mod test_helpers {
             mock_mode: mock_mode.clone(),
             auth_context: AuthContext::from_mode(&AuthnMode::Auth0, auth.token().cloned()),
             user_agent: None,
+            qos: Default::default(),
         };
         let mut client =
             FloxhubClient::new(catalog_config).expect("failed to create catalog client");
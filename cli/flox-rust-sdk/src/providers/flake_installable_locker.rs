@@ -195,6 +195,7 @@ mod tests {
     use crate::flox::test_helpers::flox_instance;
     use crate::models::environment::path_environment::test_helpers::new_path_environment;
     use crate::models::environment::{Environment, copy_dir_recursive};
+    use crate::providers::lock_manifest::ResolutionOverrides;
 
     /// Returns the path to a bundled flake that contains a number of test packages
     /// for sped up evaluation
@@ -278,7 +279,7 @@ mod tests {
             url: Url::parse(&format!("path:{}", flake_dir.display())).unwrap(),
             outputs: None,
         })];
-        let res = env.install(&pkgs, &flox);
+        let res = env.install(&pkgs, &flox, &ResolutionOverrides::default());
         if let Err(e) = res {
             eprintln!("Error: {:?}", e);
             let err_string = e.to_string();
@@ -550,6 +550,20 @@ impl GitCommandProvider {
         }
     }
 
+    /// Return the directory git runs hooks from,
+    /// which respects `core.hooksPath` if it is set.
+    pub fn hooks_dir(&self) -> Result<PathBuf, GitCommandError> {
+        let out = GitCommandProvider::run_command(
+            self.new_command()
+                .arg("rev-parse")
+                .arg("--git-path")
+                .arg("hooks"),
+        )?;
+        let hooks_dir = PathBuf::from(out.to_string_lossy().trim());
+        // `--git-path` may return a path relative to the repo
+        Ok(self.path.join(hooks_dir))
+    }
+
     /// Clone a branch from a remote repository
     pub fn clone_branch_with(
         options: GitCommandOptions,
@@ -1532,6 +1546,12 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn hooks_dir() {
+        let (repo, _tempdir_handle) = init_temp_repo(false);
+        assert_eq!(repo.hooks_dir().unwrap(), repo.path().join(".git/hooks"));
+    }
+
     #[test]
     fn test_open() {
         let (_, tempdir_handle) = init_temp_repo(false);
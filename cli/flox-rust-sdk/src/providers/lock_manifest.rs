@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Display;
 use std::path::PathBuf;
@@ -23,13 +24,9 @@ use flox_manifest::lockfile::{
     LockedPackageStorePath,
     Lockfile,
 };
-use flox_manifest::parsed::common::{
-    Allows,
-    DEFAULT_PRIORITY,
-    IncludeDescriptor,
-    KnownSchemaVersion,
-};
+use flox_manifest::parsed::common::{DEFAULT_PRIORITY, IncludeDescriptor, KnownSchemaVersion};
 use flox_manifest::parsed::latest::{
+    Allows,
     ManifestLatest,
     ManifestPackageDescriptor,
     PackageDescriptorCatalog,
@@ -171,6 +168,18 @@ impl From<LockResult> for Lockfile {
     }
 }
 
+/// One-shot allowances that only apply to resolution and validation.
+///
+/// They are not recorded in the merged manifest or the lockfile.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResolutionOverrides {
+    /// Allow broken packages regardless of `options.allow.broken`.
+    pub allow_broken: bool,
+    /// Install IDs of packages that are allowed to be insecure,
+    /// in addition to `options.allow.insecure`.
+    pub allow_insecure: Vec<String>,
+}
+
 /// A provider responsible for operations responsible for locking manifests,
 /// which includes extracting and organizing constraints from the manifest
 /// and an optionally pre-existing lockfile.
@@ -293,10 +302,15 @@ impl LockManifest {
                         version: desc.version.clone(),
                         allow_pre_releases: manifest.options.semver.allow_pre_releases,
                         allow_broken: manifest.options.allow.broken,
-                        // TODO: add support for insecure
-                        allow_insecure: None,
+                        allow_insecure: manifest
+                            .options
+                            .allow
+                            .insecure
+                            .as_ref()
+                            .is_some_and(|insecure| insecure.contains(id))
+                            .then_some(true),
                         allow_unfree: manifest.options.allow.unfree,
-                        allow_missing_builds: None,
+                        allow_missing_builds: desc.allow_missing_builds,
                         allowed_licenses: maybe_licenses.clone(),
                         systems: vec![system],
                     };
@@ -324,6 +338,8 @@ impl LockManifest {
             seed_lockfile,
             include_fetcher,
             None,
+            false,
+            &ResolutionOverrides::default(),
         )
         .await
     }
@@ -341,12 +357,17 @@ impl LockManifest {
     /// re-fetched.
     /// If to_upgrade is None, only included environments not in the seed lockfile
     /// are fetched.
+    ///
+    /// See [Self::merge_manifest] for `locked_includes_only`.
+    /// `overrides` are applied to the merged manifest for resolution only.
     pub async fn lock_manifest_with_include_upgrades(
         flox: &Flox,
         manifest: &Manifest<MigratedTypedOnly>,
         seed_lockfile: Option<&Lockfile>,
         include_fetcher: &IncludeFetcher,
         to_upgrade: Option<Vec<String>>,
+        locked_includes_only: bool,
+        overrides: &ResolutionOverrides,
     ) -> Result<Lockfile, EnvironmentError> {
         // Attempt the merge once, then do the backwards compatibility test
         let (merged, mut compose) = Self::merge_manifest(
@@ -356,14 +377,35 @@ impl LockManifest {
             include_fetcher,
             ManifestMerger::Shallow(ShallowMerger),
             to_upgrade,
+            locked_includes_only,
         )
         .map_err(EnvironmentError::Recoverable)?;
 
+        // One-shot broken and insecure allowances only apply to resolution
+        // and validation, they're not recorded in the merged manifest.
+        let mut resolution_manifest = Cow::Borrowed(&merged);
+        if overrides.allow_broken {
+            resolution_manifest.to_mut().options.allow.broken = Some(true);
+        }
+        if !overrides.allow_insecure.is_empty() {
+            resolution_manifest
+                .to_mut()
+                .options
+                .allow
+                .insecure
+                .get_or_insert_default()
+                .extend(overrides.allow_insecure.iter().cloned());
+        }
+
         let catalog = &flox.floxhub_client;
-        let packages =
-            Self::resolve_manifest(&merged, seed_lockfile, catalog, &flox.installable_locker)
-                .await
-                .map_err(|e| EnvironmentError::Core(CoreEnvironmentError::Resolve(e)))?;
+        let packages = Self::resolve_manifest(
+            &resolution_manifest,
+            seed_lockfile,
+            catalog,
+            &flox.installable_locker,
+        )
+        .await
+        .map_err(|e| EnvironmentError::Core(CoreEnvironmentError::Resolve(e)))?;
 
         let proposed_lockfile = Lockfile {
             version: Version,
@@ -419,6 +461,10 @@ impl LockManifest {
     /// re-fetched.
     /// If to_upgrade is None, only included environments not in the seed lockfile
     /// are fetched.
+    ///
+    /// If `locked_includes_only` is set, upgrades are rejected and
+    /// included environments whose descriptor only differs from a locked one
+    /// in its `name` reuse the locked include instead of being fetched.
     #[instrument(skip_all, fields(progress = "Composing environments"))]
     fn merge_manifest(
         flox: &Flox,
@@ -427,6 +473,7 @@ impl LockManifest {
         include_fetcher: &IncludeFetcher,
         merger: ManifestMerger,
         mut to_upgrade: Option<Vec<String>>,
+        locked_includes_only: bool,
     ) -> Result<(ManifestLatest, Option<Compose>), RecoverableMergeError> {
         if manifest.include.environments.is_empty() {
             if to_upgrade.is_some() {
@@ -437,6 +484,12 @@ impl LockManifest {
             return Ok((manifest.clone(), None));
         }
 
+        if locked_includes_only && to_upgrade.is_some() {
+            return Err(RecoverableMergeError::Catchall(
+                "cannot upgrade included environments when only using locked includes".to_string(),
+            ));
+        }
+
         debug!("composing included environments");
 
         // Fetch included manifests we don't already have in seed_lockfile.
@@ -469,11 +522,19 @@ impl LockManifest {
                 };
                 // And we can find an identical include descriptor in the seed lockfile
                 // Then use the existing locked include
-                compose
+                let identical = compose
                     .include
                     .iter()
                     .find(|locked_include| &locked_include.descriptor == include_environment)
-                    .cloned()
+                    .cloned();
+                if identical.is_some() || !locked_includes_only {
+                    break 'existing identical;
+                }
+                // Otherwise, if we shouldn't fetch already locked includes,
+                // reuse an include that has only been renamed
+                compose.include.iter().find_map(|locked_include| {
+                    Self::renamed_locked_include(locked_include, include_environment)
+                })
             };
 
             let locked_include = match existing_locked_include {
@@ -605,8 +666,47 @@ impl LockManifest {
         }
     }
 
+    /// Reuse `locked_include` for `descriptor` if the two descriptors only
+    /// differ in their `name`.
+    ///
+    /// Returns None if the name of the include can't be determined without
+    /// fetching it,
+    /// i.e. if the locked descriptor sets a name but `descriptor` doesn't.
+    fn renamed_locked_include(
+        locked_include: &LockedInclude,
+        descriptor: &IncludeDescriptor,
+    ) -> Option<LockedInclude> {
+        fn split_name(descriptor: &IncludeDescriptor) -> (Option<String>, IncludeDescriptor) {
+            let mut unnamed = descriptor.clone();
+            let name = match &mut unnamed {
+                IncludeDescriptor::Local { name, .. } | IncludeDescriptor::Remote { name, .. } => {
+                    name.take()
+                },
+            };
+            (name, unnamed)
+        }
+
+        let (new_name, new_unnamed) = split_name(descriptor);
+        let (locked_name, locked_unnamed) = split_name(&locked_include.descriptor);
+        if new_unnamed != locked_unnamed {
+            return None;
+        }
+
+        let name = match (new_name, locked_name) {
+            (Some(new_name), _) => new_name,
+            (None, None) => locked_include.name.clone(),
+            (None, Some(_)) => return None,
+        };
+
+        Some(LockedInclude {
+            name,
+            descriptor: descriptor.clone(),
+            ..locked_include.clone()
+        })
+    }
+
     /// Check that all names in a list of locked includes are unique
-    fn check_locked_names_unique(
+    pub fn check_locked_names_unique(
         locked_includes: &[LockedInclude],
     ) -> Result<(), RecoverableMergeError> {
         let mut seen_names = HashSet::new();
@@ -726,47 +826,75 @@ impl LockManifest {
         allow: &Allows,
     ) -> Result<(), ResolveError> {
         for package in locked_packages {
-            if let Some(ref licenses) = allow.licenses {
-                // If licenses is empty, allow any license.
-                // There isn't any reason to disallow all licenses,
-                // and setting licenses to [] is the only way with composition
-                // currently to allow all licenses if an included environment has licenses.
-                if !licenses.is_empty() {
-                    let Some(ref license) = package.license else {
-                        continue;
-                    };
-
-                    if !licenses.iter().any(|allowed| allowed == license) {
-                        return Err(ResolveError::LicenseNotAllowed(
-                            package.install_id.to_string(),
-                            license.to_string(),
-                        ));
-                    }
-                }
+            if let Some(err) = Self::check_package_is_allowed(package, allow) {
+                return Err(err);
             }
+        }
+
+        Ok(())
+    }
+
+    /// Like [Self::check_packages_are_allowed],
+    /// but collect a violation for every disallowed package
+    /// rather than stopping at the first one.
+    ///
+    /// This allows auditing an existing lock against changed
+    /// `options.allow` without re-locking.
+    pub fn disallowed_packages<'a>(
+        locked_packages: impl IntoIterator<Item = &'a LockedPackageCatalog>,
+        allow: &Allows,
+    ) -> Vec<ResolveError> {
+        locked_packages
+            .into_iter()
+            .filter_map(|package| Self::check_package_is_allowed(package, allow))
+            .collect()
+    }
 
-            // Don't allow broken by default
-            if !allow.broken.unwrap_or(false) {
-                // Assume a package isn't broken
-                if package.broken.unwrap_or(false) {
-                    return Err(ResolveError::BrokenNotAllowed(
-                        package.install_id.to_owned(),
+    /// Return the reason `package` is not allowed by `allow`, if any.
+    fn check_package_is_allowed(
+        package: &LockedPackageCatalog,
+        allow: &Allows,
+    ) -> Option<ResolveError> {
+        if let Some(ref licenses) = allow.licenses {
+            // If licenses is empty, allow any license.
+            // There isn't any reason to disallow all licenses,
+            // and setting licenses to [] is the only way with composition
+            // currently to allow all licenses if an included environment has licenses.
+            if !licenses.is_empty() {
+                let Some(ref license) = package.license else {
+                    return None;
+                };
+
+                if !licenses.iter().any(|allowed| allowed == license) {
+                    return Some(ResolveError::LicenseNotAllowed(
+                        package.install_id.to_string(),
+                        license.to_string(),
                     ));
                 }
             }
+        }
 
-            // Allow unfree by default
-            if !allow.unfree.unwrap_or(true) {
-                // Assume a package isn't unfree
-                if package.unfree.unwrap_or(false) {
-                    return Err(ResolveError::UnfreeNotAllowed(
-                        package.install_id.to_owned(),
-                    ));
-                }
+        // Don't allow broken by default
+        if !allow.broken.unwrap_or(false) {
+            // Assume a package isn't broken
+            if package.broken.unwrap_or(false) {
+                return Some(ResolveError::BrokenNotAllowed(
+                    package.install_id.to_owned(),
+                ));
             }
         }
 
-        Ok(())
+        // Allow unfree by default
+        if !allow.unfree.unwrap_or(true) {
+            // Assume a package isn't unfree
+            if package.unfree.unwrap_or(false) {
+                return Some(ResolveError::UnfreeNotAllowed(
+                    package.install_id.to_owned(),
+                ));
+            }
+        }
+
+        None
     }
 
     /// Update the priority of already locked packages to match the manifest.
@@ -1154,28 +1282,38 @@ impl LockManifest {
 
     /// Filter out packages from the locked manifest by install_id or group
     /// If groups_or_iids is empty, all packages are unlocked.
+    /// Packages whose install_id is in `frozen` are never unlocked,
+    /// so their seed entries are copied forward unchanged.
     ///
     /// This is used to create a seed lockfile to upgrade a subset of packages,
     /// as packages that are not in the seed lockfile will be re-resolved unconstrained.
-    pub fn unlock_specified_packages_or_groups(lockfile: &mut Lockfile, groups_or_iids: &[&str]) {
-        if groups_or_iids.is_empty() {
-            lockfile.packages = Vec::new();
-        } else {
-            lockfile.packages = std::mem::take(&mut lockfile.packages)
-                .into_iter()
-                .filter(|package| {
-                    if groups_or_iids.contains(&package.install_id()) {
-                        return false;
-                    }
+    pub fn unlock_specified_packages_or_groups(
+        lockfile: &mut Lockfile,
+        groups_or_iids: &[&str],
+        frozen: &[&str],
+    ) {
+        lockfile.packages = std::mem::take(&mut lockfile.packages)
+            .into_iter()
+            .filter(|package| {
+                if frozen.contains(&package.install_id()) {
+                    return true;
+                }
 
-                    if let Some(catalog_package) = package.as_catalog_package_ref() {
-                        return !groups_or_iids.contains(&catalog_package.group.as_str());
-                    }
+                if groups_or_iids.is_empty() {
+                    return false;
+                }
 
-                    true
-                })
-                .collect();
-        }
+                if groups_or_iids.contains(&package.install_id()) {
+                    return false;
+                }
+
+                if let Some(catalog_package) = package.as_catalog_package_ref() {
+                    return !groups_or_iids.contains(&catalog_package.group.as_str());
+                }
+
+                true
+            })
+            .collect();
     }
 
     /// Collect flake installable descriptors from the manifest and create a list of
@@ -1414,7 +1552,7 @@ mod tests {
         "#};
 
     static TEST_MANIFEST_LATEST_CONTENTS: &str = indoc! {r#"
-          schema-version = "1.13.0"
+          schema-version = "1.14.0"
 
           [install]
           hello_install_id.pkg-path = "hello"
@@ -1588,6 +1726,33 @@ mod tests {
         assert_eq!(actual_params, expected_params);
     }
 
+    /// Only packages listed in `options.allow.insecure` are allowed to be
+    /// insecure
+    #[test]
+    fn make_params_allow_insecure() {
+        let manifest_str = with_latest_schema(indoc! {r#"
+            [install]
+            vim.pkg-path = "vim"
+            emacs.pkg-path = "emacs"
+
+            [options]
+            systems = ["aarch64-darwin"]
+            allow.insecure = ["vim"]
+        "#});
+        let manifest = toml_edit::de::from_str::<ManifestLatest>(&manifest_str).unwrap();
+
+        let allow_insecure = LockManifest::collect_resolution_package_groups(&manifest, None)
+            .unwrap()
+            .flat_map(|group| group.descriptors)
+            .map(|descriptor| (descriptor.install_id, descriptor.allow_insecure))
+            .collect::<Vec<_>>();
+
+        assert_eq!(allow_insecure, vec![
+            ("emacs".to_string(), None),
+            ("vim".to_string(), Some(true)),
+        ]);
+    }
+
     /// When `options.systems` defines multiple systems,
     /// request groups for each system separately.
     /// If a package specifies systems, use those instead.
@@ -1765,6 +1930,7 @@ mod tests {
                 version: None,
                 priority: None,
                 outputs: None,
+                allow_missing_builds: None,
             }
             .into(),
         );
@@ -2181,7 +2347,8 @@ mod tests {
             compose: None,
         };
 
-        LockManifest::unlock_specified_packages_or_groups(&mut lockfile, &[&foo_iid, &baz_iid]);
+        LockManifest::unlock_specified_packages_or_groups(&mut lockfile, &[&foo_iid, &baz_iid], &[
+        ]);
 
         assert_eq!(lockfile.packages, vec![
             bar_locked.into(),
@@ -2210,7 +2377,7 @@ mod tests {
             compose: None,
         };
 
-        LockManifest::unlock_specified_packages_or_groups(&mut lockfile, &["group"]);
+        LockManifest::unlock_specified_packages_or_groups(&mut lockfile, &["group"], &[]);
 
         assert_eq!(lockfile.packages, vec![]);
     }
@@ -2239,11 +2406,44 @@ mod tests {
             compose: None,
         };
 
-        LockManifest::unlock_specified_packages_or_groups(&mut lockfile, &[&foo_iid]);
+        LockManifest::unlock_specified_packages_or_groups(&mut lockfile, &[&foo_iid], &[]);
 
         assert_eq!(lockfile.packages, vec![]);
     }
 
+    /// Frozen packages keep their locked entries,
+    /// whether all packages or their group are unlocked
+    #[test]
+    fn unlock_keeps_frozen_packages() {
+        let mut manifest = ManifestLatest::default();
+        let (foo_iid, foo_descriptor, foo_locked) = fake_catalog_package_lock("foo", Some("group"));
+        let (bar_iid, bar_descriptor, bar_locked) = fake_catalog_package_lock("bar", Some("group"));
+        manifest
+            .install
+            .inner_mut()
+            .insert(foo_iid.clone(), foo_descriptor);
+        manifest
+            .install
+            .inner_mut()
+            .insert(bar_iid.clone(), bar_descriptor);
+        let lockfile = Lockfile {
+            version: Version::<1>,
+            manifest: manifest.as_typed_only(),
+            packages: vec![foo_locked.clone().into(), bar_locked.into()],
+            compose: None,
+        };
+
+        let mut unlock_all = lockfile.clone();
+        LockManifest::unlock_specified_packages_or_groups(&mut unlock_all, &[], &[&foo_iid]);
+        assert_eq!(unlock_all.packages, vec![foo_locked.clone().into()]);
+
+        let mut unlock_group = lockfile;
+        LockManifest::unlock_specified_packages_or_groups(&mut unlock_group, &["group"], &[
+            &foo_iid,
+        ]);
+        assert_eq!(unlock_group.packages, vec![foo_locked.into()]);
+    }
+
     #[test]
     fn unlock_by_iid_noop_if_already_unlocked() {
         let mut seed = TEST_LOCKED_MANIFEST.clone();
@@ -2251,7 +2451,7 @@ mod tests {
         // If the package is not in the seed, the lockfile should be unchanged
         let expected = seed.packages.clone();
 
-        LockManifest::unlock_specified_packages_or_groups(&mut seed, &["not in here"]);
+        LockManifest::unlock_specified_packages_or_groups(&mut seed, &["not in here"], &[]);
 
         assert_eq!(seed.packages, expected,);
     }
@@ -2857,6 +3057,65 @@ mod tests {
 
         assert_eq!(locked_manifest, locked);
     }
+    /// A broken allowance override permits an already locked broken package
+    /// without recording the allowance in the lockfile
+    #[tokio::test]
+    async fn lock_manifest_allow_broken_override() {
+        let (flox, _tempdir) = flox_instance();
+        let (foo_iid, foo_descriptor, mut foo_locked) = fake_catalog_package_lock("foo", None);
+        foo_locked.broken = Some(true);
+
+        let mut manifest = mk_test_manifest_from_contents(with_latest_schema(""));
+        let inner_manifest = manifest.as_latest_schema_mut();
+        inner_manifest.options.systems = Some(vec![PackageSystem::Aarch64Darwin.to_string()]);
+        inner_manifest
+            .install
+            .inner_mut()
+            .insert(foo_iid.clone(), foo_descriptor.clone());
+
+        let locked = Lockfile {
+            version: Version::<1>,
+            manifest: manifest.as_typed_only(),
+            packages: vec![foo_locked.into()],
+            compose: None,
+        };
+        let include_fetcher = IncludeFetcher {
+            base_directory: None,
+        };
+
+        let err = LockManifest::lock_manifest(
+            &flox,
+            &manifest.as_migrated_typed_only(),
+            Some(&locked),
+            &include_fetcher,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            EnvironmentError::Core(CoreEnvironmentError::Resolve(
+                ResolveError::BrokenNotAllowed(_)
+            ))
+        ));
+
+        let locked_manifest = LockManifest::lock_manifest_with_include_upgrades(
+            &flox,
+            &manifest.as_migrated_typed_only(),
+            Some(&locked),
+            &include_fetcher,
+            None,
+            false,
+            &ResolutionOverrides {
+                allow_broken: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(locked_manifest, locked);
+    }
+
     proptest! {
         // This probably isn't the best suited for proptest as there are lots of
         // writes to disk.
@@ -2913,6 +3172,7 @@ mod tests {
                 version: None,
                 systems: None,
                 outputs: None,
+                allow_missing_builds: None,
             }),
         );
         manifest
@@ -3119,7 +3379,8 @@ mod tests {
             LockManifest::check_packages_are_allowed(&vec![foo_locked], &Allows {
                 unfree: None,
                 broken: None,
-                licenses: Some(vec!["allowed".to_string()])
+                licenses: Some(vec!["allowed".to_string()]),
+                insecure: None
             }),
             Err(ResolveError::LicenseNotAllowed { .. })
         ));
@@ -3136,7 +3397,8 @@ mod tests {
             LockManifest::check_packages_are_allowed(&vec![foo_locked], &Allows {
                 unfree: None,
                 broken: None,
-                licenses: Some(vec!["allowed".to_string()])
+                licenses: Some(vec!["allowed".to_string()]),
+                insecure: None
             })
             .is_ok()
         );
@@ -3154,6 +3416,7 @@ mod tests {
                 unfree: None,
                 broken: None,
                 licenses: Some(vec![]),
+                insecure: None,
             })
             .is_ok()
         );
@@ -3170,7 +3433,8 @@ mod tests {
             LockManifest::check_packages_are_allowed(&vec![foo_locked], &Allows {
                 unfree: None,
                 broken: None,
-                licenses: None
+                licenses: None,
+                insecure: None
             }),
             Err(ResolveError::BrokenNotAllowed { .. })
         ));
@@ -3187,7 +3451,8 @@ mod tests {
             LockManifest::check_packages_are_allowed(&vec![foo_locked], &Allows {
                 unfree: None,
                 broken: Some(true),
-                licenses: None
+                licenses: None,
+                insecure: None
             })
             .is_ok()
         );
@@ -3204,7 +3469,8 @@ mod tests {
             LockManifest::check_packages_are_allowed(&vec![foo_locked], &Allows {
                 unfree: None,
                 broken: Some(false),
-                licenses: None
+                licenses: None,
+                insecure: None
             }),
             Err(ResolveError::BrokenNotAllowed { .. })
         ));
@@ -3221,7 +3487,8 @@ mod tests {
             LockManifest::check_packages_are_allowed(&vec![foo_locked], &Allows {
                 unfree: None,
                 broken: None,
-                licenses: None
+                licenses: None,
+                insecure: None
             })
             .is_ok()
         );
@@ -3238,7 +3505,8 @@ mod tests {
             LockManifest::check_packages_are_allowed(&vec![foo_locked], &Allows {
                 unfree: Some(true),
                 broken: None,
-                licenses: None
+                licenses: None,
+                insecure: None
             })
             .is_ok()
         );
@@ -3255,12 +3523,39 @@ mod tests {
             LockManifest::check_packages_are_allowed(&vec![foo_locked], &Allows {
                 unfree: Some(false),
                 broken: None,
-                licenses: None
+                licenses: None,
+                insecure: None
             }),
             Err(ResolveError::UnfreeNotAllowed { .. })
         ));
     }
 
+    /// [LockManifest::disallowed_packages] reports every disallowed package
+    #[test]
+    fn disallowed_packages_reports_all_violations() {
+        let (_, _, mut foo_locked) = fake_catalog_package_lock("foo", None);
+        foo_locked.unfree = Some(true);
+        let (_, _, mut bar_locked) = fake_catalog_package_lock("bar", None);
+        bar_locked.broken = Some(true);
+        let (_, _, baz_locked) = fake_catalog_package_lock("baz", None);
+
+        let violations =
+            LockManifest::disallowed_packages(&vec![foo_locked, bar_locked, baz_locked], &Allows {
+                unfree: Some(false),
+                broken: None,
+                licenses: None,
+                insecure: None,
+            });
+
+        assert!(matches!(
+            violations.as_slice(),
+            [
+                ResolveError::UnfreeNotAllowed(foo),
+                ResolveError::BrokenNotAllowed(bar),
+            ] if foo == "foo" && bar == "bar"
+        ));
+    }
+
     #[test]
     fn respects_flake_descriptor_systems() {
         let manifest_contents = with_latest_schema(formatdoc! {r#"
@@ -3311,6 +3606,7 @@ mod tests {
             },
             ManifestMerger::Shallow(ShallowMerger),
             None,
+            false,
         )
         .unwrap();
 
@@ -3380,6 +3676,7 @@ mod tests {
             },
             ManifestMerger::Shallow(ShallowMerger),
             None,
+            false,
         )
         .unwrap();
 
@@ -3496,6 +3793,7 @@ mod tests {
             &include_fetcher,
             ManifestMerger::Shallow(ShallowMerger),
             None,
+            false,
         )
         .unwrap();
 
@@ -3582,7 +3880,8 @@ mod tests {
             "#});
         let dep1_edited_manifest = mk_test_manifest_from_contents(&dep1_edited_manifest_contents);
 
-        dep1.edit(&flox, dep1_edited_manifest_contents).unwrap();
+        dep1.edit(&flox, dep1_edited_manifest_contents, false, false)
+            .unwrap();
 
         if modify_include_descriptor {
             manifest_contents = with_latest_schema(indoc! {r#"
@@ -3602,6 +3901,7 @@ mod tests {
             &include_fetcher,
             ManifestMerger::Shallow(ShallowMerger),
             None,
+            false,
         )
         .unwrap();
 
@@ -3640,6 +3940,120 @@ mod tests {
         re_merge_after_editing_dep(true).await;
     }
 
+    /// With `locked_includes_only`, locked includes are reused even if
+    /// they are unreachable and have been renamed,
+    /// but new includes are still fetched.
+    #[tokio::test]
+    async fn merge_manifest_locked_includes_only_does_not_fetch_locked_includes() {
+        let (flox, tempdir) = flox_instance();
+
+        let manifest = mk_test_manifest_from_contents(with_latest_schema(indoc! {r#"
+            [include]
+            environments = [
+              { dir = "dep1" }
+            ]
+            "#}));
+
+        let dep1_path = tempdir.path().join("dep1");
+        let dep1_manifest_contents = with_latest_schema(indoc! {r#"
+            [vars]
+            foo = "dep1"
+            "#});
+        std::fs::create_dir(&dep1_path).unwrap();
+        let mut dep1 = new_path_environment_in(&flox, &dep1_manifest_contents, &dep1_path);
+        dep1.lockfile(&flox).unwrap();
+
+        let include_fetcher = IncludeFetcher {
+            base_directory: Some(tempdir.path().to_path_buf()),
+        };
+        let lockfile = LockManifest::lock_manifest(
+            &flox,
+            &manifest.as_migrated_typed_only(),
+            None,
+            &include_fetcher,
+        )
+        .await
+        .unwrap();
+
+        // Make dep1 unreachable and rename it
+        std::fs::remove_dir_all(&dep1_path).unwrap();
+        let renamed = mk_test_manifest_from_contents(with_latest_schema(indoc! {r#"
+            [include]
+            environments = [
+              { dir = "dep1", name = "renamed" }
+            ]
+            "#}));
+
+        let err = LockManifest::merge_manifest(
+            &flox,
+            renamed.as_latest_schema(),
+            Some(&lockfile),
+            &include_fetcher,
+            ManifestMerger::Shallow(ShallowMerger),
+            None,
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(err, RecoverableMergeError::Fetch { .. }));
+
+        let (merged, compose) = LockManifest::merge_manifest(
+            &flox,
+            renamed.as_latest_schema(),
+            Some(&lockfile),
+            &include_fetcher,
+            ManifestMerger::Shallow(ShallowMerger),
+            None,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            merged.vars,
+            Vars::from_map(BTreeMap::from([("foo".to_string(), "dep1".to_string())]))
+        );
+        let compose = compose.unwrap();
+        assert_eq!(compose.include[0].name, "renamed");
+        assert_eq!(
+            &compose.include[0].descriptor,
+            &renamed.as_latest_schema().include.environments[0]
+        );
+
+        // Includes that aren't locked yet still have to be fetched
+        let with_new_include = mk_test_manifest_from_contents(with_latest_schema(indoc! {r#"
+            [include]
+            environments = [
+              { dir = "dep1" },
+              { dir = "dep2" }
+            ]
+            "#}));
+        let err = LockManifest::merge_manifest(
+            &flox,
+            with_new_include.as_latest_schema(),
+            Some(&lockfile),
+            &include_fetcher,
+            ManifestMerger::Shallow(ShallowMerger),
+            None,
+            true,
+        )
+        .unwrap_err();
+        assert!(
+            matches!(&err, RecoverableMergeError::Fetch { include, .. } if include.to_string() == "dep2"),
+            "{err}"
+        );
+
+        // Upgrades are rejected
+        let err = LockManifest::merge_manifest(
+            &flox,
+            manifest.as_latest_schema(),
+            Some(&lockfile),
+            &include_fetcher,
+            ManifestMerger::Shallow(ShallowMerger),
+            Some(vec![]),
+            true,
+        )
+        .unwrap_err();
+        assert!(matches!(err, RecoverableMergeError::Catchall(_)));
+    }
+
     // [LockManifest::merge_manifest] doesn't leave stale locked includes
     #[tokio::test]
     async fn merge_manifest_removes_stale_locked_includes() {
@@ -3706,6 +4120,7 @@ mod tests {
             &include_fetcher,
             ManifestMerger::Shallow(ShallowMerger),
             None,
+            false,
         )
         .unwrap();
 
@@ -3756,6 +4171,7 @@ mod tests {
             &include_fetcher,
             ManifestMerger::Shallow(ShallowMerger),
             None,
+            false,
         )
         .unwrap_err();
 
@@ -456,7 +456,7 @@ mod tests {
             ##
             ## -------------------------------------------------------------------
             # Flox manifest version managed by Flox CLI
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
 
             ## Install Packages --------------------------------------------------
@@ -569,6 +569,8 @@ mod tests {
                 version: Some("3.11.6".to_string()),
                 systems: None,
                 outputs: None,
+                pkg_group: None,
+                allow_missing_builds: false,
             }]),
             ..Default::default()
         };
@@ -582,7 +584,7 @@ mod tests {
             ##
             ## -------------------------------------------------------------------
             # Flox manifest version managed by Flox CLI
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
 
             ## Install Packages --------------------------------------------------
@@ -711,7 +713,7 @@ mod tests {
             ##
             ## -------------------------------------------------------------------
             # Flox manifest version managed by Flox CLI
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
 
             ## Install Packages --------------------------------------------------
@@ -835,7 +837,7 @@ mod tests {
             ##
             ## -------------------------------------------------------------------
             # Flox manifest version managed by Flox CLI
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
 
             ## Install Packages --------------------------------------------------
@@ -948,7 +950,7 @@ mod tests {
             ##
             ## -------------------------------------------------------------------
             # Flox manifest version managed by Flox CLI
-            schema-version = "1.13.0"
+            schema-version = "1.14.0"
 
 
             ## Install Packages --------------------------------------------------
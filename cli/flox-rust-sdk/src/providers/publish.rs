@@ -14,6 +14,7 @@ use floxhub_client::{
     CatalogStoreConfig,
     CatalogStoreConfigNixCopy,
     FloxhubClientError,
+    NarInfo,
     NarInfos,
     PackageOutput,
     PackageOutputs,
@@ -105,6 +106,12 @@ pub enum PublishError {
 
     #[error("Timed out waiting for publish completion")]
     PublishTimeout,
+
+    #[error("Invalid signing key '{}': {reason}", path.display())]
+    InvalidSigningKey { path: PathBuf, reason: String },
+
+    #[error("Failed to sign artifact metadata: {0}")]
+    SignMetadata(String),
 }
 
 /// The `Publish` trait describes the high level behavior of publishing a package to a catalog.
@@ -282,6 +289,17 @@ impl ClientSideCatalogStoreConfig {
         }
     }
 
+    /// Whether the narinfos of uploaded artifacts, including their signatures,
+    /// are submitted to the catalog.
+    pub fn records_nar_infos(&self) -> bool {
+        match self {
+            ClientSideCatalogStoreConfig::NixCopy { .. } => true,
+            ClientSideCatalogStoreConfig::MetadataOnly => true,
+            ClientSideCatalogStoreConfig::Null => false,
+            ClientSideCatalogStoreConfig::Publisher { .. } => false,
+        }
+    }
+
     /// Returns the path of the local signing key if one is configured.
     pub fn local_signing_key_path(&self) -> Option<PathBuf> {
         if let ClientSideCatalogStoreConfig::NixCopy {
@@ -552,6 +570,7 @@ pub struct PublishProvider<A> {
     pub env_metadata: CheckedEnvironmentMetadata,
     pub package_metadata: PackageMetadata,
     auth: A,
+    metadata_signing_key: Option<MetadataSigningKey>,
 }
 
 impl<A> PublishProvider<A> {
@@ -564,7 +583,107 @@ impl<A> PublishProvider<A> {
             env_metadata,
             package_metadata,
             auth,
+            metadata_signing_key: None,
+        }
+    }
+
+    /// Sign the metadata of the build outputs with `key` before uploading them,
+    /// see [MetadataSigningKey].
+    pub fn with_metadata_signing_key(self, key: MetadataSigningKey) -> Self {
+        Self {
+            metadata_signing_key: Some(key),
+            ..self
+        }
+    }
+}
+
+/// A Nix secret key, as created by `nix key generate-secret`,
+/// used to sign the metadata (narinfo) of published build outputs.
+///
+/// The signatures are stored alongside the outputs in the local store,
+/// so they are uploaded with the artifacts
+/// and recorded in the narinfos submitted to the catalog,
+/// where consumers can verify them with the matching public key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataSigningKey {
+    path: PathBuf,
+    /// The name of the key, which prefixes every signature made with it
+    name: String,
+}
+
+impl MetadataSigningKey {
+    /// Read the key at `path` and check that it is a Nix secret key.
+    pub fn from_file(path: impl Into<PathBuf>) -> Result<Self, PublishError> {
+        let path = path.into();
+        let invalid = |reason: String| PublishError::InvalidSigningKey {
+            path: path.clone(),
+            reason,
+        };
+
+        let contents = std::fs::read_to_string(&path).map_err(|err| invalid(err.to_string()))?;
+        let Some((name, _)) = contents
+            .trim()
+            .split_once(':')
+            .filter(|(name, key)| !name.is_empty() && !key.is_empty())
+        else {
+            return Err(invalid(
+                "expected a secret key in the format '<name>:<key>', as created by 'nix key generate-secret'"
+                    .to_string(),
+            ));
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            path,
+        })
+    }
+
+    /// Add a signature by this key to the given paths in the local store.
+    fn sign<'a>(&self, store_paths: impl IntoIterator<Item = &'a str>) -> Result<(), PublishError> {
+        let mut sign_command = nix_base_command();
+        sign_command
+            .args(["store", "sign", "--key-file"])
+            .arg(&self.path)
+            .args(store_paths);
+
+        debug!(cmd = %sign_command.display(), "signing build outputs");
+        let output = sign_command
+            .output()
+            .map_err(|e| PublishError::SignMetadata(e.to_string()))?;
+        if !output.status.success() {
+            return Err(PublishError::SignMetadata(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
         }
+        Ok(())
+    }
+
+    /// Whether `nar_info` carries a signature made with this key
+    fn has_signed(&self, nar_info: &NarInfo) -> bool {
+        nar_info.signatures.iter().flatten().any(|signature| {
+            signature.split_once(':').map(|(name, _)| name) == Some(self.name.as_str())
+        })
+    }
+
+    /// Ensure that the narinfo of every build output is signed with this key,
+    /// so that no partially signed metadata is submitted to the catalog.
+    fn check_signed(
+        &self,
+        build_outputs: &[PackageOutput],
+        nar_infos: &NarInfos,
+    ) -> Result<(), PublishError> {
+        for output in build_outputs {
+            let signed = nar_infos
+                .get(&output.store_path)
+                .is_some_and(|nar_info| self.has_signed(nar_info));
+            if !signed {
+                return Err(PublishError::SignMetadata(format!(
+                    "'{}' is not signed with key '{}'",
+                    output.store_path, self.name
+                )));
+            }
+        }
+        Ok(())
     }
 }
 
@@ -638,12 +757,38 @@ where
             catalog_store_config,
             ClientSideCatalogStoreConfig::Publisher { .. }
         );
+
+        // Sign before uploading anything,
+        // so that a failure to sign doesn't leave unsigned artifacts behind.
+        if let Some(signing_key) = &self.metadata_signing_key {
+            if !catalog_store_config.records_nar_infos() {
+                return Err(PublishError::SignMetadata(
+                    "the catalog store doesn't record the signatures of artifacts".to_string(),
+                ));
+            }
+            signing_key.sign(
+                build_metadata
+                    .outputs
+                    .iter()
+                    .map(|output| output.store_path.as_str()),
+            )?;
+        }
+
         let upload_result = catalog_store_config.maybe_upload_artifacts(&build_metadata.outputs)?;
         let (narinfos, narinfos_source_url) = match upload_result {
             Some((nar_infos, source_url)) => (Some(nar_infos), Some(source_url)),
             None => (None, None),
         };
 
+        if let Some(signing_key) = &self.metadata_signing_key {
+            let Some(narinfos) = &narinfos else {
+                return Err(PublishError::SignMetadata(
+                    "no artifact metadata was collected to verify the signatures".to_string(),
+                ));
+            };
+            signing_key.check_signed(&build_metadata.outputs, narinfos)?;
+        }
+
         let build_info = UserBuildPublish {
             derivation: UserDerivationInfo {
                 description: build_metadata.description.clone(),
@@ -750,6 +895,35 @@ where
     }
 }
 
+/// Get the configuration for uploading artifacts to the Catalog Store of
+/// `catalog_name` without publishing a package.
+///
+/// Only `nix copy` Catalog Stores can be written to outside of a publish,
+/// other stores either don't store artifacts or only accept them from a publisher.
+pub async fn catalog_store_config_for_upload(
+    client: &impl CatalogClientTrait,
+    catalog_name: &str,
+    key_file: Option<PathBuf>,
+    auth: &dyn AuthProvider,
+) -> Result<ClientSideCatalogStoreConfig, PublishError> {
+    let catalog_store_config = client
+        .catalog_store_config(catalog_name)
+        .await
+        .map_err(PublishError::CatalogError)?;
+
+    if !matches!(catalog_store_config, CatalogStoreConfig::NixCopy(_)) {
+        return Err(PublishError::Catchall(format!(
+            "The catalog '{catalog_name}' does not have a store that accepts uploaded artifacts."
+        )));
+    }
+
+    get_client_side_catalog_store_config(false, key_file, auth, PublishResponse {
+        catalog_store_config,
+        ingress_auth: None,
+        ingress_uri: None,
+    })
+}
+
 /// Get the complete configuration for client-side interactions with the provided
 /// Catalog Store.
 fn get_client_side_catalog_store_config(
@@ -1919,6 +2093,80 @@ pub mod tests {
         (build_metadata, env_metadata, package_metadata)
     }
 
+    #[test]
+    fn metadata_signing_key_requires_nix_secret_key() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let key_path = tempdir.path().join("key");
+
+        let err = MetadataSigningKey::from_file(&key_path).unwrap_err();
+        assert!(
+            matches!(err, PublishError::InvalidSigningKey { .. }),
+            "{err}"
+        );
+
+        std::fs::write(&key_path, "not a key").unwrap();
+        let err = MetadataSigningKey::from_file(&key_path).unwrap_err();
+        assert!(
+            matches!(err, PublishError::InvalidSigningKey { .. }),
+            "{err}"
+        );
+
+        std::fs::write(&key_path, "example.org-1:c2VjcmV0\n").unwrap();
+        let key = MetadataSigningKey::from_file(&key_path).unwrap();
+        assert_eq!(key.name, "example.org-1");
+    }
+
+    #[test]
+    fn only_stores_submitting_narinfos_record_signatures() {
+        assert!(ClientSideCatalogStoreConfig::MetadataOnly.records_nar_infos());
+        assert!(!ClientSideCatalogStoreConfig::Null.records_nar_infos());
+        assert!(
+            !ClientSideCatalogStoreConfig::Publisher {
+                ingress_uri: Url::parse("https://example.com").unwrap(),
+                signing_private_key_path: None,
+                ingress_auth: None,
+            }
+            .records_nar_infos()
+        );
+    }
+
+    #[test]
+    fn metadata_signing_key_rejects_unsigned_outputs() {
+        let key = MetadataSigningKey {
+            path: PathBuf::from("key"),
+            name: "example.org-1".to_string(),
+        };
+        let outputs = [
+            PackageOutput {
+                name: "out".to_string(),
+                store_path: "/nix/store/signed".to_string(),
+            },
+            PackageOutput {
+                name: "man".to_string(),
+                store_path: "/nix/store/unsigned".to_string(),
+            },
+        ];
+        let nar_info = |signatures: &[&str]| NarInfo {
+            signatures: Some(signatures.iter().map(ToString::to_string).collect()),
+            ..Default::default()
+        };
+
+        let nar_infos: NarInfos = HashMap::from([
+            (
+                "/nix/store/signed".to_string(),
+                nar_info(&["example.org-1:c2ln"]),
+            ),
+            (
+                "/nix/store/unsigned".to_string(),
+                nar_info(&["cache.nixos.org-1:c2ln"]),
+            ),
+        ])
+        .into();
+        assert!(key.check_signed(&outputs[..1], &nar_infos).is_ok());
+        let err = key.check_signed(&outputs, &nar_infos).unwrap_err();
+        assert!(matches!(err, PublishError::SignMetadata(_)), "{err}");
+    }
+
     #[tokio::test]
     async fn publish_errors_without_key() {
         let (mut flox, _tempdir) = flox_instance();
@@ -1,11 +1,15 @@
-use std::io::{BufWriter, stdout};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::io::{self, BufWriter, stdout};
 use std::os::unix::process::CommandExt;
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::str::FromStr;
 use std::{env, fs};
 
 use anyhow::{Context, Result, anyhow, bail};
-use bpaf::Bpaf;
+use bpaf::{Bpaf, Parser};
 use crossterm::tty::IsTty;
 use flox_core::activate::context::{
     ActivateCtx,
@@ -14,17 +18,21 @@ use flox_core::activate::context::{
     AttachProjectCtx,
     InvocationType,
 };
+use flox_core::activate::events::{ActivationEventKind, write_activation_event};
 use flox_core::activate::vars::{FLOX_ACTIVATIONS_BIN, FLOX_ACTIVATIONS_VERBOSITY_VAR};
 use flox_core::activations::activation_state_dir_path;
 use flox_core::data::System;
 use flox_core::data::environment_ref::DEFAULT_NAME;
 use flox_core::traceable_path;
+use flox_core::vars::FLOX_DISABLE_METRICS_VAR;
 use flox_events::EventsHub;
 use flox_manifest::interfaces::{AsLatestSchema, AsWritableManifest, WriteManifest};
+use flox_manifest::lockfile::Lockfile;
 use flox_manifest::parsed::Inner;
 use flox_manifest::parsed::common::IncludeDescriptor;
-use flox_manifest::{Manifest, MigratedTypedOnly};
-use flox_rust_sdk::flox::Flox;
+use flox_manifest::parsed::latest::ManifestLatest;
+use flox_manifest::{Manifest, ManifestError, MigratedTypedOnly};
+use flox_rust_sdk::flox::{FLOX_VERSION, Flox};
 use flox_rust_sdk::models::environment::floxmeta_branch::BranchOrd;
 use flox_rust_sdk::models::environment::generations::{GenerationId, GenerationsExt};
 use flox_rust_sdk::models::environment::managed_environment::DivergedMetadata;
@@ -33,12 +41,15 @@ use flox_rust_sdk::models::environment::{
     Environment,
     EnvironmentError,
     UpgradeResult,
+    build_activation_profile,
 };
-use flox_rust_sdk::providers::lock_manifest::LockResult;
+use flox_rust_sdk::providers::lock_manifest::{LockResult, ResolutionOverrides};
 use flox_rust_sdk::providers::services::process_compose::{PROCESS_COMPOSE_BIN, ProcessStates};
 use flox_rust_sdk::providers::upgrade_checks::UpgradeInformationGuard;
 use flox_rust_sdk::utils::FLOX_INTERPRETER;
 use indoc::{formatdoc, indoc};
+use itertools::Itertools;
+use serde::Serialize;
 use toml_edit::Key;
 use tracing::{debug, trace, warn};
 
@@ -51,7 +62,15 @@ use super::{
 };
 use crate::commands::check_for_upgrades::spawn_detached_check_for_upgrades_process;
 use crate::commands::general::update_config_with_query;
-use crate::commands::services::ServicesCommandsError;
+use crate::commands::list::List;
+use crate::commands::services::{
+    ServicesCommandsError,
+    ServicesEnvironment,
+    guard_service_commands_available,
+    service_does_not_exist_error,
+    service_not_available_on_system_error,
+    stop_services_by_name,
+};
 use crate::commands::{
     EnvironmentSelectError,
     SHELL_COMPLETION_COMMAND,
@@ -60,7 +79,7 @@ use crate::commands::{
     render_composition_manifest,
     uninitialized_environment_description,
 };
-use crate::config::{AutoActivationPreference, Config, EnvironmentPromptConfig};
+use crate::config::{AutoActivationPreference, Config, EnvironmentPromptConfig, PromptStyle};
 use crate::utils::detect_shell::{detect_shell_for_in_place, detect_shell_for_subshell};
 use crate::utils::errors::format_diverged_metadata;
 use crate::utils::events::env_detail_from_concrete;
@@ -113,6 +132,14 @@ impl Activate {
             ActivateSubcommandOrOptions::ActivateOptions { .. } => "activate",
         }
     }
+
+    /// Whether this is a `--pure` activation
+    pub fn is_pure(&self) -> bool {
+        matches!(
+            &self.subcommand_or_options,
+            ActivateSubcommandOrOptions::ActivateOptions { options } if options.pure
+        )
+    }
 }
 
 #[derive(Bpaf, Clone)]
@@ -139,6 +166,71 @@ pub enum AutoActivate {
     Deny,
 }
 
+/// Whether an activation inherits the variables of the process running
+/// `flox activate`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum InheritEnv {
+    /// Inherit all variables (the default)
+    #[default]
+    Allow,
+    /// Only inherit [PRESERVED_VARS] and start from [MINIMAL_PATH]
+    Deny,
+}
+
+impl FromStr for InheritEnv {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(InheritEnv::Allow),
+            "deny" => Ok(InheritEnv::Deny),
+            _ => Err(format!("invalid value '{s}', expected 'allow' or 'deny'")),
+        }
+    }
+}
+
+/// The directory an activation starts in, selected with `--cwd`
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActivateCwd {
+    /// The directory containing the environment, i.e. `FLOX_ENV_PROJECT`
+    Environment,
+    /// A directory given on the command line
+    Dir(PathBuf),
+}
+
+/// Parse `--cwd [<dir>]`, where omitting `<dir>` selects the environment's
+/// directory.
+fn activate_cwd() -> impl Parser<Option<ActivateCwd>> {
+    let dir = bpaf::long("cwd")
+        .help(
+            "Start the activation in <dir> rather than the current directory.\n\
+             Without <dir>, start in the directory containing the environment.",
+        )
+        .argument::<PathBuf>("dir")
+        .complete_shell(SHELL_COMPLETION_FILE)
+        .map(ActivateCwd::Dir);
+    let environment = bpaf::long("cwd").req_flag(ActivateCwd::Environment).hide();
+    bpaf::construct!([dir, environment]).optional()
+}
+
+/// Variables that are inherited even with `--inherit-env deny`
+const PRESERVED_VARS: [&str; 9] = [
+    "HOME", "USER", "LOGNAME", "SHELL", "TERM", "TMPDIR", "LANG", "LC_ALL", "TZ",
+];
+
+/// The PATH an activation starts from with `--inherit-env deny`,
+/// before the environment's bin directories are prepended
+const MINIMAL_PATH: &str = "/usr/bin:/bin:/usr/sbin:/sbin";
+
+/// Select the [PRESERVED_VARS] from `vars`.
+fn preserved_vars(
+    vars: impl IntoIterator<Item = (OsString, OsString)>,
+) -> Vec<(OsString, OsString)> {
+    vars.into_iter()
+        .filter(|(name, _)| PRESERVED_VARS.iter().any(|preserved| name == preserved))
+        .collect()
+}
+
 #[derive(Bpaf, Clone)]
 pub struct ActivateOptions {
     /// Trust a remote environment temporarily for this activation, including
@@ -146,9 +238,8 @@ pub struct ActivateOptions {
     #[bpaf(long, short)]
     pub trust: bool,
 
-    /// Print an activation script to stdout instead of spawning a subshell
-    #[bpaf(long("print-script"), short, hide)]
-    pub print_script: bool,
+    #[bpaf(external(activate_output), optional)]
+    pub output: Option<ActivateOutput>,
 
     /// Whether to start services when activating the environment
     #[bpaf(long, short)]
@@ -158,6 +249,13 @@ pub struct ActivateOptions {
     #[bpaf(long)]
     pub no_start_services: bool,
 
+    /// Start only the service <name> instead of all services of the
+    /// environment.
+    /// Implies '--start-services'.
+    /// Can be specified multiple times.
+    #[bpaf(long("service"), argument("name"))]
+    pub service: Vec<String>,
+
     /// Activate the environment in either "dev" or "run" mode.
     /// Overrides the "options.activate.mode" setting in the manifest.
     #[bpaf(short, long)]
@@ -167,10 +265,127 @@ pub struct ActivateOptions {
     #[bpaf(long, short)]
     pub generation: Option<GenerationId>,
 
+    /// Abort activation if running 'hook.on-activate' takes longer than
+    /// <secs> seconds.
+    /// By default hooks may run indefinitely.
+    #[bpaf(long, argument("secs"))]
+    pub timeout: Option<u64>,
+
+    /// Layer the overrides of activation profile <name> onto the environment.
+    /// Profiles are defined in the '[activation-profiles]' section of the
+    /// manifest.
+    #[bpaf(long, argument("name"))]
+    pub profile: Option<String>,
+
+    /// Prepend <dir> to PATH for this activation only.
+    /// Directories are searched after the environment's own bin directories,
+    /// in the order given.
+    #[bpaf(long("add-to-path"), argument("dir"), many)]
+    pub add_to_path: Vec<PathBuf>,
+
+    #[bpaf(external(activate_cwd))]
+    pub cwd: Option<ActivateCwd>,
+
+    /// Whether the activation inherits the variables of the calling
+    /// environment ('allow', the default),
+    /// or only a minimal set of variables such as HOME and TERM ('deny').
+    #[bpaf(long, argument("allow|deny"), fallback(InheritEnv::Allow))]
+    pub inherit_env: InheritEnv,
+
+    /// Activate a minimal environment for reproducible runs.
+    /// Implies '--inherit-env deny',
+    /// and disables the update check and metrics for the session.
+    /// Hooks still run unless '--no-hooks' is also given.
+    #[bpaf(long)]
+    pub pure: bool,
+
+    /// Don't run 'hook.on-activate' when starting the activation.
+    /// Profile scripts are still run.
+    #[bpaf(long("no-hooks"))]
+    pub no_hooks: bool,
+
+    /// Write the manifest, lockfile, system, flox version, and config of this
+    /// activation to <path> as JSON, e.g. to attach to a bug report.
+    /// Secrets such as the FloxHub token are redacted.
+    #[bpaf(long, argument("path"))]
+    pub record: Option<PathBuf>,
+
+    /// Write the manifest of this activation to <path>,
+    /// merged with the manifests of included environments if there are any
+    #[bpaf(long, argument("path"), complete_shell(SHELL_COMPLETION_FILE))]
+    pub export_manifest: Option<PathBuf>,
+
+    /// Append lifecycle events of the activation to <path> as JSON lines,
+    /// e.g. for an IDE to show the progress of the activation.
+    /// Use '/dev/fd/<n>' to write to an open file descriptor.
+    #[bpaf(long, argument("path"), complete_shell(SHELL_COMPLETION_FILE))]
+    pub json_events: Option<PathBuf>,
+
+    /// How to indicate the activation in the shell prompt:
+    /// not at all ('off'), with the number of active environments
+    /// ('minimal'), or with their names ('full').
+    /// Defaults to the 'prompt' config option.
+    #[bpaf(long, argument("off|minimal|full"))]
+    pub prompt: Option<PromptStyle>,
+
+    /// Allow the insecure package <iid> if the environment is locked for
+    /// this activation, without adding it to 'options.allow.insecure' in
+    /// the manifest.
+    /// Can be specified multiple times.
+    #[bpaf(long("allow-insecure"), argument("iid"))]
+    pub allow_insecure: Vec<String>,
+
     #[bpaf(external(command_select), optional)]
     pub command: Option<CommandSelect>,
 }
 
+/// What an activation outputs instead of spawning a subshell
+#[derive(Bpaf, Debug, Clone, PartialEq)]
+pub enum ActivateOutput {
+    /// Print an activation script to stdout instead of spawning a subshell
+    #[bpaf(long("print-script"), short, hide)]
+    PrintScript,
+
+    /// Print the variables, PATH additions, and outputs of the environment as JSON
+    /// instead of spawning a subshell.
+    /// Hooks and profile scripts are not run.
+    #[bpaf(long)]
+    Json,
+
+    /// Print only shell statements that export the variables and PATH
+    /// additions of the environment, e.g. for 'eval' in a parent shell.
+    /// Hooks and profile scripts are not run.
+    #[bpaf(long)]
+    SourceOnly,
+
+    Detach {
+        /// Start the services of the environment and return without activating
+        /// a shell, e.g. in CI.
+        /// Implies '--start-services'.
+        /// Prints the path of the services socket.
+        #[bpaf(long("detach"), req_flag(()))]
+        detach: (),
+
+        /// With '--detach', stop the services of the environment instead of
+        /// starting them
+        #[bpaf(long)]
+        stop: bool,
+    },
+}
+
+impl ActivateOutput {
+    /// The flag that selects the output, e.g. for error messages
+    fn flag(&self) -> &'static str {
+        match self {
+            ActivateOutput::PrintScript => "--print-script",
+            ActivateOutput::Json => "--json",
+            ActivateOutput::SourceOnly => "--source-only",
+            ActivateOutput::Detach { stop: false, .. } => "--detach",
+            ActivateOutput::Detach { stop: true, .. } => "--stop",
+        }
+    }
+}
+
 impl ActivateOptions {
     /// Validate that `--start-services` and `--no-start-services` are not
     /// used together, since they are mutually exclusive.
@@ -178,8 +393,78 @@ impl ActivateOptions {
         if self.start_services && self.no_start_services {
             bail!("--start-services and --no-start-services are mutually exclusive");
         }
+        if !self.service.is_empty() && self.no_start_services {
+            bail!("'--service' cannot be used with '--no-start-services'");
+        }
+        Ok(())
+    }
+
+    /// Validate that the selected output is not combined with options
+    /// that only apply when activating a shell or running a command.
+    fn validate_output_flags(&self) -> Result<()> {
+        let output = match &self.output {
+            None | Some(ActivateOutput::PrintScript) => return Ok(()),
+            Some(output) => output,
+        };
+        let flag = output.flag();
+        if self.command.is_some() {
+            bail!("'{flag}' cannot be used with a command");
+        }
+        if self.cwd.is_some() {
+            bail!("'{flag}' cannot be used with '--cwd'");
+        }
+        match output {
+            ActivateOutput::Detach { stop, .. } => {
+                if self.no_start_services {
+                    bail!("'{flag}' cannot be used with '--no-start-services'");
+                }
+                if *stop && (self.start_services || !self.service.is_empty()) {
+                    bail!("'--stop' cannot be used with '--start-services' or '--service'");
+                }
+            },
+            ActivateOutput::Json | ActivateOutput::SourceOnly => {
+                if self.start_services || !self.service.is_empty() {
+                    bail!("'{flag}' cannot be used with '--start-services' or '--service'");
+                }
+            },
+            ActivateOutput::PrintScript => {},
+        }
         Ok(())
     }
+
+    /// Validate that `--json-events` is not combined with options
+    /// that don't start an activation to report events of.
+    fn validate_json_events_flags(&self) -> Result<()> {
+        if self.json_events.is_none() {
+            return Ok(());
+        }
+        match &self.output {
+            None | Some(ActivateOutput::Detach { stop: false, .. }) => Ok(()),
+            Some(output) => bail!("'--json-events' cannot be used with '{}'", output.flag()),
+        }
+    }
+
+    /// Whether services were requested explicitly,
+    /// either all of them with '--start-services' or '--detach',
+    /// or some with '--service'.
+    fn requests_services(&self) -> bool {
+        self.start_services
+            || !self.service.is_empty()
+            || matches!(
+                self.output,
+                Some(ActivateOutput::Detach { stop: false, .. })
+            )
+    }
+
+    /// Whether the activation inherits the variables of the calling environment,
+    /// which '--pure' activations never do.
+    fn effective_inherit_env(&self) -> InheritEnv {
+        if self.pure {
+            InheritEnv::Deny
+        } else {
+            self.inherit_env
+        }
+    }
 }
 
 impl Activate {
@@ -192,10 +477,16 @@ impl Activate {
             },
             ActivateSubcommandOrOptions::ActivateOptions { options } => {
                 options.validate_service_flags()?;
+                options.validate_output_flags()?;
+                options.validate_json_events_flags()?;
                 options
             },
         };
 
+        if !options.allow_insecure.is_empty() {
+            message::insecure_packages_allowed(&options.allow_insecure, "activation");
+        }
+
         let mut concrete_environment = match self
             .environment
             .to_concrete_environment(&mut flox, options.generation)
@@ -216,7 +507,7 @@ impl Activate {
         environment_subcommand_metric!(
             "activate",
             concrete_environment,
-            start_services = options.start_services,
+            start_services = options.requests_services(),
             mode = options
                 .mode
                 .clone()
@@ -241,30 +532,48 @@ impl Activate {
             .unwrap_or(ActivateMode::Dev)
             .to_string();
         if let Err(err) = EventsHub::global().record_environment_activate_with(v2_env_detail, |p| {
-            p.with_start_services(options.start_services)
+            p.with_start_services(options.requests_services())
                 .with_mode(v2_mode)
         }) {
             debug!(error = %err, "Failed to record v2 event");
         }
 
+        // Stopping services doesn't run anything of the environment,
+        // so it doesn't require trust.
+        if let Some(ActivateOutput::Detach { stop: true, .. }) = options.output {
+            let env = ServicesEnvironment::from_concrete_environment(&flox, concrete_environment)?;
+            guard_service_commands_available(&env, &flox.system)?;
+            return stop_services_by_name(&flox, &env, &[]);
+        }
+
         if let ConcreteEnvironment::Remote(ref env) = concrete_environment
             && !options.trust
         {
+            // Show the manifest file as it is on disk, including comments.
+            // For composed environments this is the composer's manifest,
+            // i.e. what the user edits, rather than the merged manifest.
+            let manifest_contents = env
+                .manifest_without_migrating(&flox)?
+                .as_writable()
+                .to_string();
             ensure_environment_trust(
                 &mut config,
                 &flox,
                 &env.env_ref(),
                 false,
-                &env.manifest_without_migrating(&flox)?
-                    .as_writable()
-                    .to_string(),
+                &manifest_contents,
             )
             .await?;
         }
 
         let invocation_type = match options.command {
+            // Like 'flox services start', start services with an activation
+            // that only runs 'true', leaving process-compose running.
+            None if matches!(options.output, Some(ActivateOutput::Detach { .. })) => {
+                InvocationType::ExecCommand(vec!["true".to_string()])
+            },
             None => {
-                if options.print_script || !stdout().is_tty() {
+                if options.output.is_some() || !stdout().is_tty() {
                     InvocationType::InPlace
                 } else {
                     InvocationType::Interactive
@@ -287,26 +596,30 @@ impl Activate {
             },
         };
 
-        if (invocation_type == InvocationType::Interactive
-            || invocation_type == InvocationType::InPlace)
-            && config.flox.upgrade_notifications.unwrap_or(true)
-        {
-            // Read the results of a previous upgrade check
-            // and print a message if an upgrade is available.
-            notify_upgrades_if_available(&flox, &mut concrete_environment, &self.environment)?;
+        if options.pure {
+            debug!("Skipping upgrade checks for pure activation");
         } else {
-            debug!("Upgrade notification disabled");
-        }
+            if (invocation_type == InvocationType::Interactive
+                || invocation_type == InvocationType::InPlace)
+                && config.flox.upgrade_notifications.unwrap_or(true)
+            {
+                // Read the results of a previous upgrade check
+                // and print a message if an upgrade is available.
+                notify_upgrades_if_available(&flox, &mut concrete_environment, &self.environment)?;
+            } else {
+                debug!("Upgrade notification disabled");
+            }
 
-        // Spawn a detached process to check for upgrades in the background.
-        let environment =
-            UninitializedEnvironment::from_concrete_environment(&concrete_environment);
-        spawn_detached_check_for_upgrades_process(
-            &environment,
-            None,
-            &concrete_environment.log_path()?,
-            None,
-        )?;
+            // Spawn a detached process to check for upgrades in the background.
+            let environment =
+                UninitializedEnvironment::from_concrete_environment(&concrete_environment);
+            spawn_detached_check_for_upgrades_process(
+                &environment,
+                None,
+                &concrete_environment.log_path()?,
+                None,
+            )?;
+        }
 
         options
             .activate(
@@ -384,15 +697,49 @@ impl ActivateOptions {
     ) -> Result<()> {
         let now_active = UninitializedEnvironment::from_concrete_environment(&concrete_environment);
 
-        let lockfile = match concrete_environment.lockfile(&flox)? {
+        // flox-activations may run in a different directory with '--cwd'
+        let events_path = self
+            .json_events
+            .as_deref()
+            .map(std::path::absolute)
+            .transpose()?;
+
+        // Changes staged with e.g. 'flox install --no-lock' are locked here,
+        // before the environment is built.
+        emit_activation_event(events_path.as_deref(), ActivationEventKind::LockStarted)?;
+        let overrides = ResolutionOverrides {
+            allow_insecure: options.allow_insecure.clone(),
+            ..Default::default()
+        };
+        let lockfile = match concrete_environment.lockfile_with_overrides(&flox, &overrides)? {
             LockResult::Changed(lockfile) => {
+                message::info("Locked changes to the manifest that were not locked yet.");
                 message::print_overridden_manifest_fields(&lockfile);
                 lockfile
             },
             LockResult::Unchanged(lockfile) => lockfile,
         };
+        emit_activation_event(events_path.as_deref(), ActivationEventKind::LockFinished)?;
         let manifest = &lockfile.migrated_manifest()?;
 
+        if let Some(path) = &self.record {
+            write_activation_record(path, &flox.system, &lockfile, &config)?;
+            message::created(format!("Recorded activation to '{}'", path.display()));
+        }
+
+        if let Some(path) = &self.export_manifest {
+            let manifest_contents = List::manifest_contents_to_print(
+                &lockfile,
+                concrete_environment
+                    .manifest_without_migrating(&flox)?
+                    .as_writable()
+                    .to_string(),
+            )?;
+            fs::write(path, manifest_contents)
+                .with_context(|| format!("Could not export manifest to '{}'", path.display()))?;
+            message::created(format!("Exported manifest to '{}'", path.display()));
+        }
+
         if !self.trust
             && let Some(compose) = &lockfile.compose
         {
@@ -442,6 +789,32 @@ impl ActivateOptions {
             other => other?,
         };
 
+        let rendered_env_path = match &self.profile {
+            Some(profile) => {
+                if let Err(ManifestError::ActivationProfileNotFound { name, available }) =
+                    manifest.as_latest_schema().with_activation_profile(profile)
+                {
+                    let available = if available.is_empty() {
+                        "The manifest doesn't define any activation profiles.".to_string()
+                    } else {
+                        format!("Available profiles: {}", available.join(", "))
+                    };
+                    bail!(formatdoc! {"
+                        Activation profile '{name}' is not defined in the manifest.
+                        {available}
+                    "});
+                }
+                build_activation_profile(
+                    &flox,
+                    &concrete_environment,
+                    &lockfile,
+                    &rendered_env_path,
+                    profile,
+                )?
+            },
+            None => rendered_env_path,
+        };
+
         // Must not be evaluated inline with the macro or we'll leak TRACE logs
         // for reasons unknown.
         let lockfile_version = lockfile.version();
@@ -474,6 +847,57 @@ impl ActivateOptions {
             )
         })?;
 
+        if matches!(
+            self.output,
+            Some(ActivateOutput::Json | ActivateOutput::SourceOnly)
+        ) {
+            let outputs = [
+                ("dev", &rendered_env_path.dev),
+                ("run", &rendered_env_path.run),
+            ]
+            .into_iter()
+            .map(|(name, link)| Ok((name.to_string(), fs::read_link(link)?)))
+            .collect::<Result<BTreeMap<_, _>, io::Error>>()?;
+            let flox_vars = BTreeMap::from([
+                (
+                    "FLOX_ENV".to_string(),
+                    mode_link_path.to_string_lossy().to_string(),
+                ),
+                (
+                    "FLOX_ENV_CACHE".to_string(),
+                    concrete_environment
+                        .cache_path()?
+                        .into_inner()
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+                (
+                    "FLOX_ENV_DESCRIPTION".to_string(),
+                    now_active.bare_description(),
+                ),
+                (
+                    "FLOX_ENV_PROJECT".to_string(),
+                    concrete_environment
+                        .project_path()?
+                        .to_string_lossy()
+                        .to_string(),
+                ),
+            ]);
+            let activation = ActivationJson::new(
+                manifest.as_latest_schema(),
+                flox_vars,
+                &mode_link_path,
+                outputs,
+                &self.add_to_path,
+            )?;
+            if self.output == Some(ActivateOutput::SourceOnly) {
+                print!("{}", activation.to_exports()?);
+            } else {
+                println!("{}", serde_json::to_string_pretty(&activation)?);
+            }
+            return Ok(());
+        }
+
         let interpreter_path = {
             let path = FLOX_INTERPRETER.clone();
             tracing::debug!(
@@ -530,12 +954,21 @@ impl ActivateOptions {
                 hide_default_prompt.unwrap_or(true),
             ),
         };
+        let prompt_style = self.prompt.or(config.flox.prompt).unwrap_or(if set_prompt {
+            PromptStyle::Full
+        } else {
+            PromptStyle::Off
+        });
+        let set_prompt = prompt_style != PromptStyle::Off;
 
         // We don't have access to the current PS1 (it's not exported), so we
         // can't modify it. Instead set FLOX_PROMPT_ENVIRONMENTS and let the
         // activation script set PS1 based on that.
-        let flox_prompt_environments =
-            Self::make_prompt_environments(hide_default_prompt, &flox_active_environments);
+        let flox_prompt_environments = Self::make_prompt_environments(
+            prompt_style,
+            hide_default_prompt,
+            &flox_active_environments,
+        );
 
         let prompt_color_1 = env::var("FLOX_PROMPT_COLOR_1")
             .unwrap_or(utils::colors::INDIGO_400.to_ansi256().to_string());
@@ -550,12 +983,21 @@ impl ActivateOptions {
         };
 
         // Determine services to start with a new process-compose
-        let is_ephemeral = !services_for_ephemeral_activation.is_empty();
-        let services_to_start = if is_ephemeral {
+        let detach = matches!(self.output, Some(ActivateOutput::Detach { .. }));
+        let is_ephemeral = !services_for_ephemeral_activation.is_empty() || detach;
+        let services_to_start = if !services_for_ephemeral_activation.is_empty() {
             services_for_ephemeral_activation
         } else {
-            self.services_to_start(manifest, &flox.system, &socket_path)
+            self.services_to_start(manifest, &flox.system, &socket_path)?
         };
+        if detach && services_to_start.is_empty() {
+            // services_to_start() has explained why nothing was started
+            if socket_path.exists() {
+                println!("{}", socket_path.display());
+            }
+            return Ok(());
+        }
+        let detached_socket_path = detach.then(|| socket_path.clone());
         debug!(
             is_ephemeral,
             ?services_to_start,
@@ -617,13 +1059,53 @@ impl ActivateOptions {
             shell,
             invocation_type: Some(invocation_type),
             remove_after_reading: true,
-            metrics_uuid: flox.metrics_device_uuid,
+            metrics_uuid: if self.pure {
+                None
+            } else {
+                flox.metrics_device_uuid
+            },
             disable_hook: config.flox.disable_hook.unwrap_or(false),
             flox_bin: std::env::current_exe()
                 .ok()
                 .and_then(|p| p.to_str().map(String::from))
                 .unwrap_or_else(|| "flox".to_string()),
             auto_activate_fish_mode: config.flox.auto_activate_fish_mode,
+            hook_timeout_secs: self.timeout,
+            skip_hook_on_activate: self.no_hooks,
+            events_path,
+        };
+
+        let inherit_env = self.effective_inherit_env();
+        let inherited_path = match inherit_env {
+            InheritEnv::Allow => env::var_os("PATH"),
+            InheritEnv::Deny => {
+                if invocation_type == InvocationType::InPlace {
+                    if self.pure {
+                        bail!("'--pure' is not supported for in-place activations.");
+                    }
+                    bail!("'--inherit-env deny' is not supported for in-place activations.");
+                }
+                Some(OsString::from(MINIMAL_PATH))
+            },
+        };
+
+        let session_path = if self.add_to_path.is_empty() {
+            None
+        } else {
+            if invocation_type == InvocationType::InPlace {
+                bail!("'--add-to-path' is not supported for in-place activations.");
+            }
+            Some(Self::session_path(&self.add_to_path, inherited_path)?)
+        };
+
+        let session_cwd = match &self.cwd {
+            None => None,
+            Some(cwd) => {
+                if invocation_type == InvocationType::InPlace {
+                    bail!("'--cwd' is not supported for in-place activations.");
+                }
+                Some(Self::session_cwd(cwd, &concrete_environment)?)
+            },
         };
 
         let tempfile = tempfile::NamedTempFile::new_in(flox.temp_dir)?;
@@ -636,11 +1118,33 @@ impl ActivateOptions {
         // more sense for 0 to be the default rather than 1.
         let verbosity_num = flox.verbosity.max(0) as u32;
         let mut command = std::process::Command::new(&*FLOX_ACTIVATIONS_BIN);
+        if inherit_env == InheritEnv::Deny {
+            // Clear before setting any variables below,
+            // `env_clear` also removes variables set explicitly on `command`.
+            command
+                .env_clear()
+                .envs(preserved_vars(env::vars_os()))
+                .env("PATH", MINIMAL_PATH);
+        }
+        if self.pure {
+            // Keep flox invocations within the session from collecting metrics
+            command.env(FLOX_DISABLE_METRICS_VAR, "true");
+        }
         command
             .env(FLOX_ACTIVATIONS_VERBOSITY_VAR, format!("{verbosity_num}"))
             .arg("activate")
             .arg("--activate-data")
             .arg(tempfile);
+        // flox-activations prepends the environment's bin directories to the
+        // PATH it inherits, so these directories end up right after them.
+        if let Some(session_path) = session_path {
+            command.env("PATH", session_path);
+        }
+        // Shells only trust an inherited PWD if it matches their working
+        // directory, so update it alongside.
+        if let Some(session_cwd) = session_cwd {
+            command.current_dir(&session_cwd).env("PWD", &session_cwd);
+        }
 
         if is_ephemeral {
             debug!("running ephemeral activation command: {:?}", command);
@@ -659,6 +1163,12 @@ impl ActivateOptions {
                 "ephemeral activation stderr:\n{}",
                 String::from_utf8_lossy(&output.stderr)
             );
+            if let Some(socket_path) = detached_socket_path {
+                message::updated(
+                    "Started services in the background, stop them with 'flox services stop' or 'flox activate --detach --stop'.",
+                );
+                println!("{}", socket_path.display());
+            }
             Ok(())
         } else {
             debug!("running activation command: {:?}", command);
@@ -688,10 +1198,53 @@ impl ActivateOptions {
         }
     }
 
+    /// Resolve the directory selected with `--cwd` to an absolute path.
+    ///
+    /// Unlike `--add-to-path` directories, the directory has to exist.
+    fn session_cwd(cwd: &ActivateCwd, environment: &ConcreteEnvironment) -> Result<PathBuf> {
+        let dir = match cwd {
+            ActivateCwd::Environment => environment.project_path()?,
+            ActivateCwd::Dir(dir) => std::path::absolute(dir)
+                .with_context(|| format!("Could not resolve directory '{}'", dir.display()))?,
+        };
+        if !dir.is_dir() {
+            bail!(
+                "Directory '{}' passed to '--cwd' does not exist.",
+                dir.display()
+            );
+        }
+        Ok(dir)
+    }
+
+    /// Prepend the `--add-to-path` directories to `path`.
+    ///
+    /// Relative directories are resolved against the current directory,
+    /// and directories that don't exist are warned about but kept.
+    fn session_path(dirs: &[PathBuf], path: Option<OsString>) -> Result<OsString> {
+        let mut session_dirs = Vec::with_capacity(dirs.len());
+        for dir in dirs {
+            let dir = std::path::absolute(dir)
+                .with_context(|| format!("Could not resolve directory '{}'", dir.display()))?;
+            if !dir.is_dir() {
+                message::warning(format!(
+                    "Directory '{}' passed to '--add-to-path' does not exist.",
+                    dir.display()
+                ));
+            }
+            session_dirs.push(dir);
+        }
+        let existing = path.iter().flat_map(env::split_paths).collect::<Vec<_>>();
+        env::join_paths(session_dirs.into_iter().chain(existing))
+            .context("Directories passed to '--add-to-path' cannot contain ':'")
+    }
+
     /// Determine which services to start on activation.
     ///
-    /// Services are started when `--start-services` is set or when the manifest
-    /// has `[services] auto-start = true` and `--no-start-services` is not set.
+    /// Services are started when `--start-services` or `--service` is set or
+    /// when the manifest has `[services] auto-start = true` and
+    /// `--no-start-services` is not set.
+    /// With `--service`, only the named services are started,
+    /// and names that aren't defined for `system` are an error.
     ///
     /// Returns an empty list (with a warning) if:
     /// - Neither flag nor manifest requests service startup
@@ -703,34 +1256,57 @@ impl ActivateOptions {
         manifest: &Manifest<MigratedTypedOnly>,
         system: &System,
         socket_path: &Path,
-    ) -> Vec<String> {
+    ) -> Result<Vec<String>> {
         let manifest_services = &manifest.as_latest_schema().services;
         let auto_start = manifest_services.auto_start == Some(true);
 
-        let should_start = self.start_services || (auto_start && !self.no_start_services);
+        let should_start = self.requests_services() || (auto_start && !self.no_start_services);
         if !should_start {
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
         // Only emit warnings for conditions the user can act on when they
         // explicitly requested service startup via `--start-services`. When
         // auto-start triggers silently on every activation, these messages
         // would be noisy and surprising.
-        let warn = self.start_services;
+        let warn = self.requests_services();
 
         if manifest_services.inner().is_empty() {
+            if !self.service.is_empty() {
+                bail!(ServicesCommandsError::NoDefinedServices);
+            }
             message::warning(ServicesCommandsError::NoDefinedServices);
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
         let services_for_system = manifest_services.copy_for_system(system);
+        let available = services_for_system.inner();
+        for name in &self.service {
+            let err = if !manifest_services.inner().contains_key(name) {
+                service_does_not_exist_error(name)
+            } else if !available.contains_key(name) {
+                service_not_available_on_system_error(name, system)
+            } else {
+                continue;
+            };
+            if available.is_empty() {
+                bail!(err);
+            }
+            bail!(formatdoc! {"
+                {err}
+
+                Available services: {}",
+                available.keys().join(", ")
+            });
+        }
+
         if services_for_system.inner().is_empty() {
             if warn {
                 message::warning(ServicesCommandsError::NoDefinedServicesForSystem {
                     system: system.clone(),
                 });
             }
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
         let has_running_services = ProcessStates::read(socket_path)
@@ -741,16 +1317,22 @@ impl ActivateOptions {
             if warn {
                 message::warning("Skipped starting services, services are already running");
             }
-            return Vec::new();
+            return Ok(Vec::new());
         }
 
-        services_for_system.inner().keys().cloned().collect()
+        if !self.service.is_empty() {
+            return Ok(self.service.iter().unique().cloned().collect());
+        }
+        Ok(services_for_system.inner().keys().cloned().collect())
     }
 
     /// Construct the environment list for the shell prompt
     ///
-    /// [`None`] if the prompt is disabled, or filters removed all components.
+    /// With [PromptStyle::Minimal], only the number of environments is
+    /// returned.
+    /// Empty if filters removed all components.
     fn make_prompt_environments(
+        prompt_style: PromptStyle,
         hide_default_prompt: bool,
         flox_active_environments: &super::ActiveEnvironments,
     ) -> String {
@@ -764,10 +1346,136 @@ impl ActivateOptions {
             })
             .collect();
 
-        prompt_envs.join(" ")
+        match prompt_style {
+            PromptStyle::Minimal if !prompt_envs.is_empty() => prompt_envs.len().to_string(),
+            _ => prompt_envs.join(" "),
+        }
     }
 }
 
+/// First line of the output of `flox activate --source-only`
+const SOURCE_ONLY_BEGIN: &str = "# >>> flox activate --source-only >>>";
+/// Last line of the output of `flox activate --source-only`,
+/// so that callers can detect the end of the exports
+const SOURCE_ONLY_END: &str = "# <<< flox activate --source-only <<<";
+
+/// The environment an activation would set up, as printed by `flox activate --json`
+#[derive(Debug, PartialEq, Serialize)]
+struct ActivationJson {
+    /// Directories prepended to PATH, in order
+    #[serde(rename = "PATH_additions")]
+    path_additions: Vec<PathBuf>,
+    /// Variables set by the activation
+    vars: BTreeMap<String, String>,
+    /// Variables of `vars` that come from the manifest,
+    /// which are exported unescaped like in the built environment
+    #[serde(skip)]
+    manifest_vars: BTreeMap<String, String>,
+    /// Store paths of the built environment by activation mode
+    outputs: BTreeMap<String, PathBuf>,
+    /// Hooks and profile scripts of the manifest, which are not run
+    hooks_skipped: Vec<String>,
+}
+
+impl ActivationJson {
+    fn new(
+        manifest: &ManifestLatest,
+        flox_vars: BTreeMap<String, String>,
+        flox_env: &Path,
+        outputs: BTreeMap<String, PathBuf>,
+        add_to_path: &[PathBuf],
+    ) -> Result<Self> {
+        let mut path_additions = vec![flox_env.join("bin"), flox_env.join("sbin")];
+        for dir in add_to_path {
+            path_additions.push(
+                std::path::absolute(dir)
+                    .with_context(|| format!("Could not resolve directory '{}'", dir.display()))?,
+            );
+        }
+
+        // Variables set by flox take precedence over those in the manifest
+        let manifest_vars: BTreeMap<_, _> = manifest
+            .vars
+            .inner()
+            .iter()
+            .filter(|(name, _)| !flox_vars.contains_key(*name))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let mut vars = manifest_vars.clone();
+        vars.extend(flox_vars);
+
+        let mut hooks_skipped = Vec::new();
+        for (section, scripts) in [
+            ("hook", serde_json::to_value(&manifest.hook)?),
+            ("profile", serde_json::to_value(&manifest.profile)?),
+        ] {
+            if let serde_json::Value::Object(scripts) = scripts {
+                hooks_skipped.extend(
+                    scripts
+                        .into_iter()
+                        .filter(|(_, script)| !script.is_null())
+                        .map(|(name, _)| format!("{section}.{name}")),
+                );
+            }
+        }
+
+        Ok(Self {
+            path_additions,
+            vars,
+            manifest_vars,
+            outputs,
+            hooks_skipped,
+        })
+    }
+
+    /// Render the variables and PATH additions as POSIX shell statements
+    /// for `flox activate --source-only`,
+    /// delimited by [SOURCE_ONLY_BEGIN] and [SOURCE_ONLY_END].
+    ///
+    /// Variables set by flox are exported first and escaped.
+    /// Manifest variables follow in double quotes, as written to the envrc
+    /// by `buildenv.nix`, so that references like `$FLOX_ENV` expand
+    /// the same way they do in an activation.
+    fn to_exports(&self) -> Result<String> {
+        if let Some(name) = self.vars.keys().find(|name| !is_shell_identifier(name)) {
+            bail!("'{name}' is not a valid shell variable name and cannot be exported.");
+        }
+
+        let mut exports = vec![SOURCE_ONLY_BEGIN.to_string()];
+        for (name, value) in &self.vars {
+            if self.manifest_vars.contains_key(name) {
+                continue;
+            }
+            exports.push(format!(
+                "export {name}={};",
+                shell_escape::escape(Cow::Borrowed(value))
+            ));
+        }
+        for (name, value) in &self.manifest_vars {
+            exports.push(format!("export {name}=\"{value}\";"));
+        }
+        let path_additions = self
+            .path_additions
+            .iter()
+            .map(|dir| shell_escape::escape(dir.to_string_lossy()))
+            .join(":");
+        exports.push(format!(
+            "export PATH={path_additions}\"${{PATH:+:$PATH}}\";"
+        ));
+        exports.push(SOURCE_ONLY_END.to_string());
+        Ok(exports.join("\n") + "\n")
+    }
+}
+
+/// Whether `name` can be used as a variable name in POSIX shells
+fn is_shell_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first == '_' || first.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
 /// Notify the user of available upgrades
 ///
 /// Upon activation flox will start a detached process to check for upgrades.
@@ -1015,6 +1723,49 @@ pub fn write_auto_activation_preference(
     Ok(())
 }
 
+/// Append an event for '--json-events' to `events_path`, if set.
+///
+/// Unlike in flox-activations, failing to write an event is an error,
+/// so that an unusable path is reported before the activation starts.
+fn emit_activation_event(events_path: Option<&Path>, kind: ActivationEventKind) -> Result<()> {
+    let Some(path) = events_path else {
+        return Ok(());
+    };
+    write_activation_event(path, kind)
+        .with_context(|| format!("Could not write activation events to '{}'", path.display()))
+}
+
+/// What is needed to reproduce an activation, written with `--record`
+#[derive(Debug, Serialize)]
+struct ActivationRecord<'a> {
+    flox_version: String,
+    system: &'a str,
+    /// The manifest as edited by the user,
+    /// the merged manifest of composed environments is part of the lockfile
+    manifest: String,
+    lockfile: &'a Lockfile,
+    config: Config,
+}
+
+/// Write an [ActivationRecord] to `path`, with secrets removed from `config`.
+fn write_activation_record(
+    path: &Path,
+    system: &str,
+    lockfile: &Lockfile,
+    config: &Config,
+) -> Result<()> {
+    let record = ActivationRecord {
+        flox_version: FLOX_VERSION.to_string(),
+        system,
+        manifest: lockfile.user_manifest_string()?,
+        lockfile,
+        config: config.redacted(),
+    };
+    let contents = serde_json::to_string_pretty(&record)?;
+    fs::write(path, contents)
+        .with_context(|| format!("Could not write activation record to '{}'", path.display()))
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::LazyLock;
@@ -1041,7 +1792,11 @@ mod tests {
     #[test]
     fn test_shell_prompt_empty_without_active_environments() {
         let active_environments = ActiveEnvironments::default();
-        let prompt = ActivateOptions::make_prompt_environments(false, &active_environments);
+        let prompt = ActivateOptions::make_prompt_environments(
+            PromptStyle::Full,
+            false,
+            &active_environments,
+        );
 
         assert_eq!(prompt, "");
     }
@@ -1052,11 +1807,19 @@ mod tests {
         active_environments.set_last_active(DEFAULT_ENV.clone(), None, ActivateMode::Dev);
 
         // with `hide_default_prompt = false` we should see the default environment
-        let prompt = ActivateOptions::make_prompt_environments(false, &active_environments);
+        let prompt = ActivateOptions::make_prompt_environments(
+            PromptStyle::Full,
+            false,
+            &active_environments,
+        );
         assert_eq!(prompt, "default".to_string());
 
         // with `hide_default_prompt = true` we should not see the default environment
-        let prompt = ActivateOptions::make_prompt_environments(true, &active_environments);
+        let prompt = ActivateOptions::make_prompt_environments(
+            PromptStyle::Full,
+            true,
+            &active_environments,
+        );
         assert_eq!(prompt, "");
     }
 
@@ -1067,14 +1830,44 @@ mod tests {
         active_environments.set_last_active(NON_DEFAULT_ENV.clone(), None, ActivateMode::Dev);
 
         // with `hide_default_prompt = false` we should see the default environment
-        let prompt = ActivateOptions::make_prompt_environments(false, &active_environments);
+        let prompt = ActivateOptions::make_prompt_environments(
+            PromptStyle::Full,
+            false,
+            &active_environments,
+        );
         assert_eq!(prompt, "wichtig default".to_string());
 
         // with `hide_default_prompt = true` we should not see the default environment
-        let prompt = ActivateOptions::make_prompt_environments(true, &active_environments);
+        let prompt = ActivateOptions::make_prompt_environments(
+            PromptStyle::Full,
+            true,
+            &active_environments,
+        );
         assert_eq!(prompt, "wichtig".to_string());
     }
 
+    #[test]
+    fn test_shell_prompt_minimal() {
+        let mut active_environments = ActiveEnvironments::default();
+        active_environments.set_last_active(DEFAULT_ENV.clone(), None, ActivateMode::Dev);
+
+        // a hidden default environment leaves nothing to indicate
+        let prompt = ActivateOptions::make_prompt_environments(
+            PromptStyle::Minimal,
+            true,
+            &active_environments,
+        );
+        assert_eq!(prompt, "");
+
+        active_environments.set_last_active(NON_DEFAULT_ENV.clone(), None, ActivateMode::Dev);
+        let prompt = ActivateOptions::make_prompt_environments(
+            PromptStyle::Minimal,
+            false,
+            &active_environments,
+        );
+        assert_eq!(prompt, "2");
+    }
+
     /// Build minimal ActivateOptions with only the service-related flags set.
     fn activate_options_with_flags(
         start_services: bool,
@@ -1082,20 +1875,276 @@ mod tests {
     ) -> ActivateOptions {
         ActivateOptions {
             trust: false,
-            print_script: false,
+            output: None,
             start_services,
             no_start_services,
+            service: vec![],
             mode: None,
             generation: None,
+            timeout: None,
+            profile: None,
+            add_to_path: vec![],
+            cwd: None,
+            inherit_env: InheritEnv::Allow,
+            pure: false,
+            no_hooks: false,
+            record: None,
+            export_manifest: None,
+            json_events: None,
+            prompt: None,
+            allow_insecure: vec![],
             command: None,
         }
     }
 
+    #[test]
+    fn pure_implies_inherit_env_deny() {
+        let options = activate_options_with_flags(false, false);
+        assert_eq!(options.effective_inherit_env(), InheritEnv::Allow);
+
+        let options = ActivateOptions {
+            pure: true,
+            ..activate_options_with_flags(false, false)
+        };
+        assert_eq!(options.effective_inherit_env(), InheritEnv::Deny);
+    }
+
+    #[test]
+    fn parses_inherit_env() {
+        assert_eq!("allow".parse(), Ok(InheritEnv::Allow));
+        assert_eq!("deny".parse(), Ok(InheritEnv::Deny));
+        assert!("clear".parse::<InheritEnv>().is_err());
+    }
+
+    #[test]
+    fn activation_record_redacts_token() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("record.json");
+        let mut config = Config::default();
+        config.flox.floxhub_token = Some("secret-token".to_string());
+
+        write_activation_record(&path, "x86_64-linux", &Lockfile::default(), &config).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("secret-token"), "{contents}");
+        let record: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(record["system"], "x86_64-linux");
+        assert_eq!(record["flox_version"], FLOX_VERSION.to_string());
+        assert_eq!(record["config"]["floxhub_token"], "<redacted>");
+        assert!(record["lockfile"].is_object());
+        assert!(record["manifest"].is_string());
+    }
+
+    #[test]
+    fn preserved_vars_keeps_only_allowlisted_variables() {
+        let vars = [
+            ("HOME", "/home/user"),
+            ("TERM", "xterm-256color"),
+            ("PATH", "/usr/bin"),
+            ("FLOX_ENV", "/flox/env"),
+            ("SECRET_TOKEN", "hunter2"),
+        ]
+        .map(|(name, value)| (OsString::from(name), OsString::from(value)));
+
+        assert_eq!(preserved_vars(vars), vec![
+            (OsString::from("HOME"), OsString::from("/home/user")),
+            (OsString::from("TERM"), OsString::from("xterm-256color")),
+        ]);
+    }
+
+    #[test]
+    fn session_path_prepends_directories_in_order() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let first = tempdir.path().join("first");
+        let second = tempdir.path().join("second");
+        fs::create_dir(&first).unwrap();
+        fs::create_dir(&second).unwrap();
+
+        let path = ActivateOptions::session_path(
+            &[first.clone(), second.clone()],
+            Some("/usr/bin:/bin".into()),
+        )
+        .unwrap();
+        assert_eq!(env::split_paths(&path).collect::<Vec<_>>(), vec![
+            first,
+            second,
+            PathBuf::from("/usr/bin"),
+            PathBuf::from("/bin")
+        ]);
+    }
+
+    #[test]
+    fn session_path_rejects_separator_in_directory() {
+        assert!(ActivateOptions::session_path(&["/a:b".into()], None).is_err());
+    }
+
+    #[test]
+    fn activation_json_lists_vars_paths_and_skipped_hooks() {
+        let manifest = Manifest::parse_and_migrate(
+            indoc! {r#"
+                version = 1
+
+                [vars]
+                FOO = "bar"
+
+                [hook]
+                on-activate = "echo hello"
+
+                [profile]
+                bash = "echo bash"
+            "#},
+            None,
+        )
+        .unwrap();
+
+        let activation = ActivationJson::new(
+            manifest.as_latest_schema(),
+            BTreeMap::from([("FLOX_ENV".to_string(), "/env".to_string())]),
+            Path::new("/env"),
+            BTreeMap::from([("run".to_string(), PathBuf::from("/nix/store/run"))]),
+            &[PathBuf::from("/extra/bin")],
+        )
+        .unwrap();
+
+        assert_eq!(activation, ActivationJson {
+            path_additions: vec![
+                PathBuf::from("/env/bin"),
+                PathBuf::from("/env/sbin"),
+                PathBuf::from("/extra/bin"),
+            ],
+            vars: BTreeMap::from([
+                ("FLOX_ENV".to_string(), "/env".to_string()),
+                ("FOO".to_string(), "bar".to_string()),
+            ]),
+            manifest_vars: BTreeMap::from([("FOO".to_string(), "bar".to_string())]),
+            outputs: BTreeMap::from([("run".to_string(), PathBuf::from("/nix/store/run"))]),
+            hooks_skipped: vec!["hook.on-activate".to_string(), "profile.bash".to_string()],
+        });
+
+        let json = serde_json::to_value(&activation).unwrap();
+        assert!(json.get("PATH_additions").is_some(), "{json}");
+    }
+
+    #[test]
+    fn source_only_exports_vars_and_path() {
+        let activation = ActivationJson {
+            path_additions: vec![PathBuf::from("/env/bin"), PathBuf::from("/my dir")],
+            vars: BTreeMap::from([
+                ("A_DATA".to_string(), "$FLOX_ENV/share".to_string()),
+                ("FLOX_ENV".to_string(), "/env".to_string()),
+                ("FLOX_PROMPT".to_string(), "it's me".to_string()),
+            ]),
+            manifest_vars: BTreeMap::from([("A_DATA".to_string(), "$FLOX_ENV/share".to_string())]),
+            outputs: BTreeMap::new(),
+            hooks_skipped: vec!["hook.on-activate".to_string()],
+        };
+
+        assert_eq!(activation.to_exports().unwrap(), indoc! {r#"
+            # >>> flox activate --source-only >>>
+            export FLOX_ENV=/env;
+            export FLOX_PROMPT='it'\''s me';
+            export A_DATA="$FLOX_ENV/share";
+            export PATH=/env/bin:'/my dir'"${PATH:+:$PATH}";
+            # <<< flox activate --source-only <<<
+        "#});
+    }
+
+    #[test]
+    fn source_only_rejects_invalid_variable_names() {
+        let activation = ActivationJson {
+            path_additions: Vec::new(),
+            vars: BTreeMap::from([("MY-VAR".to_string(), "value".to_string())]),
+            manifest_vars: BTreeMap::from([("MY-VAR".to_string(), "value".to_string())]),
+            outputs: BTreeMap::new(),
+            hooks_skipped: Vec::new(),
+        };
+
+        assert_eq!(
+            activation.to_exports().unwrap_err().to_string(),
+            "'MY-VAR' is not a valid shell variable name and cannot be exported."
+        );
+        assert!(is_shell_identifier("_MY_VAR1"));
+        assert!(!is_shell_identifier("1VAR"));
+        assert!(!is_shell_identifier(""));
+    }
+
+    #[test]
+    fn source_only_rejects_services_and_command() {
+        let mut options = activate_options_with_flags(false, false);
+        options.output = Some(ActivateOutput::SourceOnly);
+        options.validate_output_flags().unwrap();
+
+        options.start_services = true;
+        assert!(options.validate_output_flags().is_err());
+
+        options.start_services = false;
+        options.command = Some(CommandSelect::ShellCommand {
+            shell_command: "true".to_string(),
+        });
+        assert!(options.validate_output_flags().is_err());
+    }
+
+    #[test]
+    fn detach_requests_services_unless_stopping() {
+        let mut options = activate_options_with_flags(false, false);
+        options.output = Some(ActivateOutput::Detach {
+            detach: (),
+            stop: true,
+        });
+        options.validate_output_flags().unwrap();
+        assert!(!options.requests_services());
+
+        options.output = Some(ActivateOutput::Detach {
+            detach: (),
+            stop: false,
+        });
+        options.validate_output_flags().unwrap();
+        assert!(options.requests_services());
+
+        options.command = Some(CommandSelect::ShellCommand {
+            shell_command: "true".to_string(),
+        });
+        assert!(options.validate_output_flags().is_err());
+    }
+
+    #[test]
+    fn json_events_require_an_activation() {
+        let mut options = activate_options_with_flags(false, false);
+        options.json_events = Some(PathBuf::from("/dev/fd/3"));
+        options.validate_json_events_flags().unwrap();
+
+        options.output = Some(ActivateOutput::Json);
+        assert!(options.validate_json_events_flags().is_err());
+    }
+
+    #[test]
+    fn json_rejects_command() {
+        let mut options = activate_options_with_flags(false, false);
+        options.output = Some(ActivateOutput::Json);
+        options.validate_output_flags().unwrap();
+
+        options.command = Some(CommandSelect::ShellCommand {
+            shell_command: "true".to_string(),
+        });
+        assert!(options.validate_output_flags().is_err());
+    }
+
     #[test]
     fn test_conflicting_service_flags_are_rejected() {
         let options = activate_options_with_flags(true, true);
         assert!(options.validate_service_flags().is_err());
     }
+
+    #[test]
+    fn service_conflicts_with_no_start_services() {
+        let mut options = activate_options_with_flags(false, false);
+        options.service = vec!["postgres".to_string()];
+        options.validate_service_flags().unwrap();
+        assert!(options.requests_services());
+
+        options.no_start_services = true;
+        assert!(options.validate_service_flags().is_err());
+    }
 }
 
 #[cfg(test)]
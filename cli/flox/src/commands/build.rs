@@ -1,6 +1,7 @@
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::str::FromStr;
 
 use anyhow::{Context, Result, bail};
 use bpaf::Bpaf;
@@ -33,7 +34,7 @@ use thiserror::Error;
 use tracing::{debug, instrument, trace};
 use url::Url;
 
-use super::{DirEnvironmentSelect, dir_environment_select};
+use super::{DirEnvironmentSelect, SHELL_COMPLETION_DIR, dir_environment_select};
 use crate::utils::message;
 use crate::{environment_subcommand_metric, subcommand_metric};
 
@@ -71,6 +72,29 @@ impl SystemOverride {
     }
 }
 
+/// Whether manifest builds run in the sandbox selected by their `sandbox`
+/// setting, selected with `--sandbox`
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SandboxSwitch {
+    /// Use the sandbox configured in the manifest (the default)
+    #[default]
+    On,
+    /// Build as if every build set `sandbox = "off"`
+    Off,
+}
+
+impl FromStr for SandboxSwitch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "on" => Ok(SandboxSwitch::On),
+            "off" => Ok(SandboxSwitch::Off),
+            _ => Err(format!("invalid value '{s}', expected 'on' or 'off'")),
+        }
+    }
+}
+
 #[derive(Bpaf, Clone)]
 pub struct Build {
     #[bpaf(external(dir_environment_select), fallback(Default::default()))]
@@ -127,6 +151,18 @@ enum SubcommandOrBuildTargets {
         #[bpaf(external(system_override))]
         system_override: SystemOverride,
 
+        /// Write intermediate build artifacts to <path>,
+        /// e.g. a cache volume shared between CI runs.
+        /// The directory is created if it doesn't exist.
+        #[bpaf(long, argument("path"), complete_shell(SHELL_COMPLETION_DIR))]
+        cache_dir: Option<PathBuf>,
+
+        /// With 'off', build without the sandbox configured in the manifest,
+        /// allowing network and impure access.
+        /// Results may not be reproducible.
+        #[bpaf(long, argument("on|off"), fallback(SandboxSwitch::On))]
+        sandbox: SandboxSwitch,
+
         /// The package to build.
         /// Corresponds to entries in the 'build' table in the environment's manifest.toml.
         /// If not specified, all packages are built.
@@ -185,6 +221,8 @@ impl Build {
                 targets,
                 base_catalog_url_select,
                 system_override,
+                cache_dir,
+                sandbox,
             } => {
                 let env = self
                     .environment
@@ -197,6 +235,8 @@ impl Build {
                     targets,
                     base_catalog_url_select,
                     system_override.into_inner(),
+                    cache_dir,
+                    sandbox,
                 )
                 .await
             },
@@ -245,6 +285,8 @@ impl Build {
         packages: Vec<String>,
         nixpkgs_url_select: Option<BaseCatalogUrlSelect>,
         system_override: Option<String>,
+        cache_dir: Option<PathBuf>,
+        sandbox: SandboxSwitch,
     ) -> Result<()> {
         match &env {
             ConcreteEnvironment::Path(_) => (),
@@ -256,6 +298,8 @@ impl Build {
             },
         };
 
+        let cache_dir = cache_dir.map(|dir| prepare_cache_dir(&dir)).transpose()?;
+
         let base_dir = env.parent_path()?;
         let built_environments = env.build(&flox)?;
 
@@ -304,17 +348,30 @@ impl Build {
         let has_manifest_build = packages_to_build
             .iter()
             .any(|target| target.kind().is_manifest_build());
+        let sandbox_disabled = sandbox == SandboxSwitch::Off;
         subcommand_metric!(
             "build",
             "has_expression_build" = has_expression_build,
-            "has_manifest_build" = has_manifest_build
+            "has_manifest_build" = has_manifest_build,
+            "sandbox_disabled" = sandbox_disabled
         );
         if let Err(err) = EventsHub::global().record_build(has_expression_build, has_manifest_build)
         {
             debug!(error = %err, "Failed to record v2 event");
         }
 
-        let builder = FloxBuildMk::new(&flox, &base_dir, &expression_ref, &built_environments);
+        let mut builder = FloxBuildMk::new(&flox, &base_dir, &expression_ref, &built_environments);
+        if let Some(cache_dir) = &cache_dir {
+            builder = builder.with_cache_dir(cache_dir);
+        }
+        if sandbox_disabled {
+            message::warning(formatdoc! {"
+                Building with the sandbox disabled ('--sandbox off').
+                Builds can access the network and files outside of their closure,
+                so the results may not be reproducible.
+            "});
+            builder = builder.with_sandbox_disabled();
+        }
         let results = builder.build(
             &base_nixpkgs_url,
             &FLOX_INTERPRETER,
@@ -554,6 +611,31 @@ impl Build {
     }
 }
 
+/// Create the directory passed to `--cache-dir` if it doesn't exist
+/// and check that it is writable.
+///
+/// Returns the absolute path of the directory,
+/// since the build is run from the environment's directory.
+fn prepare_cache_dir(cache_dir: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(cache_dir)
+        .with_context(|| format!("could not create cache directory '{}'", cache_dir.display()))?;
+    let cache_dir = cache_dir.canonicalize().with_context(|| {
+        format!(
+            "could not canonicalize cache directory '{}'",
+            cache_dir.display()
+        )
+    })?;
+
+    if let Err(err) = tempfile::NamedTempFile::new_in(&cache_dir) {
+        bail!(
+            "cache directory '{}' is not writable: {err}",
+            cache_dir.display()
+        );
+    }
+
+    Ok(cache_dir)
+}
+
 /// Check that all packages are compatible with the selected Nixpkgs URL selection.
 pub(crate) fn disallow_base_url_select_for_manifest_builds<'p>(
     packages: impl IntoIterator<Item = &'p PackageTarget>,
@@ -828,6 +910,30 @@ mod test {
         assert_eq!(displayed, vec![symlink.to_string_lossy()]);
     }
 
+    #[test]
+    fn prepare_cache_dir_creates_missing_directory() {
+        let (flox, _temp_dir) = flox_instance();
+        let cache_dir = flox.temp_dir.join("ci-cache").join("build");
+
+        let prepared = prepare_cache_dir(&cache_dir).unwrap();
+        assert!(prepared.is_dir());
+        assert!(prepared.is_absolute());
+        assert_eq!(prepared, cache_dir.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn prepare_cache_dir_rejects_file() {
+        let (flox, _temp_dir) = flox_instance();
+        let file = flox.temp_dir.join("not-a-directory");
+        File::create(&file).unwrap();
+
+        let err = prepare_cache_dir(&file).unwrap_err();
+        assert!(
+            err.to_string().contains("could not create cache directory"),
+            "{err}"
+        );
+    }
+
     /// Test that conflicting build names are detected if builds are defined via the manifest and nix expressions.
     #[test]
     fn conflicting_build_names() {
@@ -51,9 +51,9 @@ impl CheckForUpgrades {
     pub async fn handle(self, mut flox: Flox) -> Result<()> {
         subcommand_metric!("check-upgrade");
 
-        // For catalog requests made by this command, set the QoS to background.
-        // Eventually we might want to prioritize these requests differently,
-        // since they are not as time-sensitive as the ones actively made by the user.
+        // For catalog requests made by this command, set the QoS to default.
+        // They are not as time-sensitive as the ones actively made by the user,
+        // which are tagged as user initiated.
         //
         // @billlevine brought up that if we start mutating the catalog client
         // or `Flox` object in multiple places, it would be preferable
@@ -63,10 +63,9 @@ impl CheckForUpgrades {
         // A possible future improvement was sketched out in the comment above [1].
         //
         // [1]: <https://github.com/flox/flox/pull/2658#discussion_r1932362747>
-        // Update the shared client's extra headers to set background QoS.
+        // Update the shared client's QoS, which is sent as a header.
         flox.floxhub_client.update_config(|config| {
-            let (qos_key, qos_value) = CatalogQoS::Background.as_header_pair();
-            config.extra_headers.insert(qos_key, qos_value);
+            config.qos = CatalogQoS::Default;
         })?;
 
         let mut environment = self.environment.into_concrete_environment(&flox, None)?;
@@ -122,7 +121,7 @@ fn check_for_package_upgrades(
 
     let upgrade_result = info_span!("check-upgrade", progress = "Performing dry upgrade")
         .entered()
-        .in_scope(|| environment.dry_upgrade(flox, &[]))?;
+        .in_scope(|| environment.dry_upgrade(flox, &[], &[]))?;
 
     let new_info = UpgradeInformation {
         last_checked: OffsetDateTime::now_utc(),
@@ -43,6 +43,7 @@ pub(crate) struct ContainerizeProxy {
     container_runtime: Runtime,
     labels: Vec<String>,
     mode: Option<ActivateMode>,
+    include_dev: bool,
 }
 
 impl ContainerizeProxy {
@@ -51,12 +52,14 @@ impl ContainerizeProxy {
         container_runtime: Runtime,
         labels: Vec<String>,
         mode: Option<ActivateMode>,
+        include_dev: bool,
     ) -> Self {
         Self {
             environment_path,
             container_runtime,
             labels,
             mode,
+            include_dev,
         }
     }
 
@@ -249,6 +252,9 @@ impl ContainerizeProxy {
         if let Some(mode) = &self.mode {
             command.args(["--mode", &mode.to_string()]);
         }
+        if self.include_dev {
+            command.arg("--include-dev");
+        }
     }
 }
 
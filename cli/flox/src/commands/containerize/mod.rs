@@ -1,6 +1,6 @@
 use std::convert::Infallible;
 use std::fmt::Display;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::str::FromStr;
@@ -15,6 +15,7 @@ use flox_manifest::lockfile::Lockfile;
 use flox_manifest::parsed::common::ContainerizeConfig;
 use flox_rust_sdk::flox::Flox;
 use flox_rust_sdk::models::environment::Environment;
+use flox_rust_sdk::providers::buildenv::{BuildEnvOutputs, BuiltStorePath};
 use flox_rust_sdk::providers::container_builder::{ContainerBuilder, MkContainerNix};
 use flox_rust_sdk::utils::{ReaderExt, WireTap};
 use indoc::indoc;
@@ -22,9 +23,10 @@ use macos_containerize_proxy::ContainerizeProxy;
 use tracing::{debug, info, instrument};
 
 use super::{EnvironmentSelect, environment_select};
-use crate::commands::SHELL_COMPLETION_FILE;
+use crate::commands::{SHELL_COMPLETION_FILE, Verbosity};
 use crate::environment_subcommand_metric;
 use crate::utils::events::env_detail_from_concrete;
+use crate::utils::init::init_logger;
 use crate::utils::message;
 use crate::utils::openers::first_in_path;
 
@@ -49,6 +51,11 @@ pub struct Containerize {
     #[bpaf(short, long, argument("file"), complete_shell(SHELL_COMPLETION_FILE))]
     file: Option<FileOrStdout>,
 
+    /// Write the image tarball to stdout, e.g. to pipe it into 'docker load'.
+    /// Suppresses all output other than errors.
+    #[bpaf(long)]
+    stream: bool,
+
     /// Tag to apply to the container, defaults to 'latest'
     #[bpaf(short, long, argument("tag"))]
     tag: Option<String>,
@@ -61,10 +68,31 @@ pub struct Containerize {
     /// Overrides the "options.activate.mode" setting in the manifest.
     #[bpaf(short, long)]
     mode: Option<ActivateMode>,
+
+    /// Include the development outputs of packages in the image,
+    /// even when containerizing in "run" mode.
+    /// This can make the image considerably larger.
+    #[bpaf(long("include-dev"))]
+    include_dev: bool,
 }
 impl Containerize {
     #[instrument(name = "containerize", skip_all)]
     pub async fn handle(self, mut flox: Flox) -> Result<()> {
+        if self.stream {
+            if self.file.is_some() {
+                bail!("'--stream' cannot be used with '--file'");
+            }
+            if io::stdout().is_terminal() {
+                bail!(indoc! {"
+                    Refusing to write the container image to a terminal.
+                    Pipe the output into another command, e.g. 'flox containerize --stream | docker load'."
+                });
+            }
+            // Anything other than the image on stdout would corrupt the stream,
+            // so only errors are logged from here on.
+            init_logger(Some(Verbosity::Quiet));
+        }
+
         let mut env = self
             .environment
             .detect_concrete_environment(&mut flox, "Containerize")
@@ -81,14 +109,12 @@ impl Containerize {
             runtime.validate_in_path()?
         }
         let runtime = self.runtime.or_else(Runtime::detect_from_path);
-        let output = match (&runtime, self.file) {
-            // Specified file.
-            (_, Some(dest)) => OutputTarget::File(dest),
-            // Or specified or detected runtime.
-            (Some(runtime), None) => OutputTarget::Runtime(runtime.clone()),
-            // Or default file.
-            (None, None) => OutputTarget::default_file(env.name().as_ref()),
-        };
+        let output = OutputTarget::select(
+            runtime.as_ref(),
+            self.file,
+            self.stream,
+            env.name().as_ref(),
+        );
 
         let output_tag: &str = match self.tag {
             Some(tag) => &tag.to_string(),
@@ -120,8 +146,11 @@ impl Containerize {
                 });
             // this method is only executed on linux
             #[cfg_attr(not(target_os = "linux"), allow(deprecated))]
-            let builder =
-                MkContainerNix::new(built_environment.for_mode(&mode), mode, container_config);
+            let builder = MkContainerNix::new(
+                image_store_path(built_environment, &mode, self.include_dev),
+                mode,
+                container_config,
+            );
 
             builder.create_container_source(&flox, env_name.as_ref(), output_tag)?
         } else {
@@ -133,7 +162,13 @@ impl Containerize {
                     Exporting a container on macOS requires Docker or Podman to be installed.
                 "#});
             };
-            let builder = ContainerizeProxy::new(env_path, proxy_runtime, self.labels, self.mode);
+            let builder = ContainerizeProxy::new(
+                env_path,
+                proxy_runtime,
+                self.labels,
+                self.mode,
+                self.include_dev,
+            );
             builder.create_container_source(&flox, env_name.as_ref(), output_tag)?
         };
 
@@ -141,11 +176,31 @@ impl Containerize {
         source.stream_container(&mut writer)?;
         writer.wait()?;
 
-        message::created(format!("'{env_name}:{output_tag}' written to {output}"));
+        if !self.stream {
+            message::created(format!("'{env_name}:{output_tag}' written to {output}"));
+        }
         Ok(())
     }
 }
 
+/// Select the built environment to pack into the image.
+///
+/// By default the image contains the environment for the activation `mode`.
+/// With `include_dev`, the development environment is packed instead,
+/// which includes the development outputs of all packages,
+/// while the container still activates in `mode`.
+fn image_store_path(
+    built_environment: BuildEnvOutputs,
+    mode: &ActivateMode,
+    include_dev: bool,
+) -> BuiltStorePath {
+    if include_dev {
+        built_environment.dev
+    } else {
+        built_environment.for_mode(mode)
+    }
+}
+
 fn should_extend_config(labels: &[String]) -> bool {
     labels.is_empty()
 }
@@ -191,6 +246,29 @@ enum OutputTarget {
 }
 
 impl OutputTarget {
+    /// Select where to write the image to.
+    ///
+    /// Streaming always writes to stdout,
+    /// even if a runtime is specified or detected,
+    /// in which case the runtime is only used to build the image on macOS.
+    fn select(
+        runtime: Option<&Runtime>,
+        file: Option<FileOrStdout>,
+        stream: bool,
+        env_name: impl AsRef<str>,
+    ) -> Self {
+        match (runtime, file) {
+            // Streamed to stdout.
+            _ if stream => OutputTarget::File(FileOrStdout::Stdout),
+            // Specified file.
+            (_, Some(dest)) => OutputTarget::File(dest),
+            // Or specified or detected runtime.
+            (Some(runtime), None) => OutputTarget::Runtime(runtime.clone()),
+            // Or default file.
+            (None, None) => OutputTarget::default_file(env_name),
+        }
+    }
+
     fn default_file(env_name: impl AsRef<str>) -> Self {
         OutputTarget::File(FileOrStdout::File(PathBuf::from(format!(
             "{}-container.tar",
@@ -414,6 +492,38 @@ impl FromStr for Runtime {
 mod tests {
     use super::*;
 
+    #[test]
+    fn include_dev_selects_dev_environment() {
+        let built_environment: BuildEnvOutputs = serde_json::from_value(serde_json::json!({
+            "dev": "/nix/store/dev",
+            "run": "/nix/store/run",
+        }))
+        .unwrap();
+
+        let path = |mode, include_dev| {
+            image_store_path(built_environment.clone(), &mode, include_dev)
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        assert_eq!(path(ActivateMode::Run, false), "/nix/store/run");
+        assert_eq!(path(ActivateMode::Dev, false), "/nix/store/dev");
+        assert_eq!(path(ActivateMode::Run, true), "/nix/store/dev");
+        assert_eq!(path(ActivateMode::Dev, true), "/nix/store/dev");
+    }
+
+    #[test]
+    fn stream_writes_to_stdout() {
+        let target = |runtime: Option<Runtime>, stream| {
+            OutputTarget::select(runtime.as_ref(), None, stream, "myenv").to_string()
+        };
+
+        assert_eq!(target(None, false), "file 'myenv-container.tar'");
+        assert_eq!(target(Some(Runtime::Docker), false), "Docker runtime");
+        assert_eq!(target(None, true), "stdout");
+        assert_eq!(target(Some(Runtime::Docker), true), "stdout");
+    }
+
     #[test]
     fn runtime_parse() {
         "docker".parse::<Runtime>().unwrap();
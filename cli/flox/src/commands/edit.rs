@@ -1,4 +1,6 @@
+use std::collections::BTreeMap;
 use std::env;
+use std::fmt::{self, Display};
 use std::fs::File;
 use std::io::stdin;
 use std::path::{Path, PathBuf};
@@ -6,10 +8,23 @@ use std::process::Command;
 
 use anyhow::{Context, Result, bail};
 use bpaf::Bpaf;
-use flox_core::data::environment_ref::EnvironmentName;
+use flox_core::data::environment_ref::{EnvironmentName, RemoteEnvironmentRef};
 use flox_events::EventsHub;
-use flox_manifest::interfaces::{AsWritableManifest, WriteManifest};
+use flox_manifest::interfaces::{
+    AsLatestSchema,
+    AsWritableManifest,
+    ContentsMatch,
+    PackageLookup,
+    WriteManifest,
+};
+use flox_manifest::lockfile::{LockedInclude, Lockfile};
+use flox_manifest::parsed::common::IncludeDescriptor;
+use flox_manifest::parsed::latest::PackageDescriptorCatalog;
+use flox_manifest::raw::{add_include, remove_include, set_package_version};
+use flox_manifest::util::unified_diff;
+use flox_manifest::{Manifest, Migrated};
 use flox_rust_sdk::flox::Flox;
+use flox_rust_sdk::models::environment::fetcher::IncludeFetcher;
 use flox_rust_sdk::models::environment::generations::{
     GenerationsEnvironment,
     GenerationsExt,
@@ -24,7 +39,9 @@ use flox_rust_sdk::models::environment::{
     EnvironmentError,
 };
 use flox_rust_sdk::providers::buildenv::BuildEnvError;
+use flox_rust_sdk::providers::lock_manifest::LockManifest;
 use flox_rust_sdk::providers::services::process_compose::ServiceError;
+use floxhub_client::CatalogClientTrait;
 use indoc::formatdoc;
 use itertools::Itertools;
 use tracing::{debug, instrument};
@@ -36,12 +53,17 @@ use super::{
     activated_environments,
     environment_select,
 };
-use crate::commands::{EnvironmentSelectError, SHELL_COMPLETION_FILE, ensure_auth};
+use crate::commands::{
+    EnvironmentSelectError,
+    SHELL_COMPLETION_DIR,
+    SHELL_COMPLETION_FILE,
+    ensure_auth,
+};
 use crate::utils::dialog::{Confirm, Dialog};
 use crate::utils::errors::format_error;
 use crate::utils::events::env_detail_from_concrete;
 use crate::utils::message;
-use crate::{environment_subcommand_metric, subcommand_metric};
+use crate::{Exit, environment_subcommand_metric, subcommand_metric};
 
 // Edit declarative environment configuration
 #[derive(Bpaf, Clone)]
@@ -49,15 +71,73 @@ pub struct Edit {
     #[bpaf(external(environment_select), fallback(Default::default()))]
     environment: EnvironmentSelect,
 
-    #[bpaf(external(edit_action), fallback(EditAction::EditManifest{file: None}))]
+    #[bpaf(external(edit_action), fallback(EditAction::EditManifest{file: None, diff: false, reuse_renamed_includes: false}))]
     action: EditAction,
 }
 #[derive(Bpaf, Clone)]
 pub enum EditAction {
     EditManifest {
         /// Replace environment manifest with that in <file>
-        #[bpaf(long, short, argument("file"), complete_shell(SHELL_COMPLETION_FILE))]
+        #[bpaf(
+            long("file"),
+            long("manifest"),
+            short('f'),
+            argument("file"),
+            complete_shell(SHELL_COMPLETION_FILE)
+        )]
         file: Option<PathBuf>,
+
+        /// Show the changes to the manifest before applying them
+        /// and ask for confirmation when editing interactively
+        #[bpaf(long)]
+        diff: bool,
+
+        /// Reuse the locked include for an included environment whose
+        /// name is the only change, instead of fetching it again
+        #[bpaf(long)]
+        reuse_renamed_includes: bool,
+    },
+
+    Format {
+        /// Rewrite the manifest in a canonical layout, preserving comments
+        #[bpaf(long("format"))]
+        _format: (),
+
+        /// Don't rewrite the manifest,
+        /// exit with a non-zero status if it isn't formatted
+        #[bpaf(long)]
+        check: bool,
+    },
+
+    Bump {
+        /// Change the version constraint of the catalog package <iid>
+        /// to the latest version in the catalog and lock the environment
+        #[bpaf(long("bump"), argument("iid"))]
+        install_id: String,
+    },
+
+    AddInclude {
+        /// Include the environment in <dir>, relative to the directory
+        /// containing '.flox', and lock the environment
+        #[bpaf(
+            long("add-include"),
+            argument("dir"),
+            complete_shell(SHELL_COMPLETION_DIR)
+        )]
+        dir: PathBuf,
+    },
+
+    AddIncludeRemote {
+        /// Include the FloxHub environment <owner/name> and lock the environment
+        #[bpaf(long("add-include-remote"), argument("owner/name"))]
+        remote: RemoteEnvironmentRef,
+    },
+
+    RemoveInclude {
+        /// Remove the included environment <name> and lock the environment.
+        /// <name> may also be the directory or owner/name of the environment.
+        #[bpaf(long("remove-include"), argument("name"))]
+        name: String,
     },
 
     Rename {
@@ -108,14 +188,69 @@ impl Edit {
         }
 
         match self.action {
-            EditAction::EditManifest { file } => {
+            EditAction::EditManifest {
+                file,
+                diff,
+                reuse_renamed_includes,
+            } => {
                 // TODO: differentiate between interactive edits and replacement
                 let span = tracing::info_span!("edit_file");
                 let _guard = span.enter();
 
                 let contents = Self::provided_manifest_contents(file)?;
 
-                Self::edit_manifest(&flox, &mut detected_environment, contents).await?
+                Self::edit_manifest(
+                    &flox,
+                    &mut detected_environment,
+                    contents,
+                    diff,
+                    reuse_renamed_includes,
+                )
+                .await?
+            },
+            EditAction::Format { check, .. } => {
+                let span = tracing::info_span!("format");
+                let _guard = span.enter();
+
+                Self::format_manifest(&flox, &mut detected_environment, check)?
+            },
+            EditAction::Bump { install_id } => {
+                let span = tracing::info_span!(
+                    "bump",
+                    progress = format!("Bumping the version of '{install_id}'")
+                );
+                let _guard = span.enter();
+
+                Self::bump_package(&flox, &mut detected_environment, &install_id).await?
+            },
+            EditAction::AddInclude { dir } => {
+                let span = tracing::info_span!("add_include");
+                let _guard = span.enter();
+
+                Self::add_include(&flox, &mut detected_environment, IncludeDescriptor::Local {
+                    dir,
+                    name: None,
+                })?
+            },
+            EditAction::AddIncludeRemote { remote } => {
+                let span = tracing::info_span!("add_include");
+                let _guard = span.enter();
+
+                Self::add_include(
+                    &flox,
+                    &mut detected_environment,
+                    IncludeDescriptor::Remote {
+                        remote,
+                        name: None,
+                        generation: None,
+                    },
+                )?
+            },
+            EditAction::RemoveInclude { name } => {
+                let span = tracing::info_span!("remove_include");
+                let _guard = span.enter();
+
+                Self::remove_include(&flox, &mut detected_environment, &name)?
             },
             EditAction::Rename { name } => {
                 let span = tracing::info_span!("rename");
@@ -207,6 +342,8 @@ impl Edit {
         flox: &Flox,
         environment: &mut ConcreteEnvironment,
         contents: Option<String>,
+        diff: bool,
+        locked_includes_only: bool,
     ) -> Result<()> {
         if let ConcreteEnvironment::Managed(environment) = environment
             && environment.has_local_changes(flox)?
@@ -216,14 +353,26 @@ impl Edit {
         };
 
         let active_environment = UninitializedEnvironment::from_concrete_environment(environment);
+        // Ineffective edits are still written to the manifest,
+        // so compare against the manifest from before the edit.
+        let old_manifest = environment.manifest_without_migrating(flox)?;
 
-        let result = match contents {
+        let (result, new_manifest) = match contents {
             // If provided with the contents of a manifest file, either via a path to a file or via
             // contents piped to stdin, use those contents to try building the environment.
-            Some(new_manifest) => environment.edit(flox, new_manifest)?,
+            Some(new_manifest) => {
+                if diff {
+                    Self::print_diff(&old_manifest.as_writable().to_string(), &new_manifest);
+                }
+                (
+                    // Edits that only change comments or formatting don't need a rebuild
+                    environment.edit(flox, new_manifest.clone(), locked_includes_only, true)?,
+                    new_manifest,
+                )
+            },
             // If not provided with new manifest contents, let the user edit the file directly
             // via $EDITOR or $VISUAL (as long as `flox edit` was invoked interactively).
-            None => Self::interactive_edit(flox, environment).await?,
+            None => Self::interactive_edit(flox, environment, diff, locked_includes_only).await?,
         };
 
         // outside the match to avoid rustfmt falling on its face
@@ -235,7 +384,14 @@ impl Edit {
 
         match result {
             EditResult::Unchanged => {
-                message::warning("No changes made to environment.");
+                if old_manifest.contents_match(&new_manifest) {
+                    message::warning("No changes made to environment.");
+                } else {
+                    message::warning(indoc::indoc! {"
+                        No effective changes made to environment.
+                        Edits that only change whitespace, comments, or formatting are saved to the manifest without rebuilding the environment."
+                    });
+                }
             },
             EditResult::Changed {
                 ref old_lockfile,
@@ -250,6 +406,14 @@ impl Edit {
                     message::updated("Environment successfully updated.")
                 }
 
+                if let Some(summary) = summarize_package_changes(
+                    old_lockfile.as_ref().as_ref(),
+                    new_lockfile,
+                    &flox.system,
+                ) {
+                    message::info(summary);
+                }
+
                 warn_manifest_changes_for_services(flox, environment);
 
                 if new_lockfile.compose.is_some() {
@@ -282,11 +446,203 @@ impl Edit {
         Ok(())
     }
 
+    /// Set the version constraint of the catalog package `install_id`
+    /// to the latest version the catalog offers for the current system,
+    /// and lock the environment with the new constraint.
+    async fn bump_package(
+        flox: &Flox,
+        environment: &mut ConcreteEnvironment,
+        install_id: &str,
+    ) -> Result<()> {
+        let manifest = environment.manifest(flox)?;
+        let descriptor = bumpable_descriptor(&manifest, install_id)?;
+
+        let versions = flox
+            .floxhub_client
+            .package_versions(&descriptor.pkg_path)
+            .await?;
+        // The catalog lists the newest version first
+        let Some(latest) = versions
+            .results
+            .iter()
+            .find(|build| build.system.to_string() == flox.system)
+        else {
+            bail!(
+                "No versions of '{}' are available for '{}'.",
+                descriptor.pkg_path,
+                flox.system
+            );
+        };
+
+        let bump = ConstraintBump {
+            install_id: install_id.to_string(),
+            old_constraint: descriptor.version,
+            new_constraint: latest.version.clone(),
+        };
+        if bump.is_unchanged() {
+            message::plain(bump.to_string());
+            return Ok(());
+        }
+
+        let contents = environment
+            .manifest_without_migrating(flox)?
+            .as_writable()
+            .to_string();
+        let new_manifest = set_package_version(&contents, install_id, &latest.version)?;
+        environment.edit(flox, new_manifest.to_string(), false, false)?;
+
+        message::updated(bump.to_string());
+        Ok(())
+    }
+
+    /// Append an environment to `include.environments` and lock the environment
+    ///
+    /// Environments that are already included, or that would have the same
+    /// name as an already included environment, are rejected
+    /// before the manifest is touched.
+    fn add_include(
+        flox: &Flox,
+        environment: &mut ConcreteEnvironment,
+        descriptor: IncludeDescriptor,
+    ) -> Result<()> {
+        let include_fetcher = include_fetcher(environment)?;
+
+        let manifest = environment.manifest(flox)?;
+        if let Some(existing) = manifest
+            .as_latest_schema()
+            .include
+            .environments
+            .iter()
+            .find(|existing| same_include_source(&include_fetcher, existing, &descriptor))
+        {
+            bail!("'{descriptor}' is already included as '{existing}'.");
+        }
+
+        let lockfile: Lockfile = environment.lockfile(flox)?.into();
+        let mut locked_includes = lockfile
+            .compose
+            .map(|compose| compose.include)
+            .unwrap_or_default();
+        let locked_include = include_fetcher.fetch(flox, &descriptor)?;
+        let name = locked_include.name.clone();
+        locked_includes.push(locked_include);
+        LockManifest::check_locked_names_unique(&locked_includes)?;
+
+        let contents = environment
+            .manifest_without_migrating(flox)?
+            .as_writable()
+            .to_string();
+        let new_manifest = add_include(&contents, &descriptor)?;
+        environment.edit(flox, new_manifest.to_string(), false, false)?;
+
+        message::updated(format!("Included environment '{name}'."));
+        Ok(())
+    }
+
+    /// Remove an environment from `include.environments` and lock the environment
+    ///
+    /// The included environment is looked up by its name,
+    /// or by the directory or remote reference it is included from.
+    /// Prints the changes to the merged manifest,
+    /// i.e. the packages, variables, etc. that the environment contributed.
+    fn remove_include(
+        flox: &Flox,
+        environment: &mut ConcreteEnvironment,
+        name: &str,
+    ) -> Result<()> {
+        let include_fetcher = include_fetcher(environment)?;
+        let lockfile: Lockfile = environment.lockfile(flox)?.into();
+        let locked_includes = lockfile
+            .compose
+            .map(|compose| compose.include)
+            .unwrap_or_default();
+
+        let manifest = environment.manifest(flox)?;
+        let Some((index, descriptor)) = find_include(
+            &include_fetcher,
+            &manifest.as_latest_schema().include.environments,
+            &locked_includes,
+            name,
+        ) else {
+            bail!("No environment named '{name}' is included.");
+        };
+        let descriptor = descriptor.clone();
+
+        let contents = environment
+            .manifest_without_migrating(flox)?
+            .as_writable()
+            .to_string();
+        let new_manifest = remove_include(&contents, index)?;
+        let result = environment.edit(flox, new_manifest.to_string(), false, false)?;
+
+        message::updated(format!("Removed included environment '{descriptor}'."));
+        if let EditResult::Changed {
+            old_lockfile,
+            new_lockfile,
+            ..
+        } = result
+        {
+            let old_merged = match old_lockfile.as_ref() {
+                Some(old_lockfile) => old_lockfile.manifest.to_toml_string()?,
+                None => String::new(),
+            };
+            let new_merged = new_lockfile.manifest.to_toml_string()?;
+            let diff = unified_diff(
+                &old_merged,
+                &new_merged,
+                "merged manifest (current)",
+                "merged manifest (edited)",
+            );
+            if diff.is_empty() {
+                message::info("The merged manifest is unchanged.");
+            } else {
+                message::info("Changes to the merged manifest:");
+                message::plain(diff.trim_end());
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrite the manifest in a canonical layout
+    ///
+    /// With `check`, print the changes formatting would make instead
+    /// and exit with a non-zero status if there are any.
+    fn format_manifest(
+        flox: &Flox,
+        environment: &mut ConcreteEnvironment,
+        check: bool,
+    ) -> Result<()> {
+        let manifest = environment.manifest_without_migrating(flox)?;
+        let current = manifest.as_writable().to_string();
+        let formatted = manifest.formatted()?;
+
+        if formatted == current {
+            message::plain("Manifest is already formatted.");
+            return Ok(());
+        }
+
+        if check {
+            Self::print_diff(&current, &formatted);
+            message::error("Manifest is not formatted, run 'flox edit --format' to format it.");
+            return Err(Exit(1.into()).into());
+        }
+
+        environment.edit(flox, formatted, false, false)?;
+        message::updated("Manifest formatted.");
+        Ok(())
+    }
+
     /// Interactively edit the manifest file
+    ///
+    /// With `diff`, the changes are shown after each edit
+    /// and only applied once confirmed.
+    /// Returns the result of the edit and the edited manifest.
     async fn interactive_edit(
         flox: &Flox,
         environment: &mut dyn Environment,
-    ) -> Result<EditResult> {
+        diff: bool,
+        locked_includes_only: bool,
+    ) -> Result<(EditResult, String)> {
         if !Dialog::can_prompt() {
             bail!("Can't edit interactively in non-interactive context")
         }
@@ -301,13 +657,11 @@ impl Edit {
             .prefix("manifest.")
             .suffix(".toml")
             .tempfile_in(&flox.temp_dir)?;
-        std::fs::write(
-            &tmp_manifest,
-            environment
-                .manifest_without_migrating(flox)?
-                .as_writable()
-                .to_string(),
-        )?;
+        let old_manifest = environment
+            .manifest_without_migrating(flox)?
+            .as_writable()
+            .to_string();
+        std::fs::write(&tmp_manifest, &old_manifest)?;
 
         let should_continue_dialog = Dialog {
             message: "Continue editing?",
@@ -317,13 +671,33 @@ impl Edit {
             },
         };
 
+        let apply_changes_dialog = Dialog {
+            message: "Apply these changes?",
+            help_message: Default::default(),
+            typed: Confirm {
+                default: Some(true),
+            },
+        };
+
         // Let the user keep editing the file until the build succeeds or the user
         // decides to stop.
         loop {
             let new_manifest = Edit::edited_manifest_contents(&tmp_manifest, &editor, &args)?;
-            let result = environment.edit(flox, new_manifest.clone());
+
+            if diff
+                && Self::print_diff(&old_manifest, &new_manifest)
+                && !apply_changes_dialog.clone().prompt().await?
+            {
+                if !should_continue_dialog.clone().prompt().await? {
+                    bail!("Environment editing cancelled");
+                }
+                continue;
+            }
+
+            // Edits that only change comments or formatting don't need a rebuild
+            let result = environment.edit(flox, new_manifest.clone(), locked_includes_only, true);
             match Self::make_interactively_recoverable(result)? {
-                Ok(result) => return Ok(result),
+                Ok(result) => return Ok((result, new_manifest)),
 
                 // for recoverable errors, prompt the user to continue editing
                 Err(e) => {
@@ -340,6 +714,19 @@ impl Edit {
         }
     }
 
+    /// Print the changes between the current and the edited manifest.
+    ///
+    /// Returns whether there are any changes.
+    fn print_diff(old_manifest: &str, new_manifest: &str) -> bool {
+        let diff = Manifest::diff_text(old_manifest, new_manifest);
+        if diff.is_empty() {
+            message::info("No changes to the manifest.");
+            return false;
+        }
+        message::plain(diff.trim_end());
+        true
+    }
+
     /// Returns `Ok` if the edit result is successful or recoverable, `Err` otherwise
     fn make_interactively_recoverable(
         result: Result<EditResult, EnvironmentError>,
@@ -424,7 +811,10 @@ impl Edit {
             let mut file: Box<dyn std::io::Read + Send> = if file == Path::new("-") {
                 Box::new(stdin())
             } else {
-                Box::new(File::open(file)?)
+                Box::new(
+                    File::open(file)
+                        .with_context(|| format!("failed to open '{}'", file.display()))?,
+                )
             };
 
             let mut contents = String::new();
@@ -455,18 +845,205 @@ impl Edit {
     }
 }
 
+/// Summarize the packages an edit installed, uninstalled,
+/// or changed the version of on `system`,
+/// e.g. `Installed: hello; Changed: curl (8.0 -> 8.1)`.
+///
+/// Returns [None] if there was no previous lockfile to compare against
+/// or the packages are unchanged.
+fn summarize_package_changes(
+    old_lockfile: Option<&Lockfile>,
+    new_lockfile: &Lockfile,
+    system: &str,
+) -> Option<String> {
+    fn package_versions<'a>(
+        lockfile: &'a Lockfile,
+        system: &str,
+    ) -> BTreeMap<&'a str, Option<&'a str>> {
+        lockfile
+            .packages
+            .iter()
+            .filter(|pkg| pkg.system() == system)
+            .map(|pkg| (pkg.install_id(), pkg.version()))
+            .collect()
+    }
+
+    let old = package_versions(old_lockfile?, system);
+    let new = package_versions(new_lockfile, system);
+
+    let installed = new.keys().filter(|id| !old.contains_key(*id)).join(", ");
+    let uninstalled = old.keys().filter(|id| !new.contains_key(*id)).join(", ");
+    let changed = new
+        .iter()
+        .filter_map(|(id, version)| {
+            let old_version = old.get(id)?;
+            (old_version != version).then(|| {
+                format!(
+                    "{id} ({} -> {})",
+                    old_version.unwrap_or("unknown"),
+                    version.unwrap_or("unknown")
+                )
+            })
+        })
+        .join(", ");
+
+    let summary = [
+        ("Installed", installed),
+        ("Uninstalled", uninstalled),
+        ("Changed", changed),
+    ]
+    .into_iter()
+    .filter(|(_, packages)| !packages.is_empty())
+    .map(|(label, packages)| format!("{label}: {packages}"))
+    .join("; ");
+    (!summary.is_empty()).then_some(summary)
+}
+
+/// The catalog descriptor of the package `install_id`,
+/// whose version constraint `flox edit --bump` can update
+fn bumpable_descriptor(
+    manifest: &Manifest<Migrated>,
+    install_id: &str,
+) -> Result<PackageDescriptorCatalog> {
+    let Some(descriptor) = manifest.pkg_descriptor_with_id(install_id) else {
+        bail!("No package with install ID '{install_id}' in the manifest.");
+    };
+    let Some(descriptor) = descriptor.unwrap_catalog_descriptor() else {
+        bail!("'{install_id}' is not a catalog package. Only catalog packages can be bumped.");
+    };
+    Ok(descriptor)
+}
+
+/// The version constraint of a package before and after `flox edit --bump`
+#[derive(Debug, Clone, PartialEq)]
+struct ConstraintBump {
+    install_id: String,
+    old_constraint: Option<String>,
+    new_constraint: String,
+}
+
+impl ConstraintBump {
+    /// Whether the package already requests the new constraint
+    fn is_unchanged(&self) -> bool {
+        self.old_constraint.as_ref() == Some(&self.new_constraint)
+    }
+}
+
+impl Display for ConstraintBump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ConstraintBump {
+            install_id,
+            old_constraint,
+            new_constraint,
+        } = self;
+        match old_constraint {
+            Some(old) if old == new_constraint => write!(
+                f,
+                "'{install_id}' already requests the latest version '{new_constraint}'."
+            ),
+            Some(old) => write!(
+                f,
+                "Changed the version constraint of '{install_id}' from '{old}' to '{new_constraint}'."
+            ),
+            None => write!(
+                f,
+                "Changed the version constraint of '{install_id}' from no constraint to '{new_constraint}'."
+            ),
+        }
+    }
+}
+
+/// The fetcher that locking uses for the includes of `environment`
+fn include_fetcher(environment: &ConcreteEnvironment) -> Result<IncludeFetcher> {
+    // Remote environments can't include local environments,
+    // which fetching reports if there is no base directory.
+    Ok(IncludeFetcher {
+        base_directory: match environment {
+            ConcreteEnvironment::Remote(_) => None,
+            _ => Some(environment.parent_path()?),
+        },
+    })
+}
+
+/// Find the include that `name` refers to in `includes`, with its index.
+///
+/// `name` can be the name the environment is included as,
+/// the directory it is included from, or its remote reference.
+fn find_include<'a>(
+    include_fetcher: &IncludeFetcher,
+    includes: &'a [IncludeDescriptor],
+    locked_includes: &[LockedInclude],
+    name: &str,
+) -> Option<(usize, &'a IncludeDescriptor)> {
+    let by_path = IncludeDescriptor::Local {
+        dir: PathBuf::from(name),
+        name: None,
+    };
+    includes.iter().find_position(|descriptor| {
+        let locked_name = locked_includes
+            .iter()
+            .find(|locked| locked.descriptor == **descriptor)
+            .map(|locked| locked.name.as_str());
+        let remote_matches = matches!(
+            descriptor,
+            IncludeDescriptor::Remote { remote, .. } if remote.to_string() == name
+        );
+        locked_name == Some(name)
+            || descriptor.to_string() == name
+            || remote_matches
+            || same_include_source(include_fetcher, descriptor, &by_path)
+    })
+}
+
+/// Whether two include descriptors refer to the same environment,
+/// regardless of the name they are included as.
+///
+/// Local directories are compared after resolving them
+/// against the directory of the including environment.
+fn same_include_source(
+    include_fetcher: &IncludeFetcher,
+    a: &IncludeDescriptor,
+    b: &IncludeDescriptor,
+) -> bool {
+    match (a, b) {
+        (IncludeDescriptor::Local { dir: a, .. }, IncludeDescriptor::Local { dir: b, .. }) => {
+            let resolve = |dir: &PathBuf| {
+                let dir = include_fetcher
+                    .expand_include_dir(dir)
+                    .unwrap_or_else(|_| dir.clone());
+                dir.canonicalize().unwrap_or(dir)
+            };
+            resolve(a) == resolve(b)
+        },
+        (
+            IncludeDescriptor::Remote { remote: a, .. },
+            IncludeDescriptor::Remote { remote: b, .. },
+        ) => a == b,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
 
+    use flox_manifest::interfaces::AsTypedOnlyManifest;
+    use flox_manifest::lockfile::test_helpers::fake_catalog_package_lock;
+    use flox_manifest::parsed::latest::ManifestLatest;
+    use flox_manifest::test_helpers::with_latest_schema;
     use flox_rust_sdk::flox::test_helpers::{flox_instance, flox_instance_with_optional_floxhub};
     use flox_rust_sdk::models::environment::managed_environment::ManagedEnvironmentError;
     use flox_rust_sdk::models::environment::managed_environment::test_helpers::mock_managed_environment_unlocked;
+    use flox_rust_sdk::models::environment::path_environment::PathEnvironment;
     use flox_rust_sdk::models::environment::path_environment::test_helpers::{
         new_path_environment,
         new_path_environment_in,
     };
-    use flox_rust_sdk::providers::lock_manifest::{ResolutionFailures, ResolveError};
+    use flox_rust_sdk::providers::lock_manifest::{
+        RecoverableMergeError,
+        ResolutionFailures,
+        ResolveError,
+    };
     use flox_rust_sdk::utils::logging::test_helpers::test_subscriber_message_only;
     use indoc::{formatdoc, indoc};
     use pretty_assertions::assert_eq;
@@ -476,6 +1053,298 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn bump_rewrites_constraint_of_catalog_packages_only() {
+        let contents = with_latest_schema(indoc! {r#"
+            [install]
+            hello.pkg-path = "hello"
+            hello.version = "2.10"
+            curl.pkg-path = "curl"
+            flake.flake = "github:flox/flox"
+        "#});
+        let manifest = Manifest::parse_and_migrate(&contents, None).unwrap();
+
+        let err = bumpable_descriptor(&manifest, "flake").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'flake' is not a catalog package. Only catalog packages can be bumped."
+        );
+        let err = bumpable_descriptor(&manifest, "missing").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No package with install ID 'missing' in the manifest."
+        );
+
+        let descriptor = bumpable_descriptor(&manifest, "hello").unwrap();
+        let bump = ConstraintBump {
+            install_id: "hello".to_string(),
+            old_constraint: descriptor.version,
+            new_constraint: "2.12".to_string(),
+        };
+        assert!(!bump.is_unchanged());
+        assert_eq!(
+            bump.to_string(),
+            "Changed the version constraint of 'hello' from '2.10' to '2.12'."
+        );
+
+        let bumped = set_package_version(&contents, "hello", &bump.new_constraint).unwrap();
+        let bumped = Manifest::parse_and_migrate(bumped.to_string(), None).unwrap();
+        assert_eq!(
+            bumpable_descriptor(&bumped, "hello").unwrap().version,
+            Some("2.12".to_string())
+        );
+
+        let descriptor = bumpable_descriptor(&manifest, "curl").unwrap();
+        let bump = ConstraintBump {
+            install_id: "curl".to_string(),
+            old_constraint: descriptor.version,
+            new_constraint: "8.1".to_string(),
+        };
+        assert_eq!(
+            bump.to_string(),
+            "Changed the version constraint of 'curl' from no constraint to '8.1'."
+        );
+
+        let unchanged = ConstraintBump {
+            install_id: "hello".to_string(),
+            old_constraint: Some("2.12".to_string()),
+            new_constraint: "2.12".to_string(),
+        };
+        assert!(unchanged.is_unchanged());
+        assert_eq!(
+            unchanged.to_string(),
+            "'hello' already requests the latest version '2.12'."
+        );
+    }
+
+    #[test]
+    fn same_include_source_ignores_names_and_relative_paths() {
+        let tempdir = tempfile::tempdir().unwrap();
+        fs::create_dir(tempdir.path().join("tools")).unwrap();
+        let include_fetcher = IncludeFetcher {
+            base_directory: Some(tempdir.path().to_path_buf()),
+        };
+
+        let relative = IncludeDescriptor::Local {
+            dir: PathBuf::from("tools"),
+            name: None,
+        };
+        let absolute = IncludeDescriptor::Local {
+            dir: tempdir.path().join("tools"),
+            name: Some("tools".to_string()),
+        };
+        let remote = |remote: &str, name: Option<&str>| IncludeDescriptor::Remote {
+            remote: remote.parse().unwrap(),
+            name: name.map(String::from),
+            generation: None,
+        };
+
+        assert!(same_include_source(&include_fetcher, &relative, &absolute));
+        assert!(same_include_source(
+            &include_fetcher,
+            &remote("owner/db", None),
+            &remote("owner/db", Some("db"))
+        ));
+        assert!(!same_include_source(
+            &include_fetcher,
+            &remote("owner/db", None),
+            &remote("owner/cache", None)
+        ));
+        assert!(!same_include_source(
+            &include_fetcher,
+            &relative,
+            &remote("owner/tools", None)
+        ));
+    }
+
+    #[test]
+    fn find_include_by_name_dir_or_remote() {
+        let tempdir = tempfile::tempdir().unwrap();
+        fs::create_dir(tempdir.path().join("tools")).unwrap();
+        let include_fetcher = IncludeFetcher {
+            base_directory: Some(tempdir.path().to_path_buf()),
+        };
+
+        let tools = IncludeDescriptor::Local {
+            dir: PathBuf::from("tools"),
+            name: None,
+        };
+        let database = IncludeDescriptor::Local {
+            dir: PathBuf::from("../db"),
+            name: Some("database".to_string()),
+        };
+        let cache = IncludeDescriptor::Remote {
+            remote: "owner/cache".parse().unwrap(),
+            name: Some("redis".to_string()),
+            generation: None,
+        };
+        let includes = [tools.clone(), database.clone(), cache.clone()];
+        // Without a name in the manifest,
+        // the name is the one of the environment when it was locked
+        let locked_includes = [LockedInclude {
+            manifest: ManifestLatest::default().as_typed_only(),
+            name: "toolbox".to_string(),
+            descriptor: tools.clone(),
+            generation: None,
+        }];
+        let find = |name| find_include(&include_fetcher, &includes, &locked_includes, name);
+
+        assert_eq!(find("toolbox"), Some((0, &tools)));
+        assert_eq!(find("database"), Some((1, &database)));
+        assert_eq!(find("redis"), Some((2, &cache)));
+        // directories, however they are spelled
+        assert_eq!(find("tools"), Some((0, &tools)));
+        assert_eq!(
+            find(tempdir.path().join("tools").to_str().unwrap()),
+            Some((0, &tools))
+        );
+        // remote references, regardless of the name they are included as
+        assert_eq!(find("owner/cache"), Some((2, &cache)));
+
+        assert_eq!(find("owner/db"), None);
+        assert_eq!(find("missing"), None);
+    }
+
+    /// Create a locked, empty path environment in `path`,
+    /// named after the last component of `path`
+    fn new_includable_environment(flox: &Flox, path: &Path) -> PathEnvironment {
+        fs::create_dir_all(path).unwrap();
+        let mut environment = new_path_environment_in(flox, &with_latest_schema(""), path);
+        environment.lockfile(flox).unwrap();
+        environment
+    }
+
+    /// The include descriptors in the manifest of `environment`
+    fn included(flox: &Flox, environment: &mut ConcreteEnvironment) -> Vec<IncludeDescriptor> {
+        environment
+            .manifest(flox)
+            .unwrap()
+            .as_latest_schema()
+            .include
+            .environments
+            .clone()
+    }
+
+    #[test]
+    fn add_include_rejects_duplicates_and_name_collisions() {
+        let (flox, tempdir) = flox_instance();
+        let tools_path = tempdir.path().join("tools");
+        new_includable_environment(&flox, &tools_path);
+        // a different environment with the same name
+        new_includable_environment(&flox, &tempdir.path().join("other").join("tools"));
+        let mut environment = ConcreteEnvironment::Path(new_includable_environment(
+            &flox,
+            &tempdir.path().join("composer"),
+        ));
+
+        let tools = IncludeDescriptor::Local {
+            dir: PathBuf::from("../tools"),
+            name: None,
+        };
+        Edit::add_include(&flox, &mut environment, tools.clone()).unwrap();
+        assert_eq!(included(&flox, &mut environment), vec![tools.clone()]);
+
+        // The same directory is rejected, however it is spelled
+        let err = Edit::add_include(&flox, &mut environment, IncludeDescriptor::Local {
+            dir: tools_path.clone(),
+            name: Some("renamed".to_string()),
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "'renamed' is already included as '../tools'."
+        );
+
+        // Another environment that would also be included as 'tools'
+        let err = Edit::add_include(&flox, &mut environment, IncludeDescriptor::Local {
+            dir: PathBuf::from("../other/tools"),
+            name: None,
+        })
+        .unwrap_err();
+        let Some(RecoverableMergeError::Catchall(message)) = err.downcast_ref() else {
+            panic!("expected a name collision, got: {err:#}");
+        };
+        assert!(message.contains("have the name 'tools'"), "{message}");
+
+        assert_eq!(included(&flox, &mut environment), vec![tools]);
+    }
+
+    /// `--diff` prints the changes and applies them when not interactive
+    #[tokio::test]
+    async fn edit_with_file_and_diff_prints_diff() {
+        let (flox, tempdir) = flox_instance();
+        let (subscriber, writer) = test_subscriber_message_only();
+
+        let environment = new_path_environment(&flox, indoc! {r#"
+            version = 1
+        "#});
+        let new_manifest_path = tempdir.path().join("temporary-manifest.toml");
+        fs::write(&new_manifest_path, indoc! {r#"
+            version = 1
+
+            [vars]
+            foo = "bar"
+        "#})
+        .unwrap();
+
+        Edit {
+            environment: EnvironmentSelect::Dir(environment.parent_path().unwrap()),
+            action: EditAction::EditManifest {
+                file: Some(new_manifest_path),
+                diff: true,
+                reuse_renamed_includes: false,
+            },
+        }
+        .handle(flox)
+        .with_subscriber(subscriber)
+        .await
+        .unwrap();
+
+        assert_eq!(writer.to_string(), indoc! {r#"
+            --- manifest.toml (current)
+            +++ manifest.toml (edited)
+            @@ -1,1 +1,4 @@
+             version = 1
+            +
+            +[vars]
+            +foo = "bar"
+            ✔ Environment successfully updated.
+            "#});
+    }
+
+    #[test]
+    fn summarize_package_changes_lists_installed_uninstalled_and_changed() {
+        let locked = |name: &str, version: &str| {
+            let (_, _, mut locked) = fake_catalog_package_lock(name, None);
+            locked.install_id = name.to_string();
+            locked.version = version.to_string();
+            locked
+        };
+        let system = locked("hello", "2.12").system;
+
+        let old_lockfile = Lockfile {
+            packages: vec![locked("hello", "2.12").into(), locked("curl", "8.0").into()],
+            ..Default::default()
+        };
+        let new_lockfile = Lockfile {
+            packages: vec![locked("curl", "8.1").into(), locked("jq", "1.7").into()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            summarize_package_changes(Some(&old_lockfile), &new_lockfile, &system).unwrap(),
+            "Installed: jq; Uninstalled: hello; Changed: curl (8.0 -> 8.1)"
+        );
+        assert_eq!(
+            summarize_package_changes(Some(&old_lockfile), &old_lockfile, &system),
+            None
+        );
+        assert_eq!(
+            summarize_package_changes(None, &new_lockfile, &system),
+            None
+        );
+    }
+
     /// successful edit returns value that will end the loop
     #[test]
     fn test_recover_edit_loop_result_success() {
@@ -831,9 +1700,15 @@ mod tests {
         // edit the local manifest
         fs::write(environment.manifest_path(&flox).unwrap(), new_contents).unwrap();
 
-        let err = Edit::edit_manifest(&flox, &mut ConcreteEnvironment::Managed(environment), None)
-            .await
-            .expect_err("edit should fail");
+        let err = Edit::edit_manifest(
+            &flox,
+            &mut ConcreteEnvironment::Managed(environment),
+            None,
+            false,
+            false,
+        )
+        .await
+        .expect_err("edit should fail");
 
         let err = err
             .downcast::<ManagedEnvironmentError>()
@@ -867,6 +1742,8 @@ mod tests {
             &flox,
             &mut ConcreteEnvironment::Managed(environment),
             Some(new_contents.to_string()),
+            false,
+            false,
         )
         .await
         .expect("edit should succeed");
@@ -914,6 +1791,8 @@ mod tests {
             environment: EnvironmentSelect::Dir(composer.parent_path().unwrap()),
             action: EditAction::EditManifest {
                 file: Some(composer_new_manifest_path),
+                diff: false,
+                reuse_renamed_includes: false,
             },
         }
         .handle(flox)
@@ -967,6 +1846,8 @@ mod tests {
             environment: EnvironmentSelect::Dir(composer.parent_path().unwrap()),
             action: EditAction::EditManifest {
                 file: Some(composer_new_manifest_path),
+                diff: false,
+                reuse_renamed_includes: false,
             },
         }
         .handle(flox)
@@ -2,34 +2,49 @@ use std::collections::BTreeSet;
 use std::fmt::Display;
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use bpaf::Bpaf;
 use crossterm::style::Stylize;
 use flox_rust_sdk::flox::Flox;
 use flox_rust_sdk::models::env_registry::{EnvRegistry, garbage_collect};
-use flox_rust_sdk::models::environment::{DotFlox, EnvironmentPointer, ManagedPointer};
+use flox_rust_sdk::models::environment::fetcher::IncludeFetcher;
+use flox_rust_sdk::models::environment::{
+    CoreEnvironment,
+    DotFlox,
+    ENV_DIR_NAME,
+    EnvironmentPointer,
+    ManagedPointer,
+};
 use serde_json::json;
 use tracing::instrument;
 
 use super::UninitializedEnvironment;
-use crate::subcommand_metric;
 use crate::utils::active_environments::{ActiveEnvironments, activated_environments};
 use crate::utils::message;
+use crate::{Exit, subcommand_metric};
 
 #[derive(Bpaf, Debug, Clone)]
 #[bpaf(fallback(Mode::All))]
 enum Mode {
     #[bpaf(long, hide)]
     All,
-    /// Show only the active environments
+    /// Show only the most recently activated environment,
+    /// exit with a non-zero status if no environment is active
     #[bpaf(long)]
     Active,
+    /// Print only the number of registered and active environments
+    #[bpaf(long)]
+    Count,
 }
 
 #[derive(Bpaf, Debug, Clone)]
 pub struct Envs {
     #[bpaf(external(mode))]
     mode: Mode,
+    /// Mark environments whose manifest has changed since they were last
+    /// locked, i.e. that will be re-locked the next time they are used
+    #[bpaf(long)]
+    stale: bool,
     /// Format output as JSON
     #[bpaf(long)]
     json: bool,
@@ -40,16 +55,39 @@ impl Envs {
     ///
     /// If `--json` is passed, dispatch to [Self::handle_json]
     ///
-    /// If `--active` is passed, print only the active environments
-    /// Always prints headers and formats the output.
+    /// If `--active` is passed, print only the active environment
+    /// and exit with a non-zero status if none is active.
+    ///
+    /// If `--count` is passed, print only the number of registered and
+    /// active environments.
+    ///
+    /// If `--stale` is passed, environments that need to be re-locked
+    /// are marked.
+    ///
+    /// Otherwise, always prints headers and formats the output.
     #[instrument(name = "envs", skip_all)]
     pub fn handle(self, flox: Flox) -> Result<()> {
         subcommand_metric!("envs");
 
+        if self.stale {
+            match self.mode {
+                Mode::Active => bail!("'--stale' cannot be used with '--active'"),
+                Mode::Count => bail!("'--stale' cannot be used with '--count'"),
+                Mode::All => {},
+            }
+        }
+
         let active = activated_environments();
 
         match self.mode {
             Mode::Active => tracing::info_span!("active").in_scope(|| self.handle_active(active)),
+            Mode::Count => tracing::info_span!("count").in_scope(|| {
+                let env_registry = garbage_collect(&flox)?;
+                let registered = get_registered_environments(&env_registry).count();
+
+                println!("{}", self.format_count(registered, active.iter().count()));
+                Ok(())
+            }),
             Mode::All => tracing::info_span!("all").in_scope(|| {
                 let env_registry = garbage_collect(&flox)?;
                 let registered = get_registered_environments(&env_registry);
@@ -59,33 +97,49 @@ impl Envs {
         }
     }
 
-    /// Print active environments only
+    /// Print the active environment
+    ///
+    /// Intended for scripting, e.g. in shell prompts:
+    /// exits with a non-zero status if no environment is active.
     ///
     /// If `--json` is passed, print a JSON list with objects for each active environment.
-    /// Otherwise, print a list of active environments.
-    /// If no environments are active, print an appropriate message.
+    /// Otherwise, print the description of the most recently activated environment.
+    /// If no environments are active, print nothing.
     fn handle_active(&self, active: ActiveEnvironments) -> Result<()> {
         if self.json {
             println!("{:#}", json!(active));
-            return Ok(());
+        } else if let Some(last_active) = active.last_active() {
+            println!("{}", last_active.bare_description());
         }
 
         if active.last_active().is_none() {
-            message::plain("No active environments");
-            return Ok(());
+            return Err(Exit(1.into()).into());
         }
 
-        message::created("Active environments:");
-        let envs =
-            indent::indent_all_by(2, DisplayEnvironments::new(active.iter(), true).to_string());
-        println!("{envs}");
-
         Ok(())
     }
 
+    /// Format the number of registered and active environments
+    ///
+    /// Intended for shell prompts and dashboards,
+    /// which only need the tally and not the listing.
+    fn format_count(&self, registered: usize, active: usize) -> String {
+        if self.json {
+            json!({
+                "registered": registered,
+                "active": active,
+            })
+            .to_string()
+        } else {
+            format!("registered={registered} active={active}")
+        }
+    }
+
     /// Print all environments
     ///
-    /// If `--json` is passed, print a JSON object with `active` and `inactive` keys.
+    /// If `--json` is passed, print a JSON object with `active` and `inactive` keys,
+    /// and with `--stale` a `stale` key listing the environments that need
+    /// to be re-locked.
     /// If any environments are active, print them first.
     /// Then print all inactive environments.
     /// If no environments are known to Flox, print an appropriate message.
@@ -95,15 +149,25 @@ impl Envs {
         registered: impl Iterator<Item = UninitializedEnvironment>,
     ) -> Result<()> {
         let inactive = get_inactive_environments(registered, active.iter())?;
+        let stale = if self.stale {
+            active
+                .iter()
+                .chain(inactive.iter())
+                .filter(|env| needs_relock(env))
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
 
         if self.json {
-            println!(
-                "{:#}",
-                json!({
-                    "active": active,
-                    "inactive": inactive,
-                })
-            );
+            let mut output = json!({
+                "active": active,
+                "inactive": inactive,
+            });
+            if self.stale {
+                output["stale"] = json!(stale);
+            }
+            println!("{output:#}");
             return Ok(());
         }
 
@@ -113,8 +177,12 @@ impl Envs {
 
         if active.iter().next().is_some() {
             message::created("Active environments:");
-            let envs =
-                indent::indent_all_by(2, DisplayEnvironments::new(active.iter(), true).to_string());
+            let envs = indent::indent_all_by(
+                2,
+                DisplayEnvironments::new(active.iter(), true)
+                    .with_stale(stale.clone())
+                    .to_string(),
+            );
             println!("{envs}");
         }
 
@@ -122,7 +190,9 @@ impl Envs {
             message::plain("Inactive environments:");
             let envs = indent::indent_all_by(
                 2,
-                DisplayEnvironments::new(inactive.iter(), false).to_string(),
+                DisplayEnvironments::new(inactive.iter(), false)
+                    .with_stale(stale)
+                    .to_string(),
             );
             println!("{envs}");
         }
@@ -134,6 +204,8 @@ impl Envs {
 pub(crate) struct DisplayEnvironments<'a> {
     envs: Vec<&'a UninitializedEnvironment>,
     format_active: bool,
+    /// Environments to mark as needing to be re-locked
+    stale: BTreeSet<&'a UninitializedEnvironment>,
 }
 
 impl<'a> DisplayEnvironments<'a> {
@@ -144,6 +216,21 @@ impl<'a> DisplayEnvironments<'a> {
         Self {
             envs: envs.into_iter().collect(),
             format_active,
+            stale: BTreeSet::new(),
+        }
+    }
+
+    pub(crate) fn with_stale(self, stale: BTreeSet<&'a UninitializedEnvironment>) -> Self {
+        Self { stale, ..self }
+    }
+
+    /// Format the location of `env`, followed by a marker if it is stale
+    fn format_entry(&self, env: &UninitializedEnvironment) -> String {
+        let location = format_location(env);
+        if self.stale.contains(env) {
+            format!("{location}  (needs re-lock)")
+        } else {
+            location
         }
     }
 }
@@ -164,12 +251,12 @@ impl Display for DisplayEnvironments<'_> {
                 return Ok(());
             };
             let first_formatted =
-                format!("{:<widest$}  {}", first.name(), format_location(first)).bold();
+                format!("{:<widest$}  {}", first.name(), self.format_entry(first)).bold();
             writeln!(f, "{first_formatted}")?;
         }
 
         for env in envs {
-            writeln!(f, "{:<widest$}  {}", env.name(), format_location(env))?;
+            writeln!(f, "{:<widest$}  {}", env.name(), self.format_entry(env))?;
         }
 
         Ok(())
@@ -210,6 +297,36 @@ fn format_path(path: &Path) -> String {
     path.parent().unwrap_or(path).to_string_lossy().to_string()
 }
 
+/// Whether the manifest of `env` has changed since it was last locked.
+///
+/// Only the local manifest is compared to the manifest recorded in the
+/// lockfile, the environment is neither locked nor built.
+/// Environments without a local manifest,
+/// e.g. managed environments that have not been edited locally,
+/// are locked by definition.
+/// A manifest that can't be read is considered stale,
+/// since it can't match its lockfile.
+fn needs_relock(env: &UninitializedEnvironment) -> bool {
+    let UninitializedEnvironment::DotFlox(DotFlox { path, .. }) = env else {
+        return false;
+    };
+    let env_dir = path.join(ENV_DIR_NAME);
+    let core_environment = CoreEnvironment::new(&env_dir, IncludeFetcher {
+        base_directory: None,
+    });
+    if !core_environment.manifest_path().exists() {
+        return false;
+    }
+
+    match core_environment.lockfile_if_up_to_date() {
+        Ok(lockfile) => lockfile.is_none(),
+        Err(err) => {
+            tracing::debug!(path = %env_dir.display(), %err, "Failed to compare manifest and lockfile");
+            true
+        },
+    }
+}
+
 fn get_registered_environments(
     registry: &EnvRegistry,
 ) -> impl Iterator<Item = UninitializedEnvironment> + '_ {
@@ -252,6 +369,55 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn format_count() {
+        let envs = Envs {
+            mode: Mode::Count,
+            stale: false,
+            json: false,
+        };
+        assert_eq!(envs.format_count(12, 2), "registered=12 active=2");
+
+        let envs = Envs {
+            mode: Mode::Count,
+            stale: false,
+            json: true,
+        };
+        let json: serde_json::Value = serde_json::from_str(&envs.format_count(12, 2)).unwrap();
+        assert_eq!(json, json!({ "registered": 12, "active": 2 }));
+    }
+
+    #[test]
+    fn needs_relock_without_lockfile() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dot_flox = temp_dir.path().join(".flox");
+        let env = UninitializedEnvironment::DotFlox(DotFlox {
+            path: dot_flox.clone(),
+            pointer: EnvironmentPointer::Path(PathPointer::new(
+                EnvironmentName::from_str("name_path").unwrap(),
+            )),
+        });
+
+        // no local manifest, nothing to re-lock
+        assert!(!needs_relock(&env));
+
+        std::fs::create_dir_all(dot_flox.join(ENV_DIR_NAME)).unwrap();
+        std::fs::write(
+            dot_flox.join(ENV_DIR_NAME).join("manifest.toml"),
+            "version = 1",
+        )
+        .unwrap();
+        assert!(needs_relock(&env));
+
+        let floxhub = Floxhub::new("https://hub.example.com".parse().unwrap(), None).unwrap();
+        let remote_env = UninitializedEnvironment::Remote(ManagedPointer::new(
+            EnvironmentOwner::from_str("owner").unwrap(),
+            EnvironmentName::from_str("name_remote").unwrap(),
+            &floxhub,
+        ));
+        assert!(!needs_relock(&remote_env));
+    }
+
     #[test]
     fn display_environments() {
         let floxhub = Floxhub::new("https://hub.example.com".parse().unwrap(), None).unwrap();
@@ -282,11 +448,19 @@ mod tests {
         let envs = DisplayEnvironments {
             envs: vec![&path_env, &managed_env, &remote_env],
             format_active: false,
+            stale: BTreeSet::new(),
         };
         assert_eq!(envs.to_string(), formatdoc! {"
             name_path                  /envs/path
             name_managed               /envs/managed (https://hub.example.com/owner/name_managed)
             name_remote                remote (https://hub.example.com/owner/name_remote)
         "});
+
+        let envs = envs.with_stale(BTreeSet::from([&path_env]));
+        assert_eq!(envs.to_string(), formatdoc! {"
+            name_path                  /envs/path  (needs re-lock)
+            name_managed               /envs/managed (https://hub.example.com/owner/name_managed)
+            name_remote                remote (https://hub.example.com/owner/name_remote)
+        "});
     }
 }
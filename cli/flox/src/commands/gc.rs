@@ -60,36 +60,181 @@
 //! the final amount of freed disk space is reported to `stdout`.
 
 use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
 use std::process::{Child, ChildStderr, ChildStdout, Stdio};
 
 use anyhow::{Context, Result, anyhow};
 use bpaf::Bpaf;
 use flox_rust_sdk::flox::Flox;
-use flox_rust_sdk::models::env_registry;
+use flox_rust_sdk::models::env_registry::{self, RegistryEntry};
+use flox_rust_sdk::models::environment::{DOT_FLOX, EnvironmentPointer};
 use flox_rust_sdk::providers::nix::nix_base_command;
+use serde::Serialize;
 use tracing::{Span, debug, info_span, instrument, trace};
 
+use crate::utils::duration::HumanDuration;
 use crate::{message, subcommand_metric};
 
 #[derive(Bpaf, Debug, Clone)]
-pub struct Gc {}
+pub struct Gc {
+    /// Only collect data for deleted environments that were last registered
+    /// longer than <duration> ago, e.g. '30d' or '1w2d'
+    #[bpaf(long, argument("duration"))]
+    older_than: Option<HumanDuration>,
+
+    /// Never collect the environment at <path>,
+    /// or the FloxHub environment <owner/name> wherever it was pulled.
+    /// Can be specified multiple times.
+    #[bpaf(long, argument("path-or-ref"))]
+    exclude: Vec<String>,
+
+    /// Print what was collected as JSON
+    #[bpaf(long)]
+    json: bool,
+}
 
 impl Gc {
     #[instrument(skip_all)]
     pub fn handle(self, flox: Flox) -> Result<()> {
         subcommand_metric!("gc");
 
+        let excluded = if self.exclude.is_empty() {
+            Vec::new()
+        } else {
+            let registry =
+                env_registry::read_environment_registry(env_registry::env_registry_path(&flox))?
+                    .unwrap_or_default();
+            let mut excluded = Vec::new();
+            for exclusion in &self.exclude {
+                let matched = excluded_entries(&registry.entries, exclusion);
+                if matched.is_empty() {
+                    message::warning(format!(
+                        "'--exclude {exclusion}' did not match any registered environment"
+                    ));
+                    continue;
+                }
+                for entry in matched {
+                    if !self.json {
+                        message::info(format!(
+                            "Excluding '{}' from garbage collection (matched '--exclude {exclusion}')",
+                            entry.path.display()
+                        ));
+                    }
+                    excluded.push(entry.path.clone());
+                }
+            }
+            excluded
+        };
+
         let span = info_span!("collecting_garbage", progress = "Collecting garbage");
         let _guard = span.enter();
-        env_registry::garbage_collect(&flox)?;
+        let (_, removed) = env_registry::garbage_collect_older_than(
+            &flox,
+            self.older_than.map(|HumanDuration(older_than)| older_than),
+            &excluded,
+        )?;
         let freed = run_store_gc()?;
         drop(_guard);
+
+        if self.json {
+            let report = GcReport::new(&removed, &freed, excluded);
+            println!("{}", serde_json::to_string(&report)?);
+            return Ok(());
+        }
+
         message::info(freed);
         message::updated("Garbage collection complete");
         Ok(())
     }
 }
 
+/// The registry entries protected by `--exclude <exclusion>`.
+///
+/// `exclusion` matches an entry if it is the path of the environment or of
+/// its `.flox` directory, or the `owner/name` of a managed environment that
+/// was registered at the entry's path.
+/// Deleted environments can't be canonicalized,
+/// so paths that don't exist are only made absolute.
+fn excluded_entries<'a>(entries: &'a [RegistryEntry], exclusion: &str) -> Vec<&'a RegistryEntry> {
+    let path = Path::new(exclusion);
+    let path = path
+        .canonicalize()
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_path_buf());
+
+    entries
+        .iter()
+        .filter(|entry| {
+            let matches_path = entry.path == path || entry.path == path.join(DOT_FLOX);
+            let matches_ref = entry.envs.iter().any(|env| match &env.pointer {
+                EnvironmentPointer::Managed(pointer) => {
+                    format!("{}/{}", pointer.owner, pointer.name) == exclusion
+                },
+                EnvironmentPointer::Path(_) => false,
+            });
+            matches_path || matches_ref
+        })
+        .collect()
+}
+
+/// What was collected by `flox gc`, printed with `--json`
+#[derive(Debug, PartialEq, Serialize)]
+struct GcReport {
+    /// `.flox` directories of deleted environments removed from the registry
+    environments_removed: Vec<PathBuf>,
+    /// Managed environments, as `owner/name`,
+    /// whose local generations were pruned along with a deleted environment
+    generations_pruned: Vec<String>,
+    /// Space freed in the Nix store,
+    /// or `None` if it couldn't be parsed from the output of `nix store gc`
+    store_bytes_freed: Option<u64>,
+    /// `.flox` directories of environments protected with `--exclude`
+    environments_excluded: Vec<PathBuf>,
+}
+
+impl GcReport {
+    fn new(removed: &[RegistryEntry], freed: &str, excluded: Vec<PathBuf>) -> Self {
+        let generations_pruned = removed
+            .iter()
+            .flat_map(|entry| entry.envs.iter())
+            .filter_map(|env| match &env.pointer {
+                EnvironmentPointer::Managed(pointer) => {
+                    Some(format!("{}/{}", pointer.owner, pointer.name))
+                },
+                EnvironmentPointer::Path(_) => None,
+            })
+            .collect();
+
+        Self {
+            environments_removed: removed.iter().map(|entry| entry.path.clone()).collect(),
+            generations_pruned,
+            store_bytes_freed: parse_freed_bytes(freed),
+            environments_excluded: excluded,
+        }
+    }
+}
+
+/// Parse the space freed from the summary printed by `nix store gc`,
+/// e.g. `42 store paths deleted, 12.34 MiB freed`.
+fn parse_freed_bytes(freed: &str) -> Option<u64> {
+    let words = freed.split_whitespace().collect::<Vec<_>>();
+    let freed_index = words.iter().position(|word| word.starts_with("freed"))?;
+    let [amount, unit] = words.get(freed_index.checked_sub(2)?..freed_index)? else {
+        return None;
+    };
+
+    let amount: f64 = amount.parse().ok()?;
+    let unit_bytes: f64 = match *unit {
+        "bytes" | "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((amount * unit_bytes).round() as u64)
+}
+
 /// Represents the stages of garbage collection in the logs of the
 /// `nix store gc` command.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -335,6 +480,13 @@ fn run_store_gc() -> Result<String> {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
+    use flox_core::data::environment_ref::{EnvironmentName, EnvironmentOwner};
+    use flox_rust_sdk::flox::Floxhub;
+    use flox_rust_sdk::models::env_registry::RegisteredEnv;
+    use flox_rust_sdk::models::environment::{ManagedPointer, PathPointer};
+
     use super::*;
 
     fn state_sequence(lines: &[&str]) -> Vec<GcProgress> {
@@ -349,6 +501,65 @@ mod tests {
         states
     }
 
+    #[test]
+    fn exclusions_match_paths_and_managed_refs() {
+        let floxhub = Floxhub::new("https://hub.example.com".parse().unwrap(), None).unwrap();
+        let entry = |path: &str, pointer: EnvironmentPointer| RegistryEntry {
+            path_hash: String::new(),
+            path: PathBuf::from(path),
+            envs: vec![RegisteredEnv {
+                created_at: 0,
+                pointer,
+            }],
+        };
+        let entries = [
+            entry(
+                "/envs/path/.flox",
+                EnvironmentPointer::Path(PathPointer::new(
+                    EnvironmentName::from_str("path_env").unwrap(),
+                )),
+            ),
+            entry(
+                "/envs/managed/.flox",
+                EnvironmentPointer::Managed(ManagedPointer::new(
+                    EnvironmentOwner::from_str("owner").unwrap(),
+                    EnvironmentName::from_str("managed_env").unwrap(),
+                    &floxhub,
+                )),
+            ),
+        ];
+        let matched_paths = |exclusion: &str| {
+            excluded_entries(&entries, exclusion)
+                .into_iter()
+                .map(|entry| entry.path.to_str().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matched_paths("/envs/path"), vec!["/envs/path/.flox"]);
+        assert_eq!(matched_paths("/envs/path/.flox"), vec!["/envs/path/.flox"]);
+        assert_eq!(matched_paths("owner/managed_env"), vec![
+            "/envs/managed/.flox"
+        ]);
+        assert!(matched_paths("owner/path_env").is_empty());
+        assert!(matched_paths("/envs").is_empty());
+    }
+
+    #[test]
+    fn parses_freed_bytes() {
+        assert_eq!(
+            parse_freed_bytes("42 store paths deleted, 1.50 MiB freed"),
+            Some(1572864)
+        );
+        assert_eq!(
+            parse_freed_bytes("0 store paths deleted, 0.00 MiB freed"),
+            Some(0)
+        );
+        assert_eq!(parse_freed_bytes("2.00 GiB freed"), Some(2147483648));
+        assert_eq!(parse_freed_bytes("freed"), None);
+        assert_eq!(parse_freed_bytes("1.00 parsecs freed"), None);
+        assert_eq!(parse_freed_bytes(""), None);
+    }
+
     #[test]
     fn ingests_full_sequence() {
         let lines = vec![
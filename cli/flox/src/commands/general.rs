@@ -1,19 +1,25 @@
 use std::io;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use bpaf::Bpaf;
-use flox_rust_sdk::flox::Flox;
+use flox_core::data::environment_ref::RemoteEnvironmentRef;
+use flox_core::write_atomically;
+use flox_rust_sdk::flox::{DefaultEnvironment, Flox};
+use flox_rust_sdk::models::environment::remote_environment::RemoteEnvironment;
+use flox_rust_sdk::models::environment::{DotFlox, ManagedPointer};
+use floxhub_client::CatalogClientTrait;
 use fslock::LockFile;
-use indoc::indoc;
+use indoc::{formatdoc, indoc};
 use serde::Serialize;
 use serde_json::Value;
 use tokio::fs;
 use toml_edit::{Key, TomlError};
 use tracing::{debug, instrument};
 
-use crate::config::{Config, FLOX_CONFIG_FILE, ReadWriteError};
+use crate::config::{Config, FLOX_CONFIG_FILE, InstallerChannel, MigrationOutcome, ReadWriteError};
 use crate::subcommand_metric;
 use crate::utils::message;
 use crate::utils::metrics::{
@@ -21,6 +27,7 @@ use crate::utils::metrics::{
     METRICS_LOCK_FILE_NAME,
     METRICS_UUID_FILE_NAME,
 };
+use crate::utils::update_notifications::UPDATE_NOTIFICATION_FILE_NAME;
 
 // Reset the metrics queue (if any), reset metrics ID, and re-prompt for consent
 #[derive(Bpaf, Clone)]
@@ -78,6 +85,34 @@ pub enum ConfigArgs {
     Set(#[bpaf(external(config_set))] ConfigSet),
     /// Delete a config value
     Delete(#[bpaf(external(config_delete))] ConfigDelete),
+    /// Export options to <path>, excluding secrets and machine specific options
+    Export {
+        #[bpaf(long("export"), argument("path"))]
+        path: PathBuf,
+    },
+    /// Import options from <path>, merging them into the user config
+    Import {
+        #[bpaf(long("import"), argument("path"))]
+        path: PathBuf,
+    },
+    /// Check that the catalog at the configured 'catalog_url' is reachable
+    #[bpaf(long("check-catalog"))]
+    CheckCatalog,
+    /// Set the release channel that update checks use
+    SetInstallerChannel {
+        #[bpaf(long("set-installer-channel"), argument("stable|nightly|qa"))]
+        channel: InstallerChannel,
+    },
+    /// Use the environment in <path>, or the FloxHub environment <owner/name>,
+    /// when no environment is found in the current directory or git repo
+    /// and none is active
+    SetDefaultEnvironment {
+        #[bpaf(long("set-default-environment"), argument("path|owner/name"))]
+        environment: String,
+    },
+    /// Run pending migrations of the user config and report what was migrated
+    #[bpaf(long("migrate"))]
+    Migrate,
 }
 
 impl ConfigArgs {
@@ -116,11 +151,150 @@ impl ConfigArgs {
             ConfigArgs::Delete(ConfigDelete { key, .. }) => {
                 update_config::<()>(&flox.config_dir, key, None)?
             },
+            ConfigArgs::Export { path } => {
+                let config_file_contents =
+                    read_config_file(&flox.config_dir.join(FLOX_CONFIG_FILE)).await?;
+                let exported = Config::export_portable(config_file_contents)?;
+                fs::write(path, exported)
+                    .await
+                    .with_context(|| format!("Could not write '{}'", path.display()))?;
+                message::created(format!("Exported config to '{}'", path.display()));
+            },
+            ConfigArgs::Import { path } => {
+                let portable = fs::read_to_string(path)
+                    .await
+                    .with_context(|| format!("Could not read '{}'", path.display()))?;
+                let config_file_path = flox.config_dir.join(FLOX_CONFIG_FILE);
+                let config_file_contents = read_config_file(&config_file_path).await?;
+                let merged = Config::import_portable(config_file_contents, &portable)
+                    .with_context(|| format!("Could not import '{}'", path.display()))?;
+                write_atomically(&config_file_path, merged)
+                    .context("Could not write config file")?;
+                message::updated(format!("Imported config from '{}'", path.display()));
+            },
+            ConfigArgs::CheckCatalog => {
+                let catalog_url = flox.floxhub_client.base_url();
+                let handle = flox.auth_context.handle();
+
+                let start = Instant::now();
+                let result = flox.floxhub_client.get_base_catalog_info().await;
+                let latency = start.elapsed();
+
+                match result {
+                    Ok(_) => {
+                        println!(
+                            "{}",
+                            render_catalog_check(catalog_url, handle, Some(latency))
+                        )
+                    },
+                    Err(err) => {
+                        println!("{}", render_catalog_check(catalog_url, handle, None));
+                        bail!(formatdoc! {"
+                            Could not reach the catalog at '{catalog_url}': {err}
+
+                            Check your network connection and the 'catalog_url' option.
+                        "});
+                    },
+                }
+            },
+            ConfigArgs::SetInstallerChannel { channel } => {
+                update_config(&flox.config_dir, "installer_channel", Some(channel))?;
+
+                // Forget when updates were last checked,
+                // so that the next check uses the new channel right away
+                match fs::remove_file(flox.cache_dir.join(UPDATE_NOTIFICATION_FILE_NAME)).await {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                        debug!(%err, "could not remove update notification file");
+                    },
+                    _ => (),
+                }
+                message::updated(format!(
+                    "Installer channel set to '{channel}', the next update check will use it."
+                ));
+            },
+            ConfigArgs::SetDefaultEnvironment { environment } => {
+                let default_environment = validate_default_environment(&flox, environment)?;
+                update_config(
+                    &flox.config_dir,
+                    "default_environment",
+                    Some(&default_environment),
+                )?;
+                message::updated(format!(
+                    "Default environment set to '{default_environment}'."
+                ));
+            },
+            ConfigArgs::Migrate => {
+                let config_file_path = flox.config_dir.join(FLOX_CONFIG_FILE);
+                let config_file_contents = read_config_file(&config_file_path).await?;
+                let (migrated, outcomes) = Config::migrate(config_file_contents)?;
+                if let Some(contents) = migrated {
+                    write_atomically(&config_file_path, contents)
+                        .context("Could not write config file")?;
+                }
+
+                for MigrationOutcome { name, change } in outcomes {
+                    match change {
+                        Some(change) => message::updated(format!("Migrated '{name}': {change}")),
+                        None => message::info(format!("Nothing to migrate for '{name}'")),
+                    }
+                }
+            },
         }
         Ok(())
     }
 }
 
+/// Check that `environment` is a directory containing an environment,
+/// or an environment on FloxHub.
+///
+/// Directories are resolved to an absolute path,
+/// so that the default environment doesn't depend on the current directory.
+fn validate_default_environment(flox: &Flox, environment: &str) -> Result<DefaultEnvironment> {
+    let path = Path::new(environment);
+    if path.exists() {
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Could not resolve '{environment}'"))?;
+        DotFlox::open_in(&path)
+            .with_context(|| format!("'{environment}' does not contain an environment"))?;
+        return Ok(DefaultEnvironment::Path(path));
+    }
+
+    let Ok(env_ref) = environment.parse::<RemoteEnvironmentRef>() else {
+        bail!("'{environment}' is neither a directory nor a FloxHub environment 'owner/name'");
+    };
+    let pointer = ManagedPointer::new(
+        env_ref.owner().clone(),
+        env_ref.name().clone(),
+        &flox.floxhub,
+    );
+    RemoteEnvironment::new(flox, pointer, None)
+        .with_context(|| format!("Could not find the environment '{env_ref}' on FloxHub"))?;
+    Ok(DefaultEnvironment::Remote(env_ref))
+}
+
+/// Render the result of `flox config --check-catalog`.
+///
+/// `latency` is `None` if the catalog could not be reached.
+fn render_catalog_check(
+    catalog_url: &str,
+    handle: Option<&str>,
+    latency: Option<Duration>,
+) -> String {
+    let credentials = match handle {
+        Some(handle) => format!("attached (as '{handle}')"),
+        None => "none".to_string(),
+    };
+    let reachable = match latency {
+        Some(latency) => format!("yes ({}ms)", latency.as_millis()),
+        None => "no".to_string(),
+    };
+    formatdoc! {"
+        Catalog URL: {catalog_url}
+        Credentials: {credentials}
+        Reachable:   {reachable}"}
+}
+
 #[derive(Debug, Clone, Bpaf)]
 #[bpaf(adjacent)]
 pub struct ConfigSet {
@@ -143,6 +317,15 @@ pub struct ConfigDelete {
     key: String,
 }
 
+/// Read the user config file, if it exists
+async fn read_config_file(config_file_path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(config_file_path).await {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err).context("Could not read config file"),
+    }
+}
+
 /// wrapper around [Config::write_to]
 pub(super) fn update_config<V: Serialize>(
     config_dir: &Path,
@@ -203,6 +386,25 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn render_catalog_check_reports_reachability() {
+        let rendered = render_catalog_check(
+            "https://catalog.example.com",
+            Some("alice"),
+            Some(Duration::from_millis(42)),
+        );
+        assert_eq!(rendered, indoc! {"
+            Catalog URL: https://catalog.example.com
+            Credentials: attached (as 'alice')
+            Reachable:   yes (42ms)"});
+
+        let rendered = render_catalog_check("https://catalog.example.com", None, None);
+        assert_eq!(rendered, indoc! {"
+            Catalog URL: https://catalog.example.com
+            Credentials: none
+            Reachable:   no"});
+    }
+
     #[test]
     fn parse_toml_key_no_quoting_needed() {
         let key = "trusted_environments.foo.bar";
@@ -0,0 +1,114 @@
+use anyhow::{Result, anyhow};
+use bpaf::Bpaf;
+use chrono::{DateTime, Utc};
+use flox_rust_sdk::flox::Flox;
+use flox_rust_sdk::models::environment::generations::{
+    AllGenerationsMetadata,
+    GenerationsEnvironment,
+    GenerationsExt,
+};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::commands::{EnvironmentSelect, environment_select};
+use crate::environment_subcommand_metric;
+
+/// Arguments for the `flox generations current` command
+#[derive(Bpaf, Debug, Clone)]
+pub struct Current {
+    #[bpaf(external(environment_select), fallback(Default::default()))]
+    environment: EnvironmentSelect,
+
+    /// Print the current generation as JSON
+    #[bpaf(long)]
+    json: bool,
+}
+
+/// The live generation of an environment, as printed by `flox generations current`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct CurrentGeneration {
+    number: usize,
+    /// Generations can't be labelled yet, so this is always `null`.
+    /// It is part of the output already so that scripts don't break
+    /// once labels are added.
+    label: Option<String>,
+    message: String,
+    created_at: DateTime<Utc>,
+}
+
+impl Current {
+    #[instrument(name = "current", skip_all)]
+    pub async fn handle(self, mut flox: Flox) -> Result<()> {
+        let env = self
+            .environment
+            .detect_concrete_environment(&mut flox, "Show current generation of")
+            .await?;
+
+        environment_subcommand_metric!("generations::current", env);
+        let env: GenerationsEnvironment = env.try_into()?;
+
+        let current = current_generation(&env.generations_metadata()?)?;
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&current)?);
+            return Ok(());
+        }
+
+        match &current.label {
+            Some(label) => println!("{} ({label}): {}", current.number, current.message),
+            None => println!("{}: {}", current.number, current.message),
+        }
+
+        Ok(())
+    }
+}
+
+/// Look up the metadata of the live generation.
+fn current_generation(metadata: &AllGenerationsMetadata) -> Result<CurrentGeneration> {
+    let number = metadata
+        .current_gen()
+        .ok_or_else(|| anyhow!("Environment does not have a live generation."))?;
+    let generation = metadata
+        .generations()
+        .remove(&number)
+        .ok_or_else(|| anyhow!("Live generation {number} does not exist."))?;
+
+    Ok(CurrentGeneration {
+        number: *number,
+        label: None,
+        message: generation.description,
+        created_at: generation.created,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use flox_rust_sdk::models::environment::generations::test_helpers::{
+        default_add_generation_options,
+        default_switch_generation_options,
+    };
+
+    use super::*;
+
+    #[test]
+    fn current_generation_follows_switches() {
+        let mut metadata = AllGenerationsMetadata::default();
+        assert!(current_generation(&metadata).is_err());
+
+        let (first_generation, ..) = metadata.add_generation(default_add_generation_options());
+        metadata.add_generation(default_add_generation_options());
+        assert_eq!(current_generation(&metadata).unwrap().number, 2);
+
+        metadata
+            .switch_generation(default_switch_generation_options(first_generation))
+            .unwrap();
+        let current = current_generation(&metadata).unwrap();
+        let first = metadata.generations()[&first_generation].clone();
+        assert_eq!(current, CurrentGeneration {
+            number: 1,
+            label: None,
+            message: first.description,
+            created_at: first.created,
+        });
+    }
+}
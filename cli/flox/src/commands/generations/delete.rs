@@ -0,0 +1,136 @@
+use anyhow::{Result, bail};
+use bpaf::Bpaf;
+use flox_rust_sdk::flox::Flox;
+use flox_rust_sdk::models::environment::generations::{
+    AllGenerationsMetadata,
+    GenerationId,
+    GenerationsEnvironment,
+    GenerationsExt,
+};
+use indoc::formatdoc;
+use tracing::instrument;
+
+use super::prune::freeable_bytes;
+use crate::commands::{EnvironmentSelect, environment_select};
+use crate::environment_subcommand_metric;
+use crate::utils::message;
+use crate::utils::path_info::format_size;
+
+/// Arguments for the `flox generations delete` command
+#[derive(Bpaf, Debug, Clone)]
+pub struct Delete {
+    #[bpaf(external(environment_select), fallback(Default::default()))]
+    environment: EnvironmentSelect,
+
+    #[bpaf(positional("generation"))]
+    generation: GenerationId,
+}
+
+impl Delete {
+    #[instrument(name = "delete", skip_all)]
+    pub async fn handle(self, mut flox: Flox) -> Result<()> {
+        let env = self
+            .environment
+            .detect_concrete_environment(&mut flox, "Delete generation of")
+            .await?;
+
+        environment_subcommand_metric!("generations::delete", env);
+        let mut env: GenerationsEnvironment = env.try_into()?;
+
+        let metadata = env.generations_metadata()?;
+        check_deletable(&metadata, self.generation)?;
+
+        let generation = self.generation;
+
+        // Estimate the freed space before the links are removed,
+        // excluding paths still used by the remaining generations.
+        let mut kept_links = Vec::new();
+        for other in metadata.generations().into_keys() {
+            if other != generation {
+                kept_links.extend(env.generation_links(&flox, other)?);
+            }
+        }
+        let bytes_freeable = freeable_bytes(&env.generation_links(&flox, generation)?, &kept_links);
+
+        let removed_links = env.delete_generation(&flox, generation)?;
+        message::deleted(format!("Deleted generation {generation}"));
+
+        if removed_links.is_empty() {
+            message::info(
+                "No local build of this generation was found, so no store space was freed.",
+            );
+            return Ok(());
+        }
+
+        let free_hint = match bytes_freeable {
+            Some(bytes) => format!("Run 'flox gc' to free up to {}.", format_size(bytes)),
+            None => "Run 'flox gc' to free the store space that is no longer in use.".to_string(),
+        };
+        message::info(formatdoc! {"
+            Removed {count} garbage collection root(s) of generation {generation}.
+            {free_hint}",
+            count = removed_links.len(),
+        });
+
+        Ok(())
+    }
+}
+
+/// Check that `generation` can be deleted,
+/// i.e. it exists and is not the live generation.
+fn check_deletable(metadata: &AllGenerationsMetadata, generation: GenerationId) -> Result<()> {
+    let generations = metadata.generations();
+
+    if !generations.contains_key(&generation) {
+        bail!("Generation {generation} does not exist.");
+    }
+
+    if generations.len() == 1 {
+        bail!(formatdoc! {"
+            Generation {generation} is the only generation of this environment.
+            An environment needs at least one generation, so it can't be deleted."
+        });
+    }
+
+    if metadata.current_gen() == Some(generation) {
+        bail!(formatdoc! {"
+            Generation {generation} is the live generation and can't be deleted.
+            Switch to another generation with 'flox generations switch' first."
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use flox_rust_sdk::models::environment::generations::test_helpers::{
+        default_add_generation_options,
+        default_switch_generation_options,
+    };
+
+    use super::*;
+
+    #[test]
+    fn check_deletable_refuses_live_and_unknown_generations() {
+        let mut metadata = AllGenerationsMetadata::default();
+        let (first_generation, ..) = metadata.add_generation(default_add_generation_options());
+
+        let err = check_deletable(&metadata, first_generation).unwrap_err();
+        assert!(err.to_string().contains("only generation"), "{err}");
+
+        let (second_generation, ..) = metadata.add_generation(default_add_generation_options());
+        let err = check_deletable(&metadata, second_generation).unwrap_err();
+        assert!(err.to_string().contains("live generation"), "{err}");
+
+        let err = check_deletable(&metadata, "3".parse().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("does not exist"), "{err}");
+
+        check_deletable(&metadata, first_generation).unwrap();
+
+        metadata
+            .switch_generation(default_switch_generation_options(first_generation))
+            .unwrap();
+        check_deletable(&metadata, second_generation).unwrap();
+    }
+}
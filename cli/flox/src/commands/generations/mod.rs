@@ -6,8 +6,11 @@ use tracing::instrument;
 use super::display_help;
 use crate::config::Config;
 
+mod current;
+mod delete;
 mod history;
 mod list;
+mod prune;
 mod rollback;
 mod switch;
 
@@ -22,6 +25,10 @@ pub enum GenerationsCommands {
     #[bpaf(command)]
     List(#[bpaf(external(list::list))] list::List),
 
+    /// Show the live generation of the environment
+    #[bpaf(command)]
+    Current(#[bpaf(external(current::current))] current::Current),
+
     /// Show the change log for the current environment
     #[bpaf(command)]
     History(#[bpaf(external(history::history))] history::History),
@@ -33,6 +40,14 @@ pub enum GenerationsCommands {
     /// Switch to the provided generation
     #[bpaf(command)]
     Switch(#[bpaf(external(switch::switch))] switch::Switch),
+
+    /// Delete a generation other than the live generation
+    #[bpaf(command)]
+    Delete(#[bpaf(external(delete::delete))] delete::Delete),
+
+    /// Delete old generations, keeping the live generation
+    #[bpaf(command)]
+    Prune(#[bpaf(external(prune::prune))] prune::Prune),
 }
 
 impl GenerationsCommands {
@@ -43,9 +58,12 @@ impl GenerationsCommands {
                 display_help(Some("generations".to_string()));
             },
             GenerationsCommands::List(args) => args.handle(flox).await?,
+            GenerationsCommands::Current(args) => args.handle(flox).await?,
             GenerationsCommands::History(args) => args.handle(flox).await?,
             GenerationsCommands::Rollback(args) => args.handle(flox).await?,
             GenerationsCommands::Switch(args) => args.handle(flox).await?,
+            GenerationsCommands::Delete(args) => args.handle(flox).await?,
+            GenerationsCommands::Prune(args) => args.handle(flox).await?,
         }
 
         Ok(())
@@ -55,9 +73,12 @@ impl GenerationsCommands {
         match self {
             GenerationsCommands::Help => "generations::help",
             GenerationsCommands::List(_) => "generations::list",
+            GenerationsCommands::Current(_) => "generations::current",
             GenerationsCommands::History(_) => "generations::history",
             GenerationsCommands::Rollback(_) => "generations::rollback",
             GenerationsCommands::Switch(_) => "generations::switch",
+            GenerationsCommands::Delete(_) => "generations::delete",
+            GenerationsCommands::Prune(_) => "generations::prune",
         }
     }
 }
@@ -0,0 +1,365 @@
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{Result, bail};
+use bpaf::Bpaf;
+use chrono::{DateTime, Utc};
+use flox_rust_sdk::flox::Flox;
+use flox_rust_sdk::models::environment::generations::{
+    AllGenerationsMetadata,
+    GenerationId,
+    GenerationsEnvironment,
+    GenerationsExt,
+};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::commands::{EnvironmentSelect, environment_select};
+use crate::environment_subcommand_metric;
+use crate::utils::duration::HumanDuration;
+use crate::utils::message;
+use crate::utils::path_info::{closure_path_infos, format_size, sum_nar_sizes};
+
+/// Arguments for the `flox generations prune` command
+#[derive(Bpaf, Debug, Clone)]
+pub struct Prune {
+    #[bpaf(external(environment_select), fallback(Default::default()))]
+    environment: EnvironmentSelect,
+
+    /// Keep the <n> most recent generations
+    #[bpaf(long, argument("n"))]
+    keep: Option<usize>,
+
+    /// Only delete generations that were last live longer than <duration> ago,
+    /// e.g. '30d' or '1w2d'
+    #[bpaf(long, argument("duration"))]
+    older_than: Option<HumanDuration>,
+
+    /// Show which generations would be deleted without deleting them
+    #[bpaf(long)]
+    dry_run: bool,
+
+    /// Print the generations that were deleted as JSON
+    #[bpaf(long)]
+    json: bool,
+}
+
+/// What was pruned by `flox generations prune`, printed with `--json`
+#[derive(Debug, PartialEq, Serialize)]
+struct PruneReport {
+    dry_run: bool,
+    generations: Vec<PrunedGeneration>,
+    /// Store space that 'flox gc' can free once the generations are deleted,
+    /// or `None` if the closures of the generations are not known locally
+    bytes_freeable: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct PrunedGeneration {
+    generation: GenerationId,
+    description: String,
+    created: DateTime<Utc>,
+    /// Links keeping the build of the generation from being garbage collected
+    links: Vec<PathBuf>,
+}
+
+impl Prune {
+    #[instrument(name = "prune", skip_all)]
+    pub async fn handle(self, mut flox: Flox) -> Result<()> {
+        if self.keep.is_none() && self.older_than.is_none() {
+            bail!("At least one of '--keep' or '--older-than' is required.");
+        }
+
+        let env = self
+            .environment
+            .detect_concrete_environment(&mut flox, "Prune generations of")
+            .await?;
+
+        environment_subcommand_metric!("generations::prune", env);
+        let mut env: GenerationsEnvironment = env.try_into()?;
+
+        let metadata = env.generations_metadata()?;
+        let to_prune = generations_to_prune(
+            &metadata,
+            self.keep,
+            self.older_than.map(|HumanDuration(older_than)| older_than),
+            Utc::now(),
+        );
+
+        let generations = metadata.generations();
+        let mut pruned = Vec::new();
+        let mut kept_links = Vec::new();
+        for generation in generations.keys().copied() {
+            let links = env.generation_links(&flox, generation)?;
+            if !to_prune.contains(&generation) {
+                kept_links.extend(links);
+                continue;
+            }
+            let generation_metadata = &generations[&generation];
+            pruned.push(PrunedGeneration {
+                generation,
+                description: generation_metadata.description.clone(),
+                created: generation_metadata.created,
+                links,
+            });
+        }
+
+        let bytes_freeable = freeable_bytes(
+            pruned.iter().flat_map(|generation| &generation.links),
+            &kept_links,
+        );
+
+        if !self.dry_run {
+            for generation in &pruned {
+                env.delete_generation(&flox, generation.generation)?;
+            }
+        }
+
+        let report = PruneReport {
+            dry_run: self.dry_run,
+            generations: pruned,
+            bytes_freeable,
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string(&report)?);
+            return Ok(());
+        }
+
+        print_report(&report);
+        Ok(())
+    }
+}
+
+fn print_report(report: &PruneReport) {
+    if report.generations.is_empty() {
+        message::info("No generations to prune.");
+        return;
+    }
+
+    for generation in &report.generations {
+        let description = generation.description.lines().next().unwrap_or_default();
+        if report.dry_run {
+            message::plain(format!(
+                "Would delete generation {}: {description}",
+                generation.generation
+            ));
+        } else {
+            message::deleted(format!(
+                "Deleted generation {}: {description}",
+                generation.generation
+            ));
+        }
+    }
+
+    match (report.dry_run, report.bytes_freeable) {
+        (true, Some(bytes)) => message::info(format!(
+            "Up to {} could be freed with 'flox gc' after pruning.",
+            format_size(bytes)
+        )),
+        (false, Some(bytes)) => message::info(format!(
+            "Run 'flox gc' to free up to {}.",
+            format_size(bytes)
+        )),
+        (true, None) => {},
+        (false, None) => {
+            message::info("Run 'flox gc' to free the store space that is no longer in use.")
+        },
+    }
+}
+
+/// Select the generations to delete.
+///
+/// The live generation is never selected.
+/// With `keep`, the `keep` most recent generations are retained.
+/// With `older_than`, only generations that were last live,
+/// or created if never live, longer than `older_than` before `now` are selected.
+/// If both are given, a generation must satisfy both to be selected.
+fn generations_to_prune(
+    metadata: &AllGenerationsMetadata,
+    keep: Option<usize>,
+    older_than: Option<Duration>,
+    now: DateTime<Utc>,
+) -> BTreeSet<GenerationId> {
+    let live = metadata.current_gen();
+    let generations = metadata.generations();
+    let kept_recent = generations
+        .keys()
+        .rev()
+        .take(keep.unwrap_or(0))
+        .copied()
+        .collect::<BTreeSet<_>>();
+
+    generations
+        .iter()
+        .filter(|(id, _)| Some(**id) != live)
+        .filter(|(id, _)| keep.is_none() || !kept_recent.contains(id))
+        .filter(|(_, generation)| {
+            let Some(older_than) = older_than else {
+                return true;
+            };
+            let last_used = generation.last_live.unwrap_or(generation.created);
+            now.signed_duration_since(last_used)
+                .to_std()
+                .is_ok_and(|age| age > older_than)
+        })
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+/// Estimate the store space that becomes collectable
+/// once the `pruned` links are removed and only the `kept` links remain.
+///
+/// Returns [None] if the closures are not available in the local store.
+pub(super) fn freeable_bytes<'a>(
+    pruned: impl IntoIterator<Item = &'a PathBuf>,
+    kept: &[PathBuf],
+) -> Option<u64> {
+    let pruned_paths = store_paths(pruned);
+    if pruned_paths.is_empty() {
+        return Some(0);
+    }
+    let kept_paths = store_paths(kept);
+
+    let pruned_closure = closure_path_infos(&pruned_paths)?;
+    let kept_closure = if kept_paths.is_empty() {
+        Default::default()
+    } else {
+        closure_path_infos(&kept_paths)?
+    };
+    sum_nar_sizes(&only_in(pruned_closure, &kept_closure))
+}
+
+/// Resolve links to the store paths they point to, skipping dangling links.
+fn store_paths<'a>(links: impl IntoIterator<Item = &'a PathBuf>) -> Vec<PathBuf> {
+    links
+        .into_iter()
+        .filter_map(|link| std::fs::read_link(link).ok())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Paths of `closure` that are not part of `other`
+fn only_in(
+    closure: serde_json::Map<String, serde_json::Value>,
+    other: &serde_json::Map<String, serde_json::Value>,
+) -> serde_json::Map<String, serde_json::Value> {
+    closure
+        .into_iter()
+        .filter(|(path, _)| !other.contains_key(path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use flox_rust_sdk::models::environment::generations::test_helpers::{
+        default_add_generation_options,
+        default_switch_generation_options,
+    };
+    use indoc::indoc;
+
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    /// Create metadata with one generation per entry of `ages`,
+    /// each created the given number of days before `now`.
+    fn metadata_with_ages(now: DateTime<Utc>, ages: &[u32]) -> AllGenerationsMetadata {
+        let mut metadata = AllGenerationsMetadata::default();
+        for age in ages {
+            let mut options = default_add_generation_options();
+            options.timestamp = now - chrono::Duration::days((*age).into());
+            metadata.add_generation(options);
+        }
+        metadata
+    }
+
+    fn ids(ids: &[usize]) -> BTreeSet<GenerationId> {
+        ids.iter().map(|id| GenerationId::from(*id)).collect()
+    }
+
+    #[test]
+    fn keep_retains_most_recent_and_live_generation() {
+        let now = Utc::now();
+        let mut metadata = metadata_with_ages(now, &[40, 30, 20, 10]);
+
+        assert_eq!(
+            generations_to_prune(&metadata, Some(2), None, now),
+            ids(&[1, 2])
+        );
+
+        metadata
+            .switch_generation(default_switch_generation_options(1.into()))
+            .unwrap();
+        assert_eq!(
+            generations_to_prune(&metadata, Some(2), None, now),
+            ids(&[2])
+        );
+        assert_eq!(
+            generations_to_prune(&metadata, Some(0), None, now),
+            ids(&[2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn older_than_uses_last_live_time() {
+        let now = Utc::now();
+        let mut metadata = metadata_with_ages(now, &[40, 30, 20, 10]);
+
+        // each generation was live until the next one was created
+        assert_eq!(
+            generations_to_prune(&metadata, None, Some(15 * DAY), now),
+            ids(&[1, 2])
+        );
+
+        // switching back to generation 1 makes generation 4 last live just now
+        let mut options = default_switch_generation_options(1.into());
+        options.timestamp = now;
+        metadata.switch_generation(options).unwrap();
+        assert_eq!(
+            generations_to_prune(&metadata, None, Some(15 * DAY), now),
+            ids(&[2])
+        );
+    }
+
+    #[test]
+    fn keep_and_older_than_must_both_hold() {
+        let now = Utc::now();
+        let metadata = metadata_with_ages(now, &[40, 30, 20, 10, 5]);
+
+        assert_eq!(
+            generations_to_prune(&metadata, Some(4), Some(25 * DAY), now),
+            ids(&[1])
+        );
+        assert_eq!(
+            generations_to_prune(&metadata, Some(1), Some(25 * DAY), now),
+            ids(&[1])
+        );
+        assert_eq!(
+            generations_to_prune(&metadata, Some(1), Some(15 * DAY), now),
+            ids(&[1, 2])
+        );
+    }
+
+    #[test]
+    fn only_in_skips_paths_shared_with_kept_closure() {
+        let pruned: serde_json::Map<_, _> = serde_json::from_str(indoc! {r#"
+            {
+              "/nix/store/a": { "narSize": 1024 },
+              "/nix/store/shared": { "narSize": 2048 }
+            }
+        "#})
+        .unwrap();
+        let kept: serde_json::Map<_, _> = serde_json::from_str(indoc! {r#"
+            {
+              "/nix/store/shared": { "narSize": 2048 },
+              "/nix/store/b": { "narSize": 4096 }
+            }
+        "#})
+        .unwrap();
+
+        assert_eq!(sum_nar_sizes(&only_in(pruned, &kept)), Some(1024));
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
 use bpaf::Bpaf;
 use flox_events::EventsHub;
+use flox_manifest::lockfile::{LockedInclude, Lockfile};
+use flox_manifest::parsed::common::IncludeDescriptor;
 use flox_rust_sdk::flox::Flox;
 use flox_rust_sdk::models::environment::Environment;
 use indoc::indoc;
@@ -18,6 +20,15 @@ pub enum IncludeCommands {
     /// Prints help information
     #[bpaf(command, hide)]
     Help,
+    /// List the environments included by an environment
+    #[bpaf(command, header(indoc! {"
+        List the environments included by an environment, in the order they
+        are merged.
+
+        For managed and remote environments, the generation that was included
+        when the environment was last locked is shown.
+    "}))]
+    List(#[bpaf(external(list))] List),
     /// Upgrade an environment with latest changes to its included environments
     #[bpaf(command, long("update"), header(indoc! {"
         Get the latest contents of included environments and merge them with the
@@ -50,6 +61,7 @@ impl IncludeCommands {
             IncludeCommands::Help => {
                 display_help(Some("include".to_string()));
             },
+            IncludeCommands::List(args) => args.handle(flox).await?,
             IncludeCommands::Upgrade(args) => args.handle(flox).await?,
         }
 
@@ -59,11 +71,64 @@ impl IncludeCommands {
     pub fn subcommand_name(&self) -> &'static str {
         match self {
             IncludeCommands::Help => "include::help",
+            IncludeCommands::List(_) => "include::list",
             IncludeCommands::Upgrade(_) => "include::upgrade",
         }
     }
 }
 
+#[derive(Bpaf, Debug, Clone)]
+pub struct List {
+    #[bpaf(external(environment_select), fallback(Default::default()))]
+    environment: EnvironmentSelect,
+}
+
+impl List {
+    #[instrument(name = "list", skip_all)]
+    pub async fn handle(self, mut flox: Flox) -> Result<()> {
+        let mut environment = self
+            .environment
+            .detect_concrete_environment(&mut flox, "List included environments of")
+            .await?;
+
+        environment_subcommand_metric!("include::list", environment);
+
+        let description = environment_description(&environment)?;
+        let lockfile: Lockfile = environment.lockfile(&flox)?.into();
+
+        let includes = lockfile
+            .compose
+            .map(|compose| compose.include)
+            .unwrap_or_default();
+
+        if includes.is_empty() {
+            message::info(format!(
+                "Environment {description} does not include any environments."
+            ));
+            return Ok(());
+        }
+
+        for include in &includes {
+            println!("{}", format_include(include));
+        }
+
+        Ok(())
+    }
+}
+
+/// Format an included environment as `<name>: <source> [(generation <N>)]`
+fn format_include(include: &LockedInclude) -> String {
+    let source = match &include.descriptor {
+        IncludeDescriptor::Local { dir, .. } => dir.to_string_lossy().to_string(),
+        IncludeDescriptor::Remote { remote, .. } => remote.to_string(),
+    };
+
+    match include.generation {
+        Some(generation) => format!("{}: {source} (generation {generation})", include.name),
+        None => format!("{}: {source}", include.name),
+    }
+}
+
 impl Upgrade {
     #[instrument(name = "upgrade", skip_all)]
     pub async fn handle(self, mut flox: Flox) -> Result<()> {
@@ -120,3 +185,41 @@ impl Upgrade {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use flox_manifest::Manifest;
+    use flox_manifest::interfaces::AsTypedOnlyManifest;
+
+    use super::*;
+
+    #[test]
+    fn format_include_shows_generation_when_known() {
+        let manifest = Manifest::parse_toml_typed("version = 1")
+            .unwrap()
+            .as_typed_only();
+
+        let local = LockedInclude {
+            manifest: manifest.clone(),
+            name: "dep".to_string(),
+            descriptor: IncludeDescriptor::Local {
+                dir: "../dep".into(),
+                name: None,
+            },
+            generation: None,
+        };
+        assert_eq!(format_include(&local), "dep: ../dep");
+
+        let remote = LockedInclude {
+            manifest,
+            name: "tools".to_string(),
+            descriptor: IncludeDescriptor::Remote {
+                remote: "owner/tools".parse().unwrap(),
+                name: None,
+                generation: None,
+            },
+            generation: Some(3),
+        };
+        assert_eq!(format_include(&remote), "tools: owner/tools (generation 3)");
+    }
+}
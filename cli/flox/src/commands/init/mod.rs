@@ -1,4 +1,7 @@
+use std::borrow::Cow;
 use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
@@ -6,14 +9,22 @@ use anyhow::{Context, Result, anyhow, bail};
 use bpaf::Bpaf;
 use flox_core::activate::mode::ActivateMode;
 use flox_core::data::environment_ref::{DEFAULT_NAME, EnvironmentName, RemoteEnvironmentRef};
+use flox_manifest::MANIFEST_FILENAME;
+use flox_manifest::lockfile::LOCKFILE_FILENAME;
 use flox_manifest::raw::{CatalogPackage, PackageToInstall};
 use flox_rust_sdk::data::AttrPath;
 use flox_rust_sdk::flox::Flox;
 use flox_rust_sdk::models::environment::path_environment::{InitCustomization, PathEnvironment};
 use flox_rust_sdk::models::environment::remote_environment::RemoteEnvironment;
-use flox_rust_sdk::models::environment::{ConcreteEnvironment, Environment, PathPointer};
+use flox_rust_sdk::models::environment::{
+    ConcreteEnvironment,
+    DOT_FLOX,
+    ENV_DIR_NAME,
+    Environment,
+    PathPointer,
+};
 use flox_rust_sdk::providers::catalog::ALL_SYSTEMS;
-use flox_rust_sdk::providers::git::{GitCommandProvider, GitProvider};
+use flox_rust_sdk::providers::git::{GitCommandProvider, GitDiscoverError, GitProvider};
 use flox_rust_sdk::providers::manifest_init::ManifestInitializer;
 use floxhub_client::{CatalogClientTrait, PackageDescriptor, PackageGroup, PackageResolutionInfo};
 use indoc::{formatdoc, indoc};
@@ -34,6 +45,8 @@ use node::Node;
 use python::Python;
 
 const AUTO_SETUP_HINT: &str = "Use '--auto-setup' to apply Flox recommendations in the future.";
+/// Identifies pre-commit hooks installed by `flox init --git-hooks`
+const PRE_COMMIT_HOOK_MARKER: &str = "# Installed by 'flox init --git-hooks'";
 
 /// The different types of init customizations
 #[derive(Debug)]
@@ -98,9 +111,15 @@ pub struct Init {
     #[bpaf(external(init_environment_type_select))]
     type_select: InitEnvironmentTypeSelect,
 
-    /// Set up the environment with the emptiest possible manifest.
-    #[bpaf(short, long)]
+    /// Set up the environment with the emptiest possible manifest,
+    /// containing only the schema version.
+    #[bpaf(short, long, long("no-default-options"))]
     bare: bool,
+
+    /// Install a git pre-commit hook that checks that the environment's
+    /// lockfile is up to date
+    #[bpaf(long)]
+    git_hooks: bool,
 }
 
 impl Init {
@@ -148,9 +167,24 @@ impl Init {
                     self.bare,
                 );
 
+                // Check where the hook goes before creating the environment,
+                // so that an unusable repository doesn't leave a half-done init behind.
+                let pre_commit_hook = if self.git_hooks {
+                    prepare_pre_commit_hook(&dir)?
+                } else {
+                    None
+                };
+
                 init_local_environment(&flox, &dir, &env_name, self.bare, do_auto_setup).await?;
+
+                if let Some(pre_commit_hook) = pre_commit_hook {
+                    install_pre_commit_hook(&pre_commit_hook)?;
+                }
             },
             InitEnvironmentTypeSelect::FloxHub { environment_ref } => {
+                if self.git_hooks {
+                    bail!("'--git-hooks' cannot be used with '--reference'");
+                }
                 let mut flox = flox;
                 ensure_auth(&mut flox).await?;
                 init_floxhub_environment_decorated(&flox, environment_ref, self.bare)?;
@@ -280,6 +314,108 @@ async fn init_local_environment(
     Ok(())
 }
 
+/// A git pre-commit hook that checks that the lockfile of an environment
+/// is up to date
+#[derive(Debug)]
+struct PreCommitHook {
+    /// The hooks directory of the git repository
+    hooks_dir: PathBuf,
+    /// The environment directory, relative to the root of the repository
+    env_dir: PathBuf,
+}
+
+/// Determine the pre-commit hook for the environment in `dir`.
+///
+/// Returns [None] if `dir` is not in a git repository,
+/// and fails if the environment would not be inside the repository.
+/// `dir` must exist, but the environment doesn't have to be initialized yet.
+fn prepare_pre_commit_hook(dir: &Path) -> Result<Option<PreCommitHook>> {
+    let git = match GitCommandProvider::discover(dir) {
+        Ok(git) => git,
+        Err(err) if err.not_found() => {
+            message::info("Not in a git repository, no git hooks will be installed.");
+            return Ok(None);
+        },
+        Err(err) => Err(err).context("Failed to find the git repository")?,
+    };
+
+    // Hooks run from the root of the repository
+    let env_dir = dir
+        .canonicalize()
+        .context("Couldn't resolve the environment directory")?
+        .join(DOT_FLOX)
+        .join(ENV_DIR_NAME);
+    let Ok(env_dir) = env_dir.strip_prefix(git.path()) else {
+        bail!(formatdoc! {"
+            The environment in '{}' would not be inside the git repository at '{}'.
+            Initialize the environment without '--git-hooks'.",
+            dir.display(),
+            git.path().display(),
+        });
+    };
+
+    Ok(Some(PreCommitHook {
+        hooks_dir: git.hooks_dir()?,
+        env_dir: env_dir.to_path_buf(),
+    }))
+}
+
+/// Install a git pre-commit hook that checks that the lockfile of the
+/// environment is up to date.
+///
+/// Keeps existing pre-commit hooks that were not installed by Flox.
+fn install_pre_commit_hook(PreCommitHook { hooks_dir, env_dir }: &PreCommitHook) -> Result<()> {
+    let hook_path = hooks_dir.join("pre-commit");
+    if hook_path.exists() && !fs::read_to_string(&hook_path)?.contains(PRE_COMMIT_HOOK_MARKER) {
+        message::warning(format!(
+            "A pre-commit hook already exists at '{}', it was not replaced.",
+            hook_path.display()
+        ));
+        return Ok(());
+    }
+
+    fs::create_dir_all(hooks_dir)?;
+    fs::write(&hook_path, pre_commit_hook(env_dir))?;
+    fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+
+    message::created(format!(
+        "Installed a git pre-commit hook at '{}'",
+        hook_path.display()
+    ));
+    Ok(())
+}
+
+/// The script of the pre-commit hook for the environment at `env_dir`,
+/// relative to the root of the repository.
+///
+/// The check can be skipped with `git commit --no-verify`.
+fn pre_commit_hook(env_dir: &Path) -> String {
+    let quote = |path: PathBuf| {
+        shell_escape::escape(Cow::Owned(path.to_string_lossy().into_owned())).into_owned()
+    };
+    let manifest = quote(env_dir.join(MANIFEST_FILENAME));
+    let lockfile = quote(env_dir.join(LOCKFILE_FILENAME));
+
+    formatdoc! {r#"
+        #!/bin/sh
+        {PRE_COMMIT_HOOK_MARKER}
+        #
+        # Checks that the lockfile of the Flox environment is up to date.
+        # Skip the check with 'git commit --no-verify'.
+
+        # Nothing to check for environments that aren't locked
+        [ -f {lockfile} ] || exit 0
+
+        if ! command -v flox > /dev/null 2>&1; then
+          echo "error: 'flox' was not found on PATH, so the lockfile can't be checked." >&2
+          echo "Add Flox to PATH, or skip the check with 'git commit --no-verify'." >&2
+          exit 1
+        fi
+
+        exec flox lock-manifest --check --lockfile {lockfile} {manifest}
+    "#}
+}
+
 /// Same as [RemoteEnvironment::init_floxhub_environment]
 /// but with added decoration/messaging on success.
 fn init_floxhub_environment_decorated(
@@ -523,6 +659,8 @@ impl From<ProvidedPackage> for CatalogPackage {
             version: value.version,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         }
     }
 }
@@ -747,6 +885,8 @@ mod tests {
                         version: None,
                         systems: None,
                         outputs: None,
+                        pkg_group: None,
+                        allow_missing_builds: false,
                     },
                     CatalogPackage {
                         id: "package2".to_string(),
@@ -754,6 +894,8 @@ mod tests {
                         version: None,
                         systems: None,
                         outputs: None,
+                        pkg_group: None,
+                        allow_missing_builds: false,
                     },
                 ]),
                 activate_mode: None,
@@ -772,6 +914,8 @@ mod tests {
                         version: None,
                         systems: None,
                         outputs: None,
+                        pkg_group: None,
+                        allow_missing_builds: false,
                     },
                     CatalogPackage {
                         id: "package1".to_string(),
@@ -779,6 +923,8 @@ mod tests {
                         version: None,
                         systems: None,
                         outputs: None,
+                        pkg_group: None,
+                        allow_missing_builds: false,
                     },
                 ]),
                 activate_mode: None,
@@ -867,21 +1013,27 @@ mod tests {
                     pkg_path: "path1".to_string(),
                     version: None,
                     systems: None,
-                    outputs: None
+                    outputs: None,
+                    pkg_group: None,
+                    allow_missing_builds: false,
                 },
                 CatalogPackage {
                     id: "package2".to_string(),
                     pkg_path: "path2".to_string(),
                     version: None,
                     systems: None,
-                    outputs: None
+                    outputs: None,
+                    pkg_group: None,
+                    allow_missing_builds: false,
                 },
                 CatalogPackage {
                     id: "pip".to_string(),
                     pkg_path: "python311Packages.pip".to_string(),
                     version: None,
                     systems: None,
-                    outputs: None
+                    outputs: None,
+                    pkg_group: None,
+                    allow_missing_builds: false,
                 },
             ]),
             activate_mode: None,
@@ -905,6 +1057,8 @@ mod tests {
                 version: Some("1.0.0".to_string()),
                 systems: None,
                 outputs: None,
+                pkg_group: None,
+                allow_missing_builds: false,
             }]),
             activate_mode: None,
         };
@@ -980,4 +1134,76 @@ mod tests {
         RemoteEnvironment::new(&flox, ManagedPointer::new(owner, name, &flox.floxhub), None)
             .expect("find initialized remote environment");
     }
+
+    #[test]
+    fn pre_commit_hook_checks_lockfile() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        GitCommandProvider::init(dir, false).unwrap();
+
+        let pre_commit_hook = prepare_pre_commit_hook(dir).unwrap().unwrap();
+        install_pre_commit_hook(&pre_commit_hook).unwrap();
+
+        let hook_path = dir.join(".git/hooks/pre-commit");
+        let hook = fs::read_to_string(&hook_path).unwrap();
+        assert!(hook.contains(PRE_COMMIT_HOOK_MARKER));
+        assert!(hook.contains(
+            "flox lock-manifest --check --lockfile .flox/env/manifest.lock .flox/env/manifest.toml"
+        ));
+        let mode = fs::metadata(&hook_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o111, 0o111);
+    }
+
+    #[test]
+    fn pre_commit_hook_keeps_existing_hook() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        GitCommandProvider::init(dir, false).unwrap();
+        let hook_path = dir.join(".git/hooks/pre-commit");
+        fs::create_dir_all(hook_path.parent().unwrap()).unwrap();
+        fs::write(&hook_path, "#!/bin/sh\nexit 0\n").unwrap();
+
+        let pre_commit_hook = prepare_pre_commit_hook(dir).unwrap().unwrap();
+        install_pre_commit_hook(&pre_commit_hook).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&hook_path).unwrap(),
+            "#!/bin/sh\nexit 0\n"
+        );
+    }
+
+    #[test]
+    fn pre_commit_hook_fails_without_flox() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = tempdir.path();
+        GitCommandProvider::init(dir, false).unwrap();
+        let pre_commit_hook = prepare_pre_commit_hook(dir).unwrap().unwrap();
+        install_pre_commit_hook(&pre_commit_hook).unwrap();
+
+        let env_dir = dir.join(DOT_FLOX).join(ENV_DIR_NAME);
+        fs::create_dir_all(&env_dir).unwrap();
+        fs::write(env_dir.join(LOCKFILE_FILENAME), "{}").unwrap();
+
+        let output = std::process::Command::new("/bin/sh")
+            .arg(".git/hooks/pre-commit")
+            .current_dir(dir)
+            .env_clear()
+            .env("PATH", dir.join("empty"))
+            .output()
+            .unwrap();
+
+        assert!(!output.status.success());
+        assert!(
+            String::from_utf8_lossy(&output.stderr).contains("'flox' was not found on PATH"),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn pre_commit_hook_outside_git_repo_is_noop() {
+        let tempdir = tempfile::tempdir().unwrap();
+        assert!(prepare_pre_commit_hook(tempdir.path()).unwrap().is_none());
+        assert!(!tempdir.path().join(".git").exists());
+    }
 }
@@ -1054,6 +1054,8 @@ impl InitHook for Node {
                     version: yarn_install.yarn.version.clone(),
                     systems: None,
                     outputs: None,
+                    pkg_group: None,
+                    allow_missing_builds: false,
                 });
                 packages.push(CatalogPackage {
                     id: "node".to_string(),
@@ -1063,6 +1065,8 @@ impl InitHook for Node {
                     version: yarn_install.node.version.clone(),
                     systems: None,
                     outputs: None,
+                    pkg_group: None,
+                    allow_missing_builds: false,
                 });
                 Some(YARN_HOOK.to_string())
             },
@@ -1074,6 +1078,8 @@ impl InitHook for Node {
                         version: result.version.clone(),
                         systems: None,
                         outputs: None,
+                        pkg_group: None,
+                        allow_missing_builds: false,
                     },
                     None => CatalogPackage {
                         id: "nodejs".to_string(),
@@ -1081,6 +1087,8 @@ impl InitHook for Node {
                         version: None,
                         systems: None,
                         outputs: None,
+                        pkg_group: None,
+                        allow_missing_builds: false,
                     },
                 };
                 packages.push(nodejs_to_install);
@@ -1099,6 +1107,8 @@ impl InitHook for Node {
                     version: yarn_install.pkg.version.clone(),
                     systems: None,
                     outputs: None,
+                    pkg_group: None,
+                    allow_missing_builds: false,
                 });
                 Some(YARN_HOOK.to_string())
             },
@@ -1503,6 +1513,8 @@ mod tests {
                         version: Some("1".to_string()),
                         systems: None,
                         outputs: None,
+                        pkg_group: None,
+                        allow_missing_builds: false,
                     },
                     CatalogPackage {
                         id: "node".to_string(),
@@ -1510,6 +1522,8 @@ mod tests {
                         version: None,
                         systems: None,
                         outputs: None,
+                        pkg_group: None,
+                        allow_missing_builds: false,
                     }
                 ]),
                 hook_on_activate: Some(YARN_HOOK.to_string()),
@@ -1543,6 +1557,8 @@ mod tests {
                     version: Some("1".to_string()),
                     systems: None,
                     outputs: None,
+                    pkg_group: None,
+                    allow_missing_builds: false,
                 }]),
                 ..Default::default()
             }
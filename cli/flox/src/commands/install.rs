@@ -40,6 +40,7 @@ use flox_rust_sdk::models::user_state::{
 use flox_rust_sdk::providers::lock_manifest::{
     ResolutionFailure,
     ResolutionFailures,
+    ResolutionOverrides,
     ResolveError,
 };
 use floxhub_client::{MsgAttrPathNotFoundNotFoundForAllSystems, MsgAttrPathNotFoundNotInCatalog};
@@ -75,6 +76,34 @@ pub struct Install {
     #[bpaf(external(pkg_with_id_option), many)]
     id: Vec<PkgWithIdOption>,
 
+    /// Allow broken packages for this install only,
+    /// without setting 'options.allow.broken' in the manifest
+    #[bpaf(long)]
+    allow_broken: bool,
+
+    /// Allow the insecure package <iid> for this install only,
+    /// without adding it to 'options.allow.insecure' in the manifest.
+    /// Can be specified multiple times.
+    #[bpaf(long("allow-insecure"), argument("iid"))]
+    allow_insecure: Vec<String>,
+
+    /// Add the packages to package group <name>,
+    /// i.e. set 'pkg-group' in their descriptors
+    #[bpaf(long, argument("name"))]
+    to_group: Option<String>,
+
+    /// Lock the packages even if a build is missing for some systems,
+    /// i.e. set 'allow-missing-builds' in their descriptors.
+    /// The packages are built from source on those systems.
+    #[bpaf(long)]
+    optional: bool,
+
+    /// Add the packages to the manifest without locking the environment.
+    /// The environment is locked when it is next activated,
+    /// so several packages can be added and locked at once.
+    #[bpaf(long("no-lock"))]
+    no_lock: bool,
+
     /// Packages specified without explicit ids
     #[bpaf(positional("packages"))]
     packages: Vec<String>,
@@ -157,6 +186,32 @@ impl Install {
         if packages_to_install.is_empty() {
             bail!("Must specify at least one package");
         }
+        if let Some(ref group) = self.to_group {
+            set_pkg_group(&mut packages_to_install, group)?;
+        }
+        if self.optional {
+            set_allow_missing_builds(&mut packages_to_install)?;
+        }
+
+        if self.no_lock && self.allow_broken {
+            bail!("'--no-lock' cannot be used with '--allow-broken'");
+        }
+
+        if self.allow_broken {
+            message::warning(formatdoc! {"
+                Allowing broken packages for this install only.
+                Broken packages may fail to build or run.
+                The allowance isn't recorded in the manifest, so locking the environment again
+                will fail until 'options.allow.broken' is set or the broken packages are removed."});
+        }
+
+        if self.no_lock && !self.allow_insecure.is_empty() {
+            bail!("'--no-lock' cannot be used with '--allow-insecure'");
+        }
+
+        if !self.allow_insecure.is_empty() {
+            message::insecure_packages_allowed(&self.allow_insecure, "install");
+        }
 
         let mut concrete_environment = match self
             .environment
@@ -195,6 +250,14 @@ impl Install {
 
         let description = environment_description(&concrete_environment)?;
 
+        if self.no_lock {
+            return Self::stage_packages(
+                &mut concrete_environment,
+                &packages_to_install,
+                &description,
+            );
+        }
+
         // Get a list of the packages that this environment is already overriding via composition.
         let maybe_lockfile = concrete_environment.existing_lockfile(&flox)?;
         let existing_composer_package_overrides = if let Some(lockfile) = maybe_lockfile {
@@ -220,8 +283,12 @@ impl Install {
                 packages_to_install.len()
             )
         );
+        let overrides = ResolutionOverrides {
+            allow_broken: self.allow_broken,
+            allow_insecure: self.allow_insecure.clone(),
+        };
         let installation =
-            span.in_scope(|| concrete_environment.install(&packages_to_install, &flox));
+            span.in_scope(|| concrete_environment.install(&packages_to_install, &flox, &overrides));
 
         let (packages_retried, installation) = match installation {
             Ok(installation) => (None, installation),
@@ -234,6 +301,7 @@ impl Install {
                         &mut concrete_environment,
                         failures,
                         &packages_retry,
+                        &overrides,
                     );
                     match res {
                         Ok(installation) => (Some(packages_retry), installation),
@@ -317,6 +385,44 @@ impl Install {
         Ok(())
     }
 
+    /// Add packages to the manifest of a local environment without locking it,
+    /// for `--no-lock`.
+    fn stage_packages(
+        environment: &mut ConcreteEnvironment,
+        packages: &[PackageToInstall],
+        description: &str,
+    ) -> Result<()> {
+        let ConcreteEnvironment::Path(environment) = environment else {
+            bail!(formatdoc! {"
+                '--no-lock' is only supported for local environments that are not on FloxHub.
+                Install the packages without '--no-lock' instead."});
+        };
+
+        let attempt = environment.stage_install(packages)?;
+
+        let staged = packages
+            .iter()
+            .map(|pkg| PackageToInstallRetry {
+                system_subset: false,
+                pkg: pkg.clone(),
+            })
+            .collect::<Vec<_>>();
+        let partitioned = Self::partition_installed_packages(&staged, &attempt);
+
+        message::packages_staged(&partitioned.successes, description);
+        message::packages_already_installed(&partitioned.already_installed, description);
+        message::packages_outputs_updated(&partitioned.outputs_updated, description);
+
+        if !attempt.modifications.is_empty() {
+            message::warning(formatdoc! {"
+                Environment {description} is out of sync with its manifest.
+                It is locked when it is next activated,
+                or lock it now by saving the manifest with 'flox edit'."});
+        }
+
+        Ok(())
+    }
+
     fn format_packages_for_tracing(packages: &[PackageToInstall]) -> String {
         packages.iter().map(Install::package_identifier).join(",")
     }
@@ -452,6 +558,7 @@ impl Install {
         environment: &mut dyn Environment,
         failures: ResolutionFailures,
         packages_to_retry: &[PackageToInstallRetry],
+        overrides: &ResolutionOverrides,
     ) -> Result<InstallationAttempt, EnvironmentError> {
         let span = span!(
             tracing::Level::INFO,
@@ -463,7 +570,8 @@ impl Install {
             .cloned()
             .map(|p| p.pkg)
             .collect::<Vec<_>>();
-        let install_result = span.in_scope(|| environment.install(&packages_installable, flox));
+        let install_result =
+            span.in_scope(|| environment.install(&packages_installable, flox, overrides));
 
         match install_result {
             Ok(install_attempt) => Ok(install_attempt),
@@ -695,6 +803,41 @@ async fn try_create_default_environment_interactive(
 
     Ok(ConcreteEnvironment::Remote(env))
 }
+
+/// Set the package group of all `packages` to `group`.
+///
+/// Only catalog packages can be grouped,
+/// so this fails if any of `packages` is a flake or store path.
+fn set_pkg_group(packages: &mut [PackageToInstall], group: &str) -> Result<()> {
+    for package in packages {
+        match package {
+            PackageToInstall::Catalog(pkg) => pkg.pkg_group = Some(group.to_string()),
+            PackageToInstall::Flake(_) | PackageToInstall::StorePath(_) => bail!(
+                "'--to-group' can only be used with catalog packages, but '{}' is not a catalog package",
+                package.id()
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Allow all `packages` to be locked without a build for some systems.
+///
+/// Only catalog packages have builds in the catalog,
+/// so this fails if any of `packages` is a flake or store path.
+fn set_allow_missing_builds(packages: &mut [PackageToInstall]) -> Result<()> {
+    for package in packages {
+        match package {
+            PackageToInstall::Catalog(pkg) => pkg.allow_missing_builds = true,
+            PackageToInstall::Flake(_) | PackageToInstall::StorePath(_) => bail!(
+                "'--optional' can only be used with catalog packages, but '{}' is not a catalog package",
+                package.id()
+            ),
+        }
+    }
+    Ok(())
+}
+
 /// Returns a formatted string representing a possibly truncated list of
 /// packages to install.
 fn package_list_for_prompt(packages: &[PackageToInstall]) -> Option<String> {
@@ -832,7 +975,7 @@ mod tests {
 
     use super::{add_activation_to_rc_file, ensure_rc_file_exists};
     use crate::commands::EnvironmentSelect;
-    use crate::commands::install::{Install, package_list_for_prompt};
+    use crate::commands::install::{Install, package_list_for_prompt, set_pkg_group};
     use crate::utils::message;
 
     /// [Install::generate_warnings] shouldn't warn for packages not in packages_to_install
@@ -858,6 +1001,8 @@ mod tests {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         }];
         assert_eq!(
             Install::generate_unfree_and_broken_warnings(
@@ -890,6 +1035,8 @@ mod tests {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         }];
         assert_eq!(
             Install::generate_unfree_and_broken_warnings(
@@ -914,6 +1061,8 @@ mod tests {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         }];
         assert_eq!(
             Install::generate_unfree_and_broken_warnings(
@@ -946,6 +1095,8 @@ mod tests {
             version: None,
             systems: None,
             outputs: None,
+            pkg_group: None,
+            allow_missing_builds: false,
         }];
         assert_eq!(
             Install::generate_unfree_and_broken_warnings(
@@ -979,6 +1130,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn set_pkg_group_only_accepts_catalog_packages() {
+        let mut packages = vec![
+            PackageToInstall::parse(&"dummy-system".to_string(), "hello").unwrap(),
+            PackageToInstall::parse(&"dummy-system".to_string(), "ripgrep").unwrap(),
+        ];
+        set_pkg_group(&mut packages, "tools").unwrap();
+        for package in &packages {
+            let PackageToInstall::Catalog(pkg) = package else {
+                panic!("expected a catalog package");
+            };
+            assert_eq!(pkg.pkg_group.as_deref(), Some("tools"));
+        }
+
+        let mut packages = vec![
+            PackageToInstall::parse(&"dummy-system".to_string(), "github:nixos/nixpkgs#hello")
+                .unwrap(),
+        ];
+        let err = set_pkg_group(&mut packages, "tools").unwrap_err();
+        assert!(err.to_string().contains("catalog packages"), "{err}");
+    }
+
+    #[test]
+    fn set_allow_missing_builds_only_accepts_catalog_packages() {
+        let mut packages =
+            vec![PackageToInstall::parse(&"dummy-system".to_string(), "hello").unwrap()];
+        set_allow_missing_builds(&mut packages).unwrap();
+        let PackageToInstall::Catalog(pkg) = &packages[0] else {
+            panic!("expected a catalog package");
+        };
+        assert!(pkg.allow_missing_builds);
+
+        let mut packages = vec![
+            PackageToInstall::parse(&"dummy-system".to_string(), "github:nixos/nixpkgs#hello")
+                .unwrap(),
+        ];
+        let err = set_allow_missing_builds(&mut packages).unwrap_err();
+        assert!(err.to_string().contains("catalog packages"), "{err}");
+    }
+
     #[test]
     fn creates_rc_file_if_parent_doesnt_exist() {
         let tmpdir = tempfile::tempdir().unwrap();
@@ -1030,6 +1221,11 @@ mod tests {
         let install_cmd = Install {
             environment: EnvironmentSelect::Dir(tempdir.path().to_path_buf()),
             id: vec![],
+            allow_broken: false,
+            allow_insecure: vec![],
+            to_group: None,
+            optional: false,
+            no_lock: false,
             packages: vec![pkg_path.to_string()],
         };
         install_cmd
@@ -1,11 +1,21 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
 use std::io::{Write, stdout};
 use std::str::FromStr;
 
 use anyhow::{Result, bail};
 use bpaf::Bpaf;
 use flox_events::EventsHub;
-use flox_manifest::interfaces::{AsWritableManifest, WriteManifest};
-use flox_manifest::lockfile::{LockedInstallable, LockedPackageFlake, Lockfile, PackageToList};
+use flox_manifest::interfaces::{AsLatestSchema, AsWritableManifest, WriteManifest};
+use flox_manifest::lockfile::{
+    LockedInstallable,
+    LockedPackage,
+    LockedPackageFlake,
+    Lockfile,
+    PackageToList,
+};
+use flox_manifest::parsed::latest::{ManifestLatest, ManifestPackageDescriptor};
+use flox_manifest::raw::DEFAULT_SYSTEMS_STR;
 use flox_rust_sdk::flox::Flox;
 use flox_rust_sdk::models::environment::generations::GenerationsExt;
 use flox_rust_sdk::models::environment::{
@@ -14,9 +24,12 @@ use flox_rust_sdk::models::environment::{
     SingleSystemUpgradeDiff,
 };
 use flox_rust_sdk::providers::buildenv::get_installed_outputs;
+use flox_rust_sdk::providers::lock_manifest::{LockManifest, ResolveError};
 use flox_rust_sdk::providers::upgrade_checks::UpgradeInformationGuard;
 use indoc::formatdoc;
 use itertools::Itertools;
+use serde::Serialize;
+use toml_edit::{DocumentMut, Item};
 use tracing::{debug, instrument};
 
 use super::{EnvironmentSelect, environment_select};
@@ -24,6 +37,7 @@ use crate::commands::render_composition_manifest;
 use crate::environment_subcommand_metric;
 use crate::utils::events::env_detail_from_concrete;
 use crate::utils::message;
+use crate::utils::path_info::store_path_infos;
 use crate::utils::tracing::sentry_set_tag;
 
 // List packages installed in an environment
@@ -35,10 +49,51 @@ pub struct List {
     #[bpaf(long, short)]
     upstream: bool,
 
+    /// Append a one-line description to each package in the extended output
+    #[bpaf(long)]
+    with_descriptions: bool,
+
+    /// List packages in the order they are declared in the manifest's
+    /// '[install]' table
+    #[bpaf(long)]
+    manifest_order: bool,
+
+    /// Show whether each package is installed by the manifest itself or
+    /// provided by an included environment
+    #[bpaf(long)]
+    installed_from: bool,
+
+    /// Show how long ago the catalog revision of each package was created
+    #[bpaf(long)]
+    age: bool,
+
+    /// List the oldest packages first, showing their age as with '--age'
+    #[bpaf(long)]
+    sort_by_age: bool,
+
+    #[bpaf(external(json_output), optional)]
+    json: Option<JsonOutput>,
+
     #[bpaf(external(list_mode), fallback(ListMode::Extended))]
     list_mode: ListMode,
 }
 
+#[derive(Bpaf, Clone, PartialEq, Debug)]
+struct JsonOutput {
+    /// Format the listing as compact JSON
+    #[bpaf(long("json"), req_flag(()))]
+    json: (),
+
+    /// Indent the JSON output
+    #[bpaf(long)]
+    pretty: bool,
+
+    /// Print the JSON output in the shape of schema version <n>,
+    /// e.g. for tools that expect an older shape
+    #[bpaf(long, argument("n"))]
+    schema_version: Option<u8>,
+}
+
 #[derive(Bpaf, Clone, PartialEq, Debug)]
 pub enum ListMode {
     /// Show the raw contents of the manifest
@@ -56,6 +111,114 @@ pub enum ListMode {
     /// Show all available package information including priority and license
     #[bpaf(long, short)]
     All,
+
+    /// Report locked packages that are not allowed by the manifest's
+    /// 'options.allow', without re-locking the environment
+    #[bpaf(long)]
+    CheckAllowed,
+
+    /// Report install IDs where the manifest and the existing lockfile
+    /// disagree for the current system, without re-locking the environment
+    #[bpaf(long)]
+    DiffManifest,
+
+    /// Print the locked URLs of the flakes that flake packages are installed
+    /// from, for all systems
+    #[bpaf(long)]
+    FlakeInputs,
+
+    /// Check whether the outputs of flake packages for the current system
+    /// are signed, reporting unsigned paths and paths that can't be verified
+    /// because they are not in the local store
+    #[bpaf(long)]
+    VerifySignatures,
+}
+
+impl ListMode {
+    /// The flag that selects the mode, e.g. for error messages
+    fn flag(&self) -> &'static str {
+        match self {
+            ListMode::Config => "--config",
+            ListMode::NameOnly => "--name",
+            ListMode::Extended => "--extended",
+            ListMode::All => "--all",
+            ListMode::CheckAllowed => "--check-allowed",
+            ListMode::DiffManifest => "--diff-manifest",
+            ListMode::FlakeInputs => "--flake-inputs",
+            ListMode::VerifySignatures => "--verify-signatures",
+        }
+    }
+}
+
+/// Install IDs for which the manifest and the lockfile disagree on a system
+#[derive(Debug, Default, PartialEq)]
+struct ManifestDrift {
+    /// Installed by the manifest for the system, but not locked (needs lock)
+    needs_lock: BTreeSet<String>,
+    /// Locked for the system, but no longer installed on it by the manifest
+    /// or any included environment (stale lock)
+    stale_lock: BTreeSet<String>,
+}
+
+impl ManifestDrift {
+    /// Compare the packages `manifest` installs on `system`
+    /// with the packages `lockfile` has locked for `system`,
+    /// keyed by install ID like the seed mapping used when re-locking.
+    fn new(manifest: &ManifestLatest, lockfile: &Lockfile, system: &str) -> Self {
+        let manifest_systems = manifest
+            .options
+            .systems
+            .as_deref()
+            .unwrap_or(&*DEFAULT_SYSTEMS_STR);
+        let declared = manifest
+            .install
+            .inner()
+            .iter()
+            .filter(|(_, descriptor)| {
+                let package_systems = match descriptor {
+                    ManifestPackageDescriptor::Catalog(d) => d.systems.as_deref(),
+                    ManifestPackageDescriptor::FlakeRef(d) => d.systems.as_deref(),
+                    ManifestPackageDescriptor::StorePath(d) => d.systems.as_deref(),
+                };
+                package_systems
+                    .unwrap_or(manifest_systems)
+                    .iter()
+                    .any(|s| s == system)
+            })
+            .map(|(install_id, _)| install_id.as_str())
+            .collect::<BTreeSet<_>>();
+        let locked = lockfile
+            .packages
+            .iter()
+            .filter(|package| package.system() == system)
+            .map(LockedPackage::install_id)
+            .collect::<BTreeSet<_>>();
+
+        let needs_lock = declared
+            .difference(&locked)
+            .map(|install_id| install_id.to_string())
+            .collect();
+        let stale_lock = locked
+            .difference(&declared)
+            .filter(|install_id| {
+                lockfile
+                    .compose
+                    .as_ref()
+                    .and_then(|compose| compose.include_providing_install_id(install_id))
+                    .is_none()
+            })
+            .map(|install_id| install_id.to_string())
+            .collect();
+
+        Self {
+            needs_lock,
+            stale_lock,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.needs_lock.is_empty() && self.stale_lock.is_empty()
+    }
 }
 
 impl List {
@@ -74,6 +237,75 @@ impl List {
             debug!(error = %err, "Failed to record v2 event");
         }
 
+        if self.upstream
+            && matches!(
+                self.list_mode,
+                ListMode::CheckAllowed | ListMode::DiffManifest
+            )
+        {
+            bail!(
+                "'{}' cannot be used with '--upstream'",
+                self.list_mode.flag()
+            );
+        }
+
+        if self.manifest_order
+            && !matches!(
+                self.list_mode,
+                ListMode::NameOnly | ListMode::Extended | ListMode::All
+            )
+        {
+            bail!(
+                "'--manifest-order' cannot be used with '{}'",
+                self.list_mode.flag()
+            );
+        }
+
+        let extended_only = [
+            ("--installed-from", self.installed_from),
+            ("--age", self.age),
+            ("--sort-by-age", self.sort_by_age),
+        ];
+        for (flag, _) in extended_only.iter().filter(|(_, used)| *used) {
+            if self.list_mode != ListMode::Extended {
+                bail!("'{flag}' cannot be used with '{}'", self.list_mode.flag());
+            }
+        }
+
+        if self.sort_by_age && self.manifest_order {
+            bail!("'--sort-by-age' cannot be used with '--manifest-order'");
+        }
+
+        if let Some(json) = &self.json {
+            if !matches!(self.list_mode, ListMode::Extended | ListMode::FlakeInputs) {
+                bail!("'--json' cannot be used with '{}'", self.list_mode.flag());
+            }
+            if self.age || self.sort_by_age {
+                let flag = if self.sort_by_age {
+                    "--sort-by-age"
+                } else {
+                    "--age"
+                };
+                bail!("'{flag}' cannot be used with '--json'");
+            }
+            if let Some(schema_version) = json.schema_version {
+                if self.list_mode == ListMode::FlakeInputs {
+                    bail!("'--schema-version' cannot be used with '--flake-inputs'");
+                }
+                if !(1..=LIST_JSON_SCHEMA_VERSION).contains(&schema_version) {
+                    bail!(
+                        "unsupported schema version '{schema_version}', expected a version between 1 and {LIST_JSON_SCHEMA_VERSION}"
+                    );
+                }
+            }
+        }
+
+        match self.list_mode {
+            ListMode::CheckAllowed => return Self::check_allowed(&flox, &mut env),
+            ListMode::DiffManifest => return Self::diff_manifest(&flox, &mut env),
+            _ => {},
+        }
+
         let (manifest_contents, lockfile) = match (&mut env, self.upstream) {
             (ConcreteEnvironment::Path(_), true) => {
                 bail!("'--upstream' cannot be used with path environments");
@@ -110,13 +342,51 @@ impl List {
             ),
         };
 
-        if self.list_mode == ListMode::Config {
-            Self::print_config(&lockfile, &manifest_contents)?;
-            return Ok(());
+        match self.list_mode {
+            ListMode::FlakeInputs => {
+                return Self::print_flake_inputs(
+                    &lockfile,
+                    self.json.is_some(),
+                    self.json.as_ref().is_some_and(|json| json.pretty),
+                );
+            },
+            ListMode::VerifySignatures => {
+                let report = signature_report(&lockfile, &flox.system, |store_paths| {
+                    // Paths that are not in the local store map to `null`,
+                    // if nix fails all paths are unverifiable
+                    store_path_infos(None, store_paths, false).unwrap_or_default()
+                });
+                return Self::print_signature_report(stdout().lock(), &report, &flox.system);
+            },
+            ListMode::Config => {
+                Self::print_config(&lockfile, &manifest_contents)?;
+                return Ok(());
+            },
+            _ => {},
         }
 
         let system = &flox.system;
-        let packages = lockfile.list_packages(system)?;
+        let mut packages = lockfile.list_packages(system)?;
+        if self.manifest_order {
+            Self::sort_by_manifest_order(&mut packages, &manifest_contents)?;
+        }
+        if self.sort_by_age {
+            Self::sort_by_age(&mut packages);
+        }
+
+        if let Some(json) = &self.json {
+            let installed_from = self
+                .installed_from
+                .then(|| Self::installed_from(&packages, &lockfile));
+            let schema_version = json.schema_version.unwrap_or(LIST_JSON_SCHEMA_VERSION);
+            let packages = JsonPackages::new(&packages, installed_from.as_ref(), schema_version);
+            if json.pretty {
+                println!("{packages:#}");
+            } else {
+                println!("{packages}");
+            }
+            return Ok(());
+        }
 
         if packages.is_empty() {
             let message = formatdoc! {"
@@ -133,6 +403,11 @@ impl List {
                 Self::print_name_only(stdout().lock(), &packages)?;
             },
             ListMode::Extended => {
+                let installed_from = self
+                    .installed_from
+                    .then(|| Self::installed_from(&packages, &lockfile));
+                let ages = (self.age || self.sort_by_age)
+                    .then(|| Self::package_ages(&packages, chrono::Utc::now()));
                 Self::print_extended(
                     stdout().lock(),
                     &packages,
@@ -141,6 +416,9 @@ impl List {
                     } else {
                         List::get_cached_upgrades_for_current_system(&flox, &mut env)?
                     },
+                    self.with_descriptions,
+                    installed_from.as_ref(),
+                    ages.as_ref(),
                 )?;
             },
             ListMode::All => {
@@ -154,9 +432,107 @@ impl List {
                     },
                 )?;
             },
-            ListMode::Config => unreachable!(),
+            ListMode::Config
+            | ListMode::CheckAllowed
+            | ListMode::DiffManifest
+            | ListMode::FlakeInputs
+            | ListMode::VerifySignatures => unreachable!(),
+        }
+
+        Ok(())
+    }
+
+    /// Check the existing lockfile against the current manifest's
+    /// `options.allow` and report any packages it no longer permits.
+    fn check_allowed(flox: &Flox, env: &mut ConcreteEnvironment) -> Result<()> {
+        let Some(lockfile) = env.existing_lockfile(flox)? else {
+            bail!("The environment has not been locked yet, so there are no packages to check.");
+        };
+        let manifest = env.manifest(flox)?;
+        let violations = LockManifest::disallowed_packages(
+            lockfile
+                .packages
+                .iter()
+                .filter_map(LockedPackage::as_catalog_package_ref),
+            &manifest.as_latest_schema().options.allow,
+        );
+
+        if violations.is_empty() {
+            message::info("All locked packages are allowed by 'options.allow'.");
+            return Ok(());
         }
 
+        Self::print_disallowed(stdout().lock(), &violations)?;
+        message::warning(formatdoc! {"
+            These packages will be rejected the next time they are locked.
+            Update 'options.allow' in the manifest or uninstall them.
+        "});
+        Ok(())
+    }
+
+    /// Compare the current manifest with the existing lockfile
+    /// and report install IDs that are out of sync for the current system.
+    fn diff_manifest(flox: &Flox, env: &mut ConcreteEnvironment) -> Result<()> {
+        let Some(lockfile) = env.existing_lockfile(flox)? else {
+            bail!("The environment has not been locked yet, so there is no lockfile to compare.");
+        };
+        let manifest = env.manifest(flox)?;
+        let drift = ManifestDrift::new(manifest.as_latest_schema(), &lockfile, &flox.system);
+
+        if drift.is_empty() {
+            message::info(format!(
+                "The lockfile is in sync with the manifest for '{}'.",
+                flox.system
+            ));
+            return Ok(());
+        }
+
+        Self::print_drift(stdout().lock(), &drift, &flox.system)?;
+        message::warning(
+            "The environment will be re-locked the next time it is used, e.g. by 'flox activate'.",
+        );
+        Ok(())
+    }
+
+    fn print_drift(mut out: impl Write, drift: &ManifestDrift, system: &str) -> Result<()> {
+        if !drift.needs_lock.is_empty() {
+            writeln!(out, "In the manifest but not locked for '{system}':")?;
+            for install_id in &drift.needs_lock {
+                writeln!(out, "  {install_id}")?;
+            }
+        }
+        if !drift.stale_lock.is_empty() {
+            writeln!(out, "Locked for '{system}' but no longer in the manifest:")?;
+            for install_id in &drift.stale_lock {
+                writeln!(out, "  {install_id}")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Print one line per disallowed package.
+    ///
+    /// Packages locked for several systems are only reported once.
+    fn print_disallowed(mut out: impl Write, violations: &[ResolveError]) -> Result<()> {
+        let unique_violations = violations.iter().unique_by(|violation| {
+            let (install_id, license) = match violation {
+                ResolveError::LicenseNotAllowed(install_id, license) => {
+                    (Some(install_id), Some(license))
+                },
+                ResolveError::BrokenNotAllowed(install_id)
+                | ResolveError::UnfreeNotAllowed(install_id) => (Some(install_id), None),
+                _ => (None, None),
+            };
+            (std::mem::discriminant(*violation), install_id, license)
+        });
+
+        writeln!(out, "Locked packages not allowed by 'options.allow':")?;
+        for violation in unique_violations {
+            // Only the summary line, the hints are printed once afterwards
+            let message = violation.to_string();
+            let reason = message.lines().next().unwrap_or_default();
+            writeln!(out, "  {reason}")?;
+        }
         Ok(())
     }
 
@@ -164,7 +540,7 @@ impl List {
     /// If the manifest includes other environments,
     /// configure the serializer to produce output closer to the reference
     /// style.
-    fn manifest_contents_to_print(
+    pub(crate) fn manifest_contents_to_print(
         lockfile: &Lockfile,
         manifest_contents: impl Into<String>,
     ) -> Result<String> {
@@ -193,14 +569,155 @@ impl List {
         Ok(())
     }
 
+    /// Print the flake references pinned by the lockfile, one per line,
+    /// or as a JSON list.
+    fn print_flake_inputs(lockfile: &Lockfile, json: bool, pretty: bool) -> Result<()> {
+        let flake_inputs = lockfile.flake_inputs();
+        if json {
+            let json = if pretty {
+                serde_json::to_string_pretty(&flake_inputs)?
+            } else {
+                serde_json::to_string(&flake_inputs)?
+            };
+            println!("{json}");
+            return Ok(());
+        }
+
+        if flake_inputs.is_empty() {
+            message::plain("No flake packages are installed.");
+            return Ok(());
+        }
+        for flake_input in flake_inputs {
+            println!("{flake_input}");
+        }
+        Ok(())
+    }
+
+    /// Print the signature status of each flake package output
+    /// and warn about outputs that are not signed.
+    fn print_signature_report(
+        mut out: impl Write,
+        report: &[OutputSignature],
+        system: &str,
+    ) -> Result<()> {
+        if report.is_empty() {
+            message::plain(format!(
+                "No flake packages are installed for your current system ('{system}')."
+            ));
+            return Ok(());
+        }
+
+        for output in report {
+            writeln!(
+                out,
+                "{}.{}: {} ({})",
+                output.install_id, output.output, output.status, output.store_path
+            )?;
+        }
+
+        let unsigned = report
+            .iter()
+            .filter(|output| output.status == SignatureStatus::Unsigned)
+            .count();
+        if unsigned > 0 {
+            message::warning(formatdoc! {"
+                {unsigned} output(s) of flake packages are not signed.
+                Their provenance can't be verified beyond the flake they were built from."
+            });
+        }
+        Ok(())
+    }
+
+    /// Sort packages in the order their install IDs are declared in the
+    /// `[install]` table of `manifest_contents`.
+    ///
+    /// The typed manifest doesn't preserve declaration order,
+    /// so the order is read from the TOML document.
+    /// Packages that are not declared in the manifest,
+    /// e.g. those provided by included environments,
+    /// keep their relative order and are listed last.
+    fn sort_by_manifest_order(
+        packages: &mut [PackageToList],
+        manifest_contents: &str,
+    ) -> Result<()> {
+        let document = manifest_contents.parse::<DocumentMut>()?;
+        let install_ids = document
+            .get("install")
+            .and_then(Item::as_table_like)
+            .map(|install| install.iter().map(|(id, _)| id.to_string()).collect_vec())
+            .unwrap_or_default();
+
+        packages.sort_by_key(|package| {
+            let install_id = package.install_id();
+            install_ids
+                .iter()
+                .position(|id| id == install_id)
+                .unwrap_or(usize::MAX)
+        });
+        Ok(())
+    }
+
+    /// Attribute each package to the manifest or the included environment
+    /// that provides it, keyed by install ID.
+    ///
+    /// Packages of environments without includes are all attributed to the
+    /// manifest.
+    fn installed_from(packages: &[PackageToList], lockfile: &Lockfile) -> BTreeMap<String, String> {
+        packages
+            .iter()
+            .map(|package| {
+                let install_id = package.install_id();
+                let source = lockfile
+                    .compose
+                    .as_ref()
+                    .and_then(|compose| compose.include_providing_install_id(install_id))
+                    .map_or_else(|| "manifest".to_string(), |include| include.name.clone());
+                (install_id.to_string(), source)
+            })
+            .collect()
+    }
+
+    /// Sort packages by the date of their catalog revision, oldest first.
+    ///
+    /// Packages without a revision date, i.e. flake and store path packages,
+    /// keep their relative order and are listed last.
+    fn sort_by_age(packages: &mut [PackageToList]) {
+        packages.sort_by_key(|package| match package {
+            PackageToList::Catalog(_, p) => (false, Some(p.rev_date)),
+            PackageToList::Flake(..) | PackageToList::StorePath(_) => (true, None),
+        });
+    }
+
+    /// Describe how long before `now` the catalog revision of each package
+    /// was created, keyed by install ID, e.g. `180 days`.
+    ///
+    /// Flake and store path packages have no revision date and are `n/a`.
+    fn package_ages(
+        packages: &[PackageToList],
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> BTreeMap<String, String> {
+        packages
+            .iter()
+            .map(|package| match package {
+                PackageToList::Catalog(_, p) => {
+                    let days = (now - p.rev_date).num_days().max(0);
+                    let age = if days == 1 {
+                        "1 day".to_string()
+                    } else {
+                        format!("{days} days")
+                    };
+                    (p.install_id.clone(), age)
+                },
+                PackageToList::Flake(_, p) => (p.install_id.clone(), "n/a".to_string()),
+                PackageToList::StorePath(p) => (p.install_id.clone(), "n/a".to_string()),
+            })
+            .collect()
+    }
+
     /// print package ids only
     fn print_name_only(mut out: impl Write, packages: &[PackageToList]) -> Result<()> {
         for p in packages {
-            let install_id = match p {
-                PackageToList::Catalog(_, p) => &p.install_id,
-                PackageToList::Flake(_, p) => &p.install_id,
-                PackageToList::StorePath(p) => &p.install_id,
-            };
+            let install_id = p.install_id();
             writeln!(&mut out, "{install_id}")?;
         }
         Ok(())
@@ -210,18 +727,25 @@ impl List {
     ///
     /// e.g. `pip: python3Packages.pip (20.3.4)`
     ///
-    /// This is the default mode
+    /// This is the default mode.
+    /// With `with_descriptions`, the first line of the package description
+    /// is appended if one is available,
+    /// e.g. `pip: python3Packages.pip (20.3.4) - Python package installer`
+    /// With `installed_from`, the source of each package is appended
+    /// before the description,
+    /// e.g. `pip: python3Packages.pip (20.3.4) [manifest]`
+    /// With `ages`, the age of each package is appended after its source,
+    /// e.g. `pip: python3Packages.pip (20.3.4) [age: 180 days]`
     fn print_extended(
         mut out: impl Write,
         packages: &[PackageToList],
         upgrades: Option<SingleSystemUpgradeDiff>,
+        with_descriptions: bool,
+        installed_from: Option<&BTreeMap<String, String>>,
+        ages: Option<&BTreeMap<String, String>>,
     ) -> Result<()> {
         for p in packages {
-            let install_id = match p {
-                PackageToList::Catalog(_, p) => &p.install_id,
-                PackageToList::Flake(_, p) => &p.install_id,
-                PackageToList::StorePath(p) => &p.install_id,
-            };
+            let install_id = p.install_id();
             let upgrade_available = if upgrades
                 .as_ref()
                 .is_some_and(|diff| diff.contains_key(install_id))
@@ -231,11 +755,32 @@ impl List {
                 ""
             };
 
+            let description = match p {
+                PackageToList::Catalog(_, p) => p.description.as_deref(),
+                PackageToList::Flake(_, p) => p.locked_installable.description.as_deref(),
+                PackageToList::StorePath(_) => None,
+            }
+            .and_then(|description| description.lines().next())
+            .map(str::trim)
+            .filter(|description| with_descriptions && !description.is_empty())
+            .map(|description| format!(" - {description}"))
+            .unwrap_or_default();
+
+            let source = installed_from
+                .and_then(|installed_from| installed_from.get(install_id))
+                .map(|source| format!(" [{source}]"))
+                .unwrap_or_default();
+
+            let age = ages
+                .and_then(|ages| ages.get(install_id))
+                .map(|age| format!(" [age: {age}]"))
+                .unwrap_or_default();
+
             match p {
                 PackageToList::Catalog(descriptor, p) => {
                     writeln!(
                         &mut out,
-                        "{id}: {path} ({version}{upgrade_available})",
+                        "{id}: {path} ({version}{upgrade_available}){source}{age}{description}",
                         id = p.install_id,
                         path = descriptor.pkg_path,
                         version = p.version,
@@ -244,7 +789,7 @@ impl List {
                 PackageToList::Flake(descriptor, locked_package) => {
                     writeln!(
                         &mut out,
-                        "{id}: {flake}{upgrade_available}",
+                        "{id}: {flake}{upgrade_available}{source}{age}{description}",
                         id = locked_package.install_id,
                         flake = descriptor.flake
                     )?;
@@ -252,7 +797,7 @@ impl List {
                 PackageToList::StorePath(locked_package_store_path) => {
                     writeln!(
                         &mut out,
-                        "{id}: {store_path}",
+                        "{id}: {store_path}{source}{age}",
                         id = locked_package_store_path.install_id,
                         store_path = locked_package_store_path.store_path
                     )?;
@@ -278,11 +823,7 @@ impl List {
             })
             .enumerate()
         {
-            let install_id = match package {
-                PackageToList::Catalog(_, p) => &p.install_id,
-                PackageToList::Flake(_, p) => &p.install_id,
-                PackageToList::StorePath(p) => &p.install_id,
-            };
+            let install_id = package.install_id();
             let upgrade_available = if upgrades
                 .as_ref()
                 .is_some_and(|diff| diff.contains_key(install_id))
@@ -448,6 +989,239 @@ where
     format!("[ {} ]", sorted_items.join(", "))
 }
 
+/// The current version of the shape of `flox list --json`
+///
+/// Bump this whenever fields are added to or changed in the output.
+/// Version 1 is a plain list of packages,
+/// later versions are an object with a `schema_version` and `packages`.
+const LIST_JSON_SCHEMA_VERSION: u8 = 2;
+
+/// A package as listed by `flox list --json`
+#[derive(Debug, Serialize)]
+struct JsonPackage<'a> {
+    install_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pkg_path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flake: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    store_path: Option<&'a str>,
+    version: Option<&'a str>,
+    description: Option<&'a str>,
+    priority: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    installed_from: Option<&'a str>,
+}
+
+/// The packages listed by `flox list --json`
+///
+/// Formats as compact, single-line JSON by default,
+/// and as indented JSON with the alternate flag, i.e. `{:#}`.
+/// Both contain the same data,
+/// in the shape of `schema_version`.
+struct JsonPackages<'a> {
+    schema_version: u8,
+    packages: Vec<JsonPackage<'a>>,
+}
+
+/// The shape of `flox list --json` since schema version 2
+#[derive(Debug, Serialize)]
+struct VersionedJsonPackages<'a> {
+    schema_version: u8,
+    packages: &'a [JsonPackage<'a>],
+}
+
+impl<'a> JsonPackages<'a> {
+    fn new(
+        packages: &'a [PackageToList],
+        installed_from: Option<&'a BTreeMap<String, String>>,
+        schema_version: u8,
+    ) -> Self {
+        let packages = packages
+            .iter()
+            .map(|package| {
+                let mut json_package = match package {
+                    PackageToList::Catalog(descriptor, locked) => JsonPackage {
+                        install_id: &locked.install_id,
+                        pkg_path: Some(&descriptor.pkg_path),
+                        flake: None,
+                        store_path: None,
+                        version: Some(&locked.version),
+                        description: locked.description.as_deref(),
+                        priority: locked.priority,
+                        installed_from: None,
+                    },
+                    PackageToList::Flake(descriptor, locked) => JsonPackage {
+                        install_id: &locked.install_id,
+                        pkg_path: None,
+                        flake: Some(&descriptor.flake),
+                        store_path: None,
+                        version: locked.locked_installable.version.as_deref(),
+                        description: locked.locked_installable.description.as_deref(),
+                        priority: locked.locked_installable.priority,
+                        installed_from: None,
+                    },
+                    PackageToList::StorePath(locked) => JsonPackage {
+                        install_id: &locked.install_id,
+                        pkg_path: None,
+                        flake: None,
+                        store_path: Some(&locked.store_path),
+                        version: None,
+                        description: None,
+                        priority: locked.priority,
+                        installed_from: None,
+                    },
+                };
+                json_package.installed_from = installed_from
+                    .and_then(|installed_from| installed_from.get(json_package.install_id))
+                    .map(String::as_str);
+                json_package
+            })
+            .collect();
+        Self {
+            schema_version,
+            packages,
+        }
+    }
+
+    fn to_json(&self, pretty: bool) -> serde_json::Result<String> {
+        if self.schema_version == 1 {
+            return if pretty {
+                serde_json::to_string_pretty(&self.packages)
+            } else {
+                serde_json::to_string(&self.packages)
+            };
+        }
+        let versioned = VersionedJsonPackages {
+            schema_version: self.schema_version,
+            packages: &self.packages,
+        };
+        if pretty {
+            serde_json::to_string_pretty(&versioned)
+        } else {
+            serde_json::to_string(&versioned)
+        }
+    }
+}
+
+impl Display for JsonPackages<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = match self.to_json(f.alternate()) {
+            Ok(data) => data,
+            Err(e) => {
+                debug!("Could not serialize packages: {e}");
+                return Err(fmt::Error);
+            },
+        };
+
+        f.write_str(&data)
+    }
+}
+
+/// Whether the store path of a flake package output is signed
+#[derive(Debug, Clone, PartialEq)]
+enum SignatureStatus {
+    /// Signed by the named keys
+    Signed(Vec<String>),
+    /// Not signed, but content-addressed,
+    /// so the path can be verified against its contents
+    ContentAddressed,
+    /// Valid in the local store, but not signed
+    Unsigned,
+    /// No path info is available,
+    /// e.g. because the path is not in the local store
+    Unverifiable,
+}
+
+impl SignatureStatus {
+    /// Determine the status from the JSON path info of a store path,
+    /// as printed by `nix path-info --json`.
+    fn from_path_info(path_info: Option<&serde_json::Value>) -> Self {
+        let Some(path_info) = path_info.filter(|info| info.is_object()) else {
+            return SignatureStatus::Unverifiable;
+        };
+
+        let keys = path_info["signatures"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|signature| signature.as_str()?.split_once(':'))
+            .map(|(key, _)| key.to_string())
+            .unique()
+            .collect::<Vec<_>>();
+        if !keys.is_empty() {
+            return SignatureStatus::Signed(keys);
+        }
+        if path_info["ca"].is_string() {
+            return SignatureStatus::ContentAddressed;
+        }
+        SignatureStatus::Unsigned
+    }
+}
+
+impl Display for SignatureStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureStatus::Signed(keys) => write!(f, "signed by {}", keys.join(", ")),
+            SignatureStatus::ContentAddressed => write!(f, "content-addressed"),
+            SignatureStatus::Unsigned => write!(f, "unsigned"),
+            SignatureStatus::Unverifiable => write!(f, "unverifiable"),
+        }
+    }
+}
+
+/// The signature status of a single output of a flake package
+#[derive(Debug, Clone, PartialEq)]
+struct OutputSignature {
+    install_id: String,
+    output: String,
+    store_path: String,
+    status: SignatureStatus,
+}
+
+/// Check the outputs of the flake packages locked for `system`,
+/// looking up the path info of all outputs at once with `path_infos`.
+fn signature_report(
+    lockfile: &Lockfile,
+    system: &str,
+    path_infos: impl FnOnce(&[&str]) -> serde_json::Map<String, serde_json::Value>,
+) -> Vec<OutputSignature> {
+    let outputs = lockfile
+        .packages
+        .iter()
+        .filter_map(|package| match package {
+            LockedPackage::Flake(flake) if flake.locked_installable.system == system => Some(flake),
+            _ => None,
+        })
+        .flat_map(|flake| {
+            flake
+                .locked_installable
+                .outputs
+                .iter()
+                .map(move |(output, store_path)| (flake, output, store_path))
+        })
+        .collect::<Vec<_>>();
+    if outputs.is_empty() {
+        return Vec::new();
+    }
+
+    let store_paths = outputs
+        .iter()
+        .map(|(_, _, store_path)| store_path.as_str())
+        .collect::<Vec<_>>();
+    let path_infos = path_infos(&store_paths);
+
+    outputs
+        .into_iter()
+        .map(|(flake, output, store_path)| OutputSignature {
+            install_id: flake.install_id.clone(),
+            output: output.clone(),
+            store_path: store_path.clone(),
+            status: SignatureStatus::from_path_info(path_infos.get(store_path)),
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -523,6 +1297,23 @@ mod tests {
         PackageToList::Flake(nix_eval_jobs_descriptor(), LOCKED_NIX_EVAL_JOBS.clone())
     }
 
+    #[test]
+    fn test_print_disallowed_deduplicates_systems() {
+        let violations = [
+            ResolveError::UnfreeNotAllowed("foo".to_string()),
+            ResolveError::UnfreeNotAllowed("foo".to_string()),
+            ResolveError::BrokenNotAllowed("bar".to_string()),
+        ];
+
+        let mut out = Vec::new();
+        List::print_disallowed(&mut out, &violations).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), indoc! {"
+            Locked packages not allowed by 'options.allow':
+              The package 'foo' has an unfree license.
+              The package 'bar' is marked as broken.
+        "});
+    }
+
     #[test]
     fn test_name_only_output() {
         let mut out = Vec::new();
@@ -534,6 +1325,79 @@ mod tests {
         "});
     }
 
+    #[test]
+    fn test_sort_by_manifest_order() {
+        let manifest_contents = indoc! {r#"
+            version = 1
+
+            [install]
+            python_install_id.pkg-path = "python3Packages.python"
+            pip_install_id.pkg-path = "python3Packages.pip"
+        "#};
+
+        let mut packages = test_packages();
+        List::sort_by_manifest_order(&mut packages, manifest_contents).unwrap();
+
+        let mut out = Vec::new();
+        List::print_name_only(&mut out, &packages).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), indoc! {"
+            python_install_id
+            pip_install_id
+        "});
+    }
+
+    #[test]
+    fn signature_report_checks_flake_outputs_of_system() {
+        let lockfile = Lockfile {
+            packages: vec![LockedPackage::Flake(LOCKED_NIX_EVAL_JOBS.clone())],
+            ..Default::default()
+        };
+        let store_path = "/nix/store/qigv8kbk1gpk0g2pfw10lbmdy44cf06r-nix-eval-jobs-2.23.0";
+
+        let report = signature_report(&lockfile, "aarch64-darwin", |paths| {
+            assert_eq!(paths, [store_path]);
+            serde_json::Map::from_iter([(
+                store_path.to_string(),
+                serde_json::json!({
+                    "signatures": ["cache.nixos.org-1:abc", "cache.nixos.org-1:def", "mine:ghi"]
+                }),
+            )])
+        });
+        assert_eq!(report, vec![OutputSignature {
+            install_id: "nix-eval-jobs".to_string(),
+            output: "out".to_string(),
+            store_path: store_path.to_string(),
+            status: SignatureStatus::Signed(vec![
+                "cache.nixos.org-1".to_string(),
+                "mine".to_string()
+            ]),
+        }]);
+
+        assert!(
+            signature_report(&lockfile, "x86_64-linux", |_| {
+                panic!("path info should not be queried without flake outputs")
+            })
+            .is_empty()
+        );
+
+        assert_eq!(
+            SignatureStatus::from_path_info(Some(&serde_json::json!({ "ca": "fixed:sha256:abc" }))),
+            SignatureStatus::ContentAddressed
+        );
+        assert_eq!(
+            SignatureStatus::from_path_info(Some(&serde_json::json!({ "signatures": [] }))),
+            SignatureStatus::Unsigned
+        );
+        assert_eq!(
+            SignatureStatus::from_path_info(Some(&serde_json::Value::Null)),
+            SignatureStatus::Unverifiable
+        );
+        assert_eq!(
+            SignatureStatus::from_path_info(None),
+            SignatureStatus::Unverifiable
+        );
+    }
+
     /// Test name only output for flake installables
     #[test]
     fn test_name_only_flake_output() {
@@ -548,7 +1412,7 @@ mod tests {
     #[test]
     fn test_print_extended_output() {
         let mut out = Vec::new();
-        List::print_extended(&mut out, &test_packages(), None).unwrap();
+        List::print_extended(&mut out, &test_packages(), None, false, None, None).unwrap();
         let out = String::from_utf8(out).unwrap();
         assert_eq!(out, indoc! {"
             pip_install_id: python3Packages.pip (20.3.4)
@@ -556,11 +1420,135 @@ mod tests {
         "});
     }
 
+    /// Compact and pretty JSON contain the same data
+    #[test]
+    fn test_json_output_compact_and_pretty() {
+        let packages = [test_packages()[0].clone(), test_flake_package()];
+        let json_packages = JsonPackages::new(&packages, None, LIST_JSON_SCHEMA_VERSION);
+
+        let compact = json_packages.to_string();
+        let pretty = format!("{json_packages:#}");
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains('\n'));
+
+        let compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+        let pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(compact, pretty);
+        assert_eq!(compact["schema_version"], LIST_JSON_SCHEMA_VERSION);
+        let compact = &compact["packages"];
+        assert_eq!(
+            compact[0],
+            serde_json::json!({
+                "install_id": "pip_install_id",
+                "pkg_path": "python3Packages.pip",
+                "version": "20.3.4",
+                "description": "Python package installer",
+                "priority": 100,
+            })
+        );
+        assert_eq!(compact[1]["install_id"], "nix-eval-jobs");
+        assert!(compact[1]["flake"].is_string());
+    }
+
+    /// Schema version 1 is a plain list of the same packages
+    #[test]
+    fn test_json_output_schema_version_1() {
+        let packages = [test_packages()[0].clone(), test_flake_package()];
+        let latest = JsonPackages::new(&packages, None, LIST_JSON_SCHEMA_VERSION).to_string();
+        let v1 = JsonPackages::new(&packages, None, 1).to_string();
+
+        let latest: serde_json::Value = serde_json::from_str(&latest).unwrap();
+        let v1: serde_json::Value = serde_json::from_str(&v1).unwrap();
+        assert!(v1.is_array());
+        assert_eq!(v1, latest["packages"]);
+    }
+
+    /// Descriptions are appended when requested, truncated to their first line
+    #[test]
+    fn test_print_extended_with_descriptions() {
+        let mut packages = test_packages();
+        let PackageToList::Catalog(_, ref mut python_lock) = packages[1] else {
+            unreachable!()
+        };
+        python_lock.description = Some("Python interpreter\nwith a second line".to_string());
+
+        let mut out = Vec::new();
+        List::print_extended(&mut out, &packages, None, true, None, None).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, indoc! {"
+            pip_install_id: python3Packages.pip (20.3.4) - Python package installer
+            python_install_id: python3Packages.python (3.9.5) - Python interpreter
+        "});
+
+        let mut out = Vec::new();
+        List::print_extended(&mut out, &[uninformative_package()], None, true, None, None).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, indoc! {"
+            pip_install_id: python3Packages.pip (N/A)
+        "});
+    }
+
+    #[test]
+    fn test_print_extended_installed_from() {
+        let installed_from = BTreeMap::from([
+            ("pip_install_id".to_string(), "manifest".to_string()),
+            ("python_install_id".to_string(), "base".to_string()),
+        ]);
+
+        let mut out = Vec::new();
+        List::print_extended(
+            &mut out,
+            &test_packages(),
+            None,
+            true,
+            Some(&installed_from),
+            None,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, indoc! {"
+            pip_install_id: python3Packages.pip (20.3.4) [manifest] - Python package installer
+            python_install_id: python3Packages.python (3.9.5) [base] - Python interpreter
+        "});
+    }
+
+    /// Ages are shown in days, and packages are sorted oldest first
+    #[test]
+    fn test_print_extended_age() {
+        let now = chrono::DateTime::parse_from_rfc3339("2025-07-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let mut packages = vec![test_flake_package()];
+        packages.extend(test_packages());
+        let PackageToList::Catalog(_, ref mut pip_lock) = packages[1] else {
+            unreachable!()
+        };
+        pip_lock.rev_date = now - chrono::Duration::days(1);
+        let PackageToList::Catalog(_, ref mut python_lock) = packages[2] else {
+            unreachable!()
+        };
+        python_lock.rev_date = now - chrono::Duration::days(180);
+
+        List::sort_by_age(&mut packages);
+        let ages = List::package_ages(&packages, now);
+
+        let mut out = Vec::new();
+        List::print_extended(&mut out, &packages, None, false, None, Some(&ages)).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[..2], [
+            "python_install_id: python3Packages.python (3.9.5) [age: 180 days]",
+            "pip_install_id: python3Packages.pip (20.3.4) [age: 1 day]",
+        ]);
+        assert!(lines[2].starts_with("nix-eval-jobs: "));
+        assert!(lines[2].ends_with(" [age: n/a]"));
+    }
+
     /// Test extended output for flake installables
     #[test]
     fn test_print_extended_flake_output() {
         let mut out = Vec::new();
-        List::print_extended(&mut out, &[test_flake_package()], None).unwrap();
+        List::print_extended(&mut out, &[test_flake_package()], None, false, None, None).unwrap();
         let out = String::from_utf8(out).unwrap();
         assert_eq!(out, indoc! {"
             nix-eval-jobs: github:nix-community/nix-eval-jobs
@@ -571,7 +1559,15 @@ mod tests {
     #[test]
     fn test_print_extended_output_handles_missing_values() {
         let mut out = Vec::new();
-        List::print_extended(&mut out, &[uninformative_package()], None).unwrap();
+        List::print_extended(
+            &mut out,
+            &[uninformative_package()],
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
         let out = String::from_utf8(out).unwrap();
         assert_eq!(out, indoc! {"
             pip_install_id: python3Packages.pip (N/A)
@@ -597,7 +1593,7 @@ mod tests {
             ),
         )]);
 
-        List::print_extended(&mut out, &packages, Some(upgrades)).unwrap();
+        List::print_extended(&mut out, &packages, Some(upgrades), false, None, None).unwrap();
         let out = String::from_utf8(out).unwrap();
         assert_eq!(out, indoc! {"
             pip_install_id: python3Packages.pip (20.3.4 - upgrade available)
@@ -869,7 +1865,15 @@ mod tests {
         lock.version = "2.7.1".to_string();
 
         let mut out = Vec::new();
-        List::print_extended(&mut out, &[PackageToList::Catalog(descriptor, lock)], None).unwrap();
+        List::print_extended(
+            &mut out,
+            &[PackageToList::Catalog(descriptor, lock)],
+            None,
+            false,
+            None,
+            None,
+        )
+        .unwrap();
 
         assert!(
             String::from_utf8(out)
@@ -896,6 +1900,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn manifest_contents_to_print_keeps_manifest_without_includes_verbatim() {
+        let manifest_contents = with_latest_schema(indoc! {r#"
+            # a comment that the typed manifest would drop
+            [install]
+            hello.pkg-path = "hello"
+        "#});
+
+        assert_eq!(
+            List::manifest_contents_to_print(&Lockfile::default(), manifest_contents.clone())
+                .unwrap(),
+            manifest_contents
+        );
+    }
+
     /// manifest_contents_to_print puts items in the same table with dotted
     /// subtables for composed environments
     #[test]
@@ -954,6 +1973,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn manifest_drift_reports_unlocked_and_stale_install_ids() {
+        let manifest = toml_edit::de::from_str::<ManifestLatest>(&with_latest_schema(indoc! {r#"
+            [install]
+            locked.pkg-path = "locked"
+            added.pkg-path = "added"
+            other_system.pkg-path = "other_system"
+            other_system.systems = ["x86_64-linux"]
+
+            [options]
+            systems = ["aarch64-darwin", "x86_64-linux"]
+        "#}))
+        .unwrap();
+
+        let lock = |install_id: &str| {
+            let (_, _, mut locked) = fake_catalog_package_lock(install_id, None);
+            locked.install_id = install_id.to_string();
+            LockedPackage::Catalog(locked)
+        };
+        let lockfile = Lockfile {
+            packages: vec![lock("locked"), lock("removed")],
+            ..Default::default()
+        };
+
+        let drift = ManifestDrift::new(&manifest, &lockfile, "aarch64-darwin");
+        assert_eq!(drift, ManifestDrift {
+            needs_lock: BTreeSet::from(["added".to_string()]),
+            stale_lock: BTreeSet::from(["removed".to_string()]),
+        });
+
+        let mut out = Vec::new();
+        List::print_drift(&mut out, &drift, "aarch64-darwin").unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), indoc! {"
+            In the manifest but not locked for 'aarch64-darwin':
+              added
+            Locked for 'aarch64-darwin' but no longer in the manifest:
+              removed
+        "});
+    }
+
     /// Test that --upstream errors with path environment
     #[tokio::test]
     async fn list_upstream_errors_with_path_environment() {
@@ -968,6 +2027,12 @@ mod tests {
         let result = List {
             environment: EnvironmentSelect::Dir(path_env.project_path().unwrap()),
             upstream: true,
+            with_descriptions: false,
+            manifest_order: false,
+            installed_from: false,
+            age: false,
+            sort_by_age: false,
+            json: None,
             list_mode: ListMode::All,
         }
         .handle(flox)
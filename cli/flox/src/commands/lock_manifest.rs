@@ -1,10 +1,12 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use bpaf::Bpaf;
 use flox_manifest::Manifest;
 use flox_manifest::interfaces::AsTypedOnlyManifest;
+use flox_manifest::lockfile::Lockfile;
+use flox_manifest::util::unified_diff;
 use flox_rust_sdk::flox::Flox;
 use flox_rust_sdk::models::environment::fetcher::IncludeFetcher;
 use tracing::instrument;
@@ -16,6 +18,7 @@ use crate::subcommand_metric;
 /// If provided, uses the lockfile from the path specified by `--lockfile`
 /// as the base lockfile.
 /// Returns the lockfile as JSON to stdout.
+/// With `--check`, instead verifies that the lockfile is up to date.
 /// Manifests with includes cannot be locked.
 #[derive(Bpaf, Clone)]
 pub struct LockManifest {
@@ -23,6 +26,18 @@ pub struct LockManifest {
     #[bpaf(long, short, argument("path"), complete_shell(SHELL_COMPLETION_FILE))]
     lockfile: Option<PathBuf>,
 
+    /// Check that the lockfile passed with '--lockfile' is up to date
+    /// instead of printing a new lockfile.
+    /// Prints a diff and fails if locking would change the lockfile.
+    #[bpaf(long)]
+    check: bool,
+
+    /// Fail if the lockfile passed with '--lockfile' locks packages that its
+    /// manifest doesn't install, or doesn't lock packages of its manifest
+    /// for all of their systems.
+    #[bpaf(long)]
+    strict: bool,
+
     /// The manifest file to lock. (default: stdin)
     #[bpaf(positional("path to manifest"), complete_shell(SHELL_COMPLETION_FILE))]
     manifest: PathBuf,
@@ -33,6 +48,13 @@ impl LockManifest {
     pub async fn handle(self, flox: Flox) -> Result<()> {
         subcommand_metric!("lock");
 
+        if self.check && self.lockfile.is_none() {
+            bail!("'--check' requires a lockfile to check, pass it with '--lockfile'");
+        }
+        if self.strict && self.lockfile.is_none() {
+            bail!("'--strict' requires a lockfile to validate, pass it with '--lockfile'");
+        }
+
         let manifest_path = if self.manifest == Path::new("-") {
             Path::new("/dev/stdin")
         } else {
@@ -44,12 +66,24 @@ impl LockManifest {
 
         let input_manifest = Manifest::parse_toml_typed(input_manifest)?;
 
-        let input_lockfile = if let Some(lockfile_path) = self.lockfile {
-            let lockfile = fs::read_to_string(lockfile_path).context("Failed to read lockfile")?;
-            Some(serde_json::from_str(&lockfile).context("Failed to parse lockfile")?)
+        let input_lockfile_contents = if let Some(lockfile_path) = &self.lockfile {
+            Some(fs::read_to_string(lockfile_path).context("Failed to read lockfile")?)
         } else {
             None
         };
+        let input_lockfile: Option<Lockfile> = if let Some(lockfile) = &input_lockfile_contents {
+            Some(serde_json::from_str(lockfile).context("Failed to parse lockfile")?)
+        } else {
+            None
+        };
+
+        if self.strict
+            && let Some(lockfile) = &input_lockfile
+        {
+            lockfile
+                .validate_consistency()
+                .context("Lockfile is inconsistent with its manifest")?;
+        }
 
         let migrated_manifest = input_manifest
             .as_typed_only()
@@ -67,8 +101,132 @@ impl LockManifest {
         .await
         .context("Failed to lock the manifest")?;
 
+        if self.check
+            && let (Some(lockfile_path), Some(lockfile_contents), Some(input_lockfile)) =
+                (&self.lockfile, &input_lockfile_contents, &input_lockfile)
+        {
+            if let Some(diff) = stale_lockfile_diff(input_lockfile, lockfile_contents, &lockfile) {
+                println!("{}", diff.trim_end());
+                bail!(
+                    "Lockfile '{}' is out of date, lock the manifest again to update it",
+                    lockfile_path.display()
+                );
+            }
+            return Ok(());
+        }
+
         serde_json::to_writer_pretty(std::io::stdout(), &lockfile)
             .context("failed to write lockfile to stdout")?;
         Ok(())
     }
 }
+
+/// Render the changes from the `current` lockfile, read from
+/// `current_contents`, to the newly `locked` lockfile as a unified diff.
+///
+/// Returns [None] if the lockfile is up to date.
+fn stale_lockfile_diff(
+    current: &Lockfile,
+    current_contents: &str,
+    locked: &Lockfile,
+) -> Option<String> {
+    if current == locked {
+        return None;
+    }
+    let mut locked_contents =
+        serde_json::to_string_pretty(locked).expect("lockfile structure is valid json");
+    locked_contents.push('\n');
+    Some(unified_diff(
+        current_contents,
+        &locked_contents,
+        "manifest.lock (current)",
+        "manifest.lock (locked)",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use flox_manifest::test_helpers::with_latest_schema;
+    use flox_rust_sdk::flox::test_helpers::flox_instance;
+    use indoc::indoc;
+
+    use super::*;
+
+    /// Lock `manifest` the way [LockManifest::handle] does without a lockfile
+    async fn lock(flox: &Flox, manifest: &str) -> Lockfile {
+        let manifest = Manifest::parse_toml_typed(manifest)
+            .unwrap()
+            .as_typed_only()
+            .migrate_typed_only(None)
+            .unwrap();
+        flox_rust_sdk::providers::lock_manifest::LockManifest::lock_manifest(
+            flox,
+            &manifest,
+            None,
+            &IncludeFetcher {
+                base_directory: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    fn check(manifest: &Path, lockfile: &Path) -> LockManifest {
+        LockManifest {
+            lockfile: Some(lockfile.to_path_buf()),
+            check: true,
+            strict: false,
+            manifest: manifest.to_path_buf(),
+        }
+    }
+
+    #[tokio::test]
+    async fn check_fails_only_for_stale_lockfile() {
+        let (flox, tempdir) = flox_instance();
+        let manifest_path = tempdir.path().join("manifest.toml");
+        let lockfile_path = tempdir.path().join("manifest.lock");
+
+        let manifest = with_latest_schema("");
+        fs::write(&manifest_path, &manifest).unwrap();
+
+        let up_to_date = lock(&flox, &manifest).await;
+        let up_to_date_contents = serde_json::to_string_pretty(&up_to_date).unwrap() + "\n";
+        fs::write(&lockfile_path, &up_to_date_contents).unwrap();
+        let (check_flox, _tempdir) = flox_instance();
+        check(&manifest_path, &lockfile_path)
+            .handle(check_flox)
+            .await
+            .unwrap();
+
+        let stale = lock(
+            &flox,
+            &with_latest_schema(indoc! {r#"
+                [vars]
+                FOO = "bar"
+            "#}),
+        )
+        .await;
+        let stale_contents = serde_json::to_string_pretty(&stale).unwrap() + "\n";
+        fs::write(&lockfile_path, &stale_contents).unwrap();
+        let (check_flox, _tempdir) = flox_instance();
+        let err = check(&manifest_path, &lockfile_path)
+            .handle(check_flox)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Lockfile '{}' is out of date, lock the manifest again to update it",
+                lockfile_path.display()
+            )
+        );
+
+        assert_eq!(
+            stale_lockfile_diff(&up_to_date, &up_to_date_contents, &up_to_date),
+            None
+        );
+        let diff = stale_lockfile_diff(&stale, &stale_contents, &up_to_date).unwrap();
+        assert!(diff.starts_with("--- manifest.lock (current)\n+++ manifest.lock (locked)\n"));
+        assert!(diff.contains("FOO"), "{diff}");
+    }
+}
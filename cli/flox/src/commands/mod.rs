@@ -31,7 +31,7 @@ mod uninstall;
 mod upgrade;
 
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::{env, fmt, mem};
 
@@ -45,6 +45,7 @@ use flox_rust_sdk::flox::{
     AuthContext,
     AuthnMode,
     DEFAULT_FLOXHUB_URL,
+    DefaultEnvironment,
     FLOX_VERSION,
     Flox,
     Floxhub,
@@ -69,13 +70,13 @@ use flox_rust_sdk::models::environment::{
 use indoc::{formatdoc, indoc};
 use tempfile::TempDir;
 use thiserror::Error;
-use toml_edit::visit_mut::VisitMut;
-use toml_edit::{Item, KeyMut, Value};
+use tokio::signal::unix::SignalKind;
 use tracing::{debug, info};
 use url::Url;
 use xdg::BaseDirectories;
 
 use self::envs::DisplayEnvironments;
+use crate::Exit;
 use crate::commands::general::update_config;
 use crate::config::{Config, EnvironmentTrust, FLOX_DIR_NAME};
 use crate::utils::active_environments::{
@@ -88,7 +89,7 @@ use crate::utils::errors::display_chain;
 use crate::utils::init::init_floxhub_client;
 use crate::utils::message;
 use crate::utils::metrics::{AWSDatalakeConnection, Client, Hub, read_metrics_uuid};
-use crate::utils::update_notifications::UpdateNotification;
+use crate::utils::update_notifications::{UpdateCheckResult, UpdateNotification};
 
 const SHELL_COMPLETION_DIR: ShellComp = ShellComp::Dir { mask: None };
 const SHELL_COMPLETION_FILE: ShellComp = ShellComp::File { mask: None };
@@ -224,9 +225,50 @@ impl Commands {
     }
 }
 
+/// Guard for the tempdir of a flox process.
+///
+/// The tempdir is removed when the guard is dropped,
+/// including while unwinding from a panic,
+/// unless it should be kept for debugging.
+struct ProcessTempDir {
+    temp_dir: Option<TempDir>,
+    keep: bool,
+}
+
+impl ProcessTempDir {
+    fn new(temp_dir: TempDir, keep: bool) -> Self {
+        Self {
+            temp_dir: Some(temp_dir),
+            keep,
+        }
+    }
+
+    fn path(&self) -> &Path {
+        self.temp_dir
+            .as_ref()
+            .expect("tempdir is only taken on drop")
+            .path()
+    }
+}
+
+impl Drop for ProcessTempDir {
+    fn drop(&mut self) {
+        let Some(temp_dir) = self.temp_dir.take() else {
+            return;
+        };
+        if self.keep {
+            debug!(temp_dir = ?temp_dir.path(), "leaving process tempdir in place");
+            let _ = temp_dir.keep();
+        } else {
+            debug!(temp_dir = ?temp_dir.path(), "removing process tempdir");
+            drop(temp_dir);
+        }
+    }
+}
+
 impl FloxArgs {
     /// Initialize the command line by creating an initial FloxBuilder
-    pub async fn handle(self, config: crate::config::Config) -> Result<()> {
+    pub async fn handle(self, mut config: crate::config::Config) -> Result<()> {
         // ensure xdg dirs exist
         tokio::fs::create_dir_all(&config.flox.config_dir).await?;
         tokio::fs::create_dir_all(&config.flox.data_dir).await?;
@@ -244,8 +286,11 @@ impl FloxArgs {
         let process_dir = config.flox.cache_dir.join("process");
         tokio::fs::create_dir_all(&process_dir).await?;
 
-        // `temp_dir` will automatically be removed from disk when the function returns
-        let temp_dir = TempDir::new_in(process_dir)?;
+        // `temp_dir` will automatically be removed from disk when the function
+        // returns or unwinds, unless it should be kept for debugging
+        let keep_tempdir = (self.debug || matches!(self.verbosity, Verbosity::Verbose(1..)))
+            && config.flox.keep_tempdir.unwrap_or_default();
+        let temp_dir = ProcessTempDir::new(TempDir::new_in(process_dir)?, keep_tempdir);
 
         let update_channel = config.flox.installer_channel.clone();
 
@@ -267,9 +312,19 @@ impl FloxArgs {
 
         let cache_dir = config.flox.cache_dir.clone();
 
+        // 'flox activate --pure' sessions neither check for updates nor collect metrics
+        let is_pure_activation = self.is_pure_activation();
+        if is_pure_activation {
+            config.flox.disable_metrics = true;
+        }
+
         let check_for_update_handle = {
             let update_channel = update_channel.clone();
             tokio::spawn(async move {
+                if is_pure_activation {
+                    debug!("Skipping update check for pure activation");
+                    return Ok(UpdateCheckResult::Skipped);
+                }
                 UpdateNotification::check_for_update(cache_dir, &update_channel).await
             })
         };
@@ -370,6 +425,7 @@ impl FloxArgs {
                 features.beta = features.beta || self.beta;
                 features
             },
+            default_environment: config.flox.default_environment.clone(),
             verbosity: self.verbosity.to_i32(),
             metrics_device_uuid,
         };
@@ -379,7 +435,13 @@ impl FloxArgs {
         );
 
         let signal_handler = async { tokio::signal::ctrl_c().await.unwrap() };
-        let keep_tempfiles = config.flox.keep_tempdir.unwrap_or_default();
+        // Process managers (systemd, container runtimes, ...) stop processes with SIGTERM
+        let terminate_handler = async {
+            tokio::signal::unix::signal(SignalKind::terminate())
+                .expect("failed to install SIGTERM handler")
+                .recv()
+                .await
+        };
 
         let cli_worker = async move {
             // command handled above
@@ -430,21 +492,25 @@ impl FloxArgs {
             result
         };
 
+        // On a signal the `cli_worker` task is dropped before it
+        // reaches its `record_command_completed`, so record it
+        // here too — an interrupted invocation still ends, and
+        // the hub's idempotent flag means at most one completion
+        // is recorded per invocation.
+        let record_interrupted_completion = || {
+            if let Err(err) =
+                flox_events::EventsHub::global().record_command_completed(v2_subcommand.to_string())
+            {
+                debug!(error = %err, "Failed to record v2 cli.command_completed event (interrupted)");
+            }
+        };
+
         // Wait for either an interrupting signal or completion of the cli work
         let result = tokio::task::LocalSet::new()
             .run_until(async {
                 tokio::select! {
                     _ = tokio::task::spawn_local(signal_handler) => {
-                        // On Ctrl-C the `cli_worker` task is dropped before it
-                        // reaches its `record_command_completed`, so record it
-                        // here too — an interrupted invocation still ends, and
-                        // the hub's idempotent flag means at most one completion
-                        // is recorded per invocation.
-                        if let Err(err) = flox_events::EventsHub::global()
-                            .record_command_completed(v2_subcommand.to_string())
-                        {
-                            debug!(error = %err, "Failed to record v2 cli.command_completed event (interrupted)");
-                        }
+                        record_interrupted_completion();
                         // TODO:
                         // For now we rely on subprocesses to inherit `flox` process group
                         // and thus being sent ctrl_c signals in sync with flox itself.
@@ -452,19 +518,25 @@ impl FloxArgs {
                         // we can find process children and propagate signals manually.
                         Err(anyhow!("user interrupted process"))
                     }
+                    _ = tokio::task::spawn_local(terminate_handler) => {
+                        record_interrupted_completion();
+                        debug!("received SIGTERM, exiting");
+                        // Exit with the conventional status for termination by SIGTERM (128 + 15)
+                        // without printing an error.
+                        Err(Exit(143.into()).into())
+                    }
                     result = tokio::task::spawn_local(cli_worker) => result?
                 }
             })
             .await;
 
         // Remove tempdirs
-        if (self.debug || matches!(self.verbosity, Verbosity::Verbose(1..))) && keep_tempfiles {
-            debug!(temp_dir = ?temp_dir.path(), "leaving process tempdir in place");
-            let _ = temp_dir.keep();
-        } else {
-            debug!(temp_dir = ?temp_dir.path(), "removing process tempdir");
-            drop(temp_dir);
-        }
+        //
+        // This runs after completion of the cli work as well as after SIGINT and SIGTERM,
+        // and [ProcessTempDir] does the same if the work panics,
+        // so `keep_tempdir` in combination with `--debug` or `-v`
+        // is respected regardless of how the command ended.
+        drop(temp_dir);
 
         result
     }
@@ -486,6 +558,14 @@ impl FloxArgs {
         )
     }
 
+    /// Whether this invocation is `flox activate --pure`
+    fn is_pure_activation(&self) -> bool {
+        matches!(
+            &self.command,
+            Some(Commands::Use(UseCommands::Activate(args))) if args.is_pure()
+        )
+    }
+
     /// Parse and validate the configured `floxhub_token`, returning `None` and
     /// emitting any user-facing warnings as a side effect.
     ///
@@ -923,7 +1003,7 @@ impl ShareCommands {
         match self {
             ShareCommands::Build(args) => args.handle(flox).await?,
             ShareCommands::Publish(args) => args.handle(config, flox).await?,
-            ShareCommands::Push(args) => args.handle(flox).await?,
+            ShareCommands::Push(args) => args.handle(config, flox).await?,
             ShareCommands::Pull(args) => args.handle(flox).await?,
             ShareCommands::Containerize(args) => args.handle(flox).await?,
         }
@@ -1282,7 +1362,7 @@ impl EnvironmentSelect {
         let env = match self {
             EnvironmentSelect::Dir(path) => DirEnvironmentSelect::Dir(path.clone())
                 .detect_concrete_environment(flox, message)?,
-            EnvironmentSelect::Unspecified => match detect_environment(message)? {
+            EnvironmentSelect::Unspecified => match detect_environment(flox, message)? {
                 Some(env) => {
                     let generation = activated_environments().is_active_with_generation(&env);
                     env.into_concrete_environment(flox, generation)?
@@ -1358,7 +1438,7 @@ impl DirEnvironmentSelect {
             // If the user doesn't specify an environment, check if there's an
             // already activated environment or an environment in the current
             // directory.
-            DirEnvironmentSelect::Unspecified => match detect_environment(message)? {
+            DirEnvironmentSelect::Unspecified => match detect_environment(flox, message)? {
                 Some(UninitializedEnvironment::Remote(_)) => {
                     Err(EnvironmentSelectError::RemoteNotSupported)
                 },
@@ -1378,7 +1458,10 @@ impl DirEnvironmentSelect {
 ///   inside a git repo.
 /// - Check if there's an already activated environment.
 /// - Prompt if both are true.
+/// - Fall back to the `default_environment` set in the user config if neither
+///   is true.
 pub fn detect_environment(
+    flox: &Flox,
     message: &str,
 ) -> Result<Option<UninitializedEnvironment>, EnvironmentSelectError> {
     let current_dir = env::current_dir().context("could not get current directory")?;
@@ -1425,11 +1508,40 @@ pub fn detect_environment(
         },
         (Some(activated_env), None) => Some(activated_env),
         (None, Some(found)) => Some(UninitializedEnvironment::DotFlox(found)),
-        (None, None) => None,
+        (None, None) => configured_default_environment(flox)?,
     };
     Ok(found)
 }
 
+/// Open the `default_environment` set in the user config, if any.
+fn configured_default_environment(flox: &Flox) -> Result<Option<UninitializedEnvironment>> {
+    let Some(default_environment) = &flox.default_environment else {
+        return Ok(None);
+    };
+    debug!(%default_environment, "using the configured default environment");
+
+    let env = match default_environment {
+        DefaultEnvironment::Path(path) => {
+            let dot_flox = DotFlox::open_in(path).with_context(|| {
+                format!(
+                    "Could not open the default environment '{}'.\n\
+                     Set a different one with 'flox config --set-default-environment'.",
+                    path.display()
+                )
+            })?;
+            UninitializedEnvironment::DotFlox(dot_flox)
+        },
+        DefaultEnvironment::Remote(env_ref) => {
+            UninitializedEnvironment::Remote(ManagedPointer::new(
+                env_ref.owner().clone(),
+                env_ref.name().clone(),
+                &flox.floxhub,
+            ))
+        },
+    };
+    Ok(Some(env))
+}
+
 /// Helper function for [detect_environment] which handles the user prompt to decide which environment to use for the current operation.
 fn query_which_environment(
     message: &str,
@@ -1687,34 +1799,7 @@ fn is_current_dir(environment: &UninitializedEnvironment) -> Result<bool> {
 /// `Environment::manifest_contents` should be used for non-composition
 /// manifests so that it matches what the user has on disk.
 fn render_composition_manifest(manifest: &Manifest<TypedOnly>) -> Result<String> {
-    // A visitor that converts inline tables to proper tables
-    // Nested tables are rendered as `dotted` tables.
-    // The default behavior when instantiating with `Visitor::new_for_document`,
-    // is to render toplevel tables as non-dotted, sections.
-    struct Visitor {
-        dotted: bool,
-    }
-    impl Visitor {
-        fn new_for_document() -> Self {
-            Visitor { dotted: false }
-        }
-    }
-    impl VisitMut for Visitor {
-        fn visit_table_like_kv_mut(&mut self, _key: KeyMut<'_>, node: &mut Item) {
-            if let toml_edit::Item::Value(Value::InlineTable(inline_table)) = node {
-                let mut table = std::mem::take(inline_table).into_table();
-                table.set_implicit(true);
-                table.set_dotted(self.dotted);
-                toml_edit::visit_mut::visit_table_mut(&mut Visitor { dotted: true }, &mut table);
-                *node = toml_edit::Item::Table(table);
-            }
-        }
-    }
-
-    let mut document = toml_edit::ser::to_document(manifest)?;
-    toml_edit::visit_mut::visit_document_mut(&mut Visitor::new_for_document(), &mut document);
-
-    Ok(document.to_string())
+    Ok(manifest.to_toml_string()?)
 }
 
 #[cfg(test)]
@@ -1785,6 +1870,12 @@ mod subcommand_name_tests {
         assert_eq!(command.subcommand_name(), "generations::list");
     }
 
+    #[test]
+    fn include_list_uses_parent_child_join_encoding() {
+        let command = parse_command(&["include", "list"]);
+        assert_eq!(command.subcommand_name(), "include::list");
+    }
+
     /// `include upgrade` must use the same `parent::child` encoding —
     /// covers the third nested-command family alongside services and
     /// generations.
@@ -1839,3 +1930,37 @@ mod subcommand_name_tests {
         assert_eq!(command.subcommand_name(), "build::update-catalogs");
     }
 }
+
+#[cfg(test)]
+mod process_temp_dir_tests {
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    use super::*;
+
+    /// Panic while holding a [ProcessTempDir] in a new process dir,
+    /// and return the process dir and the path of the tempdir.
+    fn panic_with_process_temp_dir(keep: bool) -> (TempDir, PathBuf) {
+        let process_dir = tempfile::tempdir().unwrap();
+        let mut path = None;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let temp_dir = ProcessTempDir::new(TempDir::new_in(process_dir.path()).unwrap(), keep);
+            path = Some(temp_dir.path().to_path_buf());
+            panic!("command panicked");
+        }));
+        assert!(result.is_err());
+
+        (process_dir, path.unwrap())
+    }
+
+    #[test]
+    fn tempdir_is_removed_on_panic() {
+        let (_process_dir, path) = panic_with_process_temp_dir(false);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn tempdir_is_kept_on_panic_if_requested() {
+        let (_process_dir, path) = panic_with_process_temp_dir(true);
+        assert!(path.exists());
+    }
+}
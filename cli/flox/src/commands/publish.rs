@@ -10,6 +10,7 @@ use flox_rust_sdk::providers::build::{COMMON_NIXPKGS_URL, PackageTarget};
 use flox_rust_sdk::providers::catalog::SystemEnum;
 use flox_rust_sdk::providers::nix_auth::NixAuth;
 use flox_rust_sdk::providers::publish::{
+    MetadataSigningKey,
     PublishProvider,
     Publisher,
     build_repo_err,
@@ -17,7 +18,7 @@ use flox_rust_sdk::providers::publish::{
     check_environment_metadata,
     check_package_metadata,
 };
-use floxhub_client::CatalogClientTrait;
+use floxhub_client::{BaseCatalogUrl, CatalogClientTrait};
 use indoc::formatdoc;
 use nef_lock_catalog::lock::NixFlakeref;
 use tracing::{debug, info_span, instrument, warn};
@@ -66,6 +67,20 @@ pub struct Publish {
     #[bpaf(external(system_override))]
     system_override: SystemOverride,
 
+    /// Sign the metadata of the published artifacts with the signing key,
+    /// so that consumers can verify their provenance.
+    /// The key is set with '--signing-private-key' or
+    /// `publish.signing_private_key` from 'flox config'.
+    #[bpaf(long)]
+    sign: bool,
+
+    /// Require the package to be published with this stability.
+    /// Nix expression builds without '--stability' are built against
+    /// its latest revision.
+    /// Takes precedence over the value of `publish.channel` from 'flox config'.
+    #[bpaf(long, argument("stability"))]
+    channel: Option<String>,
+
     #[bpaf(external(publish_target), optional)]
     publish_target: Option<PublishTarget>,
 }
@@ -104,6 +119,8 @@ struct PublishConfig {
     cache_args: CacheArgs,
     base_catalog_url_select: Option<BaseCatalogUrlSelect>,
     system_override: SystemOverride,
+    channel: Option<String>,
+    sign: bool,
 }
 
 impl Publish {
@@ -118,11 +135,22 @@ impl Publish {
             debug!(error = %err, "Failed to record v2 event");
         }
 
+        // CLI args take precedence over config
+        let channel = self.channel.or_else(|| {
+            config
+                .flox
+                .publish
+                .as_ref()
+                .and_then(|cfg| cfg.channel.clone())
+        });
+
         let publish_config = PublishConfig {
             metadata_only: self.metadata_only,
             cache_args: self.cache,
             base_catalog_url_select: self.base_catalog_url_select,
             system_override: self.system_override,
+            channel,
+            sign: self.sign,
         };
 
         Self::publish(config, flox, env, self.publish_target, publish_config).await
@@ -157,6 +185,26 @@ impl Publish {
         // Fail as early as possible if the user isn't authenticated or doesn't
         // belong to an org with a catalog.
         let handle = ensure_auth(&mut flox).await?;
+
+        // CLI args take precedence over config
+        let key_file = publish_config.cache_args.signing_private_key.or(config
+            .flox
+            .publish
+            .as_ref()
+            .and_then(|cfg| cfg.signing_private_key.clone()));
+
+        // Likewise, fail before building if the signing key can't be used.
+        let metadata_signing_key = match (publish_config.sign, &key_file) {
+            (false, _) => None,
+            (true, Some(key_file)) => Some(MetadataSigningKey::from_file(key_file)?),
+            (true, None) => bail!(formatdoc! {"
+                A signing key is required to sign the published artifacts.
+
+                You can supply a signing key by either:
+                - Providing a path to a key with the '--signing-private-key' option.
+                - Setting it in the config via 'flox config --set publish.signing_private_key <path>'
+            "}),
+        };
         let catalog_name = publish_config.cache_args.org.clone().unwrap_or(handle);
 
         let env_detail = env_detail_from_concrete(&env);
@@ -200,12 +248,22 @@ impl Publish {
             publish_config.base_catalog_url_select.is_some(),
         )?;
 
+        // Without an explicit base catalog selection,
+        // expression builds are built against the latest page of the channel.
+        let base_catalog_url_select = publish_config.base_catalog_url_select.or_else(|| {
+            publish_config
+                .channel
+                .clone()
+                .filter(|_| package.kind().is_expression_build())
+                .map(BaseCatalogUrlSelect::Stability)
+        });
+
         // Check the environment for appropriate state to build and publish
         let env_metadata = check_environment_metadata(&flox, &path_env)?;
 
         let selected_base_nixpkgs_url = base_nixpkgs_url_from_url_select(
             &flox,
-            publish_config.base_catalog_url_select,
+            base_catalog_url_select,
             Some(&env_metadata.lockfile),
         )
         .await?;
@@ -221,8 +279,20 @@ impl Publish {
             package,
         )?;
 
+        if let Some(channel) = &publish_config.channel {
+            check_publish_channel(
+                &flox.floxhub_client,
+                channel,
+                &package_metadata.base_catalog_ref,
+            )
+            .await?;
+        }
+
         let auth = NixAuth::from_flox(&flox)?;
-        let publish_provider = PublishProvider::new(env_metadata, package_metadata, auth);
+        let mut publish_provider = PublishProvider::new(env_metadata, package_metadata, auth);
+        if let Some(metadata_signing_key) = metadata_signing_key {
+            publish_provider = publish_provider.with_metadata_signing_key(metadata_signing_key);
+        }
 
         // Check that we can publish before building.
         let catalog = &flox.floxhub_client;
@@ -323,13 +393,6 @@ impl Publish {
             &publish_provider.package_metadata.package,
         )?;
 
-        // CLI args take precedence over config
-        let key_file = publish_config.cache_args.signing_private_key.or(config
-            .flox
-            .publish
-            .as_ref()
-            .and_then(|cfg| cfg.signing_private_key.clone()));
-
         debug!(
             "publishing package: {}",
             &publish_provider.package_metadata.package
@@ -341,7 +404,7 @@ impl Publish {
                 &catalog_name,
                 package_created,
                 &build_metadata,
-                key_file,
+                key_file.clone(),
                 publish_config.metadata_only,
             )
             .await
@@ -374,6 +437,12 @@ impl Publish {
                     .context("Failed while waiting for publish confirmation")?;
             }
         }
+        if let Some(key_file) = key_file.filter(|_| publish_config.sign) {
+            message::info(format!(
+                "Signed the metadata of the published artifacts with '{}'.",
+                key_file.display()
+            ));
+        }
         message::updated(formatdoc! {"
             Package published successfully.
 
@@ -383,6 +452,50 @@ impl Publish {
     }
 }
 
+/// Ensure that `channel` is a stability known to the catalog
+/// and that the package will be published with that stability.
+///
+/// The catalog derives the stabilities of a published package
+/// from the page its base catalog url is locked to.
+/// Publishing to a channel therefore requires the base catalog url
+/// to point to a page tagged with that stability.
+async fn check_publish_channel(
+    catalog: &impl CatalogClientTrait,
+    channel: &str,
+    base_catalog_ref: &BaseCatalogUrl,
+) -> Result<()> {
+    let base_catalog_info = catalog
+        .get_base_catalog_info()
+        .await
+        .context("could not get information about the base catalog")?;
+
+    let available_stabilities = base_catalog_info.available_stabilities();
+    if !available_stabilities.contains(&channel) {
+        bail!(formatdoc! {"
+            Unknown channel '{channel}'.
+            Available channels are: {available}
+            ", available = available_stabilities.join(", ")
+        });
+    }
+
+    let page_stabilities = base_catalog_info
+        .stabilities_of_url(base_catalog_ref)
+        .unwrap_or_default();
+    if !page_stabilities.contains(&channel) {
+        bail!(formatdoc! {"
+            The package would not be published to channel '{channel}'.
+            Its base catalog '{base_catalog_ref}' is not part of the '{channel}' stability.
+
+            For nix expression builds, pass '--stability {channel}'.
+            For manifest builds, upgrade the environment's 'toplevel' group
+            to a revision of the '{channel}' stability.
+        "});
+    }
+
+    debug!(%channel, %base_catalog_ref, "publishing to channel");
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use flox_manifest::test_helpers::with_latest_schema;
@@ -519,4 +632,32 @@ mod tests {
             )
         );
     }
+
+    #[tokio::test]
+    async fn check_publish_channel_accepts_base_catalog_with_channel() {
+        use floxhub_client::client::test_helpers::client_config;
+        use floxhub_client::{BaseCatalogInfo, FloxhubClient};
+        use httpmock::MockServer;
+
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.path("/api/v1/catalog/info/base-catalog");
+            then.status(200)
+                .json_body(serde_json::to_value(BaseCatalogInfo::new_mock()).unwrap());
+        });
+        let client = FloxhubClient::new(client_config(server.base_url().as_str())).unwrap();
+
+        let base_catalog_ref = BaseCatalogInfo::new_mock()
+            .url_for_latest_page_with_stability("not-default")
+            .unwrap();
+
+        check_publish_channel(&client, "not-default", &base_catalog_ref)
+            .await
+            .expect("base catalog page is tagged with the channel");
+
+        let err = check_publish_channel(&client, "does-not-exist", &base_catalog_ref)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown channel 'does-not-exist'"));
+    }
 }
@@ -62,6 +62,12 @@ enum PullSelect {
         #[bpaf(short, long)]
         generation: Option<GenerationId>,
 
+        /// Mark the pulled environment as read-only.
+        /// It can be activated, but not modified with 'flox edit', 'flox install',
+        /// 'flox uninstall', or 'flox upgrade'.
+        #[bpaf(long)]
+        read_only: bool,
+
         /// Reference of an environment to pull into a directory
         #[bpaf(positional("owner>/<name"))]
         remote: RemoteEnvironmentRef,
@@ -99,6 +105,7 @@ impl Pull {
                 dir,
                 copy,
                 generation,
+                read_only,
             } => {
                 // This could be a `--copy` to `PathEnvironment`, rather than
                 // `ManagedEnvironment`, but we want to keep the remote name.
@@ -129,6 +136,10 @@ impl Pull {
                     bail!("The --generation option can only be used when pulling with --copy");
                 };
 
+                if read_only && copy {
+                    bail!("'--read-only' cannot be used with '--copy'");
+                }
+
                 let start_message = format!(
                     "Pulling {env_ref} from {host} into {into_dir}",
                     env_ref = &remote,
@@ -144,7 +155,9 @@ impl Pull {
                         progress = start_message.as_str());
                 let _guard = span.entered();
 
-                Self::pull_new_environment(&flox, dir, remote, copy, self.force, generation)?;
+                Self::pull_new_environment(
+                    &flox, dir, remote, copy, self.force, generation, read_only,
+                )?;
             },
             PullSelect::RemoteUpdate { environment, copy } => {
                 let environment = environment
@@ -300,6 +313,7 @@ impl Pull {
     ///
     /// If the directory already exists, this will fail early.
     /// If opening the environment fails, the .flox/ directory will be cleaned up.
+    /// If `read_only` is set, the pointer marks the environment as read-only.
     fn pull_new_environment(
         flox: &Flox,
         env_path: PathBuf,
@@ -307,6 +321,7 @@ impl Pull {
         copy: bool,
         force: bool,
         generation: Option<GenerationId>,
+        read_only: bool,
     ) -> Result<()> {
         let dot_flox_path = env_path.join(DOT_FLOX);
         if dot_flox_path.exists() {
@@ -338,11 +353,12 @@ impl Pull {
         }
 
         // region: write pointer
-        let pointer = ManagedPointer::new(
+        let mut pointer = ManagedPointer::new(
             env_ref.owner().clone(),
             env_ref.name().clone(),
             &flox.floxhub,
         );
+        pointer.read_only = read_only;
         let mut pointer_content =
             serde_json::to_string_pretty(&pointer).context("Could not serialize pointer")?;
         pointer_content.push('\n');
@@ -407,6 +423,11 @@ impl Pull {
 
         let message_lead = if copy {
             format!("Created path environment from {env_ref}.")
+        } else if read_only {
+            format!(
+                "Pulled {env_ref} from {floxhub_host} as read-only.",
+                floxhub_host = flox.floxhub.base_url()
+            )
         } else {
             format!(
                 "Pulled {env_ref} from {floxhub_host}.",
@@ -4,6 +4,8 @@ use anyhow::{Context, Result, bail};
 use bpaf::Bpaf;
 use flox_core::data::environment_ref::EnvironmentOwner;
 use flox_events::EventsHub;
+use flox_manifest::interfaces::AsLatestSchema;
+use flox_manifest::parsed::Inner;
 use flox_rust_sdk::flox::Flox;
 use flox_rust_sdk::models::environment::managed_environment::{
     ManagedEnvironment,
@@ -18,14 +20,20 @@ use flox_rust_sdk::models::environment::{
     EnvironmentError,
     ManagedPointer,
 };
+use flox_rust_sdk::providers::build::build_symlink_path;
+use flox_rust_sdk::providers::nix_auth::NixAuth;
+use flox_rust_sdk::providers::publish::catalog_store_config_for_upload;
+use floxhub_client::PackageOutput;
 use indoc::formatdoc;
 use tracing::{debug, instrument};
 
 use crate::commands::{EnvironmentSelect, ensure_auth, environment_select};
+use crate::config::Config;
 use crate::environment_subcommand_metric;
 use crate::utils::errors::format_core_error;
 use crate::utils::events::env_detail_from_concrete;
 use crate::utils::message;
+use crate::utils::path_info::{closure_path_infos, format_size, sum_nar_sizes};
 
 // Send environment to FloxHub
 //
@@ -48,11 +56,33 @@ pub struct Push {
     /// Forcibly overwrite the remote copy of the environment
     #[bpaf(long, short)]
     force: bool,
+
+    /// Only push if the environment does not exist on FloxHub yet,
+    /// failing instead of updating an existing remote environment
+    #[bpaf(long)]
+    create_only: bool,
+
+    /// Also upload the built packages of the environment to the store of
+    /// the owner's catalog, so that pulling the environment gets prebuilt
+    /// packages
+    #[bpaf(long)]
+    include_build_artifacts: bool,
 }
 
 impl Push {
     #[instrument(name = "push", skip_all)]
-    pub async fn handle(self, mut flox: Flox) -> Result<()> {
+    pub async fn handle(self, config: Config, mut flox: Flox) -> Result<()> {
+        if self.create_only && self.force {
+            bail!("'--create-only' cannot be used with '--force'");
+        }
+
+        if self.include_build_artifacts && matches!(self.environment, EnvironmentSelect::Remote(_))
+        {
+            bail!(
+                "'--include-build-artifacts' cannot be used with remote environments, they have no builds."
+            );
+        }
+
         // Ensure the user is logged in for the following remote operations
         ensure_auth(&mut flox).await?;
 
@@ -78,7 +108,7 @@ impl Push {
             }
         }
 
-        let env = self
+        let mut env = self
             .environment
             .detect_concrete_environment(&mut flox, "Push")
             .await?;
@@ -91,6 +121,25 @@ impl Push {
             debug!(error = %err, "Failed to record v2 event");
         }
 
+        // Collect the artifacts before pushing,
+        // so that missing builds are reported before anything is changed.
+        let build_artifacts = if self.include_build_artifacts {
+            let owner = match &env {
+                ConcreteEnvironment::Managed(managed_environment) => {
+                    managed_environment.pointer().owner.clone()
+                },
+                _ => match &self.owner {
+                    Some(owner) => owner.clone(),
+                    None => EnvironmentOwner::from_str(
+                        flox.auth_context.handle().context("Need to be logged in")?,
+                    )?,
+                },
+            };
+            Some((owner, built_store_paths(&flox, &mut env)?))
+        } else {
+            None
+        };
+
         match (env, self.owner) {
             (ConcreteEnvironment::Managed(managed_environment), Some(owner)) => {
                 cant_change_owner_error(managed_environment.pointer(), owner)?
@@ -98,8 +147,18 @@ impl Push {
             (ConcreteEnvironment::Remote(remote_environment), Some(owner)) => {
                 cant_change_owner_error(remote_environment.pointer(), owner)?
             },
-            (ConcreteEnvironment::Path(path_environment), owner) => {
-                handle_path_environment_push(&flox, path_environment, owner, self.force)?
+            (ConcreteEnvironment::Path(path_environment), owner) => handle_path_environment_push(
+                &flox,
+                path_environment,
+                owner,
+                self.force,
+                self.create_only,
+            )?,
+            (ConcreteEnvironment::Managed(managed_environment), None) if self.create_only => {
+                already_exists_error(managed_environment.pointer())?
+            },
+            (ConcreteEnvironment::Remote(remote_environment), None) if self.create_only => {
+                already_exists_error(remote_environment.pointer())?
             },
             (ConcreteEnvironment::Managed(managed_environment), None) => {
                 handle_managed_environment_push(&flox, managed_environment, self.force)?
@@ -109,15 +168,107 @@ impl Push {
             },
         }
 
+        if let Some((owner, store_paths)) = build_artifacts {
+            upload_build_artifacts(&config, &flox, &owner, &store_paths).await?;
+        }
+
         Ok(())
     }
 }
 
+/// Build artifacts larger than this are reported with a warning,
+/// as everyone pulling the environment may download them.
+const LARGE_BUILD_ARTIFACTS_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Store paths of the environment's builds,
+/// as linked to by the `result-<build>` links created by 'flox build'.
+///
+/// Fails if any of the builds in the manifest has not been built.
+fn built_store_paths(flox: &Flox, env: &mut ConcreteEnvironment) -> Result<Vec<String>> {
+    let manifest = env.manifest(flox)?;
+    let builds = manifest.as_latest_schema().build.inner().keys();
+
+    let mut store_paths = Vec::new();
+    let mut unbuilt = Vec::new();
+    for build in builds {
+        let link = build_symlink_path(&*env, build)?;
+        match std::fs::canonicalize(&link) {
+            Ok(store_path) => store_paths.push(store_path.to_string_lossy().to_string()),
+            Err(err) => {
+                debug!(build, link = %link.display(), %err, "build result not found");
+                unbuilt.push(build.as_str());
+            },
+        }
+    }
+
+    if !unbuilt.is_empty() {
+        bail!(formatdoc! {"
+            The following packages have not been built: {unbuilt}
+
+            Build them with 'flox build' before pushing with '--include-build-artifacts'.",
+            unbuilt = unbuilt.join(", ")
+        });
+    }
+    Ok(store_paths)
+}
+
+/// Upload `store_paths` to the store of the catalog of `owner`,
+/// reusing the upload of 'flox publish', and report the uploaded size.
+async fn upload_build_artifacts(
+    config: &Config,
+    flox: &Flox,
+    owner: &EnvironmentOwner,
+    store_paths: &[String],
+) -> Result<()> {
+    if store_paths.is_empty() {
+        message::info("The environment has no builds, no build artifacts were uploaded.");
+        return Ok(());
+    }
+
+    let key_file = config
+        .flox
+        .publish
+        .as_ref()
+        .and_then(|publish| publish.signing_private_key.clone());
+    let auth = NixAuth::from_flox(flox)?;
+    let catalog_store_config =
+        catalog_store_config_for_upload(&flox.floxhub_client, owner.as_str(), key_file, &auth)
+            .await?;
+
+    let outputs = store_paths
+        .iter()
+        .map(|store_path| PackageOutput {
+            name: "out".to_string(),
+            store_path: store_path.clone(),
+        })
+        .collect::<Vec<_>>();
+    catalog_store_config.maybe_upload_artifacts(&outputs)?;
+
+    let Some(size) = closure_path_infos(store_paths).and_then(|infos| sum_nar_sizes(&infos)) else {
+        message::updated("Uploaded build artifacts.");
+        return Ok(());
+    };
+
+    message::updated(format!(
+        "Uploaded build artifacts with a total size of {}.",
+        format_size(size)
+    ));
+    if size > LARGE_BUILD_ARTIFACTS_BYTES {
+        message::warning(formatdoc! {"
+            The build artifacts are larger than {threshold},
+            pulling the environment may download all of them.",
+            threshold = format_size(LARGE_BUILD_ARTIFACTS_BYTES)
+        });
+    }
+    Ok(())
+}
+
 fn handle_path_environment_push(
     flox: &Flox,
     path_environment: PathEnvironment,
     owner: Option<EnvironmentOwner>,
     force: bool,
+    create_only: bool,
 ) -> Result<()> {
     let owner = if let Some(owner) = owner {
         owner
@@ -128,8 +279,13 @@ fn handle_path_environment_push(
     let pointer = ManagedPointer::new(owner.clone(), path_environment.name(), &flox.floxhub);
 
     let managed_environment =
-        ManagedEnvironment::push_new(flox, path_environment, owner, force, false)
-            .map_err(|err| convert_error(err, pointer, true))?;
+        match ManagedEnvironment::push_new(flox, path_environment, owner, force, false) {
+            Ok(managed_environment) => managed_environment,
+            Err(EnvironmentError::ManagedEnvironment(
+                ManagedEnvironmentError::UpstreamAlreadyExists { .. },
+            )) if create_only => return already_exists_error(&pointer),
+            Err(err) => return Err(convert_error(err, pointer, true)),
+        };
 
     message::updated(push_message(managed_environment.pointer(), force, true)?);
     Ok(())
@@ -258,6 +414,16 @@ fn convert_error(
     }
 }
 
+/// Error for `--create-only` when the environment already exists on FloxHub
+fn already_exists_error(pointer: &ManagedPointer) -> Result<()> {
+    bail!(formatdoc! {"
+        An environment named {owner}/{name} already exists on FloxHub.
+
+        '--create-only' only pushes environments that don't exist on FloxHub yet.
+        To push to a new environment, rename it with 'flox edit --name <new name>'.
+    ", owner = pointer.owner, name = pointer.name});
+}
+
 fn cant_change_owner_error(pointer: &ManagedPointer, owner: EnvironmentOwner) -> Result<()> {
     bail!(formatdoc! {"
         Cannot change the owner of an environment already pushed to FloxHub.
@@ -274,18 +440,23 @@ mod tests {
     use std::str::FromStr;
 
     use flox_core::data::environment_ref::EnvironmentOwner;
-    use flox_rust_sdk::flox::test_helpers::{flox_instance_with_optional_floxhub, set_test_auth};
+    use flox_rust_sdk::flox::test_helpers::{
+        flox_instance,
+        flox_instance_with_optional_floxhub,
+        set_test_auth,
+    };
     use flox_rust_sdk::models::environment::managed_environment::test_helpers::mock_managed_environment_in;
     use flox_rust_sdk::models::environment::path_environment::test_helpers::new_path_environment_in;
     use flox_rust_sdk::models::environment::remote_environment::RemoteEnvironment;
-    use flox_rust_sdk::models::environment::{Environment, ManagedPointer};
+    use flox_rust_sdk::models::environment::{ConcreteEnvironment, Environment, ManagedPointer};
     use flox_rust_sdk::utils::logging::test_helpers::test_subscriber_message_only;
     use indoc::indoc;
     use pretty_assertions::assert_eq;
     use tracing::instrument::WithSubscriber;
 
-    use super::Push;
+    use super::{Push, built_store_paths};
     use crate::commands::EnvironmentSelect;
+    use crate::config::Config;
 
     const EMPTY_MANIFEST: &str = "version = 1";
 
@@ -303,10 +474,12 @@ mod tests {
             environment: EnvironmentSelect::Dir(env.parent_path().unwrap()),
             owner: Some(owner),
             force: false,
+            create_only: false,
+            include_build_artifacts: false,
         };
 
         push_cmd
-            .handle(flox)
+            .handle(Config::default(), flox)
             .with_subscriber(subscriber)
             .await
             .unwrap();
@@ -341,16 +514,19 @@ mod tests {
             environment: EnvironmentSelect::Dir(env.parent_path().unwrap()),
             owner: None,
             force: false,
+            create_only: false,
+            include_build_artifacts: false,
         };
 
         let updated_manifest = indoc! {"
             # load bearing comment
             version = 1
         "};
-        env.edit(&flox, updated_manifest.to_string()).unwrap();
+        env.edit(&flox, updated_manifest.to_string(), false, false)
+            .unwrap();
 
         push_cmd
-            .handle(flox)
+            .handle(Config::default(), flox)
             .with_subscriber(subscriber)
             .await
             .unwrap();
@@ -385,10 +561,12 @@ mod tests {
             environment: EnvironmentSelect::Dir(env.parent_path().unwrap()),
             owner: None,
             force: false,
+            create_only: false,
+            include_build_artifacts: false,
         };
 
         push_cmd
-            .handle(flox)
+            .handle(Config::default(), flox)
             .with_subscriber(subscriber)
             .await
             .unwrap();
@@ -412,9 +590,11 @@ mod tests {
             environment: EnvironmentSelect::Remote(env_ref),
             owner: None,
             force: false,
+            create_only: false,
+            include_build_artifacts: false,
         };
 
-        let result = push_cmd.handle(flox).await;
+        let result = push_cmd.handle(Config::default(), flox).await;
         assert!(result.is_err());
         let err_msg = result.unwrap_err().to_string();
         assert!(
@@ -453,7 +633,7 @@ mod tests {
             version = 1
         "};
         remote_env
-            .edit(&flox, updated_manifest.to_string())
+            .edit(&flox, updated_manifest.to_string(), false, false)
             .unwrap();
 
         // Push the remote environment changes using -r
@@ -463,10 +643,12 @@ mod tests {
             environment: EnvironmentSelect::Remote(env_ref),
             owner: None,
             force: false,
+            create_only: false,
+            include_build_artifacts: false,
         };
 
         push_remote_cmd
-            .handle(flox)
+            .handle(Config::default(), flox)
             .with_subscriber(subscriber)
             .await
             .unwrap();
@@ -509,10 +691,12 @@ mod tests {
             environment: EnvironmentSelect::Remote(env_ref),
             owner: None,
             force: false,
+            create_only: false,
+            include_build_artifacts: false,
         };
 
         push_remote_cmd
-            .handle(flox)
+            .handle(Config::default(), flox)
             .with_subscriber(subscriber)
             .await
             .unwrap();
@@ -523,4 +707,76 @@ mod tests {
 
         "});
     }
+
+    #[tokio::test]
+    async fn push_create_only_fails_if_remote_exists() {
+        let name = "my-env";
+        let owner = EnvironmentOwner::from_str("owner").unwrap();
+
+        let (mut flox, tempdir) = flox_instance_with_optional_floxhub(Some(&owner));
+        set_test_auth(&mut flox, owner.as_str());
+
+        // Create and push a managed environment to mock FloxHub
+        let mut env = mock_managed_environment_in(
+            &flox,
+            EMPTY_MANIFEST,
+            owner.clone(),
+            tempdir.path().join("existing").join(name),
+            Some(name),
+        );
+        env.push(&flox, false).unwrap();
+
+        let env = new_path_environment_in(&flox, EMPTY_MANIFEST, tempdir.path().join(name));
+        let err = Push {
+            environment: EnvironmentSelect::Dir(env.parent_path().unwrap()),
+            owner: Some(owner),
+            force: false,
+            create_only: true,
+            include_build_artifacts: false,
+        }
+        .handle(Config::default(), flox)
+        .await
+        .unwrap_err();
+
+        assert!(
+            err.to_string().contains("already exists on FloxHub"),
+            "in: {err}"
+        );
+    }
+
+    /// Build artifacts are the targets of the `result-<build>` links,
+    /// and all builds of the manifest have to be built
+    #[test]
+    fn built_store_paths_requires_all_builds() {
+        let (flox, tempdir) = flox_instance();
+        let manifest = indoc! {r#"
+            version = 1
+
+            [build.built]
+            command = "true"
+
+            [build.unbuilt]
+            command = "true"
+        "#};
+        let env = new_path_environment_in(&flox, manifest, tempdir.path().join("env"));
+        let parent = env.parent_path().unwrap();
+        let mut env = ConcreteEnvironment::Path(env);
+
+        let output = tempdir.path().join("output");
+        std::fs::create_dir(&output).unwrap();
+        std::os::unix::fs::symlink(&output, parent.join("result-built")).unwrap();
+
+        let err = built_store_paths(&flox, &mut env).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("The following packages have not been built: unbuilt")
+        );
+
+        std::os::unix::fs::symlink(&output, parent.join("result-unbuilt")).unwrap();
+        let output = std::fs::canonicalize(&output).unwrap();
+        assert_eq!(built_store_paths(&flox, &mut env).unwrap(), vec![
+            output.to_string_lossy().to_string(),
+            output.to_string_lossy().to_string(),
+        ]);
+    }
 }
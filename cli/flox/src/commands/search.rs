@@ -1,24 +1,62 @@
+use std::collections::BTreeMap;
 use std::fmt::Write;
+use std::fs;
 use std::num::NonZeroU8;
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 use bpaf::Bpaf;
+use chrono::{DateTime, Duration, Utc};
 use flox_events::EventsHub;
+use flox_manifest::interfaces::AsLatestSchema;
+use flox_manifest::raw::DEFAULT_SYSTEMS_STR;
 use flox_rust_sdk::flox::Flox;
+use flox_rust_sdk::models::environment::Environment;
 use flox_rust_sdk::providers::catalog::SearchTerm;
-use floxhub_client::{CatalogClientTrait, SearchResults};
+use floxhub_client::{
+    CatalogClientTrait,
+    CatalogQoS,
+    PackageBuild,
+    PackageSystem,
+    SearchResult,
+    SearchResults,
+};
+use futures::StreamExt;
 use indoc::{formatdoc, indoc};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use tracing::{debug, instrument};
 
+use crate::commands::EnvironmentSelect;
 use crate::config::Config;
 use crate::subcommand_metric;
 use crate::utils::didyoumean::{DidYouMean, SearchSuggestion};
+use crate::utils::duration::HumanDuration;
 use crate::utils::message::{self, stderr_supports_color, stdout_supports_color};
-use crate::utils::search::DisplaySearchResults;
+use crate::utils::search::{
+    DisplaySearchResults,
+    OutputName,
+    PnameGroup,
+    dedup_results,
+    group_by_pname,
+    sort_newest_first,
+};
 use crate::utils::tracing::sentry_set_tag;
 
 pub(crate) const DEFAULT_SEARCH_LIMIT: Option<NonZeroU8> = NonZeroU8::new(10);
 const FLOX_SHOW_HINT: &str = "Use 'flox show <package>' to see available versions";
+const SEARCH_CACHE_FILE_NAME: &str = "search-cache.json";
+const SEARCH_CACHE_TTL: Duration = Duration::minutes(5);
+/// Name of the catalog that is searched by default
+pub(crate) const BASE_CATALOG: &str = "nixpkgs";
+/// Maximum number of concurrent catalog requests for details of search results
+const CATALOG_LOOKUP_CONCURRENCY: usize = 8;
+/// Maximum number of search results that filters applied before the limit
+/// are applied to, since they may need a catalog request for each result
+const MAX_FILTERED_RESULTS: NonZeroU8 = NonZeroU8::MAX;
 
 fn missing_search_term<T>() -> Result<T> {
     bail!(indoc! {"
@@ -34,10 +72,56 @@ pub struct Search {
     #[bpaf(long)]
     pub json: bool,
 
+    /// With '--json', keep results with the same name, version,
+    /// and system as an earlier result
+    #[bpaf(long)]
+    pub no_dedup: bool,
+
     /// Print all search results
     #[bpaf(short, long)]
     pub all: bool,
 
+    /// Sort results by version, newest first
+    #[bpaf(long)]
+    pub newest_first: bool,
+
+    /// Search for packages available on <system> instead of the current system
+    #[bpaf(long, argument("system"))]
+    pub system: Option<SearchSystem>,
+
+    /// Only show packages available on all systems of the current environment,
+    /// as listed in its 'options.systems'
+    #[bpaf(long)]
+    pub manifest_systems: bool,
+
+    /// Only show packages added to the catalog within <duration>,
+    /// e.g. '30d' or '1w2d'
+    #[bpaf(long, argument("duration"))]
+    pub since: Option<HumanDuration>,
+
+    /// Only show packages the catalog has marked as renamed, removed,
+    /// or deprecated
+    #[bpaf(long)]
+    pub deprecated_only: bool,
+
+    /// Query the catalog even if results for this search are cached
+    #[bpaf(long)]
+    pub no_cache: bool,
+
+    /// Only search the catalog <name>, e.g. a custom catalog of your
+    /// organization, instead of the default catalogs
+    #[bpaf(long, argument("name"))]
+    pub catalog: Option<String>,
+
+    /// Include the output names of each package in the results
+    #[bpaf(long)]
+    pub output_names: bool,
+
+    /// Show one line per package name with the number of versions found
+    /// and the newest version
+    #[bpaf(long)]
+    pub group_by_pname: bool,
+
     /// The package to search for in the format '<pkg-path>'.
     ///
     /// ex. python310Packages.pip
@@ -45,14 +129,63 @@ pub struct Search {
     pub search_term: String,
 }
 
+/// A system packages can be searched for in the catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSystem(pub PackageSystem);
+
+const SEARCHABLE_SYSTEMS: [PackageSystem; 4] = [
+    PackageSystem::Aarch64Darwin,
+    PackageSystem::Aarch64Linux,
+    PackageSystem::X8664Darwin,
+    PackageSystem::X8664Linux,
+];
+
+impl FromStr for SearchSystem {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SEARCHABLE_SYSTEMS
+            .into_iter()
+            .find(|system| system.to_string() == s)
+            .map(SearchSystem)
+            .ok_or_else(|| {
+                format!(
+                    "unsupported system '{s}', expected one of: {}",
+                    SEARCHABLE_SYSTEMS.iter().join(", ")
+                )
+            })
+    }
+}
+
 impl Search {
     #[instrument(name = "search", skip_all)]
-    pub async fn handle(self, config: Config, flox: Flox) -> Result<()> {
+    pub async fn handle(self, config: Config, mut flox: Flox) -> Result<()> {
         let search_term = &self.search_term;
 
+        if self.manifest_systems && self.system.is_some() {
+            bail!("'--manifest-systems' cannot be used with '--system'");
+        }
+        if self.no_dedup && !self.json {
+            bail!("'--no-dedup' can only be used with '--json'");
+        }
+
         sentry_set_tag("json", self.json);
         sentry_set_tag("show_all", self.all);
+        sentry_set_tag("output_names", self.output_names);
+        sentry_set_tag("group_by_pname", self.group_by_pname);
+        sentry_set_tag("manifest_systems", self.manifest_systems);
+        sentry_set_tag("since", self.since.is_some());
+        sentry_set_tag("deprecated_only", self.deprecated_only);
         sentry_set_tag("search_term", search_term);
+        if let Some(catalog) = &self.catalog {
+            sentry_set_tag("search_catalog", catalog);
+        }
+
+        // The user is waiting for the results,
+        // so tag the requests for the catalog to prioritize them.
+        flox.floxhub_client.update_config(|config| {
+            config.qos = CatalogQoS::UserInitiated;
+        })?;
         subcommand_metric!("search", search_term = search_term);
         if let Err(err) = EventsHub::global().record_search(search_term.clone()) {
             debug!(error = %err, "Failed to record v2 event");
@@ -60,13 +193,42 @@ impl Search {
 
         debug!("performing search for term: {}", search_term);
 
+        let system = match self.system {
+            Some(SearchSystem(system)) => system,
+            None => flox.system.clone().try_into()?,
+        };
+        let is_other_system = system.to_string() != flox.system;
+        sentry_set_tag("search_system", system.to_string());
+
+        let manifest_systems = if self.manifest_systems {
+            Some(environment_systems(&mut flox).await?)
+        } else {
+            None
+        };
+
         let limit = if self.all {
             None
         } else {
             config.flox.search_limit.or(DEFAULT_SEARCH_LIMIT)
         };
+        // '--manifest-systems', the '--since' window, and '--deprecated-only'
+        // have to be applied before the limit, otherwise matching packages
+        // beyond the limit would be missed.
+        // They are only applied to the first MAX_FILTERED_RESULTS results,
+        // and no more results are filtered once the limit is filled.
+        let filter_before_limit =
+            manifest_systems.is_some() || self.since.is_some() || self.deprecated_only;
+        let search_limit = if filter_before_limit {
+            Some(MAX_FILTERED_RESULTS)
+        } else {
+            limit
+        };
 
-        let results = {
+        if let Some(catalog) = &self.catalog {
+            ensure_catalog_exists(&flox.floxhub_client, catalog).await?;
+        }
+
+        let mut results = {
             tracing::debug!("using catalog client for search");
             let parsed_search = match SearchTerm::from_arg(search_term) {
                 SearchTerm::Clean(term) => term,
@@ -79,31 +241,191 @@ impl Search {
                 },
             };
 
-            let catalog = &flox.floxhub_client;
-            catalog
-                .search_with_spinner(parsed_search, flox.system.clone().try_into()?, limit)
-                .await?
+            let mut cache = SearchCache::open(&flox.cache_dir, flox.floxhub_client.base_url());
+            let cache_key = SearchCache::key(
+                &parsed_search,
+                &system,
+                search_limit,
+                self.catalog.as_deref(),
+                flox.auth_context.handle(),
+            );
+
+            match cache.get(&cache_key).filter(|_| !self.no_cache) {
+                Some(results) => {
+                    debug!(%cache_key, "using cached search results");
+                    results
+                },
+                None => {
+                    let client = &flox.floxhub_client;
+                    let results = match &self.catalog {
+                        Some(catalog) => {
+                            client
+                                .search_in_catalog(
+                                    parsed_search,
+                                    catalog,
+                                    system.clone(),
+                                    search_limit,
+                                )
+                                .await?
+                        },
+                        None => {
+                            client
+                                .search_with_spinner(parsed_search, system.clone(), search_limit)
+                                .await?
+                        },
+                    };
+                    if let Err(err) = cache.insert(cache_key, results.clone()) {
+                        debug!(error = %err, "failed to cache search results");
+                    }
+                    results
+                },
+            }
+        };
+
+        // There were more than MAX_FILTERED_RESULTS results to filter
+        let candidates_capped = filter_before_limit
+            && results
+                .count
+                .is_some_and(|count| count > results.results.len() as u64);
+
+        // The catalog may return the same package on several pages
+        if self.json && !self.no_dedup {
+            let duplicates = dedup_results(&mut results.results);
+            if duplicates > 0 {
+                debug!(duplicates, "removed duplicate search results");
+            }
+        }
+
+        let filter_limit = limit.map(|limit| usize::from(limit.get()));
+        let mut stopped_at_limit = false;
+
+        if self.deprecated_only {
+            results
+                .results
+                .retain(|result| result.deprecation.is_some());
+        }
+
+        let unavailable_results = match &manifest_systems {
+            Some(systems) => {
+                // The '--since' window is applied afterwards,
+                // so the limit can't be filled yet
+                let systems_limit = if self.since.is_some() {
+                    None
+                } else {
+                    filter_limit
+                };
+                let available = retain_available_on_systems(
+                    &flox.floxhub_client,
+                    results.results,
+                    systems,
+                    systems_limit,
+                )
+                .await?;
+                stopped_at_limit |= !available.all_checked;
+                let unavailable = available.n_checked - available.matches.len();
+                results.results = available.matches;
+                unavailable
+            },
+            None => 0,
+        };
+
+        if let Some(HumanDuration(since)) = self.since {
+            let cutoff = Utc::now() - Duration::from_std(since)?;
+            let added_since = retain_added_since(
+                &flox.floxhub_client,
+                results.results,
+                &system,
+                cutoff,
+                filter_limit,
+            )
+            .await?;
+            stopped_at_limit |= !added_since.all_checked;
+            results.results = added_since.matches;
+        }
+
+        // The count of matching results is only known if all results were filtered
+        let mut filtered_results_hint = None;
+        if filter_before_limit {
+            let n_matching = results.results.len();
+            if let Some(limit) = filter_limit {
+                results.results.truncate(limit);
+            }
+            results.count = (!stopped_at_limit && !candidates_capped).then_some(n_matching as u64);
+
+            if stopped_at_limit {
+                filtered_results_hint = Some(format!(
+                    "Showing the first {n_matching} matching results. Use 'flox search {search_term} --all' to see more."
+                ));
+            } else if candidates_capped {
+                filtered_results_hint = Some(format!(
+                    "Only the first {MAX_FILTERED_RESULTS} results for '{search_term}' were filtered, more may match."
+                ));
+            }
+        }
+
+        let unparseable_versions = if self.newest_first {
+            sort_newest_first(&mut results.results)
+        } else {
+            0
+        };
+        if unparseable_versions > 0 {
+            debug!(
+                unparseable_versions,
+                "sorted results without semantic versions by their version components"
+            );
+        }
+
+        let output_names = if self.output_names {
+            Some(fetch_output_names(&flox.floxhub_client, &results.results, &system).await)
+        } else {
+            None
         };
 
+        let pname_groups = self
+            .group_by_pname
+            .then(|| group_by_pname(results.results.clone()));
+
         // Render what we have no matter what, then indicate whether we encountered an error.
         if self.json {
             debug!("printing search results as JSON");
-            render_search_results_json(results)?;
+            let json = match pname_groups {
+                Some(groups) => render_pname_groups_json(groups, output_names.as_ref())?,
+                None => render_search_results_json(results, output_names.as_ref())?,
+            };
+            println!("{json}");
         } else {
             debug!("printing search results as user facing");
 
-            let system = flox.system.clone();
             let catalog = &flox.floxhub_client;
             let suggestion = DidYouMean::<SearchSuggestion>::new(
                 search_term,
                 catalog,
-                system,
+                system.to_string(),
                 stderr_supports_color(),
             );
 
             if results.results.is_empty() {
                 let mut message =
                     format!("No packages matched this search term: '{}'", search_term);
+                if is_other_system {
+                    message = format!("{message}\nNo results are available for '{system}'.");
+                }
+                if let Some(systems) = &manifest_systems {
+                    message = format!(
+                        "{message}\nNo results are available on all of the environment's systems ({}).",
+                        systems.iter().join(", ")
+                    );
+                }
+                if self.since.is_some() {
+                    message = format!(
+                        "{message}\nNo results were added to the catalog within the '--since' window."
+                    );
+                }
+                if self.deprecated_only {
+                    message = format!(
+                        "{message}\nNo results have deprecation information in the catalog."
+                    );
+                }
                 if suggestion.has_suggestions() {
                     message = formatdoc! {"
                         {message}
@@ -116,11 +438,17 @@ impl Search {
                 bail!(message);
             }
 
-            let results = DisplaySearchResults::from_search_results(
+            let mut results = DisplaySearchResults::from_search_results(
                 search_term,
                 results,
                 stdout_supports_color(),
             )?;
+            if let Some(output_names) = output_names {
+                results = results.with_output_names(output_names);
+            }
+            if let Some(groups) = &pname_groups {
+                results = results.with_pname_groups(groups);
+            }
             println!("{results}");
 
             let mut hints = String::new();
@@ -130,6 +458,55 @@ impl Search {
                 writeln!(&mut hints, "{hint}")?;
             }
 
+            if is_other_system {
+                writeln!(&mut hints)?;
+                writeln!(&mut hints, "Showing packages available for '{system}'.")?;
+            }
+
+            if let Some(systems) = &manifest_systems {
+                writeln!(&mut hints)?;
+                writeln!(
+                    &mut hints,
+                    "Showing packages available on all of the environment's systems ({}).",
+                    systems.iter().join(", ")
+                )?;
+                if unavailable_results > 0 {
+                    writeln!(
+                        &mut hints,
+                        "{unavailable_results} results not available on all of these systems were hidden."
+                    )?;
+                }
+            }
+
+            if self.since.is_some() {
+                writeln!(&mut hints)?;
+                writeln!(
+                    &mut hints,
+                    "Showing packages added to the catalog within the '--since' window."
+                )?;
+            }
+
+            if self.deprecated_only {
+                writeln!(&mut hints)?;
+                writeln!(
+                    &mut hints,
+                    "Showing packages the catalog has marked as renamed, removed, or deprecated."
+                )?;
+            }
+
+            if let Some(hint) = &filtered_results_hint {
+                writeln!(&mut hints)?;
+                writeln!(&mut hints, "{hint}")?;
+            }
+
+            if unparseable_versions > 0 {
+                writeln!(&mut hints)?;
+                writeln!(
+                    &mut hints,
+                    "{unparseable_versions} results without a semantic version are sorted last."
+                )?;
+            }
+
             writeln!(&mut hints)?;
             writeln!(&mut hints, "{FLOX_SHOW_HINT}")?;
 
@@ -147,8 +524,653 @@ impl Search {
     }
 }
 
-fn render_search_results_json(search_results: SearchResults) -> Result<()> {
-    let json = serde_json::to_string(&search_results.results)?;
-    println!("{json}");
+/// Short-lived on-disk cache of search results,
+/// so that repeated searches don't query the catalog each time.
+///
+/// Entries are keyed on the normalized search term, system, limit, catalog,
+/// and the handle of the authenticated user,
+/// as results can depend on the catalogs a user has access to.
+/// Entries expire after [SEARCH_CACHE_TTL].
+/// All entries are discarded if the catalog URL changes.
+#[derive(Debug)]
+struct SearchCache {
+    path: PathBuf,
+    contents: SearchCacheContents,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SearchCacheContents {
+    catalog_url: String,
+    entries: BTreeMap<String, SearchCacheEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SearchCacheEntry {
+    cached_at: DateTime<Utc>,
+    results: SearchResults,
+}
+
+impl SearchCache {
+    /// Read the cache in `cache_dir` for the catalog at `catalog_url`.
+    ///
+    /// A missing, unreadable, or outdated cache is treated as empty.
+    fn open(cache_dir: &Path, catalog_url: &str) -> Self {
+        let path = cache_dir.join(SEARCH_CACHE_FILE_NAME);
+        let contents = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<SearchCacheContents>(&contents).ok())
+            .filter(|contents| contents.catalog_url == catalog_url)
+            .unwrap_or_else(|| SearchCacheContents {
+                catalog_url: catalog_url.to_string(),
+                entries: BTreeMap::new(),
+            });
+        Self { path, contents }
+    }
+
+    fn key(
+        search_term: &str,
+        system: &PackageSystem,
+        limit: Option<NonZeroU8>,
+        catalog: Option<&str>,
+        handle: Option<&str>,
+    ) -> String {
+        let limit = limit.map_or_else(|| "all".to_string(), |limit| limit.to_string());
+        let key = format!("{system}/{limit}/{}", search_term.trim().to_lowercase());
+        let key = match catalog {
+            Some(catalog) => format!("{catalog}:{key}"),
+            None => key,
+        };
+        match handle {
+            Some(handle) => format!("{handle}@{key}"),
+            None => key,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<SearchResults> {
+        self.contents
+            .entries
+            .get(key)
+            .filter(|entry| Utc::now() - entry.cached_at < SEARCH_CACHE_TTL)
+            .map(|entry| entry.results.clone())
+    }
+
+    /// Store `results` for `key`, dropping expired entries.
+    fn insert(&mut self, key: String, results: SearchResults) -> Result<()> {
+        let now = Utc::now();
+        self.contents
+            .entries
+            .retain(|_, entry| now - entry.cached_at < SEARCH_CACHE_TTL);
+        self.contents.entries.insert(key, SearchCacheEntry {
+            cached_at: now,
+            results,
+        });
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tempfile =
+            tempfile::NamedTempFile::new_in(self.path.parent().unwrap_or(Path::new(".")))?;
+        serde_json::to_writer(&tempfile, &self.contents)?;
+        tempfile.persist(&self.path)?;
+        Ok(())
+    }
+}
+
+/// The systems of the current environment, as listed in its manifest,
+/// or the default systems if the manifest doesn't list any.
+async fn environment_systems(flox: &mut Flox) -> Result<Vec<PackageSystem>> {
+    let mut environment = EnvironmentSelect::Unspecified
+        .detect_concrete_environment(flox, "Search for packages available on the systems of")
+        .await?;
+    let manifest = environment.manifest(flox)?;
+    let systems = match &manifest.as_latest_schema().options.systems {
+        Some(systems) => systems.clone(),
+        None => DEFAULT_SYSTEMS_STR.to_vec(),
+    };
+
+    systems
+        .into_iter()
+        .map(|system| {
+            SearchSystem::from_str(&system)
+                .map(|SearchSystem(system)| system)
+                .map_err(|err| anyhow!("The environment's systems can't be searched: {err}"))
+        })
+        .collect()
+}
+
+/// Fail if the catalog server doesn't know the catalog `name`.
+///
+/// The base catalog is always known.
+pub(crate) async fn ensure_catalog_exists(
+    client: &impl CatalogClientTrait,
+    name: &str,
+) -> Result<()> {
+    if name == BASE_CATALOG {
+        return Ok(());
+    }
+    if !client.catalog_exists(name).await? {
+        bail!("The catalog '{name}' does not exist.");
+    }
     Ok(())
 }
+
+/// Keep only the first `limit` search results with builds of the same version
+/// for each of `systems`.
+///
+/// Search results are only for a single system,
+/// so the builds of each package are requested with [first_matches].
+/// Fails if the builds of any package can't be fetched.
+async fn retain_available_on_systems(
+    client: &impl CatalogClientTrait,
+    results: Vec<SearchResult>,
+    systems: &[PackageSystem],
+    limit: Option<usize>,
+) -> Result<FirstMatches<SearchResult>> {
+    let lookups = results.into_iter().map(|result| async move {
+        let details = client
+            .package_versions(&result.pkg_path)
+            .await
+            .with_context(|| format!("Failed to look up the systems of '{}'", result.pkg_path))?;
+        let available = available_on_systems(&details.results, systems, result.version.as_deref());
+        Ok(available.then_some(result))
+    });
+
+    first_matches(lookups, limit).await
+}
+
+/// Whether there are builds of the same version for each of `systems`,
+/// i.e. of `version`, or of any version if `version` is unknown.
+fn available_on_systems(
+    builds: &[PackageBuild],
+    systems: &[PackageSystem],
+    version: Option<&str>,
+) -> bool {
+    let available_for_all_systems = |version: &str| {
+        systems.iter().all(|system| {
+            builds
+                .iter()
+                .any(|build| build.system == *system && build.version == version)
+        })
+    };
+    match version {
+        Some(version) => available_for_all_systems(version),
+        None => builds
+            .iter()
+            .any(|build| available_for_all_systems(&build.version)),
+    }
+}
+
+/// The results retained by [first_matches]
+#[derive(Debug, PartialEq)]
+struct FirstMatches<T> {
+    matches: Vec<T>,
+    /// How many results were checked
+    n_checked: usize,
+    /// Whether all results were checked before the limit was filled
+    all_checked: bool,
+}
+
+/// Keep the first `limit` results of `lookups` that are `Some`, in order,
+/// or all of them if there is no limit.
+///
+/// At most [CATALOG_LOOKUP_CONCURRENCY] lookups run at a time,
+/// and no more lookups are run once the limit is filled.
+async fn first_matches<T>(
+    lookups: impl ExactSizeIterator<Item = impl Future<Output = Result<Option<T>>>>,
+    limit: Option<usize>,
+) -> Result<FirstMatches<T>> {
+    let n_lookups = lookups.len();
+    let mut lookups = pin!(futures::stream::iter(lookups).buffered(CATALOG_LOOKUP_CONCURRENCY));
+    let mut matches = Vec::new();
+    let mut n_checked = 0;
+    while let Some(lookup) = lookups.next().await {
+        n_checked += 1;
+        if let Some(matched) = lookup? {
+            matches.push(matched);
+        }
+        if limit.is_some_and(|limit| matches.len() >= limit) {
+            break;
+        }
+    }
+    Ok(FirstMatches {
+        matches,
+        n_checked,
+        all_checked: n_checked == n_lookups,
+    })
+}
+
+/// Keep only the first `limit` search results added to the catalog
+/// at or after `cutoff`.
+///
+/// Search results don't include dates,
+/// so the builds of each package are requested with [first_matches].
+/// Packages whose builds can't be fetched are left out.
+/// Fails if the catalog doesn't record when any of the builds were added,
+/// rather than silently filtering out every result.
+async fn retain_added_since(
+    client: &impl CatalogClientTrait,
+    results: Vec<SearchResult>,
+    system: &PackageSystem,
+    cutoff: DateTime<Utc>,
+    limit: Option<usize>,
+) -> Result<FirstMatches<SearchResult>> {
+    let any_looked_up = AtomicBool::new(false);
+    let any_dated = AtomicBool::new(false);
+    let lookups = results.into_iter().map(|result| {
+        let any_looked_up = &any_looked_up;
+        let any_dated = &any_dated;
+        async move {
+            match client.package_versions(&result.pkg_path).await {
+                Ok(details) => {
+                    any_looked_up.store(true, Ordering::Relaxed);
+                    let added = first_added(&details.results, system, result.version.as_deref());
+                    if added.is_some() {
+                        any_dated.store(true, Ordering::Relaxed);
+                    }
+                    Ok(added.is_some_and(|added| added >= cutoff).then_some(result))
+                },
+                Err(err) => {
+                    debug!(pkg_path = %result.pkg_path, %err, "failed to look up package dates");
+                    Ok(None)
+                },
+            }
+        }
+    });
+    let added_since = first_matches(lookups, limit).await?;
+
+    if any_looked_up.load(Ordering::Relaxed) && !any_dated.load(Ordering::Relaxed) {
+        bail!(indoc! {"
+            The catalog doesn't record when these packages were added,
+            so '--since' can't be used to filter the results.
+
+            Try searching without '--since'."});
+    }
+
+    Ok(added_since)
+}
+
+/// When a package was first added to the catalog for `system`,
+/// i.e. the earliest scrape date of its builds of `version`,
+/// or of any of its builds if `version` is unknown.
+///
+/// Returns `None` if none of these builds have a scrape date.
+fn first_added(
+    builds: &[PackageBuild],
+    system: &PackageSystem,
+    version: Option<&str>,
+) -> Option<DateTime<Utc>> {
+    builds
+        .iter()
+        .filter(|build| build.system == *system)
+        .filter(|build| version.is_none_or(|version| build.version == version))
+        .filter_map(|build| build.scrape_date)
+        .min()
+}
+
+/// Look up the output names of each search result in the catalog.
+///
+/// Search results don't include outputs,
+/// so the builds of each package are requested,
+/// at most [CATALOG_LOOKUP_CONCURRENCY] at a time.
+/// Packages whose builds can't be fetched are left out of the returned map.
+async fn fetch_output_names(
+    client: &impl CatalogClientTrait,
+    results: &[SearchResult],
+    system: &PackageSystem,
+) -> BTreeMap<String, Vec<OutputName>> {
+    let lookups = results.iter().map(|result| async move {
+        match client.package_versions(&result.pkg_path).await {
+            Ok(details) => output_names(&details.results, system, result.version.as_deref())
+                .map(|names| (result.pkg_path.clone(), names)),
+            Err(err) => {
+                debug!(pkg_path = %result.pkg_path, %err, "failed to look up package outputs");
+                None
+            },
+        }
+    });
+
+    futures::stream::iter(lookups)
+        .buffer_unordered(CATALOG_LOOKUP_CONCURRENCY)
+        .filter_map(futures::future::ready)
+        .collect()
+        .await
+}
+
+/// The output names of the build of a package for `system`,
+/// preferring the build of `version`.
+fn output_names(
+    builds: &[PackageBuild],
+    system: &PackageSystem,
+    version: Option<&str>,
+) -> Option<Vec<OutputName>> {
+    let mut system_builds = builds.iter().filter(|build| build.system == *system);
+    let build = match version {
+        Some(version) => system_builds
+            .clone()
+            .find(|build| build.version == version)
+            .or_else(|| system_builds.next()),
+        None => system_builds.next(),
+    }?;
+
+    let names = build
+        .outputs
+        .0
+        .iter()
+        .map(|output| OutputName {
+            name: output.name.clone(),
+            installed_by_default: build
+                .outputs_to_install
+                .as_ref()
+                .is_some_and(|to_install| to_install.contains(&output.name)),
+        })
+        .collect();
+    Some(names)
+}
+
+fn render_search_results_json(
+    search_results: SearchResults,
+    output_names: Option<&BTreeMap<String, Vec<OutputName>>>,
+) -> Result<String> {
+    let json = match output_names {
+        Some(output_names) => {
+            serde_json::to_string(&results_with_outputs(search_results.results, output_names)?)?
+        },
+        None => serde_json::to_string(&search_results.results)?,
+    };
+    Ok(json)
+}
+
+/// Render groups of search results as a JSON array,
+/// with all results of each group included under `results`.
+fn render_pname_groups_json(
+    groups: Vec<PnameGroup>,
+    output_names: Option<&BTreeMap<String, Vec<OutputName>>>,
+) -> Result<String> {
+    let groups = groups
+        .into_iter()
+        .map(|group| {
+            let count = group.results.len();
+            let results = match output_names {
+                Some(output_names) => results_with_outputs(group.results, output_names)?,
+                None => group
+                    .results
+                    .into_iter()
+                    .map(serde_json::to_value)
+                    .collect::<Result<_, _>>()?,
+            };
+            Ok(serde_json::json!({
+                "pname": group.pname,
+                "count": count,
+                "newest_version": group.newest.version,
+                "results": results,
+            }))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(serde_json::to_string(&groups)?)
+}
+
+/// Serialize search results with an added `outputs` field
+/// that lists the plain output names
+fn results_with_outputs(
+    results: Vec<SearchResult>,
+    output_names: &BTreeMap<String, Vec<OutputName>>,
+) -> Result<Vec<serde_json::Value>> {
+    results
+        .into_iter()
+        .map(|result| {
+            let outputs = output_names.get(&result.pkg_path).map(|outputs| {
+                outputs
+                    .iter()
+                    .map(|output| output.name.clone())
+                    .collect::<Vec<_>>()
+            });
+            let mut value = serde_json::to_value(result)?;
+            if let serde_json::Value::Object(ref mut map) = value {
+                map.insert("outputs".to_string(), serde_json::to_value(outputs)?);
+            }
+            Ok(value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use floxhub_client::{PackageOutput, PackageOutputs};
+
+    use super::*;
+
+    #[test]
+    fn search_cache_roundtrip_and_invalidation() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let results = SearchResults {
+            results: vec![],
+            count: Some(42),
+        };
+        let key = SearchCache::key(
+            " Hello ",
+            &PackageSystem::X8664Linux,
+            DEFAULT_SEARCH_LIMIT,
+            None,
+            None,
+        );
+        assert_eq!(key, "x86_64-linux/10/hello");
+        let catalog_key = SearchCache::key(
+            " Hello ",
+            &PackageSystem::X8664Linux,
+            DEFAULT_SEARCH_LIMIT,
+            Some("myorg"),
+            None,
+        );
+        assert_eq!(catalog_key, "myorg:x86_64-linux/10/hello");
+        // results of authenticated users are cached separately per user
+        let user_key = SearchCache::key(
+            " Hello ",
+            &PackageSystem::X8664Linux,
+            DEFAULT_SEARCH_LIMIT,
+            Some("myorg"),
+            Some("alice"),
+        );
+        assert_eq!(user_key, "alice@myorg:x86_64-linux/10/hello");
+
+        let mut cache = SearchCache::open(cache_dir.path(), "https://catalog.example");
+        assert_eq!(cache.get(&key), None);
+        cache.insert(key.clone(), results.clone()).unwrap();
+
+        let cache = SearchCache::open(cache_dir.path(), "https://catalog.example");
+        assert_eq!(cache.get(&key), Some(results.clone()));
+
+        // a different catalog invalidates the cache
+        let cache = SearchCache::open(cache_dir.path(), "https://other.example");
+        assert_eq!(cache.get(&key), None);
+
+        // expired entries are ignored
+        let mut cache = SearchCache::open(cache_dir.path(), "https://catalog.example");
+        cache.contents.entries.get_mut(&key).unwrap().cached_at =
+            Utc::now() - SEARCH_CACHE_TTL - Duration::seconds(1);
+        assert_eq!(cache.get(&key), None);
+    }
+
+    #[tokio::test]
+    async fn first_matches_stops_once_limit_is_filled() {
+        let started = std::sync::atomic::AtomicUsize::new(0);
+        let lookups = (0..100).map(|n| {
+            let started = &started;
+            async move {
+                started.fetch_add(1, Ordering::Relaxed);
+                Ok((n % 2 == 0).then_some(n))
+            }
+        });
+
+        let first = first_matches(lookups, Some(3)).await.unwrap();
+
+        assert_eq!(first, FirstMatches {
+            matches: vec![0, 2, 4],
+            n_checked: 5,
+            all_checked: false,
+        });
+        assert!(started.load(Ordering::Relaxed) <= 5 + CATALOG_LOOKUP_CONCURRENCY);
+    }
+
+    #[tokio::test]
+    async fn first_matches_without_limit_runs_all_lookups() {
+        let lookups = (0..10).map(|n| async move { Ok((n % 2 == 0).then_some(n)) });
+
+        let first = first_matches(lookups, None).await.unwrap();
+
+        assert_eq!(first, FirstMatches {
+            matches: vec![0, 2, 4, 6, 8],
+            n_checked: 10,
+            all_checked: true,
+        });
+    }
+
+    fn build(version: &str, system: PackageSystem, outputs: &[&str]) -> PackageBuild {
+        PackageBuild {
+            pkg_path: "pkg".to_string(),
+            version: version.to_string(),
+            description: None,
+            system,
+            attr_path: String::new(),
+            broken: None,
+            cache_uri: None,
+            catalog: None,
+            derivation: String::new(),
+            insecure: None,
+            license: None,
+            locked_url: String::new(),
+            missing_builds: None,
+            name: String::new(),
+            outputs: PackageOutputs(
+                outputs
+                    .iter()
+                    .map(|name| PackageOutput {
+                        name: name.to_string(),
+                        store_path: String::new(),
+                    })
+                    .collect(),
+            ),
+            outputs_to_install: Some(vec!["out".to_string()]),
+            pname: String::new(),
+            rev: String::new(),
+            rev_count: 0,
+            rev_date: Utc::now(),
+            scrape_date: None,
+            stabilities: None,
+            unfree: None,
+        }
+    }
+
+    #[test]
+    fn output_names_prefers_matching_version_and_system() {
+        let builds = vec![
+            build("2.0", PackageSystem::Aarch64Darwin, &["out", "lib"]),
+            build("2.0", PackageSystem::X8664Linux, &["out", "dev", "man"]),
+            build("1.0", PackageSystem::X8664Linux, &["out"]),
+        ];
+        let output = |name: &str| OutputName {
+            name: name.to_string(),
+            installed_by_default: name == "out",
+        };
+
+        assert_eq!(
+            output_names(&builds, &PackageSystem::X8664Linux, Some("1.0")),
+            Some(vec![output("out")])
+        );
+        // unknown versions fall back to the first build for the system
+        assert_eq!(
+            output_names(&builds, &PackageSystem::X8664Linux, Some("3.0")),
+            Some(vec![output("out"), output("dev"), output("man")])
+        );
+        assert_eq!(
+            output_names(&builds, &PackageSystem::Aarch64Darwin, None),
+            Some(vec![output("out"), output("lib")])
+        );
+        assert_eq!(
+            output_names(&builds, &PackageSystem::Aarch64Linux, None),
+            None
+        );
+    }
+
+    #[test]
+    fn available_on_systems_requires_same_version_for_each_system() {
+        let builds = vec![
+            build("2.0", PackageSystem::Aarch64Darwin, &["out"]),
+            build("2.0", PackageSystem::X8664Linux, &["out"]),
+            build("1.0", PackageSystem::X8664Linux, &["out"]),
+            build("1.0", PackageSystem::Aarch64Linux, &["out"]),
+        ];
+        let darwin_and_linux = [PackageSystem::Aarch64Darwin, PackageSystem::X8664Linux];
+        let linux = [PackageSystem::X8664Linux, PackageSystem::Aarch64Linux];
+
+        assert!(available_on_systems(
+            &builds,
+            &darwin_and_linux,
+            Some("2.0")
+        ));
+        assert!(!available_on_systems(
+            &builds,
+            &darwin_and_linux,
+            Some("1.0")
+        ));
+        assert!(available_on_systems(&builds, &linux, Some("1.0")));
+        assert!(!available_on_systems(&builds, &linux, Some("2.0")));
+        assert!(available_on_systems(&builds, &linux, None));
+
+        // every system has a build, but not of the same version
+        let all = [
+            PackageSystem::Aarch64Darwin,
+            PackageSystem::X8664Linux,
+            PackageSystem::Aarch64Linux,
+        ];
+        assert!(!available_on_systems(&builds, &all, None));
+        assert!(available_on_systems(&builds, &[], Some("2.0")));
+    }
+
+    #[test]
+    fn first_added_uses_earliest_scrape_date_of_version() {
+        let day = |day: u32| Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap();
+        let scraped =
+            |version: &str, system: PackageSystem, date: Option<DateTime<Utc>>| PackageBuild {
+                scrape_date: date,
+                ..build(version, system, &["out"])
+            };
+        let builds = vec![
+            scraped("2.0", PackageSystem::X8664Linux, Some(day(20))),
+            scraped("2.0", PackageSystem::X8664Linux, Some(day(10))),
+            scraped("2.0", PackageSystem::Aarch64Darwin, Some(day(1))),
+            scraped("1.0", PackageSystem::X8664Linux, Some(day(2))),
+            scraped("1.0", PackageSystem::Aarch64Linux, None),
+        ];
+
+        assert_eq!(
+            first_added(&builds, &PackageSystem::X8664Linux, Some("2.0")),
+            Some(day(10))
+        );
+        assert_eq!(
+            first_added(&builds, &PackageSystem::X8664Linux, None),
+            Some(day(2))
+        );
+        assert_eq!(
+            first_added(&builds, &PackageSystem::Aarch64Linux, Some("1.0")),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_search_system() {
+        assert_eq!(
+            "aarch64-linux".parse::<SearchSystem>().unwrap(),
+            SearchSystem(PackageSystem::Aarch64Linux)
+        );
+        assert_eq!(
+            "x86_64-darwin".parse::<SearchSystem>().unwrap(),
+            SearchSystem(PackageSystem::X8664Darwin)
+        );
+
+        for invalid in ["invalid", "riscv64-linux", ""] {
+            let err = invalid.parse::<SearchSystem>().unwrap_err();
+            assert!(err.contains("expected one of: aarch64-darwin"), "{err}");
+        }
+    }
+}
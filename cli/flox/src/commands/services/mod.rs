@@ -24,7 +24,7 @@ use super::{
     UninitializedEnvironment,
     activated_environments,
 };
-use crate::commands::activate::{ActivateOptions, CommandSelect};
+use crate::commands::activate::{ActivateOptions, CommandSelect, InheritEnv};
 use crate::commands::display_help;
 use crate::config::Config;
 use crate::utils::message;
@@ -36,6 +36,8 @@ mod start;
 mod status;
 mod stop;
 
+pub(crate) use stop::stop_services_by_name;
+
 /// The state of process-compose relative to the current activation.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ProcessComposeState {
@@ -412,11 +414,24 @@ pub async fn start_services_with_new_process_compose(
         // We currently only check for trust for remote environments,
         // but set this to false in case that changes.
         trust: false,
-        print_script: false,
+        output: None,
         start_services: true,
         no_start_services: false,
+        service: vec![],
         mode: Some(activate_mode),
         generation,
+        timeout: None,
+        profile: None,
+        add_to_path: vec![],
+        cwd: None,
+        inherit_env: InheritEnv::Allow,
+        pure: false,
+        no_hooks: false,
+        record: None,
+        export_manifest: None,
+        json_events: None,
+        prompt: None,
+        allow_insecure: vec![],
         // this isn't actually used because we pass invocation type below
         command: Some(CommandSelect::ExecCommand {
             command: "true".to_string(),
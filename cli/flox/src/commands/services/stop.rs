@@ -35,33 +35,42 @@ impl Stop {
         }
         guard_service_commands_available(&env, &flox.system)?;
 
-        let socket = env.socket();
+        stop_services_by_name(&flox, &env, &self.names)
+    }
+}
 
-        let processes = ProcessStates::read(socket)?;
-        let named_processes = super::processes_by_name_or_default_to_all(
-            &processes,
-            &env.manifest.as_latest_schema().services,
-            &flox.system,
-            &self.names,
-        )?;
+/// Stop the named services of `env`, or all of its services if `names` is empty.
+pub(crate) fn stop_services_by_name(
+    flox: &Flox,
+    env: &ServicesEnvironment,
+    names: &[String],
+) -> Result<()> {
+    let socket = env.socket();
 
-        for process in named_processes {
-            if !process.is_running {
-                message::warning(format!("Service '{}' is not running", process.name));
-                continue;
-            }
+    let processes = ProcessStates::read(socket)?;
+    let named_processes = super::processes_by_name_or_default_to_all(
+        &processes,
+        &env.manifest.as_latest_schema().services,
+        &flox.system,
+        names,
+    )?;
 
-            if let Err(err) = stop_services(socket, &[&process.name]) {
-                message::error(format!(
-                    "Failed to stop service '{}': {}",
-                    process.name, err
-                ));
-                continue;
-            }
+    for process in named_processes {
+        if !process.is_running {
+            message::warning(format!("Service '{}' is not running", process.name));
+            continue;
+        }
 
-            message::updated(format!("Service '{}' stopped", process.name));
+        if let Err(err) = stop_services(socket, &[&process.name]) {
+            message::error(format!(
+                "Failed to stop service '{}': {}",
+                process.name, err
+            ));
+            continue;
         }
 
-        Ok(())
+        message::updated(format!("Service '{}' stopped", process.name));
     }
+
+    Ok(())
 }
@@ -1,15 +1,47 @@
+use std::borrow::Cow;
 use std::cmp::max;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use bpaf::Bpaf;
+use crossterm::style::Stylize;
+use flox_manifest::Manifest;
+use flox_manifest::interfaces::{AsLatestSchema, AsTypedOnlyManifest, PackageLookup};
+use flox_manifest::parsed::Inner;
+use flox_manifest::parsed::latest::{
+    Install,
+    ManifestLatest,
+    ManifestPackageDescriptor,
+    Options,
+    PackageDescriptorCatalog,
+};
+use flox_manifest::raw::DEFAULT_SYSTEMS_STR;
 use flox_rust_sdk::data::System;
 use flox_rust_sdk::flox::Flox;
-use floxhub_client::{CatalogClientTrait, PackageBuild, PackageDetails, VersionsError};
+use flox_rust_sdk::models::environment::fetcher::IncludeFetcher;
+use flox_rust_sdk::providers::lock_manifest::LockManifest;
+use floxhub_client::{
+    CatalogClientTrait,
+    CatalogQoS,
+    DeprecationInfo,
+    DeprecationKind,
+    PackageBuild,
+    PackageDetails,
+    PackageSystem,
+    VersionsError,
+};
+use indoc::formatdoc;
+use itertools::Itertools;
 use tracing::instrument;
 
+use crate::commands::SHELL_COMPLETION_FILE;
+use crate::commands::search::{BASE_CATALOG, SearchSystem, ensure_catalog_exists};
 use crate::subcommand_metric;
+use crate::utils::message;
+use crate::utils::path_info::{closure_path_infos, format_size, store_path_infos};
 use crate::utils::search::DEFAULT_DESCRIPTION;
 use crate::utils::tracing::sentry_set_tag;
 
@@ -20,16 +52,204 @@ pub struct Show {
     /// for a pkg-path e.g. something copy-pasted from the output of `flox search`.
     #[bpaf(positional("pkg-path"))]
     pub pkg_path: String,
+
+    /// Only list the versions available for <system>
+    #[bpaf(long, argument("system"))]
+    pub versions_available_for: Option<SearchSystem>,
+
+    /// Show <system> instead of the current system with '--install-command',
+    /// '--as-install-descriptor', '--deps', '--json-lines',
+    /// or '--first-available-version'
+    #[bpaf(long, argument("system"))]
+    pub system: Option<SearchSystem>,
+
+    /// Look up the package in the catalog <name> instead of the default
+    /// catalogs, e.g. a custom catalog of your organization
+    #[bpaf(long, argument("name"))]
+    pub catalog: Option<String>,
+
+    #[bpaf(external(show_mode), optional)]
+    pub mode: Option<ShowMode>,
+}
+
+/// What to show instead of the description and versions of the package
+#[derive(Bpaf, Clone, PartialEq, Debug)]
+pub enum ShowMode {
+    /// Show the availability of each version on all default systems
+    #[bpaf(long)]
+    AllSystems,
+
+    /// Only show whether the catalog has marked the package as deprecated
+    #[bpaf(long)]
+    Deprecated,
+
+    /// Show the estimated closure size of each version on each system
+    #[bpaf(long)]
+    Size,
+
+    /// Print the 'flox install' command that installs the shown version
+    #[bpaf(long)]
+    InstallCommand,
+
+    /// Print the '[install]' table of a manifest that installs the shown
+    /// version, ready to be pasted into a manifest
+    #[bpaf(long)]
+    AsInstallDescriptor,
+
+    /// Only print the systems the latest version is available for,
+    /// on a single line
+    #[bpaf(long)]
+    SystemsAvailable,
+
+    Deps {
+        /// List the runtime dependencies of the latest version
+        /// for the current system, or <system> with '--system'
+        #[bpaf(long("deps"), req_flag(()))]
+        deps: (),
+
+        /// With '--deps', list the full transitive closure of runtime dependencies
+        /// instead of only the immediate ones
+        #[bpaf(long)]
+        closure: bool,
+    },
+
+    ResolveAgainst(
+        /// Show the version that would be locked using the version constraint
+        /// and options of the manifest at <manifest>,
+        /// without modifying any environment
+        #[bpaf(
+            long("resolve-against"),
+            argument("manifest"),
+            complete_shell(SHELL_COMPLETION_FILE)
+        )]
+        PathBuf,
+    ),
+
+    /// Print each build of the package as a JSON object on its own line
+    /// (NDJSON), e.g. for streaming into other tools
+    #[bpaf(long)]
+    JsonLines,
+
+    Json {
+        /// Print the builds of the package as a JSON array
+        #[bpaf(long("json"), req_flag(()))]
+        json: (),
+
+        /// With '--json', include the builds of every package matching
+        /// <pkg-path> rather than only the best match
+        #[bpaf(long)]
+        all_matches: bool,
+    },
+
+    /// Show which systems share a catalog page,
+    /// to explain why the package can't be resolved for all systems of
+    /// a pkg-group
+    #[bpaf(long)]
+    SamePageSystems,
+
+    /// Only print the oldest version the catalog offers for the current
+    /// system, or <system> with '--system',
+    /// e.g. to pin the package conservatively
+    #[bpaf(long)]
+    FirstAvailableVersion,
+}
+
+impl ShowMode {
+    /// The flag that selects the mode, e.g. for error messages
+    fn flag(&self) -> &'static str {
+        match self {
+            ShowMode::AllSystems => "--all-systems",
+            ShowMode::Deprecated => "--deprecated",
+            ShowMode::Size => "--size",
+            ShowMode::InstallCommand => "--install-command",
+            ShowMode::AsInstallDescriptor => "--as-install-descriptor",
+            ShowMode::SystemsAvailable => "--systems-available",
+            ShowMode::Deps { .. } => "--deps",
+            ShowMode::ResolveAgainst(_) => "--resolve-against",
+            ShowMode::JsonLines => "--json-lines",
+            ShowMode::Json { .. } => "--json",
+            ShowMode::SamePageSystems => "--same-page-systems",
+            ShowMode::FirstAvailableVersion => "--first-available-version",
+        }
+    }
+
+    /// Whether '--system' chooses the system the mode shows
+    fn takes_system(&self) -> bool {
+        match self {
+            ShowMode::InstallCommand
+            | ShowMode::AsInstallDescriptor
+            | ShowMode::Deps { .. }
+            | ShowMode::JsonLines
+            | ShowMode::FirstAvailableVersion => true,
+            ShowMode::AllSystems
+            | ShowMode::Deprecated
+            | ShowMode::Size
+            | ShowMode::SystemsAvailable
+            | ShowMode::ResolveAgainst(_)
+            | ShowMode::Json { .. }
+            | ShowMode::SamePageSystems => false,
+        }
+    }
 }
 
 impl Show {
     #[instrument(name = "show", skip_all)]
-    pub async fn handle(self, flox: Flox) -> Result<()> {
+    pub async fn handle(self, mut flox: Flox) -> Result<()> {
         subcommand_metric!("show");
         sentry_set_tag("pkg_path", &self.pkg_path);
 
+        match &self.mode {
+            Some(mode) => {
+                if self.versions_available_for.is_some() {
+                    if mode.takes_system() {
+                        bail!(formatdoc! {"
+                            '--versions-available-for' cannot be used with '{flag}'.
+                            Use '--system' to choose the system for '{flag}'.",
+                            flag = mode.flag()
+                        });
+                    }
+                    bail!(
+                        "'--versions-available-for' cannot be used with '{}'.",
+                        mode.flag()
+                    );
+                }
+                if self.system.is_some() && !mode.takes_system() {
+                    bail!("'--system' cannot be used with '{}'.", mode.flag());
+                }
+                if self.catalog.is_some() && matches!(mode, ShowMode::ResolveAgainst(_)) {
+                    bail!("'--resolve-against' cannot be used with '--catalog'.");
+                }
+            },
+            None if self.system.is_some() => {
+                bail!(formatdoc! {"
+                    '--system' requires '--install-command', '--as-install-descriptor', '--deps',
+                    '--json-lines', or '--first-available-version'.
+                    Use '--versions-available-for' to list the versions available for a system."
+                });
+            },
+            None => {},
+        }
+
+        // The user is waiting for the package details,
+        // so tag the requests for the catalog to prioritize them.
+        flox.floxhub_client.update_config(|config| {
+            config.qos = CatalogQoS::UserInitiated;
+        })?;
+
+        if let Some(ShowMode::ResolveAgainst(manifest_path)) = &self.mode {
+            return resolve_against(&flox, &self.pkg_path, manifest_path).await;
+        }
+
+        let pkg_path = match &self.catalog {
+            Some(catalog) => {
+                ensure_catalog_exists(&flox.floxhub_client, catalog).await?;
+                catalog_pkg_path(catalog, &self.pkg_path)?
+            },
+            None => Cow::Borrowed(self.pkg_path.as_str()),
+        };
+
         tracing::debug!("using catalog client for show");
-        let results = match flox.floxhub_client.package_versions(&self.pkg_path).await {
+        let results = match flox.floxhub_client.package_versions(&pkg_path).await {
             Ok(results) => results,
             // Below, results.is_empty() is used to mean the search_term
             // didn't match a package.
@@ -38,11 +258,12 @@ impl Show {
             Err(VersionsError::NotFound) => PackageDetails {
                 results: vec![],
                 count: None::<u64>,
+                deprecation: None,
             },
             Err(e) => Err(e)?,
         };
         if results.results.is_empty() {
-            bail!("no packages matched this pkg-path: '{}'", self.pkg_path);
+            bail!("no packages matched this pkg-path: '{pkg_path}'");
         }
         let expected_systems = [
             "aarch64-darwin",
@@ -53,12 +274,317 @@ impl Show {
         .iter()
         .map(|s| s.to_string())
         .collect::<HashSet<_>>();
-        render_show_catalog(&mut std::io::stdout(), &results.results, &expected_systems)?;
+        // At most one of them is set, depending on the mode
+        let system = self
+            .system
+            .as_ref()
+            .or(self.versions_available_for.as_ref())
+            .map(|SearchSystem(system)| system);
+        match &self.mode {
+            Some(ShowMode::Deprecated) => {
+                match &results.deprecation {
+                    Some(deprecation) => {
+                        println!("{}", format_deprecation(&self.pkg_path, deprecation))
+                    },
+                    None => println!(
+                        "No deprecation information is available for '{}'.",
+                        self.pkg_path
+                    ),
+                }
+                return Ok(());
+            },
+            Some(ShowMode::FirstAvailableVersion) => {
+                let system = match system {
+                    Some(system) => system.to_string(),
+                    None => flox.system.clone(),
+                };
+                let pkg = first_available_version(&results.results, &system)?;
+                println!("{}", pkg.version);
+                message::info(format!(
+                    "Pin the package to this version with '{}'",
+                    install_command(pkg, self.catalog.as_deref())?
+                ));
+            },
+            Some(ShowMode::SamePageSystems) => {
+                render_same_page_systems(&mut std::io::stdout(), &results.results)?;
+                message::info(formatdoc! {"
+                    Packages in the same pkg-group are resolved from a single catalog page
+                    for all systems of an environment.
+                    If no page contains all systems of the environment,
+                    install the package in its own pkg-group,
+                    or limit it to the systems of one page with 'systems = [...]'."
+                });
+            },
+            Some(ShowMode::Json { all_matches, .. }) => {
+                let builds = if *all_matches {
+                    results.results.clone()
+                } else {
+                    best_match(&results.results).0
+                };
+                println!("{}", serde_json::to_string(&builds)?);
+            },
+            Some(ShowMode::JsonLines) => {
+                render_json_lines(&mut std::io::stdout().lock(), &results.results, system)?;
+            },
+            Some(ShowMode::InstallCommand) => {
+                println!(
+                    "{}",
+                    format_install_command(&results.results, system, self.catalog.as_deref())?
+                );
+            },
+            Some(ShowMode::AsInstallDescriptor) => {
+                let pkg = shown_build(&results.results, system)?;
+                print!(
+                    "{}",
+                    format_install_descriptor(pkg, self.catalog.as_deref())?
+                );
+            },
+            Some(ShowMode::SystemsAvailable) => {
+                println!("{}", format_systems_available(&results.results)?);
+            },
+            Some(ShowMode::Deps { closure, .. }) => {
+                let system = match system {
+                    Some(system) => system.to_string(),
+                    None => flox.system.clone(),
+                };
+                let (builds, _) = best_match(&results.results);
+                let Some(pkg) = builds.iter().find(|pkg| pkg.system.to_string() == system) else {
+                    bail!("No versions of '{pkg_path}' are available for '{system}'.");
+                };
+                // Prefer the local store, which doesn't require network access
+                let substituter = pkg.cache_uri.as_deref().unwrap_or(DEFAULT_SUBSTITUTER);
+                let path_infos = |store_paths: &[&str], recursive: bool| {
+                    store_path_infos(None, store_paths, recursive)
+                        .or_else(|| store_path_infos(Some(substituter), store_paths, recursive))
+                };
+                let Some(dependencies) = runtime_dependencies(pkg, *closure, path_infos) else {
+                    bail!(formatdoc! {"
+                        Dependencies of '{}@{}' for '{system}' are unknown.
+                        The catalog doesn't provide dependency information,
+                        and the package is neither in the local store nor in '{substituter}'.",
+                        pkg.pkg_path, pkg.version
+                    });
+                };
+                if dependencies.is_empty() {
+                    message::info(format!(
+                        "'{}@{}' has no runtime dependencies.",
+                        pkg.pkg_path, pkg.version
+                    ));
+                }
+                for dependency in dependencies {
+                    println!("{dependency}");
+                }
+            },
+            Some(ShowMode::Size) => {
+                let path_infos = local_path_infos(&results.results);
+                render_show_sizes(&mut std::io::stdout(), &results.results, |pkg| {
+                    closure_size(pkg, &path_infos)
+                })?;
+            },
+            Some(ShowMode::AllSystems) => {
+                render_show_systems_matrix(
+                    &mut std::io::stdout(),
+                    &results.results,
+                    &DEFAULT_SYSTEMS_STR[..],
+                    message::stdout_supports_color(),
+                )?;
+            },
+            Some(ShowMode::ResolveAgainst(_)) => {
+                unreachable!("'--resolve-against' is handled before querying the catalog")
+            },
+            None => match system {
+                Some(system) => {
+                    render_versions_for_system(&mut std::io::stdout(), &results.results, system)?;
+                },
+                None => {
+                    let (builds, other_matches) = best_match(&results.results);
+                    render_show_catalog(&mut std::io::stdout(), &builds, &expected_systems)?;
+                    if other_matches > 0 {
+                        message::info(format!(
+                            "{other_matches} other package(s) match '{}', use '--json --all-matches' to list all of them",
+                            self.pkg_path
+                        ));
+                    }
+                },
+            },
+        }
+
+        if let Some(deprecation) = &results.deprecation {
+            message::warning(format_deprecation(&self.pkg_path, deprecation));
+        }
 
         Ok(())
     }
 }
 
+/// Split off the builds of the best match among `search_results`,
+/// i.e. the package of the first result, identified by catalog and pkg-path.
+///
+/// Also returns the number of other packages in `search_results`.
+fn best_match(search_results: &[PackageBuild]) -> (Vec<PackageBuild>, usize) {
+    let Some(first) = search_results.first() else {
+        return (Vec::new(), 0);
+    };
+    let is_best =
+        |pkg: &PackageBuild| pkg.catalog == first.catalog && pkg.pkg_path == first.pkg_path;
+    let (best, others): (Vec<_>, Vec<_>) = search_results.iter().partition(|pkg| is_best(pkg));
+    let other_matches = others
+        .iter()
+        .map(|pkg| (&pkg.catalog, &pkg.pkg_path))
+        .unique()
+        .count();
+    (best.into_iter().cloned().collect(), other_matches)
+}
+
+/// Qualify `pkg_path` with `catalog` as the catalog server expects it.
+///
+/// Packages of the base catalog are not qualified,
+/// and a `pkg_path` that already names a different catalog is an error.
+fn catalog_pkg_path<'a>(catalog: &str, pkg_path: &'a str) -> Result<Cow<'a, str>> {
+    if !flox_manifest::util::is_custom_package(pkg_path) {
+        if catalog == BASE_CATALOG {
+            return Ok(Cow::Borrowed(pkg_path));
+        }
+        return Ok(Cow::Owned(format!("{catalog}/{pkg_path}")));
+    }
+    match pkg_path.split_once('/') {
+        Some((prefix, _)) if prefix == catalog => Ok(Cow::Borrowed(pkg_path)),
+        _ => bail!("'{pkg_path}' is not a package of the catalog '{catalog}'."),
+    }
+}
+
+/// Lock `pkg_path` for the current system the way the manifest at
+/// `manifest_path` would, and print the resolved version.
+///
+/// The manifest is only read, the package is locked in a separate manifest
+/// that contains nothing but the package.
+async fn resolve_against(flox: &Flox, pkg_path: &str, manifest_path: &Path) -> Result<()> {
+    let contents = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read manifest '{}'", manifest_path.display()))?;
+    let mut manifest = Manifest::parse_toml_typed(contents)?
+        .as_typed_only()
+        .migrate_typed_only(None)?;
+
+    let (resolution_manifest, install_id) =
+        resolution_manifest(manifest.as_latest_schema(), pkg_path, &flox.system);
+    let constraint = resolution_manifest
+        .catalog_descriptor_with_id(&install_id)
+        .and_then(|descriptor| descriptor.version);
+    *manifest.as_latest_schema_mut() = resolution_manifest;
+
+    let lockfile = LockManifest::lock_manifest(flox, &manifest, None, &IncludeFetcher {
+        base_directory: None,
+    })
+    .await
+    .with_context(|| {
+        format!(
+            "Failed to resolve '{pkg_path}' against '{}'",
+            manifest_path.display()
+        )
+    })?;
+
+    let Some(locked) = lockfile
+        .packages
+        .iter()
+        .filter(|pkg| pkg.install_id() == install_id)
+        .find_map(|pkg| pkg.as_catalog_package_ref())
+    else {
+        bail!("'{pkg_path}' is not available for '{}'.", flox.system);
+    };
+
+    match constraint {
+        Some(constraint) => println!(
+            "{pkg_path} resolves to version {} on {} (constraint: '{constraint}')",
+            locked.version, locked.system
+        ),
+        None => println!(
+            "{pkg_path} resolves to version {} on {}",
+            locked.version, locked.system
+        ),
+    }
+    Ok(())
+}
+
+/// Create a manifest that only installs `pkg_path` for `system`,
+/// using the allow and semver options of `manifest`.
+///
+/// If `manifest` already installs `pkg_path`,
+/// its descriptor is reused so that version constraints apply.
+/// Returns the manifest and the install ID of the package.
+fn resolution_manifest(
+    manifest: &ManifestLatest,
+    pkg_path: &str,
+    system: &System,
+) -> (ManifestLatest, String) {
+    let existing = manifest
+        .install
+        .inner()
+        .iter()
+        .find_map(|(install_id, descriptor)| {
+            let descriptor = descriptor.as_catalog_descriptor_ref()?;
+            (descriptor.pkg_path == pkg_path).then(|| (install_id.clone(), descriptor.clone()))
+        });
+    let (install_id, descriptor) = existing.unwrap_or_else(|| {
+        let install_id = pkg_path.rsplit('.').next().unwrap_or(pkg_path).to_string();
+        let descriptor = PackageDescriptorCatalog {
+            pkg_path: pkg_path.to_string(),
+            pkg_group: None,
+            priority: None,
+            version: None,
+            systems: None,
+            outputs: None,
+            allow_missing_builds: None,
+        };
+        (install_id, descriptor)
+    });
+    // Resolve the package on its own rather than alongside the rest of its
+    // group, and only for the requested system.
+    let descriptor = PackageDescriptorCatalog {
+        pkg_group: None,
+        systems: None,
+        ..descriptor
+    };
+
+    let resolution_manifest = ManifestLatest {
+        install: Install::from(BTreeMap::from([(
+            install_id.clone(),
+            ManifestPackageDescriptor::Catalog(descriptor),
+        )])),
+        options: Options {
+            systems: Some(vec![system.clone()]),
+            allow: manifest.options.allow.clone(),
+            semver: manifest.options.semver.clone(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    (resolution_manifest, install_id)
+}
+
+/// Describe a catalog deprecation notice for `pkg_path`
+fn format_deprecation(pkg_path: &str, deprecation: &DeprecationInfo) -> String {
+    let mut notice = match (&deprecation.kind, &deprecation.replacement) {
+        (DeprecationKind::Renamed, Some(replacement)) => {
+            format!("'{pkg_path}' has been renamed to '{replacement}'.")
+        },
+        (DeprecationKind::Renamed, None) => format!("'{pkg_path}' has been renamed."),
+        (DeprecationKind::Removed, _) => {
+            format!("'{pkg_path}' has been removed from the catalog.")
+        },
+        (DeprecationKind::Deprecated, _) => format!("'{pkg_path}' is deprecated."),
+    };
+    if deprecation.kind != DeprecationKind::Renamed
+        && let Some(replacement) = &deprecation.replacement
+    {
+        notice.push_str(&format!(" Use '{replacement}' instead."));
+    }
+    if let Some(message) = &deprecation.message {
+        notice.push('\n');
+        notice.push_str(message);
+    }
+    notice
+}
+
 fn render_show_catalog(
     writer: &mut impl Write,
     search_results: &[PackageBuild],
@@ -191,8 +717,510 @@ fn render_show_catalog(
     Ok(())
 }
 
+const VERSION_HEADER: &str = "VERSION";
+
+/// Write each build as a JSON object on its own line,
+/// optionally only the builds for `system`.
+///
+/// Each line is written as soon as it is serialized,
+/// so consumers can process builds without reading the whole output.
+fn render_json_lines(
+    writer: &mut impl Write,
+    search_results: &[PackageBuild],
+    system: Option<&PackageSystem>,
+) -> Result<()> {
+    let builds = search_results
+        .iter()
+        .filter(|pkg| system.is_none_or(|system| pkg.system == *system));
+    for build in builds {
+        serde_json::to_writer(&mut *writer, build)?;
+        writeln!(writer)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Render the catalog pages of a package grouped by the systems they contain,
+/// most recent first.
+///
+/// A catalog page is a revision of the catalog, identified by its `rev_count`.
+fn render_same_page_systems(
+    writer: &mut impl Write,
+    search_results: &[PackageBuild],
+) -> Result<()> {
+    let mut pages: BTreeMap<i64, Vec<&PackageBuild>> = BTreeMap::new();
+    for pkg in search_results {
+        pages.entry(pkg.rev_count).or_default().push(pkg);
+    }
+
+    // Pages are iterated most recent first,
+    // so the first page of each group of systems is its latest page.
+    let mut groups: Vec<(Vec<String>, Vec<Vec<&PackageBuild>>)> = Vec::new();
+    for (_, builds) in pages.into_iter().rev() {
+        let systems = builds
+            .iter()
+            .map(|pkg| pkg.system.to_string())
+            .sorted()
+            .unique()
+            .collect::<Vec<_>>();
+        match groups.iter_mut().find(|(group, _)| *group == systems) {
+            Some((_, group_pages)) => group_pages.push(builds),
+            None => groups.push((systems, vec![builds])),
+        }
+    }
+
+    for (systems, group_pages) in groups {
+        let latest = &group_pages[0];
+        let versions = latest
+            .iter()
+            .map(|pkg| pkg.version.as_str())
+            .unique()
+            .join(", ");
+        let count = group_pages.len();
+        writeln!(writer, "{}", systems.join(", "))?;
+        writeln!(
+            writer,
+            "    {count} {pages}, latest: page {rev_count} from {date} with version {versions}",
+            pages = if count == 1 { "page" } else { "pages" },
+            rev_count = latest[0].rev_count,
+            date = latest[0].rev_date.format("%Y-%m-%d"),
+        )?;
+    }
+    Ok(())
+}
+
+/// Render the versions of a package available for `system`, one per line,
+/// in the order returned by the catalog.
+///
+/// Fails if no version is available for `system`.
+fn render_versions_for_system(
+    writer: &mut impl Write,
+    search_results: &[PackageBuild],
+    system: &PackageSystem,
+) -> Result<()> {
+    let versions = search_results
+        .iter()
+        .filter(|pkg| pkg.system == *system)
+        .map(|pkg| pkg.version.as_str())
+        .unique()
+        .collect::<Vec<_>>();
+
+    if versions.is_empty() {
+        let pkg_path = search_results
+            .first()
+            .map(|pkg| pkg.pkg_path.as_str())
+            .unwrap_or_default();
+        bail!("No versions of '{pkg_path}' are available for '{system}'.");
+    }
+
+    for version in versions {
+        writeln!(writer, "{version}")?;
+    }
+    Ok(())
+}
+
+/// Find the build of the oldest version available for `system`.
+///
+/// Versions are compared as semantic versions.
+/// Versions that cannot be parsed as a semantic version are only considered
+/// if no version can be parsed, in which case the lowest version by string
+/// order is returned, mirroring the ordering of `flox search`.
+///
+/// Fails if no version is available for `system`.
+fn first_available_version<'a>(
+    search_results: &'a [PackageBuild],
+    system: &str,
+) -> Result<&'a PackageBuild> {
+    let builds = search_results
+        .iter()
+        .filter(|pkg| pkg.system.to_string() == system)
+        .collect::<Vec<_>>();
+
+    let oldest_semver = builds
+        .iter()
+        .filter_map(|pkg| Some((semver::Version::parse(&pkg.version).ok()?, *pkg)))
+        .min_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, pkg)| pkg);
+    let oldest = oldest_semver.or_else(|| builds.iter().copied().min_by_key(|pkg| &pkg.version));
+
+    let Some(oldest) = oldest else {
+        let pkg_path = search_results
+            .first()
+            .map(|pkg| pkg.pkg_path.as_str())
+            .unwrap_or_default();
+        bail!("No versions of '{pkg_path}' are available for '{system}'.");
+    };
+    Ok(oldest)
+}
+
+/// The build of the shown version,
+/// i.e. the latest version, or the latest version available for `system`.
+///
+/// Fails if no version is available for `system`.
+fn shown_build<'a>(
+    search_results: &'a [PackageBuild],
+    system: Option<&PackageSystem>,
+) -> Result<&'a PackageBuild> {
+    let Some(pkg) = search_results
+        .iter()
+        .find(|pkg| system.is_none_or(|system| pkg.system == *system))
+    else {
+        let pkg_path = search_results
+            .first()
+            .map(|pkg| pkg.pkg_path.as_str())
+            .unwrap_or_default();
+        match system {
+            Some(system) => bail!("No versions of '{pkg_path}' are available for '{system}'."),
+            None => bail!("No packages found."),
+        }
+    };
+    Ok(pkg)
+}
+
+/// Format the systems the latest version of the best match is available for
+/// as a single, sorted line.
+fn format_systems_available(search_results: &[PackageBuild]) -> Result<String> {
+    let (builds, _) = best_match(search_results);
+    let Some(latest) = builds.first() else {
+        bail!("No packages found.");
+    };
+    Ok(builds
+        .iter()
+        .filter(|pkg| pkg.version == latest.version)
+        .map(|pkg| pkg.system.to_string())
+        .sorted()
+        .unique()
+        .join(", "))
+}
+
+/// Format the `flox install` command that installs the shown version.
+///
+/// Fails if no version is available for `system`.
+fn format_install_command(
+    search_results: &[PackageBuild],
+    system: Option<&PackageSystem>,
+    catalog: Option<&str>,
+) -> Result<String> {
+    let pkg = shown_build(search_results, system)?;
+    install_command(pkg, catalog)
+}
+
+/// Format the `flox install` command that installs exactly the version of `pkg`.
+fn install_command(pkg: &PackageBuild, catalog: Option<&str>) -> Result<String> {
+    let package = format!("{}@={}", install_pkg_path(pkg, catalog)?, pkg.version);
+    Ok(format!(
+        "flox install {}",
+        shell_escape::escape(Cow::Borrowed(&package))
+    ))
+}
+
+/// Format the `[install]` table of a manifest that installs exactly the
+/// version of `pkg`, using [default_install_id] as the install ID.
+fn format_install_descriptor(pkg: &PackageBuild, catalog: Option<&str>) -> Result<String> {
+    let install_id = default_install_id(&pkg.pname, &pkg.pkg_path);
+    Ok(formatdoc! {"
+        [install]
+        {install_id}.pkg-path = {}
+        {install_id}.version = {}
+        ",
+        toml_edit::Value::from(install_pkg_path(pkg, catalog)?.as_ref()),
+        toml_edit::Value::from(format!("={}", pkg.version)),
+    })
+}
+
+/// The pkg-path to install `pkg` with,
+/// qualified with `catalog` if the package was shown from a specific catalog.
+fn install_pkg_path<'a>(pkg: &'a PackageBuild, catalog: Option<&str>) -> Result<Cow<'a, str>> {
+    match catalog {
+        Some(catalog) => catalog_pkg_path(catalog, &pkg.pkg_path),
+        None => Ok(Cow::Borrowed(&pkg.pkg_path)),
+    }
+}
+
+/// An install ID for a package derived from its `pname`,
+/// or the last attribute of its `pkg_path` if the `pname` is empty.
+///
+/// Characters that aren't allowed in a bare TOML key are replaced with `_`,
+/// so that the ID can be used without quoting.
+fn default_install_id(pname: &str, pkg_path: &str) -> String {
+    let name = if pname.is_empty() {
+        pkg_path.rsplit(['.', '/']).next().unwrap_or(pkg_path)
+    } else {
+        pname
+    };
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Availability of a package version on a single system
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SystemAvailability {
+    Available,
+    Unavailable,
+    Broken,
+    Unfree,
+}
+
+impl SystemAvailability {
+    fn of(build: Option<&PackageBuild>) -> Self {
+        match build {
+            None => SystemAvailability::Unavailable,
+            Some(build) if build.broken.unwrap_or(false) => SystemAvailability::Broken,
+            Some(build) if build.unfree.unwrap_or(false) => SystemAvailability::Unfree,
+            Some(_) => SystemAvailability::Available,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            SystemAvailability::Available => "available",
+            SystemAvailability::Unavailable => "unavailable",
+            SystemAvailability::Broken => "broken",
+            SystemAvailability::Unfree => "unfree",
+        }
+    }
+}
+
+/// Render a matrix of versions and their availability on each of `systems`.
+///
+/// Versions are listed in the order returned by the server.
+/// If `highlight` is set, systems where a version is unavailable, broken,
+/// or unfree are colored.
+fn render_show_systems_matrix(
+    writer: &mut impl Write,
+    search_results: &[PackageBuild],
+    systems: &[System],
+    highlight: bool,
+) -> Result<()> {
+    if search_results.is_empty() {
+        bail!("No packages found.");
+    }
+    let pkg_path = &search_results[0].pkg_path;
+
+    let mut versions: Vec<&str> = vec![];
+    for pkg in search_results {
+        if !versions.contains(&pkg.version.as_str()) {
+            versions.push(&pkg.version);
+        }
+    }
+
+    let version_column_width = versions
+        .iter()
+        .map(|version| version.len())
+        .chain([VERSION_HEADER.len()])
+        .max()
+        .unwrap_or_default();
+    let system_column_widths = systems
+        .iter()
+        .map(|system| max(system.len(), SystemAvailability::Unavailable.label().len()))
+        .collect::<Vec<_>>();
+
+    writeln!(writer, "{pkg_path}")?;
+    let mut header = format!("{VERSION_HEADER:<version_column_width$}");
+    for (system, width) in systems.iter().zip(system_column_widths.iter().copied()) {
+        header.push_str(&format!("  {system:<width$}"));
+    }
+    writeln!(writer, "{}", header.trim_end())?;
+
+    for version in versions {
+        let mut row = format!("{version:<version_column_width$}");
+        for (system, width) in systems.iter().zip(system_column_widths.iter().copied()) {
+            let build = search_results
+                .iter()
+                .find(|pkg| pkg.version == version && pkg.system.to_string() == *system);
+            let availability = SystemAvailability::of(build);
+            let cell = format!("{:<width$}", availability.label());
+            let cell = match availability {
+                _ if !highlight => cell,
+                SystemAvailability::Available => cell,
+                SystemAvailability::Unfree => cell.yellow().to_string(),
+                SystemAvailability::Unavailable | SystemAvailability::Broken => {
+                    cell.red().to_string()
+                },
+            };
+            row.push_str(&format!("  {cell}"));
+        }
+        writeln!(writer, "{}", row.trim_end())?;
+    }
+    Ok(())
+}
+
+const SYSTEM_HEADER: &str = "SYSTEM";
+const SIZE_HEADER: &str = "CLOSURE SIZE";
+const UNKNOWN_SIZE: &str = "unknown";
+/// Binary cache of packages that don't specify a cache in the catalog
+const DEFAULT_SUBSTITUTER: &str = "https://cache.nixos.org";
+
+/// Render the estimated closure size of every version and system in
+/// `search_results`.
+///
+/// The catalog doesn't provide size metadata,
+/// so sizes are looked up with `closure_size`,
+/// and shown as unknown if it can't determine one.
+fn render_show_sizes(
+    writer: &mut impl Write,
+    search_results: &[PackageBuild],
+    closure_size: impl Fn(&PackageBuild) -> Option<u64>,
+) -> Result<()> {
+    if search_results.is_empty() {
+        bail!("No packages found.");
+    }
+    let pkg_path = &search_results[0].pkg_path;
+
+    let rows = search_results
+        .iter()
+        .map(|pkg| {
+            let size = closure_size(pkg)
+                .map(format_size)
+                .unwrap_or(UNKNOWN_SIZE.to_string());
+            (pkg.version.as_str(), pkg.system.to_string(), size)
+        })
+        .collect::<Vec<_>>();
+
+    let version_column_width = rows
+        .iter()
+        .map(|(version, ..)| version.len())
+        .chain([VERSION_HEADER.len()])
+        .max()
+        .unwrap_or_default();
+    let system_column_width = rows
+        .iter()
+        .map(|(_, system, _)| system.len())
+        .chain([SYSTEM_HEADER.len()])
+        .max()
+        .unwrap_or_default();
+
+    writeln!(writer, "{pkg_path}")?;
+    writeln!(
+        writer,
+        "{VERSION_HEADER:<version_column_width$}  {SYSTEM_HEADER:<system_column_width$}  {SIZE_HEADER}"
+    )?;
+    for (version, system, size) in rows {
+        writeln!(
+            writer,
+            "{version:<version_column_width$}  {system:<system_column_width$}  {size}"
+        )?;
+    }
+    Ok(())
+}
+
+/// The store paths of the outputs `pkg` installs by default
+fn default_output_paths(pkg: &PackageBuild) -> Vec<&str> {
+    pkg.outputs
+        .0
+        .iter()
+        .filter(|output| {
+            pkg.outputs_to_install
+                .as_ref()
+                .is_none_or(|to_install| to_install.contains(&output.name))
+        })
+        .map(|output| output.store_path.as_str())
+        .collect()
+}
+
+/// Look up the closures of the outputs all of `search_results` install by
+/// default in the local store, with a single `nix path-info` call.
+///
+/// Returns an empty map if nix can't be run or fails.
+fn local_path_infos(search_results: &[PackageBuild]) -> serde_json::Map<String, serde_json::Value> {
+    let store_paths = search_results
+        .iter()
+        .flat_map(default_output_paths)
+        .unique()
+        .collect::<Vec<_>>();
+    if store_paths.is_empty() {
+        return serde_json::Map::new();
+    }
+
+    closure_path_infos(&store_paths).unwrap_or_default()
+}
+
+/// Determine the closure size of the outputs a package installs by default
+/// by following the references in `path_infos`.
+///
+/// Returns [None] if any path of the closure is missing from `path_infos`,
+/// e.g. because it is not available locally,
+/// since the size of the closure can't be known without downloading it.
+fn closure_size(
+    pkg: &PackageBuild,
+    path_infos: &serde_json::Map<String, serde_json::Value>,
+) -> Option<u64> {
+    let mut pending = default_output_paths(pkg);
+    if pending.is_empty() {
+        return None;
+    }
+
+    let mut closure = HashSet::new();
+    let mut size = 0;
+    while let Some(path) = pending.pop() {
+        if !closure.insert(path) {
+            continue;
+        }
+        // nix reports paths that are not valid in the store as `null`
+        let info = path_infos.get(path)?;
+        size += info.get("narSize")?.as_u64()?;
+        for reference in info.get("references")?.as_array()? {
+            pending.push(reference.as_str()?);
+        }
+    }
+    Some(size)
+}
+
+/// The store paths the outputs `pkg` installs by default depend on at runtime,
+/// i.e. the paths they reference, or with `closure` every path of their closure.
+///
+/// The catalog doesn't provide references,
+/// so they are looked up with `path_infos`,
+/// which is asked for the closure of the outputs with `closure`.
+/// Returns [None] if it doesn't know all outputs of `pkg`,
+/// e.g. because they are neither in the local store nor in the binary cache.
+fn runtime_dependencies(
+    pkg: &PackageBuild,
+    closure: bool,
+    path_infos: impl Fn(&[&str], bool) -> Option<serde_json::Map<String, serde_json::Value>>,
+) -> Option<BTreeSet<String>> {
+    let store_paths = default_output_paths(pkg);
+    if store_paths.is_empty() {
+        return None;
+    }
+
+    let path_infos = path_infos(&store_paths, closure)?;
+    // nix reports paths that are not valid in the store as `null`
+    if path_infos.values().any(|info| info.is_null()) {
+        return None;
+    }
+
+    let dependencies = if closure {
+        path_infos.keys().cloned().collect::<BTreeSet<_>>()
+    } else {
+        let mut references = BTreeSet::new();
+        for store_path in &store_paths {
+            for reference in path_infos.get(*store_path)?.get("references")?.as_array()? {
+                references.insert(reference.as_str()?.to_string());
+            }
+        }
+        references
+    };
+
+    // Outputs commonly reference themselves or each other
+    Some(
+        dependencies
+            .into_iter()
+            .filter(|path| !store_paths.contains(&path.as_str()))
+            .collect(),
+    )
+}
+
 #[cfg(test)]
 mod test {
+    use chrono::TimeZone;
     use flox_rust_sdk::flox::test_helpers::flox_instance;
     use flox_rust_sdk::providers::catalog::test_helpers::auto_recording_catalog_client;
     use floxhub_client::{PackageOutput, PackageOutputs, PackageSystem};
@@ -201,33 +1229,12 @@ mod test {
 
     use super::*;
 
-    #[tokio::test]
-    async fn show_handles_404() {
-        let (mut flox, _temp_dir_handle) = flox_instance();
-        flox.floxhub_client = auto_recording_catalog_client("show_handles_404");
-        let search_term = "search_term";
-        let err = Show {
-            pkg_path: search_term.to_string(),
-        }
-        .handle(flox)
-        .await
-        .unwrap_err();
-
-        assert_eq!(
-            err.to_string(),
-            format!("no packages matched this pkg-path: '{search_term}'")
-        );
-    }
-
-    #[test]
-    fn test_column_alignment_for_system_restrictions() {
-        use chrono::TimeZone;
-
+    fn mock_pkg(version: &str, system: &str) -> PackageBuild {
         let rev_date = chrono::Utc
             .with_ymd_and_hms(2025, 5, 31, 12, 5, 15)
             .unwrap();
 
-        let mock_pkg = |version: &str, system: &str| PackageBuild {
+        PackageBuild {
             pkg_path: "pkg".to_string(),
             version: version.to_string(),
             description: Some("test".to_string()),
@@ -260,8 +1267,56 @@ mod test {
             scrape_date: None,
             stabilities: None,
             unfree: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn show_handles_404() {
+        let (mut flox, _temp_dir_handle) = flox_instance();
+        flox.floxhub_client = auto_recording_catalog_client("show_handles_404");
+        let search_term = "search_term";
+        let err = Show {
+            pkg_path: search_term.to_string(),
+            versions_available_for: None,
+            system: None,
+            catalog: None,
+            mode: None,
+        }
+        .handle(flox)
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!("no packages matched this pkg-path: '{search_term}'")
+        );
+    }
+
+    #[test]
+    fn formats_deprecation_notices() {
+        let renamed = DeprecationInfo {
+            kind: DeprecationKind::Renamed,
+            message: None,
+            replacement: Some("new-pkg".to_string()),
         };
+        assert_eq!(
+            format_deprecation("pkg", &renamed),
+            "'pkg' has been renamed to 'new-pkg'."
+        );
 
+        let deprecated = DeprecationInfo {
+            kind: DeprecationKind::Deprecated,
+            message: Some("End of life upstream".to_string()),
+            replacement: Some("new-pkg".to_string()),
+        };
+        assert_eq!(
+            format_deprecation("pkg", &deprecated),
+            "'pkg' is deprecated. Use 'new-pkg' instead.\nEnd of life upstream"
+        );
+    }
+
+    #[test]
+    fn test_column_alignment_for_system_restrictions() {
         let packages = vec![
             mock_pkg("1.0", "aarch64-darwin"),
             mock_pkg("10.0.0", "aarch64-darwin"),
@@ -295,4 +1350,425 @@ mod test {
                     pkg@10.0.0 (aarch64-darwin only)
             "});
     }
+
+    #[test]
+    fn systems_matrix_marks_unavailable_broken_and_unfree() {
+        let mut broken = mock_pkg("2.0", "aarch64-linux");
+        broken.broken = Some(true);
+        let mut unfree = mock_pkg("1.0", "x86_64-linux");
+        unfree.unfree = Some(true);
+
+        let packages = vec![
+            mock_pkg("2.0", "aarch64-darwin"),
+            broken,
+            mock_pkg("2.0", "x86_64-linux"),
+            mock_pkg("1.0", "aarch64-darwin"),
+            unfree,
+        ];
+
+        let mut buf = vec![];
+        render_show_systems_matrix(&mut buf, &packages, &DEFAULT_SYSTEMS_STR[..], false).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+
+        assert_eq!(output, indoc! {"
+            pkg
+            VERSION  aarch64-darwin  aarch64-linux  x86_64-darwin  x86_64-linux
+            2.0      available       broken         unavailable    available
+            1.0      available       unavailable    unavailable    unfree
+        "});
+    }
+
+    #[test]
+    fn sizes_are_formatted_or_marked_unknown() {
+        let search_results = vec![
+            mock_pkg("1.0.0", "x86_64-linux"),
+            mock_pkg("1.0.0", "aarch64-darwin"),
+            mock_pkg("0.9.0", "x86_64-linux"),
+        ];
+
+        let mut out = Vec::new();
+        render_show_sizes(&mut out, &search_results, |pkg| {
+            (pkg.version == "1.0.0" && pkg.system.to_string() == "x86_64-linux")
+                .then_some(5 * 1024 * 1024 + 512 * 1024)
+        })
+        .unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap(), indoc! {"
+            pkg
+            VERSION  SYSTEM          CLOSURE SIZE
+            1.0.0    x86_64-linux    5.5 MiB
+            1.0.0    aarch64-darwin  unknown
+            0.9.0    x86_64-linux    unknown
+        "});
+    }
+
+    #[test]
+    fn best_match_keeps_builds_of_first_package() {
+        let mut other = mock_pkg("3.0", "x86_64-linux");
+        other.pkg_path = "pkgFull".to_string();
+        let search_results = vec![
+            mock_pkg("2.0", "x86_64-linux"),
+            other.clone(),
+            mock_pkg("1.0", "x86_64-linux"),
+            other,
+        ];
+
+        let (best, other_matches) = best_match(&search_results);
+        assert_eq!(
+            best.iter()
+                .map(|pkg| pkg.version.as_str())
+                .collect::<Vec<_>>(),
+            ["2.0", "1.0"]
+        );
+        assert_eq!(other_matches, 1);
+    }
+
+    #[test]
+    fn json_lines_prints_one_build_per_line() {
+        let search_results = vec![
+            mock_pkg("2.0", "x86_64-linux"),
+            mock_pkg("2.0", "aarch64-linux"),
+            mock_pkg("1.0", "x86_64-linux"),
+        ];
+
+        let mut out = Vec::new();
+        render_json_lines(&mut out, &search_results, None).unwrap();
+        let builds = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<PackageBuild>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(builds, search_results);
+
+        let mut out = Vec::new();
+        render_json_lines(&mut out, &search_results, Some(&PackageSystem::X8664Linux)).unwrap();
+        let versions = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<PackageBuild>(line).unwrap().version)
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec!["2.0", "1.0"]);
+    }
+
+    #[test]
+    fn same_page_systems_groups_pages_by_systems() {
+        let on_page = |version: &str, system: &str, rev_count: i64| PackageBuild {
+            rev_count,
+            ..mock_pkg(version, system)
+        };
+        let search_results = vec![
+            on_page("2.0", "x86_64-linux", 30),
+            on_page("2.0", "aarch64-linux", 30),
+            on_page("1.1", "x86_64-linux", 20),
+            on_page("1.0", "x86_64-linux", 10),
+            on_page("1.0", "aarch64-linux", 10),
+        ];
+
+        let mut out = Vec::new();
+        render_same_page_systems(&mut out, &search_results).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), indoc! {"
+            aarch64-linux, x86_64-linux
+                2 pages, latest: page 30 from 2025-05-31 with version 2.0
+            x86_64-linux
+                1 page, latest: page 20 from 2025-05-31 with version 1.1
+        "});
+    }
+
+    #[test]
+    fn versions_for_system_filters_other_systems() {
+        let search_results = vec![
+            mock_pkg("2.0", "x86_64-linux"),
+            mock_pkg("2.0", "aarch64-linux"),
+            mock_pkg("1.1", "x86_64-linux"),
+            mock_pkg("1.0", "aarch64-linux"),
+        ];
+
+        let mut out = Vec::new();
+        render_versions_for_system(&mut out, &search_results, &PackageSystem::Aarch64Linux)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), indoc! {"
+            2.0
+            1.0
+        "});
+
+        let err = render_versions_for_system(
+            &mut Vec::new(),
+            &search_results,
+            &PackageSystem::X8664Darwin,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No versions of 'pkg' are available for 'x86_64-darwin'."
+        );
+    }
+
+    #[test]
+    fn first_available_version_prefers_lowest_semver_for_system() {
+        let search_results = vec![
+            mock_pkg("2.0.0", "x86_64-linux"),
+            mock_pkg("1.10.0", "x86_64-linux"),
+            mock_pkg("1.9.0", "x86_64-linux"),
+            mock_pkg("unstable-2024-01-01", "x86_64-linux"),
+            mock_pkg("1.0.0", "aarch64-linux"),
+        ];
+
+        let oldest = first_available_version(&search_results, "x86_64-linux").unwrap();
+        assert_eq!(oldest.version, "1.9.0");
+        let oldest = first_available_version(&search_results, "aarch64-linux").unwrap();
+        assert_eq!(oldest.version, "1.0.0");
+
+        let search_results = vec![
+            mock_pkg("unstable-2024-05-01", "x86_64-linux"),
+            mock_pkg("unstable-2024-01-01", "x86_64-linux"),
+        ];
+        let oldest = first_available_version(&search_results, "x86_64-linux").unwrap();
+        assert_eq!(oldest.version, "unstable-2024-01-01");
+
+        let err = first_available_version(&search_results, "x86_64-darwin").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No versions of 'pkg' are available for 'x86_64-darwin'."
+        );
+    }
+
+    #[test]
+    fn install_command_uses_latest_version_for_system() {
+        let search_results = vec![
+            mock_pkg("2.0", "x86_64-linux"),
+            mock_pkg("1.0", "aarch64-linux"),
+        ];
+
+        assert_eq!(
+            format_install_command(&search_results, None, None).unwrap(),
+            "flox install pkg@=2.0"
+        );
+        assert_eq!(
+            format_install_command(&search_results, Some(&PackageSystem::Aarch64Linux), None)
+                .unwrap(),
+            "flox install pkg@=1.0"
+        );
+
+        let err = format_install_command(&search_results, Some(&PackageSystem::X8664Darwin), None)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No versions of 'pkg' are available for 'x86_64-darwin'."
+        );
+
+        let search_results = vec![mock_pkg("1.0 beta", "x86_64-linux")];
+        assert_eq!(
+            format_install_command(&search_results, None, None).unwrap(),
+            "flox install 'pkg@=1.0 beta'"
+        );
+
+        assert_eq!(
+            format_install_command(&search_results, None, Some("myorg")).unwrap(),
+            "flox install 'myorg/pkg@=1.0 beta'"
+        );
+    }
+
+    #[test]
+    fn install_descriptor_pins_version_with_sanitized_id() {
+        let pkg = PackageBuild {
+            pname: "python3.12".to_string(),
+            pkg_path: "python312".to_string(),
+            ..mock_pkg("3.12.4", "x86_64-linux")
+        };
+        assert_eq!(format_install_descriptor(&pkg, None).unwrap(), indoc! {r#"
+            [install]
+            python3_12.pkg-path = "python312"
+            python3_12.version = "=3.12.4"
+        "#});
+
+        let pkg = PackageBuild {
+            pname: "tool".to_string(),
+            pkg_path: "tool".to_string(),
+            ..mock_pkg("1.0", "x86_64-linux")
+        };
+        assert_eq!(
+            format_install_descriptor(&pkg, Some("myorg")).unwrap(),
+            indoc! {r#"
+            [install]
+            tool.pkg-path = "myorg/tool"
+            tool.version = "=1.0"
+        "#}
+        );
+
+        assert_eq!(default_install_id("", "python312Packages.pip"), "pip");
+        assert_eq!(default_install_id("", "mycatalog/tool"), "tool");
+        assert_eq!(default_install_id("gnu-sed", "gnused"), "gnu-sed");
+    }
+
+    #[test]
+    fn systems_available_lists_systems_of_latest_version() {
+        let mut other = mock_pkg("3.0", "x86_64-darwin");
+        other.pkg_path = "pkgFull".to_string();
+        let search_results = vec![
+            mock_pkg("2.0", "x86_64-linux"),
+            mock_pkg("2.0", "aarch64-darwin"),
+            other,
+            mock_pkg("1.0", "aarch64-linux"),
+        ];
+
+        assert_eq!(
+            format_systems_available(&search_results).unwrap(),
+            "aarch64-darwin, x86_64-linux"
+        );
+    }
+
+    #[test]
+    fn runtime_dependencies_exclude_the_outputs() {
+        let mut pkg = mock_pkg("1.0", "x86_64-linux");
+        pkg.outputs = PackageOutputs(vec![
+            PackageOutput {
+                name: "out".to_string(),
+                store_path: "/nix/store/aaa-pkg-1.0".to_string(),
+            },
+            PackageOutput {
+                name: "man".to_string(),
+                store_path: "/nix/store/bbb-pkg-1.0-man".to_string(),
+            },
+        ]);
+        pkg.outputs_to_install = None;
+        let path_infos = |_: &[&str], _: bool| {
+            serde_json::json!({
+                "/nix/store/aaa-pkg-1.0": {
+                    "references": ["/nix/store/aaa-pkg-1.0", "/nix/store/ccc-glibc"]
+                },
+                "/nix/store/bbb-pkg-1.0-man": {
+                    "references": ["/nix/store/aaa-pkg-1.0"]
+                },
+                "/nix/store/ccc-glibc": {
+                    "references": ["/nix/store/ddd-libidn2"]
+                },
+                "/nix/store/ddd-libidn2": { "references": [] },
+            })
+            .as_object()
+            .cloned()
+        };
+
+        assert_eq!(
+            runtime_dependencies(&pkg, false, path_infos).unwrap(),
+            BTreeSet::from(["/nix/store/ccc-glibc".to_string()])
+        );
+        assert_eq!(
+            runtime_dependencies(&pkg, true, path_infos).unwrap(),
+            BTreeSet::from([
+                "/nix/store/ccc-glibc".to_string(),
+                "/nix/store/ddd-libidn2".to_string()
+            ])
+        );
+
+        // Unknown if nix can't be queried or the outputs aren't in the store
+        assert_eq!(runtime_dependencies(&pkg, false, |_, _| None), None);
+        let missing = |_: &[&str], _: bool| {
+            serde_json::json!({ "/nix/store/aaa-pkg-1.0": null })
+                .as_object()
+                .cloned()
+        };
+        assert_eq!(runtime_dependencies(&pkg, true, missing), None);
+    }
+
+    #[test]
+    fn resolution_manifest_keeps_constraint_and_options() {
+        let manifest: ManifestLatest = toml_edit::de::from_str(indoc! {r#"
+            schema-version = "1.13.0"
+
+            [install]
+            hello.pkg-path = "hello"
+            hello.version = "^2.10"
+            hello.pkg-group = "tools"
+            hello.systems = ["aarch64-darwin"]
+            curl.pkg-path = "curl"
+
+            [options]
+            systems = ["aarch64-darwin", "x86_64-linux"]
+            allow.unfree = true
+            semver.allow-pre-releases = true
+        "#})
+        .unwrap();
+
+        let (resolution, install_id) =
+            resolution_manifest(&manifest, "hello", &"x86_64-linux".to_string());
+        assert_eq!(install_id, "hello");
+        assert_eq!(resolution.install.inner().len(), 1);
+        let descriptor = resolution.install.inner()["hello"]
+            .as_catalog_descriptor_ref()
+            .unwrap();
+        assert_eq!(descriptor.version.as_deref(), Some("^2.10"));
+        assert_eq!(descriptor.pkg_group, None);
+        assert_eq!(descriptor.systems, None);
+        assert_eq!(
+            resolution.options.systems,
+            Some(vec!["x86_64-linux".to_string()])
+        );
+        assert_eq!(resolution.options.allow, manifest.options.allow);
+        assert_eq!(resolution.options.semver, manifest.options.semver);
+
+        let (resolution, install_id) = resolution_manifest(
+            &manifest,
+            "python3Packages.pip",
+            &"x86_64-linux".to_string(),
+        );
+        assert_eq!(install_id, "pip");
+        let descriptor = resolution.install.inner()["pip"]
+            .as_catalog_descriptor_ref()
+            .unwrap();
+        assert_eq!(descriptor.pkg_path, "python3Packages.pip");
+        assert_eq!(descriptor.version, None);
+    }
+
+    #[test]
+    fn closure_size_follows_references_of_default_outputs() {
+        let mut pkg = mock_pkg("1.0", "x86_64-linux");
+        pkg.outputs = PackageOutputs(vec![
+            PackageOutput {
+                name: "out".to_string(),
+                store_path: "/nix/store/aaa-pkg-1.0".to_string(),
+            },
+            PackageOutput {
+                name: "man".to_string(),
+                store_path: "/nix/store/bbb-pkg-1.0-man".to_string(),
+            },
+        ]);
+        pkg.outputs_to_install = Some(vec!["out".to_string()]);
+        let path_infos: serde_json::Map<_, _> = serde_json::from_str(indoc! {r#"
+            {
+              "/nix/store/aaa-pkg-1.0": {
+                "narSize": 1024,
+                "references": ["/nix/store/aaa-pkg-1.0", "/nix/store/ccc-glibc"]
+              },
+              "/nix/store/bbb-pkg-1.0-man": { "narSize": 4096, "references": [] },
+              "/nix/store/ccc-glibc": { "narSize": 2048, "references": [] },
+              "/nix/store/ddd-unrelated": { "narSize": 8192, "references": [] }
+            }
+        "#})
+        .unwrap();
+        assert_eq!(closure_size(&pkg, &path_infos), Some(3072));
+
+        let missing: serde_json::Map<_, _> = serde_json::from_str(indoc! {r#"
+            {
+              "/nix/store/aaa-pkg-1.0": {
+                "narSize": 1024,
+                "references": ["/nix/store/ccc-glibc"]
+              },
+              "/nix/store/ccc-glibc": null
+            }
+        "#})
+        .unwrap();
+        assert_eq!(closure_size(&pkg, &missing), None);
+        assert_eq!(closure_size(&pkg, &serde_json::Map::new()), None);
+    }
+
+    #[test]
+    fn catalog_pkg_path_qualifies_base_paths() {
+        assert_eq!(catalog_pkg_path("nixpkgs", "hello").unwrap(), "hello");
+        assert_eq!(catalog_pkg_path("myorg", "hello").unwrap(), "myorg/hello");
+        assert_eq!(
+            catalog_pkg_path("myorg", "myorg/hello").unwrap(),
+            "myorg/hello"
+        );
+        assert!(catalog_pkg_path("myorg", "otherorg/hello").is_err());
+    }
 }
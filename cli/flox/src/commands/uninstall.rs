@@ -1,11 +1,14 @@
+use std::fmt::Display;
+
 use anyhow::{Result, bail};
 use bpaf::Bpaf;
 use flox_events::{EventsHub, PackageOutcome};
+use flox_manifest::lockfile::{Lockfile, PackageToList};
 use flox_manifest::parsed::latest::SelectedOutputs;
 use flox_manifest::raw::PackageModification;
 use flox_rust_sdk::flox::Flox;
 use flox_rust_sdk::models::environment::uninstall::UninstallSpec;
-use flox_rust_sdk::models::environment::{Environment, EnvironmentError};
+use flox_rust_sdk::models::environment::{ConcreteEnvironment, Environment, EnvironmentError};
 use indoc::formatdoc;
 use itertools::Itertools;
 use tracing::{debug, info_span, instrument};
@@ -13,6 +16,7 @@ use tracing::{debug, info_span, instrument};
 use super::services::warn_manifest_changes_for_services;
 use super::{EnvironmentSelect, environment_select};
 use crate::commands::{EnvironmentSelectError, ensure_auth, environment_description};
+use crate::utils::dialog::{Dialog, MultiSelect};
 use crate::utils::events::env_detail_from_concrete;
 use crate::utils::message;
 use crate::utils::tracing::sentry_set_tag;
@@ -26,7 +30,9 @@ pub struct Uninstall {
 
     /// The install IDs or package paths of the packages to remove.
     /// Supports output specification: "pkg^out,man" to uninstall specific outputs.
-    #[bpaf(positional("packages"), some("Must specify at least one package"))]
+    /// If omitted in an interactive terminal,
+    /// select the packages to remove from the installed packages.
+    #[bpaf(positional("packages"), many)]
     packages: Vec<String>,
 }
 
@@ -38,6 +44,10 @@ impl Uninstall {
 
         sentry_set_tag("packages", self.packages.iter().join(","));
 
+        if self.packages.is_empty() && !Dialog::can_prompt() {
+            bail!("Must specify at least one package");
+        }
+
         debug!(
             "uninstalling packages [{}] from {:?}",
             self.packages.as_slice().join(", "),
@@ -83,9 +93,20 @@ impl Uninstall {
 
         let description = environment_description(&concrete_environment)?;
 
+        let packages = if self.packages.is_empty() {
+            let selected =
+                Self::select_packages(&flox, &mut concrete_environment, &description).await?;
+            if selected.is_empty() {
+                message::plain("No packages selected, nothing to uninstall.");
+                return Ok(());
+            }
+            selected
+        } else {
+            self.packages
+        };
+
         // Parse package arguments into UninstallSpec
-        let uninstall_specs: Vec<UninstallSpec> = self
-            .packages
+        let uninstall_specs: Vec<UninstallSpec> = packages
             .iter()
             .map(|s| UninstallSpec::parse(s))
             .collect::<Result<Vec<_>, _>>()
@@ -94,7 +115,7 @@ impl Uninstall {
         let span = info_span!(
             "uninstall",
             concrete_environment = %description,
-            progress = format!("Uninstalling {} packages", self.packages.len()));
+            progress = format!("Uninstalling {} packages", packages.len()));
 
         let attempt = span.in_scope(|| concrete_environment.uninstall(uninstall_specs, &flox))?;
 
@@ -149,4 +170,64 @@ impl Uninstall {
 
         Ok(())
     }
+
+    /// Prompt for the packages to uninstall,
+    /// offering the packages installed for the current system.
+    async fn select_packages(
+        flox: &Flox,
+        environment: &mut ConcreteEnvironment,
+        description: &str,
+    ) -> Result<Vec<String>> {
+        let lockfile: Lockfile = environment.lockfile(flox)?.into();
+        let installed = lockfile
+            .list_packages(&flox.system)?
+            .into_iter()
+            .map(InstalledPackage::from)
+            .unique_by(|package| package.install_id.clone())
+            .collect::<Vec<_>>();
+
+        if installed.is_empty() {
+            bail!("No packages are installed in environment {description}.");
+        }
+
+        let dialog = Dialog {
+            message: "Select packages to uninstall:",
+            help_message: Some("Use space to select, enter to confirm"),
+            typed: MultiSelect { options: installed },
+        };
+        let selected = dialog.prompt().await?;
+
+        Ok(selected
+            .into_iter()
+            .map(|package| package.install_id)
+            .collect())
+    }
+}
+
+/// An installed package offered by [Uninstall::select_packages]
+struct InstalledPackage {
+    install_id: String,
+    /// Where the package is installed from, e.g. its catalog package path
+    source: String,
+}
+
+impl From<PackageToList> for InstalledPackage {
+    fn from(package: PackageToList) -> Self {
+        let (install_id, source) = match package {
+            PackageToList::Catalog(descriptor, locked) => (locked.install_id, descriptor.pkg_path),
+            PackageToList::Flake(descriptor, locked) => (locked.install_id, descriptor.flake),
+            PackageToList::StorePath(locked) => (locked.install_id, locked.store_path),
+        };
+        Self { install_id, source }
+    }
+}
+
+impl Display for InstalledPackage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.install_id == self.source {
+            write!(f, "{}", self.install_id)
+        } else {
+            write!(f, "{} ({})", self.install_id, self.source)
+        }
+    }
 }
@@ -1,12 +1,14 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use bpaf::Bpaf;
 use crossterm::style::Stylize;
 use flox_events::{EventsHub, PackageOutcome};
-use flox_manifest::lockfile::LockedPackage;
+use flox_manifest::interfaces::AsLatestSchema;
+use flox_manifest::lockfile::{LockedPackage, Lockfile};
 use flox_rust_sdk::flox::Flox;
-use flox_rust_sdk::models::environment::{Environment, SingleSystemUpgradeDiff};
+use flox_rust_sdk::models::environment::{Environment, SingleSystemUpgradeDiff, UpgradeResult};
 use indoc::formatdoc;
 use itertools::Itertools;
+use serde::Serialize;
 use tracing::{debug, info_span, instrument};
 
 use super::services::warn_manifest_changes_for_services;
@@ -27,6 +29,21 @@ pub struct Upgrade {
     #[bpaf(long)]
     dry_run: bool,
 
+    /// Print the changed packages of all systems as a JSON array
+    #[bpaf(long)]
+    json: bool,
+
+    /// ID of a package to keep at its locked version while upgrading others.
+    /// Can be specified multiple times.
+    #[bpaf(long("freeze"), argument("iid"))]
+    frozen: Vec<String>,
+
+    /// Only upgrade packages that were locked as insecure through
+    /// 'options.allow.insecure', keeping all other packages at their locked
+    /// versions
+    #[bpaf(long)]
+    only_security: bool,
+
     /// ID of a package or pkg-group name to upgrade
     #[bpaf(positional("package or pkg-group"))]
     groups_or_iids: Vec<String>,
@@ -42,6 +59,18 @@ impl Upgrade {
             "upgrading groups and install ids"
         );
 
+        if let Some(iid) = self
+            .frozen
+            .iter()
+            .find(|iid| self.groups_or_iids.contains(iid))
+        {
+            bail!("'{iid}' cannot be both upgraded and frozen");
+        }
+
+        if self.only_security && !self.groups_or_iids.is_empty() {
+            bail!("'--only-security' cannot be used with a package or pkg-group.");
+        }
+
         // Ensure the user is logged in for the following remote operations
         if let EnvironmentSelect::Remote(_) = self.environment {
             ensure_auth(&mut flox).await?;
@@ -60,11 +89,38 @@ impl Upgrade {
 
         let description = environment_description(&concrete_environment)?;
 
+        let groups_or_iids = if self.only_security {
+            let insecure = match concrete_environment.existing_lockfile(&flox)? {
+                Some(lockfile) => insecure_locked_install_ids(&lockfile)?,
+                None => Vec::new(),
+            };
+            if insecure.is_empty() {
+                // The catalog doesn't provide security advisories for packages,
+                // the only insecure packages we know of are the ones that were
+                // explicitly allowed when locking.
+                message::warning(formatdoc! {"
+                    No packages in {description} were locked as insecure, and the catalog does not
+                    provide security advisory data for other packages.
+                    No packages were upgraded."});
+                if self.json {
+                    println!("[]");
+                }
+                return Ok(());
+            }
+            debug!(
+                install_ids = insecure.join(","),
+                "upgrading packages allowed as insecure"
+            );
+            insecure
+        } else {
+            self.groups_or_iids.clone()
+        };
+
         let progress_message = {
-            let num_upgrades = if self.groups_or_iids.is_empty() {
+            let num_upgrades = if groups_or_iids.is_empty() {
                 "all".to_string()
             } else {
-                format!("{}", self.groups_or_iids.len())
+                format!("{}", groups_or_iids.len())
             };
 
             let dry_prefix = if self.dry_run { "Dry run: " } else { "" };
@@ -78,23 +134,31 @@ impl Upgrade {
             progress = %progress_message
         );
         let result = span.in_scope(|| {
-            let groups_or_iids = &self
-                .groups_or_iids
+            let groups_or_iids = &groups_or_iids
                 .iter()
                 .map(String::as_str)
                 .collect::<Vec<_>>();
+            let frozen = &self.frozen.iter().map(String::as_str).collect::<Vec<_>>();
 
             if self.dry_run {
-                concrete_environment.dry_upgrade(&flox, groups_or_iids)
+                concrete_environment.dry_upgrade(&flox, groups_or_iids, frozen)
             } else {
-                concrete_environment.upgrade(&flox, groups_or_iids)
+                concrete_environment.upgrade(&flox, groups_or_iids, frozen)
             }
         })?;
 
+        if self.json {
+            println!("{}", serde_json::to_string(&upgrade_changes(&result))?);
+            if !self.dry_run {
+                warn_manifest_changes_for_services(&flox, &concrete_environment);
+            }
+            return Ok(());
+        }
+
         let diff = result.diff();
 
         if diff.is_empty() {
-            if self.groups_or_iids.is_empty() {
+            if groups_or_iids.is_empty() {
                 message::plain(format!(
                     "No upgrades available for packages in {description}."
                 ));
@@ -103,6 +167,7 @@ impl Upgrade {
                     "No upgrades available for the specified packages in {description}."
                 ));
             }
+            report_frozen(&self.frozen);
             return Ok(());
         }
 
@@ -115,12 +180,13 @@ impl Upgrade {
                 message::plain(formatdoc! {"
                     Upgrades are not available for {description} on this system, but upgrades are
                     available for other systems supported by this environment."});
-                if self.groups_or_iids.is_empty() {
+                if groups_or_iids.is_empty() {
                 } else {
                     message::plain(format!(
                         "No upgrades available for the specified packages in {description}."
                     ));
                 }
+                report_frozen(&self.frozen);
                 return Ok(());
             }
             let (version_changes, rebuilds) = count_upgrade_categories(&diff_for_system);
@@ -131,6 +197,7 @@ impl Upgrade {
 
                 To apply these changes, run upgrade without the '--dry-run' flag.
             "});
+            report_frozen(&self.frozen);
 
             return Ok(());
         }
@@ -153,6 +220,7 @@ impl Upgrade {
             {rendered_diff}
             "});
         }
+        report_frozen(&self.frozen);
 
         warn_manifest_changes_for_services(&flox, &concrete_environment);
 
@@ -169,6 +237,71 @@ impl Upgrade {
     }
 }
 
+/// Tell the user which packages were kept at their locked version
+/// Install IDs of the locked catalog packages that were allowed to be insecure
+/// by 'options.allow.insecure' when the environment was locked.
+///
+/// Packages allowed once with '--allow-insecure' are not recorded in the
+/// lockfile and are therefore not included.
+fn insecure_locked_install_ids(lockfile: &Lockfile) -> Result<Vec<String>> {
+    let manifest = lockfile.migrated_manifest()?;
+    let Some(allowed_insecure) = &manifest.as_latest_schema().options.allow.insecure else {
+        return Ok(Vec::new());
+    };
+
+    Ok(lockfile
+        .packages
+        .iter()
+        .filter_map(LockedPackage::as_catalog_package_ref)
+        .map(|package| &package.install_id)
+        .filter(|install_id| allowed_insecure.contains(install_id))
+        .unique()
+        .cloned()
+        .collect())
+}
+
+fn report_frozen(frozen: &[String]) {
+    if frozen.is_empty() {
+        return;
+    }
+    message::info(format!(
+        "Kept frozen package(s) unchanged: {}",
+        frozen.join(", ")
+    ));
+}
+
+/// A package changed by an upgrade, as reported by `flox upgrade --json`
+#[derive(Debug, PartialEq, Serialize)]
+struct UpgradeChange {
+    install_id: String,
+    system: String,
+    old_version: Option<String>,
+    new_version: Option<String>,
+    old_derivation: Option<String>,
+    new_derivation: Option<String>,
+}
+
+/// List the packages changed by an upgrade, for all systems,
+/// ordered by install ID and system.
+fn upgrade_changes(result: &UpgradeResult) -> Vec<UpgradeChange> {
+    result
+        .diff()
+        .into_iter()
+        .flat_map(|(install_id, by_system)| {
+            by_system
+                .into_iter()
+                .map(move |(system, (before, after))| UpgradeChange {
+                    install_id: install_id.clone(),
+                    system,
+                    old_version: before.version().map(String::from),
+                    new_version: after.version().map(String::from),
+                    old_derivation: before.derivation().map(String::from),
+                    new_derivation: after.derivation().map(String::from),
+                })
+        })
+        .collect()
+}
+
 /// Render a diff of locked packages before and after an upgrade.
 ///
 /// Version changes show: `- pkg: 1.0 -> 2.0`
@@ -228,6 +361,7 @@ mod tests {
         new_named_path_environment_from_env_files,
     };
     use flox_rust_sdk::providers::catalog::test_helpers::catalog_replay_client;
+    use flox_rust_sdk::providers::lock_manifest::ResolutionOverrides;
     use flox_rust_sdk::utils::logging::test_helpers::test_subscriber_message_only;
     use flox_test_utils::GENERATED_DATA;
     use indoc::indoc;
@@ -254,6 +388,9 @@ mod tests {
         Upgrade {
             environment: EnvironmentSelect::Dir(environment.parent_path().unwrap()),
             dry_run: true,
+            json: false,
+            frozen: Vec::new(),
+            only_security: false,
             groups_or_iids: Vec::new(),
         }
         .handle(flox)
@@ -266,8 +403,75 @@ mod tests {
         assert_eq!(printed, "No upgrades available for packages in 'name'.\n");
     }
 
+    /// Without packages allowed as insecure, '--only-security' leaves the
+    /// lockfile unchanged
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_security_without_insecure_packages_changes_nothing() {
+        let (flox, _tempdir) = flox_instance();
+        let (subscriber, writer) = test_subscriber_message_only();
+
+        let environment = new_named_path_environment_from_env_files(
+            &flox,
+            GENERATED_DATA.join("envs/hello"),
+            "name",
+        );
+        let lockfile_path = environment.lockfile_path(&flox).unwrap();
+        let lockfile_before = std::fs::read_to_string(&lockfile_path).unwrap();
+
+        Upgrade {
+            environment: EnvironmentSelect::Dir(environment.parent_path().unwrap()),
+            dry_run: false,
+            json: false,
+            frozen: Vec::new(),
+            only_security: true,
+            groups_or_iids: Vec::new(),
+        }
+        .handle(flox)
+        .with_subscriber(subscriber)
+        .await
+        .unwrap();
+
+        assert!(
+            writer.to_string().contains(
+                "No packages in 'name' were locked as insecure, and the catalog does not"
+            )
+        );
+        assert_eq!(
+            std::fs::read_to_string(&lockfile_path).unwrap(),
+            lockfile_before
+        );
+    }
+
+    /// Only packages listed in 'options.allow.insecure' of the locked manifest
+    /// are selected, once for all systems they are locked for
+    #[test]
+    fn insecure_locked_install_ids_follow_allow_insecure() {
+        let lockfile_contents =
+            std::fs::read_to_string(GENERATED_DATA.join("envs/hello/manifest.lock")).unwrap();
+        let mut lockfile_json: serde_json::Value =
+            serde_json::from_str(&lockfile_contents).unwrap();
+
+        let lockfile: Lockfile = lockfile_contents.parse().unwrap();
+        assert_eq!(
+            insecure_locked_install_ids(&lockfile).unwrap(),
+            Vec::<String>::new()
+        );
+
+        lockfile_json["manifest"]["schema-version"] = "1.14.0".into();
+        lockfile_json["manifest"]["options"] = serde_json::json!({
+            "allow": { "insecure": ["hello", "not-locked"] }
+        });
+        let lockfile: Lockfile = lockfile_json.to_string().parse().unwrap();
+        assert_eq!(insecure_locked_install_ids(&lockfile).unwrap(), vec![
+            "hello".to_string()
+        ]);
+    }
+
     /// Run an upgrade of an environment that only has upgrades on other systems
-    async fn run_upgrade_with_upgrades_on_other_system(dry_run: bool) -> String {
+    async fn run_upgrade_with_upgrades_on_other_system(
+        dry_run: bool,
+        frozen: Vec<String>,
+    ) -> String {
         let (mut flox, _tempdir) = flox_instance();
         let (subscriber, writer) = test_subscriber_message_only();
 
@@ -284,6 +488,7 @@ mod tests {
             .install(
                 &[PackageToInstall::parse(&flox.system, "hello").unwrap()],
                 &flox,
+                &ResolutionOverrides::default(),
             )
             .unwrap();
 
@@ -292,6 +497,9 @@ mod tests {
         Upgrade {
             environment: EnvironmentSelect::Dir(environment.parent_path().unwrap()),
             dry_run,
+            json: false,
+            frozen,
+            only_security: false,
             groups_or_iids: Vec::new(),
         }
         .handle(flox)
@@ -305,7 +513,7 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn upgrade_on_other_system() {
         assert_eq!(
-            run_upgrade_with_upgrades_on_other_system(false).await,
+            run_upgrade_with_upgrades_on_other_system(false, Vec::new()).await,
             indoc! {"
             ✔ Upgraded 'name'.
             Upgrades were not available for this system, but upgrades were applied for other
@@ -317,7 +525,7 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn upgrade_dry_run_on_other_system() {
         assert_eq!(
-            run_upgrade_with_upgrades_on_other_system(true).await,
+            run_upgrade_with_upgrades_on_other_system(true, Vec::new()).await,
             indoc! {"
             Upgrades are not available for 'name' on this system, but upgrades are
             available for other systems supported by this environment.
@@ -325,11 +533,22 @@ mod tests {
         );
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn upgrade_keeps_frozen_packages() {
+        assert_eq!(
+            run_upgrade_with_upgrades_on_other_system(false, vec!["hello".to_string()]).await,
+            indoc! {"
+            No upgrades available for packages in 'name'.
+            ℹ Kept frozen package(s) unchanged: hello
+            "}
+        );
+    }
+
     mod render_diff_tests {
         use std::collections::BTreeMap;
 
         use chrono::TimeZone;
-        use flox_manifest::lockfile::{LockedPackage, LockedPackageCatalog};
+        use flox_manifest::lockfile::{LockedPackage, LockedPackageCatalog, Lockfile};
 
         use super::super::*;
 
@@ -470,6 +689,35 @@ mod tests {
             );
         }
 
+        #[test]
+        fn upgrade_changes_lists_changed_packages() {
+            let date = chrono::Utc.with_ymd_and_hms(2025, 1, 15, 0, 0, 0).unwrap();
+            let before_curl = make_catalog_package("curl", "8.9.0", "/nix/store/old", "aaa", date);
+            let after_curl = make_catalog_package("curl", "8.10.1", "/nix/store/new", "bbb", date);
+            let hello = make_catalog_package("hello", "2.12.1", "/nix/store/hello", "abc", date);
+
+            let result = UpgradeResult {
+                old_lockfile: Some(Lockfile {
+                    packages: vec![before_curl, hello.clone()],
+                    ..Default::default()
+                }),
+                new_lockfile: Lockfile {
+                    packages: vec![after_curl, hello],
+                    ..Default::default()
+                },
+                store_path: None,
+            };
+
+            assert_eq!(upgrade_changes(&result), vec![UpgradeChange {
+                install_id: "curl".to_string(),
+                system: "x86_64-linux".to_string(),
+                old_version: Some("8.9.0".to_string()),
+                new_version: Some("8.10.1".to_string()),
+                old_derivation: Some("/nix/store/old".to_string()),
+                new_derivation: Some("/nix/store/new".to_string()),
+            }]);
+        }
+
         #[test]
         fn count_categories_mixed() {
             let before_curl =
@@ -521,6 +769,7 @@ mod tests {
             .install(
                 &[PackageToInstall::parse(&flox.system, "hello").unwrap()],
                 &flox,
+                &ResolutionOverrides::default(),
             )
             .unwrap();
 
@@ -529,6 +778,9 @@ mod tests {
         Upgrade {
             environment: EnvironmentSelect::Dir(environment.parent_path().unwrap()),
             dry_run: true,
+            json: false,
+            frozen: Vec::new(),
+            only_security: false,
             groups_or_iids: Vec::new(),
         }
         .handle(flox)
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::{env, fs};
 
 use anyhow::{Context, Result};
@@ -8,7 +9,7 @@ use config::{Config as HierarchicalConfig, Environment};
 use flox_core::activate::context::AutoActivateFishMode;
 use flox_core::data::environment_ref::RemoteEnvironmentRef;
 use flox_core::{WriteError, write_atomically};
-use flox_rust_sdk::flox::Features;
+use flox_rust_sdk::flox::{DefaultEnvironment, Features};
 use floxhub_client::{AuthnMode, SearchLimit};
 use itertools::{Either, Itertools};
 use serde::{Deserialize, Serialize};
@@ -98,6 +99,11 @@ pub struct FloxConfig {
     /// Hide environments named 'default' from the shell prompt
     pub hide_default_prompt: Option<bool>,
 
+    /// How active environments are indicated in the shell prompt.
+    /// Possible values: `off`, `minimal`, `full` (default).
+    /// Takes precedence over set_prompt.
+    pub prompt: Option<PromptStyle>,
+
     /// Print notification if upgrades are available on `flox activate`.
     /// The notification message is:
     /// ```
@@ -133,6 +139,12 @@ pub struct FloxConfig {
     #[serde(default)]
     pub auto_activate_environments: HashMap<PathBuf, AutoActivationPreference>,
 
+    /// The environment to use when no environment is found in the current
+    /// directory or git repo and none is active.
+    /// An absolute path to a directory containing a `.flox` directory,
+    /// or `owner/name` of a FloxHub environment.
+    pub default_environment: Option<DefaultEnvironment>,
+
     /// Don't setup the Flox prompt hook as part of activation.
     /// This disables auto-activation as well as features like `flox deactivate`
     /// without `--print-script` (default: false)
@@ -158,10 +170,40 @@ pub enum EnvironmentPromptConfig {
     HideDefault,
 }
 
+/// How active environments are indicated in the shell prompt
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptStyle {
+    /// Do not change the shell prompt
+    Off,
+    /// Only show the number of active environments
+    Minimal,
+    /// Show the names of the active environments
+    #[default]
+    Full,
+}
+
+impl FromStr for PromptStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(PromptStyle::Off),
+            "minimal" => Ok(PromptStyle::Minimal),
+            "full" => Ok(PromptStyle::Full),
+            _ => Err(format!(
+                "invalid value '{s}', expected 'off', 'minimal', or 'full'"
+            )),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct PublishConfig {
     /// Default path of the signing key used by 'flox publish'
     pub signing_private_key: Option<PathBuf>,
+    /// Default stability 'flox publish' requires packages to be published with
+    pub channel: Option<String>,
 }
 
 /// Channels must match: https://downloads.flox.dev/?prefix=by-env/
@@ -207,6 +249,21 @@ impl Display for InstallerChannel {
     }
 }
 
+impl FromStr for InstallerChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stable" => Ok(InstallerChannel::Stable),
+            "nightly" => Ok(InstallerChannel::Nightly),
+            "qa" => Ok(InstallerChannel::Qa),
+            _ => Err(format!(
+                "unknown installer channel '{s}', expected one of: stable, nightly, qa"
+            )),
+        }
+    }
+}
+
 /// Error returned by [`Config::get()`]
 #[derive(Debug, Error)]
 pub enum ReadWriteError {
@@ -229,6 +286,8 @@ pub enum ReadWriteError {
     ReadConfig(std::io::Error),
     #[error("Could not write config file")]
     WriteConfig(#[source] WriteError),
+    #[error("Invalid value for config key '{key}': {reason}")]
+    InvalidValue { key: String, reason: String },
 }
 
 /// Locates the system wide flox config dir.
@@ -424,6 +483,30 @@ impl Config {
         Ok(value.to_string())
     }
 
+    /// Check invariants that aren't enforced by deserializing into [Config].
+    ///
+    /// Unknown `installer_channel`s and malformed `trusted_environments`
+    /// entries already fail to deserialize,
+    /// this additionally checks that the configured URLs are usable.
+    ///
+    /// Called on the resulting config before any write is persisted,
+    /// so an invalid value leaves the config file unchanged.
+    pub fn validate(&self) -> Result<(), ReadWriteError> {
+        if let Some(floxhub_url) = &self.flox.floxhub_url {
+            validate_http_url("floxhub_url", floxhub_url)?;
+        }
+
+        if let Some(catalog_url) = &self.flox.catalog_url {
+            let url = Url::parse(catalog_url).map_err(|err| ReadWriteError::InvalidValue {
+                key: "catalog_url".to_string(),
+                reason: err.to_string(),
+            })?;
+            validate_http_url("catalog_url", &url)?;
+        }
+
+        Ok(())
+    }
+
     /// Append or update a key value paring in the toml representation of a partial config
     ///
     /// Validate using [Self]
@@ -477,6 +560,7 @@ impl Config {
                 let validation_config: Config = toml_edit::de::from_document(validation_document)?;
 
                 validation_config.get(path)?;
+                validation_config.validate()?;
             },
         }
 
@@ -510,6 +594,223 @@ impl Config {
     }
 }
 
+/// Keys of the user config that are never exported or imported
+/// with `flox config --export` and `flox config --import`.
+/// Keys of nested tables are separated by `.`.
+///
+/// `floxhub_token` is a secret, the remaining keys are specific to a machine.
+const NON_PORTABLE_KEYS: &[&str] = &[
+    "floxhub_token",
+    "cache_dir",
+    "data_dir",
+    "state_dir",
+    "config_dir",
+    "auto_activate_environments",
+    "default_environment",
+    "publish.signing_private_key",
+];
+
+/// Placeholder for secrets in [Config::redacted]
+const REDACTED: &str = "<redacted>";
+
+impl Config {
+    /// A copy of this config with secrets replaced by a placeholder,
+    /// e.g. to include in bug reports.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        if config.flox.floxhub_token.is_some() {
+            config.flox.floxhub_token = Some(REDACTED.to_string());
+        }
+        config
+    }
+
+    /// Render the portable subset of a user config file,
+    /// i.e. without secrets or machine specific options.
+    pub fn export_portable(config_file: Option<String>) -> Result<String, ReadWriteError> {
+        let mut document = match config_file {
+            Some(content) => content.parse::<DocumentMut>()?,
+            None => DocumentMut::new(),
+        };
+        remove_non_portable_keys(&mut document);
+
+        Ok(document.to_string())
+    }
+
+    /// Merge an exported config into the contents of a user config file,
+    /// preserving any keys that aren't set by the exported config.
+    ///
+    /// Validate using [Self]
+    pub fn import_portable(
+        config_file: Option<String>,
+        portable: &str,
+    ) -> Result<String, ReadWriteError> {
+        let mut imported = portable.parse::<DocumentMut>()?;
+        remove_non_portable_keys(&mut imported);
+
+        let mut document = match config_file {
+            Some(content) => content.parse::<DocumentMut>()?,
+            None => DocumentMut::new(),
+        };
+        merge_tables(document.as_table_mut(), imported.as_table());
+
+        trace!("try parsing the merged config (validation)");
+        let mut validation_document = toml_edit::ser::to_document(&Config::default())?;
+        merge_tables(validation_document.as_table_mut(), document.as_table());
+        let validation_config: Config = toml_edit::de::from_document(validation_document)?;
+        validation_config.validate()?;
+
+        Ok(document.to_string())
+    }
+
+    /// Run all [CONFIG_MIGRATIONS] on the contents of a user config file.
+    ///
+    /// Returns the migrated contents if any migration changed the config,
+    /// and the outcome of every migration.
+    pub fn migrate(
+        config_file: Option<String>,
+    ) -> Result<(Option<String>, Vec<MigrationOutcome>), ReadWriteError> {
+        let Some(content) = config_file else {
+            let outcomes = CONFIG_MIGRATIONS
+                .iter()
+                .map(|migration| MigrationOutcome {
+                    name: migration.name,
+                    change: None,
+                })
+                .collect();
+            return Ok((None, outcomes));
+        };
+
+        let mut document = content.parse::<DocumentMut>()?;
+        let outcomes = CONFIG_MIGRATIONS
+            .iter()
+            .map(|migration| {
+                Ok(MigrationOutcome {
+                    name: migration.name,
+                    change: (migration.run)(&mut document)?,
+                })
+            })
+            .collect::<Result<Vec<_>, ReadWriteError>>()?;
+
+        let migrated = outcomes
+            .iter()
+            .any(|outcome| outcome.change.is_some())
+            .then(|| document.to_string());
+        Ok((migrated, outcomes))
+    }
+}
+
+/// A migration of the user config file.
+///
+/// Migrations must be idempotent,
+/// returning [None] if there is nothing (left) to migrate,
+/// or a description of what was changed.
+struct ConfigMigration {
+    name: &'static str,
+    run: fn(&mut DocumentMut) -> Result<Option<String>, ReadWriteError>,
+}
+
+/// Migrations run by `flox config --migrate`, in order
+const CONFIG_MIGRATIONS: &[ConfigMigration] = &[ConfigMigration {
+    name: "shell_prompt",
+    run: migrate_shell_prompt,
+}];
+
+/// The outcome of a single [ConfigMigration]
+#[derive(Debug, PartialEq)]
+pub struct MigrationOutcome {
+    pub name: &'static str,
+    /// What the migration changed, [None] if there was nothing to migrate
+    pub change: Option<String>,
+}
+
+/// Replace the deprecated `shell_prompt` with the equivalent
+/// `set_prompt` and `hide_default_prompt`.
+///
+/// If either of the new keys is already set, `shell_prompt` is ignored by
+/// activations and is only removed.
+fn migrate_shell_prompt(document: &mut DocumentMut) -> Result<Option<String>, ReadWriteError> {
+    #[derive(Deserialize)]
+    struct ShellPrompt {
+        shell_prompt: Option<EnvironmentPromptConfig>,
+    }
+
+    let ShellPrompt {
+        shell_prompt: Some(shell_prompt),
+    } = toml_edit::de::from_document(document.clone())?
+    else {
+        return Ok(None);
+    };
+
+    document.remove("shell_prompt");
+    if document.contains_key("set_prompt") || document.contains_key("hide_default_prompt") {
+        return Ok(Some(
+            "removed 'shell_prompt', which is superseded by 'set_prompt' and 'hide_default_prompt'"
+                .to_string(),
+        ));
+    }
+
+    let (set_prompt, hide_default_prompt) = match shell_prompt {
+        EnvironmentPromptConfig::ShowAll => (true, false),
+        EnvironmentPromptConfig::HideDefault => (true, true),
+        EnvironmentPromptConfig::HideAll => (false, false),
+    };
+    document["set_prompt"] = toml_edit::value(set_prompt);
+    document["hide_default_prompt"] = toml_edit::value(hide_default_prompt);
+
+    Ok(Some(format!(
+        "replaced 'shell_prompt' with 'set_prompt = {set_prompt}' and 'hide_default_prompt = {hide_default_prompt}'"
+    )))
+}
+
+/// Ensure `url` can be used to talk to a FloxHub or catalog server
+fn validate_http_url(key: &str, url: &Url) -> Result<(), ReadWriteError> {
+    let reason = if !matches!(url.scheme(), "http" | "https") {
+        format!(
+            "expected an http or https URL, found scheme '{}'",
+            url.scheme()
+        )
+    } else if url.host_str().is_none_or(str::is_empty) {
+        "URL is missing a host".to_string()
+    } else {
+        return Ok(());
+    };
+
+    Err(ReadWriteError::InvalidValue {
+        key: key.to_string(),
+        reason,
+    })
+}
+
+fn remove_non_portable_keys(document: &mut DocumentMut) {
+    'keys: for key in NON_PORTABLE_KEYS {
+        let mut path = key.split('.').collect::<Vec<_>>();
+        let leaf = path.pop().expect("split yields at least one element");
+
+        let mut table: &mut dyn TableLike = document.as_table_mut();
+        for parent in path {
+            match table.get_mut(parent).and_then(Item::as_table_like_mut) {
+                Some(parent_table) => table = parent_table,
+                None => continue 'keys,
+            }
+        }
+        table.remove(leaf);
+    }
+}
+
+/// Recursively merge `source` into `target`, overwriting values in `target`.
+fn merge_tables(target: &mut dyn TableLike, source: &dyn TableLike) {
+    for (key, item) in source.iter() {
+        if let (Some(target_table), Some(source_table)) = (
+            target.get_mut(key).and_then(Item::as_table_like_mut),
+            item.as_table_like(),
+        ) {
+            merge_tables(target_table, source_table);
+            continue;
+        }
+        target.insert(key, item.clone());
+    }
+}
+
 fn mk_environment(envs: &mut Vec<(String, String)>, prefix: &str) -> Environment {
     let (prefixed_envs, flox_envs): (HashMap<String, String>, Vec<(String, String)>) = envs
         .iter()
@@ -541,6 +842,111 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn export_portable_omits_secrets_and_dirs() {
+        let config_file = indoc! {r#"
+            disable_metrics = true
+            floxhub_token = "secret"
+            cache_dir = "/home/user/.cache/flox"
+
+            [trusted_environments]
+            "owner/name" = "trust"
+
+            [publish]
+            signing_private_key = "/home/user/.flox/key"
+            channel = "stable"
+        "#};
+
+        let exported = Config::export_portable(Some(config_file.to_string())).unwrap();
+        assert_eq!(exported, indoc! {r#"
+            disable_metrics = true
+
+            [trusted_environments]
+            "owner/name" = "trust"
+
+            [publish]
+            channel = "stable"
+        "#});
+    }
+
+    #[test]
+    fn import_portable_merges_and_preserves_unrelated_keys() {
+        let config_file = indoc! {r#"
+            # my config
+            floxhub_token = "secret"
+            search_limit = 5
+
+            [trusted_environments]
+            "owner/a" = "trust"
+        "#};
+        let portable = indoc! {r#"
+            floxhub_token = "other"
+            search_limit = 20
+
+            [trusted_environments]
+            "owner/b" = "deny"
+        "#};
+
+        let merged = Config::import_portable(Some(config_file.to_string()), portable).unwrap();
+        assert_eq!(merged, indoc! {r#"
+            # my config
+            floxhub_token = "secret"
+            search_limit = 20
+
+            [trusted_environments]
+            "owner/a" = "trust"
+            "owner/b" = "deny"
+        "#});
+    }
+
+    #[test]
+    fn import_portable_rejects_invalid_config() {
+        let portable = "search_limit = \"many\"";
+        Config::import_portable(None, portable).unwrap_err();
+
+        let portable = "floxhub_url = \"ftp://hub.example.com\"";
+        let err = Config::import_portable(None, portable).unwrap_err();
+        assert!(matches!(err, ReadWriteError::InvalidValue { .. }));
+    }
+
+    #[test]
+    fn migrate_replaces_shell_prompt() {
+        let config_file = indoc! {r#"
+            disable_metrics = true
+            shell_prompt = "hide-default"
+        "#};
+
+        let (migrated, outcomes) = Config::migrate(Some(config_file.to_string())).unwrap();
+        let migrated = migrated.unwrap();
+        assert_eq!(migrated, indoc! {r#"
+            disable_metrics = true
+            set_prompt = true
+            hide_default_prompt = true
+        "#});
+        assert!(outcomes[0].change.is_some());
+
+        // running again is a no-op
+        let (migrated_again, outcomes) = Config::migrate(Some(migrated)).unwrap();
+        assert_eq!(migrated_again, None);
+        assert_eq!(outcomes, vec![MigrationOutcome {
+            name: "shell_prompt",
+            change: None
+        }]);
+    }
+
+    #[test]
+    fn migrate_removes_superseded_shell_prompt() {
+        let config_file = indoc! {r#"
+            shell_prompt = "show-all"
+            set_prompt = false
+        "#};
+
+        let (migrated, _) = Config::migrate(Some(config_file.to_string())).unwrap();
+        assert_eq!(migrated.unwrap(), indoc! {r#"
+            set_prompt = false
+        "#});
+    }
+
     #[test]
     fn test_read_bool() {
         let mut config = Config::default();
@@ -746,6 +1152,21 @@ mod tests {
         "});
     }
 
+    #[test]
+    fn writing_invalid_url_is_rejected() {
+        for (key, value) in [
+            ("floxhub_url", "ftp://example.com"),
+            ("catalog_url", "not a url"),
+            ("catalog_url", "file:///tmp/catalog"),
+        ] {
+            let result = Config::write_to(None, &Key::parse(key).unwrap(), Some(value));
+            assert!(
+                matches!(result, Err(ReadWriteError::InvalidValue { .. })),
+                "{key} = {value} should be rejected, got {result:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_writing_bool() {
         let config_content =
@@ -785,6 +1206,29 @@ mod tests {
         "#});
     }
 
+    #[test]
+    fn default_environment_is_a_path_or_remote_ref() {
+        assert_eq!(
+            "/home/user/env".parse::<DefaultEnvironment>().unwrap(),
+            DefaultEnvironment::Path(PathBuf::from("/home/user/env"))
+        );
+        assert_eq!(
+            "owner/name".parse::<DefaultEnvironment>().unwrap(),
+            DefaultEnvironment::Remote("owner/name".parse().unwrap())
+        );
+        assert!("env".parse::<DefaultEnvironment>().is_err());
+
+        let rendered = Config::write_to(
+            None,
+            &Key::parse("default_environment").unwrap(),
+            Some(DefaultEnvironment::Path(PathBuf::from("/home/user/env"))),
+        )
+        .unwrap();
+        assert_eq!(rendered, indoc! {r#"
+            default_environment = "/home/user/env"
+        "#});
+    }
+
     #[test]
     fn test_remove() {
         let config_before = indoc! {"
@@ -874,5 +1318,10 @@ mod tests {
             let serialized = serde_json::to_string(&channel).unwrap();
             prop_assert_eq!(display_quoted, serialized);
         }
+
+        #[test]
+        fn installer_channel_parses_from_display(channel in any::<InstallerChannel>()) {
+            prop_assert_eq!(channel.to_string().parse::<InstallerChannel>(), Ok(channel));
+        }
     }
 }
@@ -83,6 +83,11 @@ pub struct Select<T> {
     pub options: Vec<T>,
 }
 
+#[derive(Clone)]
+pub struct MultiSelect<T> {
+    pub options: Vec<T>,
+}
+
 /// Marker type for a dialog that waits for the user to press Enter.
 #[derive(Debug, Clone)]
 pub struct Checkpoint;
@@ -203,6 +208,49 @@ impl<T: Display> Dialog<'_, Select<T>> {
     }
 }
 
+impl<T: Display> Dialog<'_, MultiSelect<T>> {
+    /// Prompt for any number of the options,
+    /// returned in the order they were offered.
+    pub async fn prompt(self) -> inquire::error::InquireResult<Vec<T>> {
+        let message = self.message.to_owned();
+        let help_message = self.help_message.map(ToOwned::to_owned);
+        let options = self.typed.options;
+
+        let choices = options
+            .iter()
+            .map(ToString::to_string)
+            .enumerate()
+            .map(|(id, value)| Choice(id, value))
+            .collect();
+
+        let selected = tokio::task::spawn_blocking(move || {
+            let _stderr_lock = TERMINAL_STDERR.lock();
+
+            let mut dialog =
+                inquire::MultiSelect::new(&message, choices).with_render_config(flox_theme());
+
+            if let Some(ref help_message) = help_message {
+                dialog = dialog.with_help_message(help_message);
+            }
+
+            dialog.prompt()
+        })
+        .await
+        .expect("Failed to join blocking dialog")?;
+
+        let selected_ids = selected
+            .into_iter()
+            .map(|Choice(id, _)| id)
+            .collect::<Vec<_>>();
+        Ok(options
+            .into_iter()
+            .enumerate()
+            .filter(|(id, _)| selected_ids.contains(id))
+            .map(|(_, option)| option)
+            .collect())
+    }
+}
+
 impl Dialog<'_, ()> {
     /// True if stderr and stdin are ttys
     pub fn can_prompt() -> bool {
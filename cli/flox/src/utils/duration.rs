@@ -0,0 +1,63 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A duration such as `30d` or `1w2d12h`, made up of integers followed by one
+/// of the units `s`, `m`, `h`, `d` or `w`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct HumanDuration(pub Duration);
+
+impl FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid duration '{s}', expected e.g. '30d', '12h' or '1w2d'");
+
+        let mut total = Duration::ZERO;
+        let mut digits = String::new();
+        for c in s.trim().chars() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+                continue;
+            }
+            let unit_secs = match c {
+                's' => 1,
+                'm' => 60,
+                'h' => 60 * 60,
+                'd' => 24 * 60 * 60,
+                'w' => 7 * 24 * 60 * 60,
+                _ => return Err(invalid()),
+            };
+            let amount: u64 = digits.parse().map_err(|_| invalid())?;
+            let secs = amount.checked_mul(unit_secs).ok_or_else(invalid)?;
+            total = total
+                .checked_add(Duration::from_secs(secs))
+                .ok_or_else(invalid)?;
+            digits.clear();
+        }
+        if !digits.is_empty() || s.trim().is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(HumanDuration(total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_human_duration() {
+        let day = Duration::from_secs(24 * 60 * 60);
+        assert_eq!("30d".parse(), Ok(HumanDuration(30 * day)));
+        assert_eq!("1w2d".parse(), Ok(HumanDuration(9 * day)));
+        assert_eq!(
+            "90m".parse(),
+            Ok(HumanDuration(Duration::from_secs(90 * 60)))
+        );
+        assert!("".parse::<HumanDuration>().is_err());
+        assert!("30".parse::<HumanDuration>().is_err());
+        assert!("d".parse::<HumanDuration>().is_err());
+        assert!("3y".parse::<HumanDuration>().is_err());
+    }
+}
@@ -416,6 +416,7 @@ pub fn format_managed_error(err: &ManagedEnvironmentError) -> String {
             }
         },
         ManagedEnvironmentError::UpstreamAlreadyExists { .. } => display_chain(err),
+        ManagedEnvironmentError::ReadOnly(_) => display_chain(err),
         ManagedEnvironmentError::Push(_) => display_chain(err),
         ManagedEnvironmentError::PushWithLocalIncludes => display_chain(err),
         ManagedEnvironmentError::DeleteBranch(_) => display_chain(err),
@@ -59,6 +59,7 @@ pub fn init_floxhub_client(
             }),
         ),
         user_agent: Some(format!("flox-cli/{}", &*FLOX_VERSION)),
+        qos: Default::default(),
     };
 
     debug!("using catalog client with url: {}", client_config.base_url);
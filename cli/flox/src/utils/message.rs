@@ -85,6 +85,21 @@ pub(crate) fn warning_to_buffer(out: &mut impl Write, v: impl Display) {
     print_message_to_buffer(out, std::format_args!("{icon} {v}"));
 }
 
+/// Warn that the insecure packages `install_ids` are allowed for a single
+/// `operation`, e.g. an install or an activation.
+pub(crate) fn insecure_packages_allowed(install_ids: &[String], operation: &str) {
+    let install_ids = install_ids
+        .iter()
+        .map(|iid| format!("'{iid}'"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    warning(formatdoc! {"
+        Allowing the insecure packages {install_ids} for this {operation} only.
+        Insecure packages have known vulnerabilities.
+        The allowance isn't recorded in the manifest, so locking the environment again
+        will fail until the packages are added to 'options.allow.insecure' or removed."});
+}
+
 pub(crate) fn package_installed(pkg: &PackageToInstall, environment_description: &str) {
     updated(format!(
         "'{}' installed to environment {environment_description}",
@@ -127,6 +142,21 @@ pub(crate) fn packages_successfully_installed(
     }
 }
 
+/// Display a message for packages that were added to the manifest
+/// without locking the environment.
+pub(crate) fn packages_staged(pkgs: &[PackageToInstall], environment_description: &str) {
+    if !pkgs.is_empty() {
+        let pkg_list = pkgs
+            .iter()
+            .map(|p| format!("'{}'", p.id()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        updated(format!(
+            "{pkg_list} added to the manifest of environment {environment_description} without locking"
+        ));
+    }
+}
+
 /// Display messages for each package that could only be installed for some of
 /// the requested systems.
 pub(crate) fn packages_installed_with_system_subsets(pkgs: &[PackageToInstall]) {
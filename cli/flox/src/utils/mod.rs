@@ -10,12 +10,14 @@ pub mod colors;
 pub mod detect_shell;
 pub mod dialog;
 pub mod didyoumean;
+pub mod duration;
 pub mod errors;
 pub mod events;
 pub mod init;
 pub mod message;
 pub mod metrics;
 pub mod openers;
+pub mod path_info;
 pub mod search;
 pub mod tracing;
 pub mod update_notifications;
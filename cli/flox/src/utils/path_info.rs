@@ -0,0 +1,116 @@
+//! Helpers for querying store paths with `nix path-info --json`
+
+use flox_rust_sdk::providers::nix::nix_base_command;
+use tracing::debug;
+
+/// Query `nix path-info --json --recursive` for the closure of `store_paths`
+/// in the local store.
+///
+/// Returns [None] if nix can't be run or fails,
+/// e.g. because a path is not in the local store.
+pub fn closure_path_infos(
+    store_paths: &[impl AsRef<std::ffi::OsStr>],
+) -> Option<serde_json::Map<String, serde_json::Value>> {
+    store_path_infos(None, store_paths, true)
+}
+
+/// Query `nix path-info --json` for `store_paths`, or with `recursive` for
+/// their closure, in `store`, or in the local store if `store` is [None].
+///
+/// Returns [None] if nix can't be run or fails,
+/// e.g. because a path is not in the store.
+pub fn store_path_infos(
+    store: Option<&str>,
+    store_paths: &[impl AsRef<std::ffi::OsStr>],
+    recursive: bool,
+) -> Option<serde_json::Map<String, serde_json::Value>> {
+    let mut cmd = nix_base_command();
+    cmd.args(["path-info", "--json"]);
+    if recursive {
+        cmd.arg("--recursive");
+    }
+    if let Some(store) = store {
+        cmd.args(["--store", store]);
+    }
+    cmd.args(store_paths);
+    let output = match cmd.output() {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            debug!(
+                store,
+                stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+                "nix path-info failed, path info unknown"
+            );
+            return None;
+        },
+        Err(err) => {
+            debug!(store, %err, "failed to run nix path-info, path info unknown");
+            return None;
+        },
+    };
+
+    serde_json::from_slice(&output.stdout)
+        .inspect_err(|err| debug!(store, %err, "failed to parse nix path-info output"))
+        .ok()
+}
+
+/// Sum the `narSize` of every path in the output of `nix path-info --json`.
+///
+/// Paths that nix reports as `null` are not valid in the store,
+/// in which case the total is unknown.
+pub fn sum_nar_sizes(path_infos: &serde_json::Map<String, serde_json::Value>) -> Option<u64> {
+    path_infos
+        .values()
+        .map(|info| info.get("narSize")?.as_u64())
+        .sum()
+}
+
+/// Format a number of bytes using binary units, e.g. `12.3 MiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next_unit in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn sum_nar_sizes_requires_all_paths() {
+        let valid: serde_json::Map<_, _> = serde_json::from_str(indoc! {r#"
+            {
+              "/nix/store/a": { "narSize": 1024 },
+              "/nix/store/b": { "narSize": 2048 }
+            }
+        "#})
+        .unwrap();
+        assert_eq!(sum_nar_sizes(&valid), Some(3072));
+
+        let missing: serde_json::Map<_, _> = serde_json::from_str(indoc! {r#"
+            {
+              "/nix/store/a": { "narSize": 1024 },
+              "/nix/store/b": null
+            }
+        "#})
+        .unwrap();
+        assert_eq!(sum_nar_sizes(&missing), None);
+
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+}
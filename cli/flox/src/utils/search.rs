@@ -1,8 +1,11 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Display;
 
 use anyhow::Result;
 use crossterm::style::Stylize;
 use floxhub_client::{SearchResult, SearchResults};
+use itertools::Itertools;
 
 pub const DEFAULT_DESCRIPTION: &'_ str = "<no description provided>";
 
@@ -15,6 +18,25 @@ pub struct DisplayItem {
     description: Option<String>,
 }
 
+/// The name of an output of a package,
+/// marked with an asterisk when displayed if it is installed by default,
+/// as in `flox show`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OutputName {
+    pub name: String,
+    pub installed_by_default: bool,
+}
+
+impl Display for OutputName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.installed_by_default {
+            write!(f, "{}*", self.name)
+        } else {
+            write!(f, "{}", self.name)
+        }
+    }
+}
+
 impl Display for DisplayItem {
     /// Render a display item in the format that should be output by
     /// `flox search`.
@@ -65,6 +87,27 @@ pub struct DisplaySearchResults {
     n_results: u64,
     /// Whether to bold the search term matches in the output
     use_bold: bool,
+    /// Output names of each package by pkg-path, if they should be shown
+    output_names: Option<BTreeMap<String, Vec<OutputName>>>,
+    /// Version variants collapsed into each package by pkg-path,
+    /// if results are grouped by pname
+    pname_groups: Option<BTreeMap<String, PnameGroupSummary>>,
+}
+
+/// Search results that share a pname, collapsed into a single entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct PnameGroup {
+    pub pname: String,
+    /// The newest result of the group, which represents it in the output
+    pub newest: SearchResult,
+    /// All results of the group, in their original order
+    pub results: Vec<SearchResult>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PnameGroupSummary {
+    count: usize,
+    newest_version: Option<String>,
 }
 
 /// A struct that wraps the functionality needed to print [SearchResults] to a
@@ -92,8 +135,45 @@ impl DisplaySearchResults {
             count: search_results.count,
             n_results: n_results as u64,
             use_bold,
+            output_names: None,
+            pname_groups: None,
         })
     }
+
+    /// Show the output names of each package after its description.
+    ///
+    /// Packages missing from `output_names` are shown with unknown outputs.
+    pub(crate) fn with_output_names(
+        mut self,
+        output_names: BTreeMap<String, Vec<OutputName>>,
+    ) -> Self {
+        self.output_names = Some(output_names);
+        self
+    }
+
+    /// Show one line per group, using the pkg-path of its newest result,
+    /// followed by the number of versions in the group and the newest version.
+    ///
+    /// The truncation hint still refers to the number of ungrouped results.
+    pub(crate) fn with_pname_groups(mut self, groups: &[PnameGroup]) -> Self {
+        self.display_items = groups
+            .iter()
+            .map(|group| group.newest.clone())
+            .collect::<Vec<_>>()
+            .into();
+        self.pname_groups = Some(
+            groups
+                .iter()
+                .map(|group| {
+                    (group.newest.pkg_path.clone(), PnameGroupSummary {
+                        count: group.results.len(),
+                        newest_version: group.newest.version.clone(),
+                    })
+                })
+                .collect(),
+        );
+        self
+    }
 }
 
 impl Display for DisplaySearchResults {
@@ -130,6 +210,27 @@ impl Display for DisplaySearchResults {
 
             // The two spaces here provide visual breathing room.
             write!(f, "{name:<width$}  {desc}")?;
+            if let Some(output_names) = &self.output_names {
+                match output_names.get(&d.pkg_path) {
+                    Some(outputs) => write!(f, " (outputs: {})", outputs.iter().join(", "))?,
+                    None => write!(f, " (outputs: unknown)")?,
+                }
+            }
+            if let Some(summary) = self
+                .pname_groups
+                .as_ref()
+                .and_then(|groups| groups.get(&d.pkg_path))
+            {
+                let versions = if summary.count == 1 {
+                    "version"
+                } else {
+                    "versions"
+                };
+                match &summary.newest_version {
+                    Some(newest) => write!(f, " ({} {versions}, newest {newest})", summary.count)?,
+                    None => write!(f, " ({} {versions})", summary.count)?,
+                }
+            }
             // Only print a newline if there are more items to print
             if items.peek().is_some() {
                 writeln!(f)?;
@@ -156,6 +257,138 @@ impl DisplaySearchResults {
     }
 }
 
+/// Sort search results by version, newest first.
+///
+/// Versions are compared as semantic versions,
+/// where missing minor and patch versions count as `0`, e.g. `2.9` < `2.12`.
+/// Results whose version is not a semantic version
+/// are sorted after all others, by comparing their components,
+/// see [cmp_version_components].
+///
+/// Returns the number of results whose version is not a semantic version.
+pub fn sort_newest_first(search_results: &mut [SearchResult]) -> usize {
+    search_results.sort_by(cmp_newest_first);
+
+    search_results
+        .iter()
+        .filter(|result| parse_version(result).is_none())
+        .count()
+}
+
+/// Parse the version of a search result as a semantic version,
+/// accepting versions with only a major, or major and minor version.
+fn parse_version(result: &SearchResult) -> Option<semver::Version> {
+    let version = result.version.as_deref()?;
+    if let Ok(version) = semver::Version::parse(version) {
+        return Some(version);
+    }
+
+    let numbers = version
+        .split('.')
+        .map(|number| number.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    match numbers[..] {
+        [major] => Some(semver::Version::new(major, 0, 0)),
+        [major, minor] => Some(semver::Version::new(major, minor, 0)),
+        _ => None,
+    }
+}
+
+/// Order search results by version, newest first, as in [sort_newest_first].
+fn cmp_newest_first(a: &SearchResult, b: &SearchResult) -> Ordering {
+    match (parse_version(a), parse_version(b)) {
+        (Some(version_a), Some(version_b)) => version_b.cmp(&version_a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => match (&a.version, &b.version) {
+            (Some(version_a), Some(version_b)) => cmp_version_components(version_b, version_a),
+            (version_a, version_b) => version_b.cmp(version_a),
+        },
+    }
+}
+
+/// Compare versions by their components,
+/// i.e. the runs of digits and of other characters between `.`, `-`, and `_`.
+///
+/// Numeric components are compared as numbers and are newer than
+/// other components, which are compared as strings,
+/// e.g. `2024-01-9` < `2024-01-10` and `unstable-1` < `1`.
+fn cmp_version_components(a: &str, b: &str) -> Ordering {
+    #[derive(PartialEq, Eq, PartialOrd, Ord)]
+    enum Component<'a> {
+        Text(&'a str),
+        Number(u64),
+    }
+
+    fn components(version: &str) -> Vec<Component<'_>> {
+        let mut components = Vec::new();
+        for part in version.split(['.', '-', '_']) {
+            let mut rest = part;
+            while let Some(first) = rest.chars().next() {
+                let is_digit = first.is_ascii_digit();
+                let end = rest
+                    .find(|c: char| c.is_ascii_digit() != is_digit)
+                    .unwrap_or(rest.len());
+                let (chunk, tail) = rest.split_at(end);
+                components.push(match chunk.parse() {
+                    Ok(number) if is_digit => Component::Number(number),
+                    _ => Component::Text(chunk),
+                });
+                rest = tail;
+            }
+        }
+        components
+    }
+
+    components(a).cmp(&components(b))
+}
+
+/// Remove search results with the same pname, version, and system as an
+/// earlier result.
+///
+/// The first result of each is kept as is,
+/// so that any sorting of the results is preserved.
+/// Returns the number of results removed.
+pub fn dedup_results(search_results: &mut Vec<SearchResult>) -> usize {
+    let n_results = search_results.len();
+    let mut seen = HashSet::new();
+    search_results.retain(|result| {
+        seen.insert((
+            result.pname.clone(),
+            result.version.clone(),
+            result.system.to_string(),
+        ))
+    });
+    n_results - search_results.len()
+}
+
+/// Collapse search results that share a pname into a single group each.
+///
+/// Groups are ordered by the first appearance of their pname,
+/// so that any sorting of the results is preserved.
+/// The newest result of each group is determined as in [sort_newest_first].
+pub fn group_by_pname(search_results: Vec<SearchResult>) -> Vec<PnameGroup> {
+    let mut groups: Vec<PnameGroup> = Vec::new();
+
+    for result in search_results {
+        match groups.iter_mut().find(|group| group.pname == result.pname) {
+            Some(group) => {
+                if cmp_newest_first(&result, &group.newest) == Ordering::Less {
+                    group.newest = result.clone();
+                }
+                group.results.push(result);
+            },
+            None => groups.push(PnameGroup {
+                pname: result.pname.clone(),
+                newest: result.clone(),
+                results: vec![result],
+            }),
+        }
+    }
+
+    groups
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
@@ -180,6 +413,92 @@ mod tests {
         }
     }
 
+    fn stub_search_result_with_version(name: &str, version: Option<&str>) -> SearchResult {
+        SearchResult {
+            version: version.map(|s| s.to_string()),
+            ..stub_search_result(name, None)
+        }
+    }
+
+    #[test]
+    fn dedup_results_keeps_first_of_each_pname_version_and_system() {
+        let mut search_results = vec![
+            stub_search_result_with_version("a", Some("1.0")),
+            SearchResult {
+                pkg_path: "other.a".to_string(),
+                ..stub_search_result_with_version("a", Some("1.0"))
+            },
+            stub_search_result_with_version("a", Some("2.0")),
+            SearchResult {
+                system: SystemEnum::from_str("x86_64-linux").unwrap(),
+                ..stub_search_result_with_version("a", Some("1.0"))
+            },
+            stub_search_result_with_version("b", None),
+            stub_search_result_with_version("b", None),
+        ];
+
+        assert_eq!(dedup_results(&mut search_results), 2);
+        let kept = search_results
+            .iter()
+            .map(|result| {
+                (
+                    result.pkg_path.as_str(),
+                    result.version.as_deref(),
+                    result.system.to_string(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(kept, vec![
+            ("a", Some("1.0"), "aarch64-darwin".to_string()),
+            ("a", Some("2.0"), "aarch64-darwin".to_string()),
+            ("a", Some("1.0"), "x86_64-linux".to_string()),
+            ("b", None, "aarch64-darwin".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn sort_newest_first_orders_by_semver_then_string() {
+        let mut search_results = vec![
+            stub_search_result_with_version("a", Some("1.2.0")),
+            stub_search_result_with_version("b", Some("2024-01-01")),
+            stub_search_result_with_version("c", Some("1.10.0")),
+            stub_search_result_with_version("d", None),
+            stub_search_result_with_version("e", Some("2025-01-01")),
+            stub_search_result_with_version("f", Some("1.10.0-rc.1")),
+        ];
+
+        let unparseable = sort_newest_first(&mut search_results);
+
+        let order = search_results
+            .iter()
+            .map(|result| result.pkg_path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec!["c", "f", "a", "e", "b", "d"]);
+        assert_eq!(unparseable, 3);
+    }
+
+    #[test]
+    fn sort_newest_first_compares_versions_leniently() {
+        let mut search_results = vec![
+            stub_search_result_with_version("a", Some("2.9")),
+            stub_search_result_with_version("b", Some("2.12")),
+            stub_search_result_with_version("c", Some("3")),
+            stub_search_result_with_version("d", Some("unstable-2024-01-9")),
+            stub_search_result_with_version("e", Some("unstable-2024-01-10")),
+            stub_search_result_with_version("f", Some("1.2.3.4")),
+            stub_search_result_with_version("g", Some("1.2.3.10")),
+        ];
+
+        let unparseable = sort_newest_first(&mut search_results);
+
+        let order = search_results
+            .iter()
+            .map(|result| result.pkg_path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec!["c", "b", "a", "g", "f", "e", "d"]);
+        assert_eq!(unparseable, 4);
+    }
+
     #[test]
     fn test_display_search_result() {
         let search_results = vec![
@@ -193,6 +512,8 @@ mod tests {
             display_items: search_results.into(),
             n_results: 2,
             use_bold: false,
+            output_names: None,
+            pname_groups: None,
         };
 
         let expected = indoc! {"
@@ -215,6 +536,8 @@ mod tests {
             display_items: search_results.into(),
             n_results: 2,
             use_bold: false,
+            output_names: None,
+            pname_groups: None,
         };
 
         let expected = indoc! {"
@@ -223,4 +546,93 @@ mod tests {
             "};
         assert_eq!(expected, format!("{}\n", display));
     }
+
+    #[test]
+    fn test_display_output_names() {
+        let search_results = vec![
+            stub_search_result("pkg1", Some("description of pkg1")),
+            stub_search_result("pkg2", Some("description of pkg2")),
+        ];
+
+        let display = DisplaySearchResults {
+            search_term: "pkg".to_string(),
+            count: Some(search_results.len() as u64),
+            display_items: search_results.into(),
+            n_results: 2,
+            use_bold: false,
+            output_names: None,
+            pname_groups: None,
+        }
+        .with_output_names(BTreeMap::from([("pkg1".to_string(), vec![
+            OutputName {
+                name: "out".to_string(),
+                installed_by_default: true,
+            },
+            OutputName {
+                name: "man".to_string(),
+                installed_by_default: false,
+            },
+        ])]));
+
+        let expected = indoc! {"
+            pkg1  description of pkg1 (outputs: out*, man)
+            pkg2  description of pkg2 (outputs: unknown)
+            "};
+        assert_eq!(expected, format!("{}\n", display));
+    }
+
+    #[test]
+    fn group_by_pname_collapses_versions() {
+        let with_pname = |pkg_path: &str, pname: &str, version: Option<&str>| SearchResult {
+            pname: pname.to_string(),
+            description: Some(format!("description of {pname}")),
+            ..stub_search_result_with_version(pkg_path, version)
+        };
+        let search_results = vec![
+            with_pname("nodejs_18", "nodejs", Some("18.20.4")),
+            with_pname("hello", "hello", Some("2.12.1")),
+            with_pname("nodejs_22", "nodejs", Some("22.9.0")),
+            with_pname("nodejs_20", "nodejs", Some("20.17.0")),
+            with_pname("unversioned", "unversioned", None),
+        ];
+
+        let groups = group_by_pname(search_results.clone());
+        let summary = groups
+            .iter()
+            .map(|group| {
+                (
+                    group.pname.as_str(),
+                    group.newest.pkg_path.as_str(),
+                    group.results.len(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(summary, vec![
+            ("nodejs", "nodejs_22", 3),
+            ("hello", "hello", 1),
+            ("unversioned", "unversioned", 1),
+        ]);
+
+        let display = DisplaySearchResults::from_search_results(
+            "nodejs",
+            SearchResults {
+                results: search_results,
+                count: Some(8),
+            },
+            false,
+        )
+        .unwrap()
+        .with_pname_groups(&groups);
+
+        let expected = indoc! {"
+            nodejs_22    description of nodejs (3 versions, newest 22.9.0)
+            hello        description of hello (1 version, newest 2.12.1)
+            unversioned  description of unversioned (1 version)
+            "};
+        assert_eq!(expected, format!("{}\n", display));
+        assert_eq!(
+            display.search_results_truncated_hint().unwrap(),
+            "Showing 5 of 8 results. Use `flox search nodejs --all` to see the full list."
+        );
+    }
 }
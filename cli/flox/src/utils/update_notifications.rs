@@ -25,7 +25,7 @@ const DEFAULT_UPDATE_INSTRUCTIONS: &str =
     "Get the latest at https://flox.dev/docs/install-flox/#upgrade-existing-flox-installation";
 const UPDATE_INSTRUCTIONS_RELATIVE_FILE_PATH: &str =
     "../../share/flox/files/update-instructions.txt";
-const UPDATE_NOTIFICATION_FILE_NAME: &str = "update-check-timestamp.json";
+pub(crate) const UPDATE_NOTIFICATION_FILE_NAME: &str = "update-check-timestamp.json";
 const UPDATE_NOTIFICATION_EXPIRY: Duration = Duration::days(1);
 
 /// Timestamp we serialize to a file to track when we last checked
@@ -14,7 +14,7 @@ use std::fmt::Debug;
 use std::future::{Future, ready};
 use std::num::NonZeroU32;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
 use async_stream::try_stream;
@@ -28,12 +28,12 @@ use reqwest::header::{self, HeaderMap};
 use tracing::{debug, instrument};
 use url::Url;
 
-use crate::MapApiErrorExt;
 use crate::auth::AuthContext;
 use crate::config::FloxhubClientConfig;
 use crate::error::{FloxhubClientError, ResolveError, SearchError, VersionsError};
 use crate::mock::MockGuard;
 use crate::types::*;
+use crate::{CatalogQoS, MapApiErrorExt};
 
 #[cfg(any(test, feature = "tests"))]
 pub const EMPTY_SEARCH_RESPONSE: &api_types::PackageSearchResult =
@@ -95,9 +95,16 @@ impl FloxhubClient {
             pre_request: Arc::clone(&pre_request),
         };
 
+        // Tag requests with their QoS class unless it's the default.
+        let mut extra_headers = config.extra_headers.clone();
+        if config.qos != CatalogQoS::default() {
+            let (qos_key, qos_value) = config.qos.as_header_pair();
+            extra_headers.insert(qos_key, qos_value);
+        }
+
         // One reqwest::Client for both inner clients. Clones share the pool.
         let http_client = build_http_client(
-            &config.extra_headers,
+            &extra_headers,
             config.user_agent.as_deref(),
             &config.base_url,
         )
@@ -125,6 +132,60 @@ impl FloxhubClient {
         &self.config.base_url
     }
 
+    /// Search for packages matching a search term,
+    /// optionally only in the catalogs `catalogs`.
+    async fn search_catalogs(
+        &self,
+        search_term: &str,
+        catalogs: Option<&str>,
+        system: api_types::PackageSystem,
+        limit: SearchLimit,
+    ) -> Result<SearchResults, SearchError> {
+        tracing::debug!(
+            search_term,
+            catalogs,
+            ?system,
+            ?limit,
+            "sending search request"
+        );
+
+        let page_size = min(
+            limit
+                .map(Into::<NonZeroU32>::into)
+                .unwrap_or(RESPONSE_PAGE_SIZE),
+            RESPONSE_PAGE_SIZE,
+        );
+        let stream = make_depaging_stream(
+            |page_number, page_size| async move {
+                let response = self
+                    .catalog
+                    .search_api_v1_catalog_search_get(
+                        catalogs,
+                        Some(page_number),
+                        Some(page_size),
+                        Some(
+                            &api_types::SearchTerm::from_str(search_term)
+                                .map_err(SearchError::InvalidSearchTerm)?,
+                        ),
+                        system,
+                    )
+                    .await
+                    .map_api_error()
+                    .await?;
+
+                let packages = response.into_inner();
+
+                Ok::<_, SearchError>((packages.total_count, packages.items))
+            },
+            page_size,
+        );
+
+        let (count, results) = collect_search_results(stream, limit).await?;
+        let search_results = SearchResults { results, count };
+
+        Ok(search_results)
+    }
+
     /// Clear mock recording state if in recording mode.
     ///
     /// Useful in tests where setup operations should not be captured.
@@ -184,6 +245,22 @@ pub trait CatalogClientTrait {
         limit: SearchLimit,
     ) -> Result<SearchResults, SearchError>;
 
+    /// Search for packages matching a search term in the catalog
+    /// `catalog_name` only.
+    async fn search_in_catalog(
+        &self,
+        search_term: impl AsRef<str> + Send + Sync,
+        catalog_name: impl AsRef<str> + Send + Sync,
+        system: api_types::PackageSystem,
+        limit: SearchLimit,
+    ) -> Result<SearchResults, SearchError>;
+
+    /// Check whether the user catalog `catalog_name` exists.
+    async fn catalog_exists(
+        &self,
+        catalog_name: impl AsRef<str> + Send + Sync,
+    ) -> Result<bool, FloxhubClientError>;
+
     /// Get all versions of an attr_path.
     async fn package_versions(
         &self,
@@ -197,6 +274,12 @@ pub trait CatalogClientTrait {
         package_name: impl AsRef<str> + Send + Sync,
     ) -> Result<PublishResponse, FloxhubClientError>;
 
+    /// Get the store configuration of a catalog.
+    async fn catalog_store_config(
+        &self,
+        catalog_name: impl AsRef<str> + Send + Sync,
+    ) -> Result<CatalogStoreConfig, FloxhubClientError>;
+
     /// Get all locked sources for a catalog.
     async fn get_catalog_locked_sources(
         &self,
@@ -310,49 +393,53 @@ impl CatalogClientTrait for FloxhubClient {
         system: api_types::PackageSystem,
         limit: SearchLimit,
     ) -> Result<SearchResults, SearchError> {
-        tracing::debug!(
-            search_term = search_term.as_ref().to_string(),
-            ?system,
-            ?limit,
-            "sending search request"
-        );
-        let search_term = search_term.as_ref();
-
-        let page_size = min(
-            limit
-                .map(Into::<NonZeroU32>::into)
-                .unwrap_or(RESPONSE_PAGE_SIZE),
-            RESPONSE_PAGE_SIZE,
-        );
-        let stream = make_depaging_stream(
-            |page_number, page_size| async move {
-                let response = self
-                    .catalog
-                    .search_api_v1_catalog_search_get(
-                        None,
-                        Some(page_number),
-                        Some(page_size),
-                        Some(
-                            &api_types::SearchTerm::from_str(search_term)
-                                .map_err(SearchError::InvalidSearchTerm)?,
-                        ),
-                        system,
-                    )
-                    .await
-                    .map_api_error()
-                    .await?;
+        self.search_catalogs(search_term.as_ref(), None, system, limit)
+            .await
+    }
 
-                let packages = response.into_inner();
+    #[instrument(skip_all, fields(
+        search_term = %search_term.as_ref(),
+        progress = format!(
+            "Searching for packages matching '{}' in catalog '{}'",
+            search_term.as_ref(),
+            catalog_name.as_ref()
+        )))]
+    async fn search_in_catalog(
+        &self,
+        search_term: impl AsRef<str> + Send + Sync,
+        catalog_name: impl AsRef<str> + Send + Sync,
+        system: api_types::PackageSystem,
+        limit: SearchLimit,
+    ) -> Result<SearchResults, SearchError> {
+        self.search_catalogs(
+            search_term.as_ref(),
+            Some(catalog_name.as_ref()),
+            system,
+            limit,
+        )
+        .await
+    }
 
-                Ok::<_, SearchError>((packages.total_count, packages.items))
+    async fn catalog_exists(
+        &self,
+        catalog_name: impl AsRef<str> + Send + Sync,
+    ) -> Result<bool, FloxhubClientError> {
+        let catalog_name = str_to_catalog_name(catalog_name)?;
+        let result = self
+            .catalog
+            .get_catalog_api_v1_catalog_catalogs_catalog_name_get(&catalog_name)
+            .await
+            .map_api_error()
+            .await;
+        match result {
+            Ok(_) => Ok(true),
+            Err(FloxhubClientError::APIError(APIError::ErrorResponse(response)))
+                if response.status() == StatusCode::NOT_FOUND =>
+            {
+                Ok(false)
             },
-            page_size,
-        );
-
-        let (count, results) = collect_search_results(stream, limit).await?;
-        let search_results = SearchResults { results, count };
-
-        Ok(search_results)
+            Err(e) => Err(e),
+        }
     }
 
     async fn package_versions(
@@ -360,6 +447,10 @@ impl CatalogClientTrait for FloxhubClient {
         attr_path: impl AsRef<str> + Send + Sync,
     ) -> Result<PackageDetails, VersionsError> {
         let attr_path = attr_path.as_ref();
+        // Deprecation applies to the package as a whole,
+        // so it is repeated on every page.
+        let deprecation = OnceLock::new();
+        let deprecation_ref = &deprecation;
         let stream = make_depaging_stream(
             |page_number, page_size| async move {
                 let response = self
@@ -382,6 +473,9 @@ impl CatalogClientTrait for FloxhubClient {
                     })?;
 
                 let packages = response.into_inner();
+                if let Some(info) = packages.deprecation {
+                    let _ = deprecation_ref.set(info);
+                }
 
                 Ok::<_, VersionsError>((packages.total_count, packages.items))
             },
@@ -389,7 +483,11 @@ impl CatalogClientTrait for FloxhubClient {
         );
 
         let (count, results) = collect_search_results(stream, None).await?;
-        let search_results = PackageDetails { results, count };
+        let search_results = PackageDetails {
+            results,
+            count,
+            deprecation: deprecation.into_inner(),
+        };
 
         Ok(search_results)
     }
@@ -444,6 +542,21 @@ impl CatalogClientTrait for FloxhubClient {
             .map(|resp| resp.into_inner())
     }
 
+    async fn catalog_store_config(
+        &self,
+        catalog_name: impl AsRef<str> + Send + Sync,
+    ) -> Result<CatalogStoreConfig, FloxhubClientError> {
+        let catalog = str_to_catalog_name(catalog_name)?;
+        self.catalog
+            .get_catalog_store_config_api_v1_catalog_catalogs_catalog_name_store_config_get(
+                &catalog,
+            )
+            .await
+            .map_api_error()
+            .await
+            .map(|resp| resp.into_inner())
+    }
+
     async fn create_package(
         &self,
         catalog_name: impl AsRef<str> + Send + Sync,
@@ -809,6 +922,7 @@ pub mod test_helpers {
             mock_mode: Default::default(),
             auth_context: AuthContext::from_mode(&Default::default(), None),
             user_agent: None,
+            qos: Default::default(),
         }
     }
 
@@ -928,6 +1042,31 @@ pub mod tests {
         mock.assert();
     }
 
+    #[tokio::test]
+    async fn qos_header_set_unless_default() {
+        let server = MockServer::start_async().await;
+        let (qos_key, qos_value) = CatalogQoS::UserInitiated.as_header_pair();
+        let mock = server.mock(|when, then| {
+            when.header(&qos_key, &qos_value);
+            then.status(200).json_body_obj(EMPTY_SEARCH_RESPONSE);
+        });
+        let config = FloxhubClientConfig {
+            qos: CatalogQoS::UserInitiated,
+            ..client_config(&server.base_url())
+        };
+        let client = FloxhubClient::new(config).unwrap();
+        let _ = client.package_versions("some-package").await;
+        mock.assert();
+
+        let mock = server.mock(|when, then| {
+            when.header_missing(&qos_key);
+            then.status(200).json_body_obj(EMPTY_SEARCH_RESPONSE);
+        });
+        let client = FloxhubClient::new(client_config(&server.base_url())).unwrap();
+        let _ = client.package_versions("some-package").await;
+        mock.assert();
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn tracing_headers_present_when_sentry_enabled() {
         let server = MockServer::start_async().await;
@@ -995,6 +1134,35 @@ pub mod tests {
         assert!(res.is_ok(), "Expected successful response, got: {:?}", res);
     }
 
+    /// Deprecation notices on the versions response are kept
+    #[tokio::test]
+    async fn versions_response_includes_deprecation() {
+        let server = MockServer::start_async().await;
+
+        let mock = server.mock(|_, then| {
+            then.status(200).json_body(json!({
+                "items": [],
+                "total_count": 0,
+                "deprecation": {
+                    "kind": "renamed",
+                    "replacement": "other-package",
+                },
+            }));
+        });
+
+        let client = FloxhubClient::new(client_config(server.base_url().as_str())).unwrap();
+        let result = client.package_versions("some-package").await.unwrap();
+        mock.assert();
+        assert_eq!(
+            result.deprecation,
+            Some(DeprecationInfo {
+                kind: DeprecationKind::Renamed,
+                message: None,
+                replacement: Some("other-package".to_string()),
+            })
+        );
+    }
+
     // region: Error response handling
     //
     // Client errors and response error handling of the progenitor generated client
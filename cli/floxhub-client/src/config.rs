@@ -3,7 +3,7 @@
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
-use crate::AuthContext;
+use crate::{AuthContext, CatalogQoS};
 
 /// Configuration for FloxHub client construction.
 ///
@@ -20,6 +20,9 @@ pub struct FloxhubClientConfig {
     pub mock_mode: FloxhubMockMode,
     pub auth_context: AuthContext,
     pub user_agent: Option<String>,
+    /// QoS class of catalog requests,
+    /// sent with every request unless it's the default.
+    pub qos: CatalogQoS,
 }
 
 /// Mock recording/replay mode for integration testing.
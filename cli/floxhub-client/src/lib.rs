@@ -15,7 +15,7 @@
 //! use floxhub_client::{
 //!     FloxhubClient, FloxhubClientConfig,
 //!     FloxhubMockMode, CatalogClientTrait, AuthContext,
-//!     FactoryClientTrait,
+//!     FactoryClientTrait, CatalogQoS,
 //! };
 //!
 //! let config = FloxhubClientConfig {
@@ -24,6 +24,7 @@
 //!     mock_mode: FloxhubMockMode::None,
 //!     auth_context: AuthContext::from_mode(&Default::default(), floxhub_token),
 //!     user_agent: Some("flox-cli/1.0".to_string()),
+//!     qos: CatalogQoS::Default,
 //! };
 //!
 //! let client = FloxhubClient::new(config)?;
@@ -36,6 +37,7 @@ pub mod client;
 mod config;
 mod error;
 mod factory;
+mod qos;
 mod token;
 mod types;
 
@@ -77,6 +79,7 @@ pub use factory_api_v1::{
     Error as FactoryApiError,
     ResponseValue as FactoryApiResponseValue,
 };
+pub use qos::CatalogQoS;
 pub use token::{FloxhubToken, FloxhubTokenError};
 // Types (re-exported from types module for convenience)
 pub use types::*;
@@ -0,0 +1,39 @@
+//! Quality of service of catalog requests.
+//!
+//! Requests made by a [`crate::FloxhubClient`] are tagged with the
+//! [`CatalogQoS`] of the client, so that the catalog can prioritize requests
+//! the user is waiting for over background work.
+
+/// The QoS class of a catalog request.
+///
+/// Referencing macos performance classes, described [1].
+///
+/// [1]: <https://blog.xoria.org/macos-tips-threading/>
+#[derive(Clone, Copy, Debug, Default, derive_more::Display, PartialEq)]
+pub enum CatalogQoS {
+    /// your app’s user interface will stutter if this work is preempted
+    #[display("user_interactive")]
+    UserInteractive,
+    /// the user must wait for this work to finish before they can keep using your app, e.g. loading the contents of a document that was just opened
+    #[display("user_initiated")]
+    UserInitiated,
+    /// used as a fallback for threads which don’t have a QoS class assigned
+    #[default]
+    #[display("default")]
+    Default,
+    /// the user knows this work is happening but doesn’t wait for it to finish because they can keep using your app while it’s in progress, e.g. exporting in a video editor, downloading a file in a web browser
+    #[display("utility")]
+    Utility,
+    /// the user doesn’t know this work is happening, e.g. search indexing
+    #[display("background")]
+    Background,
+    /// the user doesn’t know this work is happening, e.g. garbage collection?
+    #[display("maintenance")]
+    Maintenance,
+}
+
+impl CatalogQoS {
+    pub fn as_header_pair(&self) -> (String, String) {
+        ("X-Flox-QoS-Context".to_string(), self.to_string())
+    }
+}
@@ -31,8 +31,23 @@ pub struct ResultsPage<T> {
 pub use api_types::PackageInfoSearch as SearchResult;
 pub type SearchResults = ResultsPage<SearchResult>;
 
-pub use api_types::{PackageOutput, PackageOutputs, PackageResolutionInfo as PackageBuild};
-pub type PackageDetails = ResultsPage<PackageBuild>;
+pub use api_types::{
+    DeprecationInfo,
+    DeprecationKind,
+    PackageOutput,
+    PackageOutputs,
+    PackageResolutionInfo as PackageBuild,
+};
+
+/// All builds of a package, along with any deprecation notice the catalog
+/// has for it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PackageDetails {
+    pub results: Vec<PackageBuild>,
+    pub count: ResultCount,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation: Option<DeprecationInfo>,
+}
 
 // ---------------------------------------------------------------------------
 // Package descriptors
@@ -422,6 +437,17 @@ impl BaseCatalogInfo {
             .collect()
     }
 
+    /// Return the stability tags of the page with the given base catalog url,
+    /// or `None` if the url does not address a known page.
+    ///
+    /// Published packages inherit the stabilities of the page
+    /// their base catalog url was locked to.
+    pub fn stabilities_of_url(&self, url: &BaseCatalogUrl) -> Option<Vec<&str>> {
+        let rev = url.rev()?;
+        let page_info = self.0.scraped_pages.iter().find(|page| page.rev == rev)?;
+        Some(page_info.stability_tags.iter().map(|tag| &**tag).collect())
+    }
+
     /// Create a mock BaseCatalogInfo for testing.
     #[cfg(feature = "tests")]
     pub fn new_mock() -> Self {
@@ -581,6 +607,23 @@ mod tests {
         assert_eq!(stabilities, vec!["not-default", "stable"]);
     }
 
+    /// `stabilities_of_url` returns the tags of the page matching the url's rev.
+    #[test]
+    fn stabilities_of_url_returns_page_tags() {
+        let info = two_page_fixture();
+
+        let url = BaseCatalogUrl::from(
+            "https://fixture.flox.dev?rev=bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb2",
+        );
+        assert_eq!(
+            info.stabilities_of_url(&url),
+            Some(vec![BaseCatalogInfo::DEFAULT_STABILITY, "not-default"])
+        );
+
+        let unknown = BaseCatalogUrl::from("https://fixture.flox.dev?rev=cccc");
+        assert_eq!(info.stabilities_of_url(&unknown), None);
+    }
+
     #[test]
     fn base_catalog_url_rev_extracts_rev() {
         let url = BaseCatalogUrl::from(
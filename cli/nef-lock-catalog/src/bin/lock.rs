@@ -49,6 +49,7 @@ async fn main() -> Result<()> {
             mock_mode: FloxhubMockMode::default_from_env(),
             auth_context,
             user_agent: None,
+            qos: Default::default(),
         };
 
         FloxhubClient::new(config)?
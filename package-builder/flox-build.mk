@@ -767,10 +767,13 @@ endef
 # Only "pure" routes away from the local build; every other value (including an
 # unrecognized one) builds locally, and libsandbox itself ignores any
 # FLOX_VIRTUAL_SANDBOX value outside (off|warn|enforce|pure).
+#
+# If set, the DISABLE_SANDBOX variable overrides the manifest's setting with
+# "off" for all builds (`flox build --sandbox off`).
 $(foreach build,$(MANIFEST_BUILDS), \
   $(eval _pname = $(notdir $(build))) \
-  $(eval _sandbox = $(shell \
-    $(_jq) -r '.manifest.build."$(_pname)".sandbox' $(MANIFEST_LOCK))) \
+  $(eval _sandbox = $(if $(DISABLE_SANDBOX),off,$(shell \
+    $(_jq) -r '.manifest.build."$(_pname)".sandbox' $(MANIFEST_LOCK)))) \
   $(eval _sandbox_allow = $(shell \
     $(_jq) -r '(.manifest.build."$(_pname)"."sandbox-allow" // []) | join(" ") | @sh' $(MANIFEST_LOCK))) \
   $(eval _version = $(shell $(shell \